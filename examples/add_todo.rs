@@ -0,0 +1,40 @@
+// Adds a todo to the current workspace without opening the app, using the library directly.
+//
+//     cargo run --example add_todo -- "Renew passport #errands due:friday"
+//
+// Uses the same data directory as the app, or the one named by PAPERCLIP_DATA_DIR.
+
+use paperclip::cli::DATA_DIR_VAR;
+use paperclip::storage::Storage;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let text = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+    if text.trim().is_empty() {
+        eprintln!("usage: add_todo <text>");
+        std::process::exit(2);
+    }
+
+    let storage = match std::env::var_os(DATA_DIR_VAR) {
+        Some(dir) => Storage::new_with_dir(PathBuf::from(dir))?,
+        None => Storage::new()?,
+    };
+
+    let (mut workspace_manager, warnings) = storage.load_workspace_manager()?;
+    for warning in warnings {
+        eprintln!("warning: {}", warning);
+    }
+    workspace_manager.ensure_workspace();
+    let workspace = workspace_manager.get_current_workspace().map(|ws| ws.name.clone()).unwrap_or_default();
+    let todo_list = workspace_manager.get_current_todo_list_mut().ok_or("no current workspace")?;
+    let id = todo_list.add_todo(text.trim().to_string());
+    let todo = &todo_list.todos[&id];
+    println!("Added #{} to {}: {}", id, workspace, todo.description);
+    if let Some(due) = todo.due_date {
+        println!("Due {}", due.format("%A %Y-%m-%d"));
+    }
+
+    // Merges with anything saved meanwhile, so this is safe while the app is open
+    storage.sync_workspace_manager(&mut workspace_manager)?;
+    Ok(())
+}
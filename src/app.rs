@@ -1,7 +1,29 @@
-use crate::colors::TokyoNightColors;
-use crate::todo::{Todo, TodoList, DueDateFilter, RecurrencePattern, WorkspaceManager};
+use crate::cli::{LaunchOptions, LaunchView};
+use crate::colors::{Theme, ThemeName};
+use crate::completion::{self, Completion, CompletionKind};
+use crate::config::{Config, DisplaySettings};
+use crate::export::{self, ExportFormat};
+use crate::external_editor;
+use crate::glyphs::Glyphs;
+use crate::import;
+use crate::keymap::Keymap;
+use crate::messages::{MessageLevel, MessageQueue, LONG, SHORT, STICKY};
+use crate::notifications::{self, Reminder, ReminderKind};
+use crate::pomodoro::{self, Phase, Pomodoro, Transition};
+use crate::storage::{expand_path, Storage};
 use crate::template::TemplateManager;
-use std::collections::VecDeque;
+use crate::text_buffer::TextBuffer;
+use crate::todo::{format_signed_time, format_tracked_time, parse_duration, parse_outline, toggle_checklist_line, DueDateFilter, FilterSet, GroupBy, RecurrencePattern, SavedFilter, SearchScope, StatusFilter, Todo, TodoList, TodoStatus, ViewMode, Workspace, WorkspaceManager};
+use chrono::{Datelike, NaiveDate};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,10 +31,61 @@ pub enum Command {
     AddTodo { workspace_id: String, todo: Todo },
     DeleteTodo { workspace_id: String, todo: Todo },
     CompleteTodo { workspace_id: String, todo_id: u32, old_status: crate::todo::TodoStatus },
-    EditTodo { workspace_id: String, todo_id: u32, old_description: String, old_raw_description: String },
-    ChangePriority { workspace_id: String, todo_id: u32, old_priority: u8 },
+    EditTodo { workspace_id: String, todo_id: u32, old_raw_description: String, new_raw_description: String },
+    ChangePriority { workspace_id: String, todo_id: u32, old_priority: u8, new_priority: u8 },
+    EditNotes { workspace_id: String, todo_id: u32, old_notes: Option<String>, new_notes: Option<String> },
     AddChildTodo { workspace_id: String, parent_id: u32, child_todo: Todo },
     DeleteWithChildren { workspace_id: String, deleted_todos: Vec<Todo> },
+    MoveTodos { workspace_id: String, target_workspace_id: String, original_todos: Vec<Todo>, moved_todos: Vec<Todo> },
+    RewriteDescriptions { workspace_id: String, old_raw_descriptions: Vec<(u32, String)>, new_raw_descriptions: Vec<(u32, String)> },
+    AddTodos { workspace_id: String, todos: Vec<Todo> }, // A pasted batch, undone in one step
+    ChangeStatus { workspace_id: String, todo_id: u32, old_status: TodoStatus, old_completed_at: Option<chrono::DateTime<chrono::Local>>, new_status: TodoStatus },
+    BulkComplete { workspace_id: String, changes: Vec<(u32, TodoStatus, Option<chrono::DateTime<chrono::Local>>)> }, // Prior status of each todo completed
+    BulkSetPriority { workspace_id: String, changes: Vec<(u32, u8)>, new_priority: u8 }, // Prior priority of each todo changed
+    BulkSetDue { workspace_id: String, changes: Vec<(u32, Option<chrono::DateTime<chrono::Local>>, String)>, new_due: Option<chrono::DateTime<chrono::Local>> }, // Prior due date and raw text of each todo changed
+}
+
+impl Command {
+    // Workspace the command was recorded in (the source workspace for moves)
+    pub fn workspace_id(&self) -> &str {
+        match self {
+            Command::AddTodo { workspace_id, .. }
+            | Command::DeleteTodo { workspace_id, .. }
+            | Command::CompleteTodo { workspace_id, .. }
+            | Command::EditTodo { workspace_id, .. }
+            | Command::ChangePriority { workspace_id, .. }
+            | Command::EditNotes { workspace_id, .. }
+            | Command::AddChildTodo { workspace_id, .. }
+            | Command::DeleteWithChildren { workspace_id, .. }
+            | Command::MoveTodos { workspace_id, .. }
+            | Command::RewriteDescriptions { workspace_id, .. }
+            | Command::AddTodos { workspace_id, .. }
+            | Command::ChangeStatus { workspace_id, .. }
+            | Command::BulkComplete { workspace_id, .. }
+            | Command::BulkSetPriority { workspace_id, .. }
+            | Command::BulkSetDue { workspace_id, .. } => workspace_id,
+        }
+    }
+    
+    // Todos the command touched, the one to select first leading
+    pub fn todo_ids(&self) -> Vec<u32> {
+        match self {
+            Command::AddTodo { todo, .. } | Command::DeleteTodo { todo, .. } => vec![todo.id],
+            Command::CompleteTodo { todo_id, .. }
+            | Command::EditTodo { todo_id, .. }
+            | Command::ChangePriority { todo_id, .. }
+            | Command::EditNotes { todo_id, .. }
+            | Command::ChangeStatus { todo_id, .. } => vec![*todo_id],
+            Command::AddChildTodo { parent_id, child_todo, .. } => vec![child_todo.id, *parent_id],
+            Command::DeleteWithChildren { deleted_todos: todos, .. }
+            | Command::MoveTodos { original_todos: todos, .. }
+            | Command::AddTodos { todos, .. } => todos.iter().map(|todo| todo.id).collect(),
+            Command::RewriteDescriptions { old_raw_descriptions, .. } => old_raw_descriptions.iter().map(|(id, _)| *id).collect(),
+            Command::BulkComplete { changes, .. } => changes.iter().map(|(id, _, _)| *id).collect(),
+            Command::BulkSetPriority { changes, .. } => changes.iter().map(|(id, _)| *id).collect(),
+            Command::BulkSetDue { changes, .. } => changes.iter().map(|(id, _, _)| *id).collect(),
+        }
+    }
 }
 
 pub struct CommandHistory {
@@ -21,6 +94,12 @@ pub struct CommandHistory {
     max_history: usize,
 }
 
+impl Default for CommandHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandHistory {
     pub fn new() -> Self {
         Self {
@@ -69,6 +148,57 @@ impl CommandHistory {
     }
 }
 
+const MAX_SEARCH_HISTORY: usize = 50;
+
+// First visible list row that keeps `scrolloff` rows of context around the selection, like vim.
+// The offset only moves when the selection gets too close to an edge.
+pub fn scroll_offset(offset: usize, selected: usize, rows: usize, height: usize, scrolloff: usize) -> usize {
+    if height == 0 {
+        return 0;
+    }
+    let margin = scrolloff.min(height.saturating_sub(1) / 2);
+    let mut offset = offset;
+    if selected < offset + margin {
+        offset = selected.saturating_sub(margin);
+    } else if selected + margin >= offset + height {
+        offset = selected + margin + 1 - height;
+    }
+    offset.min(rows.saturating_sub(height))
+}
+
+// Visual mode selection over the ids currently on screen. Working from the visible list
+// means todos hidden by a filter never end up in a bulk operation.
+pub fn select_all_visible(selected: &mut HashSet<u32>, visible: &[u32]) {
+    *selected = visible.iter().copied().collect();
+}
+
+pub fn invert_visible_selection(selected: &mut HashSet<u32>, visible: &[u32]) {
+    *selected = visible.iter().copied().filter(|id| !selected.contains(id)).collect();
+}
+
+// Tree motions over the depths of the visible rows. A sibling is the nearest row at the same
+// depth that doesn't mean passing a shallower row, which would be leaving the parent.
+pub fn next_sibling_index(depths: &[u32], current: usize) -> Option<usize> {
+    let depth = *depths.get(current)?;
+    depths.iter().enumerate().skip(current + 1)
+        .find(|(_, &other)| other <= depth)
+        .filter(|(_, &other)| other == depth)
+        .map(|(index, _)| index)
+}
+
+pub fn previous_sibling_index(depths: &[u32], current: usize) -> Option<usize> {
+    let depth = *depths.get(current)?;
+    depths[..current].iter().enumerate().rev()
+        .find(|(_, &other)| other <= depth)
+        .filter(|(_, &other)| other == depth)
+        .map(|(index, _)| index)
+}
+
+pub fn parent_index(depths: &[u32], current: usize) -> Option<usize> {
+    let depth = *depths.get(current)?;
+    depths[..current].iter().rposition(|&other| other < depth)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppMode {
     Welcome,
@@ -87,20 +217,102 @@ pub enum AppMode {
     TimeTracking,
     WorkspaceSelection,
     CreateWorkspace,
+    MoveToWorkspace,
+    SavedFilterSelection,
+    SaveFilter, // Typing a name for the current filters
+    RenameTag, // Typing a new name for the tag highlighted in the tag popup
+    RenameContext,
+    BulkTag, // Typing a tag to add to every todo in the visual selection
+    BulkContext,
+    BulkDue, // Typing a due expression for every todo in the visual selection
+    EditDue, // Typing a new due expression for the selected todo
+    LogTime, // Typing a duration to add to (or take off) the selected todo's tracked time
+    EditTimeEntry, // Typing a new note for the entry highlighted in the time entries popup
+    Calendar, // Week of due todos, navigated with `calendar`
+    Board, // Status columns, navigated with `board_selection`
+    Confirm, // Yes/no question about `pending_confirmation`
+    Export, // Picking a format from ExportFormat::ALL
+    ExportPath, // Typing where the export picked in the popup goes
+    ImportPath, // Typing the path of a todo.txt file to add to the workspace
+    ImportWorkspacePath, // Typing the path of a shared workspace file
+    ImportWorkspaceName, // Renaming a shared workspace whose name is taken
     // Bulk operations
     Visual,
     BulkOperation,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WelcomeOption {
+    GetStarted,
+    BrowseWorkspaces,
+    LearnBasics,
+    QuickDemo,
+    RemoveDemo,
+    CreateWorkspace,
+    Exit,
+}
+
+impl WelcomeOption {
+    // Title and description on the welcome screen
+    pub fn label(self, first_launch: bool) -> (&'static str, &'static str) {
+        match self {
+            WelcomeOption::GetStarted => ("🚀 Get Started", "Create your first todo and jump right in"),
+            WelcomeOption::BrowseWorkspaces if first_launch => ("📂 Browse Workspaces", "Explore existing workspaces or create new ones"),
+            WelcomeOption::BrowseWorkspaces => ("📂 Browse Workspaces", "Select from your existing workspaces"),
+            WelcomeOption::LearnBasics => ("❓ Learn the Basics", "View help and keyboard shortcuts"),
+            WelcomeOption::QuickDemo => ("⚡ Quick Demo", "See Paperclip in action with sample todos in a Demo workspace"),
+            WelcomeOption::RemoveDemo => ("🧹 Remove Demo Workspace", "Delete the Quick Demo's workspace and its todos"),
+            WelcomeOption::CreateWorkspace => ("🆕 Create New Workspace", "Start fresh with a new workspace"),
+            WelcomeOption::Exit => ("❌ Exit", "Close Paperclip"),
+        }
+    }
+}
+
+// Destructive actions waiting for a yes/no answer
 #[derive(Debug, Clone, PartialEq)]
-pub enum ViewMode {
-    All,
-    Pending,
-    Completed,
-    Search(String),
-    FilterByTag(String),
-    FilterByContext(String),
-    FilterByDueDate(DueDateFilter),
+pub enum Confirmation {
+    DeleteTag(String),
+    DeleteContext(String),
+    PasteTodos(String), // Pasted outline, one todo per line
+    BulkDelete(usize), // Deleting the visual selection, this many todos
+    OverwriteExport(PathBuf), // Exporting over a file that's already there
+}
+
+// Board columns, left to right
+pub const BOARD_COLUMNS: [TodoStatus; 3] = [TodoStatus::Pending, TodoStatus::InProgress, TodoStatus::Completed];
+
+// Where the week calendar is looking
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarState {
+    pub week_start: NaiveDate, // Always a Monday
+    pub focused_day: Option<usize>, // 0 is Monday; None is the overdue gutter
+    pub selected_idx: usize, // Todo highlighted in the focused column
+}
+
+impl CalendarState {
+    // The week holding `day`, focused on it
+    pub fn for_day(day: NaiveDate) -> Self {
+        let offset = day.weekday().num_days_from_monday() as usize;
+        Self {
+            week_start: day - chrono::Duration::days(offset as i64),
+            focused_day: Some(offset),
+            selected_idx: 0,
+        }
+    }
+    
+    pub fn days(&self) -> RangeInclusive<NaiveDate> {
+        self.week_start..=self.week_start + chrono::Duration::days(6)
+    }
+}
+
+// A visible todo: (owning workspace id, todo, depth)
+pub type VisibleRow<'a> = (&'a str, &'a Todo, u32);
+
+// A line of the rendered list: a group header, or an index into the visible todos
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListRow {
+    Header(String, usize), // Label and number of todos in the group
+    Todo(usize),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -113,33 +325,113 @@ pub enum BulkOperationType {
     MoveTo(String), // Move to different workspace
 }
 
+// Everything the visible rows are worked out from. The todos themselves are covered by the
+// generation, which every change to them bumps through `mark_dirty`.
+#[derive(Debug, Clone, PartialEq)]
+struct VisibleRowsKey {
+    generation: u64,
+    workspace: Option<String>,
+    view_mode: ViewMode,
+    filters: FilterSet,
+    group_by: GroupBy,
+    search_scope: SearchScope,
+    minute: i64, // Due filters and agenda buckets move on with the clock
+}
+
+// The last visible rows as (group, workspace id, todo id, depth), kept between calls so
+// drawing a frame doesn't filter and sort the whole tree each time it asks
+#[derive(Debug, Clone)]
+struct VisibleRowsCache {
+    key: VisibleRowsKey,
+    rows: Vec<(Option<String>, String, u32, u32)>,
+}
+
+// View state remembered for each workspace while switching between them
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceViewState {
+    pub selected: usize,
+    pub selected_todo_id: Option<u32>,
+    pub view_mode: ViewMode,
+    pub filters: FilterSet,
+}
+
 pub struct App {
     pub workspace_manager: WorkspaceManager,
     pub mode: AppMode,
     pub view_mode: ViewMode,
+    pub filters: FilterSet, // Status, tag, context, due date and text filters applied together
+    pub group_by: GroupBy,
     pub selected: usize,
-    pub input_buffer: String,
-    pub search_buffer: String,
-    pub search_cursor_pos: usize, // Cursor position in search buffer
-    pub colors: TokyoNightColors,
+    pub selected_anchor: Option<(String, u32)>, // Workspace and id of the selected todo, to find it again after changes
+    pub list_offset: usize, // First list row on screen
+    pub list_view_height: usize, // List rows that fit on screen, recorded when drawing
+    pub scrolloff: usize, // Rows kept visible above and below the selection
+    pub allow_concurrent_timers: bool, // Off: starting a timer stops the running one
+    pub pomodoro: Option<Pomodoro>, // Work or break countdown, advanced on each tick
+    pub bell_pending: bool, // Ring the terminal bell on the next tick
+    pub reminders_checked_at: Option<chrono::DateTime<chrono::Local>>, // Due times before this have been dealt with
+    pub reminder_banner_dismissed: bool, // Hidden with Ctrl+X for the rest of the session
+    pub notified: HashSet<(String, u32, chrono::DateTime<chrono::Local>, ReminderKind)>, // Workspace, todo and due time already notified
+    pub workspace_view_states: HashMap<String, WorkspaceViewState>,
+    pub input_buffer: TextBuffer, // Add todo, create workspace and popup prompts
+    pub search_buffer: TextBuffer,
+    pub search_scope: SearchScope, // Which workspaces a search covers (toggled with Ctrl+W)
+    pub view_before_search: Option<(FilterSet, usize)>, // Restored when a search is cancelled
+    pub search_history: Vec<String>, // Most recent last
+    pub search_history_index: Option<usize>, // Entry being shown while browsing with Up/Down
+    pub search_draft: String, // What was typed before browsing history
+    pub last_search: Option<String>, // Query used by n/N match navigation
+    pub colors: Theme,
+    pub true_color: bool, // Off: theme colors are mapped to the 16 ANSI colors
+    pub no_color: bool, // NO_COLOR or `monochrome = true`: no colors, whatever the theme
+    pub glyphs: Glyphs, // Unicode or ASCII symbols for the list and menus
     pub should_quit: bool,
+    pub shutdown_signal: Arc<AtomicBool>, // Set from a signal handler; checked once per loop
+    pub dirty: bool, // Changes not yet written to disk
+    pub generation: u64, // Bumped on every change to the todos, to tell when cached rows are stale
+    visible_rows: RefCell<Option<VisibleRowsCache>>,
+    pub save_now: bool, // Write on the next pass of the loop instead of waiting for the interval
+    pub last_saved: Instant, // Last write, successful or not
     pub show_help: bool,
-    pub message: Option<String>,
+    pub help_scroll: usize, // First help row shown
+    pub help_view_height: usize, // Help rows that fit on screen, recorded when drawing
+    pub help_filter: String, // Only help lines containing this (and their headings) are shown
+    pub help_filtering: bool, // Typing into the help filter after `/`
+    pub show_time_report: bool, // Full-screen time report; any key closes it
+    pub messages: MessageQueue, // The status bar message on show, and any error waiting under it
+    pub data_warning: Option<String>, // Problem loading the saved data; stays on screen all session
+    pub data_dir: Option<PathBuf>, // Where the data is saved, shown on the help screen
     pub inserting_child_for: Option<u32>, // Track which todo we're adding a child for
     // Selection popup state
+    pub pending_key: Option<char>, // First key of a two-key sequence such as `g t`
+    pub keymap: Keymap,
+    pub config: Config,
+    pub display: DisplaySettings, // Saved in its own file, not with the workspace data
+    pub pending_confirmation: Option<Confirmation>,
+    pub export_format: Option<ExportFormat>, // Picked in the export popup, waiting for a path
+    pub export_workspace_id: Option<String>, // Picked with x in the workspace screen; otherwise the current one
+    pub return_mode: Option<AppMode>, // Screen a path or name prompt was opened over, when not the todo list
+    pub pending_workspace_import: Option<(Workspace, TodoList)>, // Read from a file, waiting for a free name
+    pub completion: Option<Completion>, // Tab completion in progress while typing a todo
     pub popup_selected: usize,
+    pub calendar: CalendarState,
+    pub board_selection: (usize, usize), // Column in BOARD_COLUMNS and row within it
+    pub popup_exclude: bool, // Tag/context popup applies an exclusion instead of a filter
     pub available_tags: Vec<String>,
     pub available_contexts: Vec<String>,
     
     // Advanced features
     pub template_manager: TemplateManager,
-    pub notes_buffer: String, // For editing notes
-    pub notes_cursor_pos: usize, // Cursor position in notes buffer
+    pub notes_buffer: TextBuffer, // For editing notes
+    pub notes_scroll: usize, // First notes row shown in the editor/viewer
+    pub notes_view_height: usize, // Notes rows that fit on screen, recorded when drawing
+    pub notes_selected_line: usize, // Highlighted line in the notes viewer
     pub editing_notes_for: Option<u32>, // Which todo's notes we're editing
-    pub edit_buffer: String, // For editing todo descriptions
-    pub edit_cursor_pos: usize, // Cursor position in edit buffer
+    pub external_editor_request: Option<String>, // Editor to launch on the next loop turn
+    pub edit_buffer: TextBuffer, // For editing todo descriptions
     pub editing_todo_id: Option<u32>, // Which todo's description we're editing
-    pub input_cursor_pos: usize, // Cursor position in input buffer
+    pub editing_workspace_id: Option<String>, // Workspace owning the todo being edited, annotated or given a child
+    pub quick_entry_count: usize, // Todos added with Shift+Enter since Insert mode opened
     pub available_templates: Vec<String>, // Template IDs for selection
     pub available_recurrence: Vec<RecurrencePattern>, // For recurrence selection
     
@@ -153,53 +445,105 @@ pub struct App {
     pub selected_todos: std::collections::HashSet<u32>,
     pub visual_start: Option<usize>, // Starting position for visual selection
     pub bulk_operation: Option<BulkOperationType>,
+    pub move_from_visual: bool, // Workspace move popup was opened from visual mode
+    pub tag_select_from_visual: bool, // Tag popup adds to the visual selection instead of filtering
     
     // Welcome screen
     pub welcome_selected: usize, // Selected option on welcome screen
-    pub is_first_launch: bool, // Track if this is the first time using the app
+    pub is_first_launch: bool, // Nothing was saved before this run; set from storage with `set_first_launch`
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         let mut workspace_manager = WorkspaceManager::new();
         // Create initial workspace
         workspace_manager.create_workspace("Personal".to_string(), Some("Your personal todos".to_string()));
+        for todo_list in workspace_manager.workspace_todos.values_mut() {
+            todo_list.sort_mode = config.default_sort;
+        }
         
-        // Get available workspace names for initial selection
+        // Get available workspace ids for initial selection
         let available_workspaces: Vec<String> = workspace_manager.get_all_workspaces()
             .iter()
-            .map(|ws| ws.name.clone())
+            .map(|ws| ws.id.clone())
             .collect();
         
-        // Check if this is first launch (no workspaces beyond default means first time)
-        let is_first_launch = available_workspaces.len() <= 1;
-        
-        Self {
+        let mut app = Self {
             workspace_manager,
-            mode: if is_first_launch { AppMode::Welcome } else { AppMode::WorkspaceSelection },
-            view_mode: ViewMode::All,
+            mode: AppMode::Welcome,
+            view_mode: config.default_view.clone(),
+            filters: FilterSet::default(),
+            group_by: GroupBy::None,
             selected: 0,
-            input_buffer: String::new(),
-            search_buffer: String::new(),
-            search_cursor_pos: 0,
-            colors: TokyoNightColors::new(),
+            selected_anchor: None,
+            list_offset: 0,
+            list_view_height: 0,
+            scrolloff: config.scrolloff,
+            allow_concurrent_timers: false,
+            pomodoro: None,
+            bell_pending: false,
+            reminders_checked_at: None,
+            reminder_banner_dismissed: false,
+            notified: HashSet::new(),
+            workspace_view_states: HashMap::new(),
+            input_buffer: TextBuffer::new(),
+            search_buffer: TextBuffer::new(),
+            search_scope: SearchScope::Workspace,
+            view_before_search: None,
+            search_history: Vec::new(),
+            search_history_index: None,
+            search_draft: String::new(),
+            last_search: None,
+            colors: if config.monochrome { Theme::monochrome() } else { config.theme.theme() },
+            true_color: true,
+            no_color: config.monochrome,
+            glyphs: if config.ascii { Glyphs::ASCII } else { Glyphs::UNICODE },
             should_quit: false,
+            shutdown_signal: Arc::new(AtomicBool::new(false)),
+            dirty: false,
+            generation: 0,
+            visible_rows: RefCell::new(None),
+            save_now: false,
+            last_saved: Instant::now(),
             show_help: false,
-            message: Some("Select a workspace to get started".to_string()),
+            help_scroll: 0,
+            help_view_height: 0,
+            help_filter: String::new(),
+            help_filtering: false,
+            show_time_report: false,
+            messages: MessageQueue::default(),
+            data_warning: None,
+            data_dir: None,
             inserting_child_for: None,
+            pending_key: None,
+            keymap: Keymap::default(),
+            config,
+            display: DisplaySettings::default(),
+            pending_confirmation: None,
+            export_format: None,
+            export_workspace_id: None,
+            return_mode: None,
+            pending_workspace_import: None,
+            completion: None,
             popup_selected: 0,
+            calendar: CalendarState::for_day(chrono::Local::now().date_naive()),
+            board_selection: (0, 0),
+            popup_exclude: false,
             available_tags: Vec::new(),
             available_contexts: Vec::new(),
             
             // Initialize advanced features
             template_manager: TemplateManager::with_builtin_templates(),
-            notes_buffer: String::new(),
-            notes_cursor_pos: 0,
+            notes_buffer: TextBuffer::new(),
+            notes_scroll: 0,
+            external_editor_request: None,
+            notes_view_height: 0,
+            notes_selected_line: 0,
             editing_notes_for: None,
-            edit_buffer: String::new(),
-            edit_cursor_pos: 0,
+            edit_buffer: TextBuffer::new(),
             editing_todo_id: None,
-            input_cursor_pos: 0,
+            editing_workspace_id: None,
+            quick_entry_count: 0,
             available_templates: Vec::new(),
             available_recurrence: vec![
                 RecurrencePattern::None,
@@ -213,13 +557,23 @@ impl App {
             selected_todos: std::collections::HashSet::new(),
             visual_start: None,
             bulk_operation: None,
+            move_from_visual: false,
+            tag_select_from_visual: false,
             welcome_selected: 0,
-            is_first_launch,
-        }
+            is_first_launch: true,
+        };
+        app.set_message("Select a workspace to get started".to_string(), MessageLevel::Info, LONG);
+        app
     }
     
     // Bulk operations functionality
     pub fn enter_visual_mode(&mut self) {
+        // Bulk selections are tracked by id, which is only unique within one workspace
+        if self.is_cross_workspace_view() {
+            self.set_message("Visual mode is not available in cross-workspace views".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        
         self.mode = AppMode::Visual;
         self.visual_start = Some(self.selected);
         self.selected_todos.clear();
@@ -227,7 +581,7 @@ impl App {
         if let Some(id) = self.get_selected_todo_id() {
             self.selected_todos.insert(id);
         }
-        self.set_message("Visual mode - use j/k to select, Space to toggle, Enter to apply operation".to_string());
+        self.set_message("Visual mode - use j/k to select, Space to toggle, Enter to apply operation".to_string(), MessageLevel::Info, LONG);
     }
     
     pub fn exit_visual_mode(&mut self) {
@@ -237,6 +591,18 @@ impl App {
         self.bulk_operation = None;
     }
     
+    // Bulk-selected todos that are still on screen, in list order. Filters or collapsed parents
+    // can hide some after they were picked; bulk operations leave those alone.
+    pub fn get_bulk_selection(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = Vec::new();
+        for (_, todo, _) in self.get_visible_todos() {
+            if self.selected_todos.contains(&todo.id) && !ids.contains(&todo.id) {
+                ids.push(todo.id);
+            }
+        }
+        ids
+    }
+    
     pub fn toggle_selection_in_visual(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
             if self.selected_todos.contains(&id) {
@@ -247,6 +613,41 @@ impl App {
         }
     }
     
+    fn visible_todo_ids(&self) -> Vec<u32> {
+        self.get_visible_todos().into_iter().map(|(_, todo, _)| todo.id).collect()
+    }
+
+    pub fn visual_select_all(&mut self) {
+        let visible = self.visible_todo_ids();
+        select_all_visible(&mut self.selected_todos, &visible);
+        self.set_message(format!("Selected {} todos", self.selected_todos.len()), MessageLevel::Info, SHORT);
+    }
+
+    pub fn visual_invert_selection(&mut self) {
+        let visible = self.visible_todo_ids();
+        invert_visible_selection(&mut self.selected_todos, &visible);
+        self.set_message(format!("Selected {} todos", self.selected_todos.len()), MessageLevel::Info, SHORT);
+    }
+
+    // Open the tag popup; picking a tag adds its visible todos to the selection
+    pub fn enter_visual_tag_selection(&mut self) {
+        self.enter_tag_selection();
+        self.tag_select_from_visual = self.mode == AppMode::TagSelection;
+    }
+
+    pub fn select_visible_with_tag(&mut self, tag: &str) {
+        let tagged: Vec<u32> = self.get_visible_todos().into_iter()
+            .filter(|(_, todo, _)| todo.tags.contains(tag))
+            .map(|(_, todo, _)| todo.id)
+            .collect();
+        if tagged.is_empty() {
+            self.set_message(format!("No visible todos tagged #{}", tag), MessageLevel::Warning, LONG);
+            return;
+        }
+        self.selected_todos.extend(tagged.iter().copied());
+        self.set_message(format!("Added {} todos tagged #{} ({} selected)", tagged.len(), tag, self.selected_todos.len()), MessageLevel::Success, SHORT);
+    }
+    
     pub fn select_range_in_visual(&mut self) {
         if let Some(start) = self.visual_start {
             let end = self.selected;
@@ -261,7 +662,7 @@ impl App {
             // Collect todo IDs first to avoid borrowing issues
             let mut todo_ids = Vec::new();
             for i in start_idx..=end_idx.min(todos.len().saturating_sub(1)) {
-                if let Some((todo, _)) = todos.get(i) {
+                if let Some((_, todo, _)) = todos.get(i) {
                     todo_ids.push(todo.id);
                 }
             }
@@ -275,37 +676,59 @@ impl App {
     }
     
     pub fn bulk_complete_todos(&mut self) {
-        if self.selected_todos.is_empty() {
-            self.set_message("No todos selected for bulk operation".to_string());
+        if self.get_bulk_selection().is_empty() {
+            self.set_message("No todos selected for bulk operation".to_string(), MessageLevel::Warning, LONG);
             return;
         }
         
-        let mut completed_count = 0;
-        let selected_ids: Vec<u32> = self.selected_todos.iter().cloned().collect();
+        let mut changes = Vec::new();
+        let mut selected_ids = self.get_bulk_selection();
+        selected_ids.sort_unstable();
         
         if let Some(todo_list) = self.get_current_todo_list_mut() {
             for id in selected_ids {
                 if let Some(todo) = todo_list.get_todo_mut(id) {
                     if !todo.is_completed() {
+                        changes.push((id, todo.status.clone(), todo.completed_at));
                         todo.complete();
-                        completed_count += 1;
                     }
                 }
             }
         }
         
-        self.set_message(format!("Bulk completed {} todos", completed_count));
+        // One undo step for the whole batch
+        let completed_count = changes.len();
+        if !changes.is_empty() {
+            self.mark_dirty();
+            if let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() {
+                self.command_history.push_command(Command::BulkComplete { workspace_id, changes });
+            }
+        }
+        
+        self.set_message(format!("Bulk completed {} todos. Press 'u' to undo.", completed_count), MessageLevel::Success, SHORT);
         self.exit_visual_mode();
     }
     
     pub fn bulk_delete_todos(&mut self) {
-        if self.selected_todos.is_empty() {
-            self.set_message("No todos selected for bulk operation".to_string());
+        let count = self.get_bulk_selection().len();
+        if count == 0 {
+            self.set_message("No todos selected for bulk operation".to_string(), MessageLevel::Warning, LONG);
             return;
         }
         
+        // Large deletions ask first, past the configured threshold
+        let threshold = self.config.confirm_bulk_delete_over;
+        if threshold > 0 && count > threshold {
+            self.pending_confirmation = Some(Confirmation::BulkDelete(count));
+            self.mode = AppMode::Confirm;
+            return;
+        }
+        self.delete_bulk_selection();
+    }
+    
+    fn delete_bulk_selection(&mut self) {
         let mut deleted_todos = Vec::new();
-        let selected_ids: Vec<u32> = self.selected_todos.iter().cloned().collect();
+        let selected_ids = self.get_bulk_selection();
         
         if let Some(todo_list) = self.get_current_todo_list_mut() {
             for id in selected_ids {
@@ -318,6 +741,7 @@ impl App {
         
         // Record command for undo
         if !deleted_todos.is_empty() {
+            self.request_save();
             if let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() {
                 let command = Command::DeleteWithChildren { workspace_id, deleted_todos: deleted_todos.clone() };
                 self.command_history.push_command(command);
@@ -325,52 +749,338 @@ impl App {
         }
         
         let count = deleted_todos.len();
-        self.set_message(format!("Bulk deleted {} todos. Press 'u' to undo.", count));
+        self.set_message(format!("Bulk deleted {} todos. Press 'u' to undo.", count), MessageLevel::Success, SHORT);
         self.exit_visual_mode();
         
         // Adjust selection after deletion
         let todos = self.get_visible_todos();
-        if self.selected >= todos.len() && todos.len() > 0 {
+        if self.selected >= todos.len() && !todos.is_empty() {
             self.selected = todos.len() - 1;
         }
     }
     
     pub fn bulk_set_priority(&mut self, priority: u8) {
-        if self.selected_todos.is_empty() {
-            self.set_message("No todos selected for bulk operation".to_string());
+        if self.get_bulk_selection().is_empty() {
+            self.set_message("No todos selected for bulk operation".to_string(), MessageLevel::Warning, LONG);
             return;
         }
         
         let mut updated_count = 0;
-        let selected_ids: Vec<u32> = self.selected_todos.iter().cloned().collect();
+        let mut changes = Vec::new();
+        let mut selected_ids = self.get_bulk_selection();
+        selected_ids.sort_unstable();
         
         if let Some(todo_list) = self.get_current_todo_list_mut() {
             for id in selected_ids {
                 if let Some(todo) = todo_list.get_todo_mut(id) {
+                    if todo.priority != priority {
+                        changes.push((id, todo.priority));
+                    }
                     todo.priority = priority;
                     updated_count += 1;
                 }
             }
         }
         
-        self.set_message(format!("Set priority to {} for {} todos", priority, updated_count));
+        if !changes.is_empty() {
+            self.mark_dirty();
+            if let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() {
+                self.command_history.push_command(Command::BulkSetPriority { workspace_id, changes, new_priority: priority });
+            }
+        }
+        
+        self.set_message(format!("Set priority to {} for {} todos. Press 'u' to undo.", priority, updated_count), MessageLevel::Success, SHORT);
+        self.exit_visual_mode();
+    }
+    
+    // `t` / `@` in visual mode: prompt for a tag or context to add to every selected todo
+    pub fn enter_bulk_label_mode(&mut self, is_tag: bool) {
+        if self.get_bulk_selection().is_empty() {
+            self.set_message("No todos selected for bulk operation".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        self.mode = if is_tag { AppMode::BulkTag } else { AppMode::BulkContext };
+        self.clear_input_buffer();
+        self.completion = None;
+    }
+    
+    pub fn submit_bulk_label(&mut self) {
+        let is_tag = self.mode == AppMode::BulkTag;
+        let marker = if is_tag { '#' } else { '@' };
+        let label = self.input_buffer.content.trim().trim_start_matches(marker).to_string();
+        if label.is_empty() || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            self.set_message("Use letters, digits and underscores only".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        
+        let mut ids = self.get_bulk_selection();
+        ids.sort_unstable();
+        let count = self.bulk_add_label(is_tag, &ids, &label);
+        
+        self.clear_input_buffer();
+        let plural = if count == 1 { "" } else { "s" };
+        self.set_message(format!("Added {}{} to {} todo{}. Press 'u' to undo.", marker, label.to_lowercase(), count, plural), MessageLevel::Success, SHORT);
+        self.exit_visual_mode();
+    }
+    
+    // Add a tag or context to the given todos as one undoable step
+    fn bulk_add_label(&mut self, is_tag: bool, ids: &[u32], label: &str) -> usize {
+        let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() else { return 0 };
+        let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) else { return 0 };
+        
+        let before: Vec<(u32, String)> = ids.iter()
+            .filter_map(|id| todo_list.get_todo(*id).map(|todo| (*id, todo.raw_description.clone())))
+            .collect();
+        let count = if is_tag { todo_list.add_tag(ids, label) } else { todo_list.add_context(ids, label) };
+        
+        // Only todos that didn't already carry the label were rewritten
+        let mut old_raw_descriptions = Vec::new();
+        let mut new_raw_descriptions = Vec::new();
+        for (id, old_raw) in before {
+            if let Some(todo) = todo_list.get_todo(id) {
+                if todo.raw_description != old_raw {
+                    new_raw_descriptions.push((id, todo.raw_description.clone()));
+                    old_raw_descriptions.push((id, old_raw));
+                }
+            }
+        }
+        
+        if count > 0 {
+            self.mark_dirty();
+            self.command_history.push_command(Command::RewriteDescriptions {
+                workspace_id,
+                old_raw_descriptions,
+                new_raw_descriptions,
+            });
+        }
+        count
+    }
+    
+    // `g` in visual mode: prompt for a due expression to give every selected todo
+    pub fn enter_bulk_due_mode(&mut self) {
+        if self.get_bulk_selection().is_empty() {
+            self.set_message("No todos selected for bulk operation".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        self.mode = AppMode::BulkDue;
+        self.clear_input_buffer();
+    }
+    
+    // Same grammar as `due:`, or "none" to clear. An expression that doesn't parse keeps the prompt open.
+    pub fn submit_bulk_due(&mut self) {
+        let Some(new_due) = self.parse_due_input() else { return };
+        
+        let mut selected_ids = self.get_bulk_selection();
+        selected_ids.sort_unstable();
+        let mut changes = Vec::new();
+        if let Some(todo_list) = self.get_current_todo_list_mut() {
+            for id in selected_ids {
+                if let Some(todo) = todo_list.get_todo_mut(id) {
+                    if todo.due_date != new_due {
+                        changes.push((id, todo.due_date, todo.raw_description.clone()));
+                        todo.set_due_date(new_due);
+                    }
+                }
+            }
+        }
+        let count = changes.len();
+        if !changes.is_empty() {
+            self.mark_dirty();
+            if let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() {
+                self.command_history.push_command(Command::BulkSetDue { workspace_id, changes, new_due });
+            }
+        }
+        
+        self.clear_input_buffer();
+        match new_due {
+            Some(due) => self.set_message(format!("Set due {} for {} todos. Press 'u' to undo.", due.format("%Y-%m-%d"), count), MessageLevel::Success, SHORT),
+            None => self.set_message(format!("Cleared due date on {} todos. Press 'u' to undo.", count), MessageLevel::Success, SHORT),
+        }
         self.exit_visual_mode();
     }
     
+    // The due prompts' input: Some(None) for "none", None (with the error shown) when it doesn't parse
+    fn parse_due_input(&mut self) -> Option<Option<chrono::DateTime<chrono::Local>>> {
+        let expression = self.input_buffer.content.trim().to_string();
+        if expression.eq_ignore_ascii_case("none") {
+            return Some(None);
+        }
+        match Todo::parse_due_date(&expression) {
+            Some(due) => Some(Some(due)),
+            None => {
+                self.set_message(format!("Can't read '{}' as a due date (try friday, in 3 days, 2024-12-25 or none)", expression), MessageLevel::Error, STICKY);
+                None
+            }
+        }
+    }
+    
+    // Change just the due date of the selected todo, starting from the one it has
+    pub fn enter_due_edit_mode(&mut self) {
+        let Some(todo) = self.get_selected_todo() else { return };
+        let current = todo.due_input();
+        self.editing_todo_id = Some(todo.id);
+        self.editing_workspace_id = self.get_selected_workspace_id();
+        self.input_buffer.set(current);
+        self.mode = AppMode::EditDue;
+    }
+    
+    pub fn submit_due_edit(&mut self) {
+        // Left as it was: a relative `due:friday` read again now could land on another day
+        let unchanged = self.editing_todo_id
+            .zip(self.get_editing_todo_list())
+            .and_then(|(id, todo_list)| todo_list.get_todo(id))
+            .is_some_and(|todo| todo.due_input() == self.input_buffer.content.trim());
+        if unchanged {
+            self.exit_due_edit_mode();
+            return;
+        }
+        let Some(new_due) = self.parse_due_input() else { return };
+        let id = self.editing_todo_id;
+        let workspace_id = self.editing_workspace_id.clone();
+        let todo = id.zip(self.get_editing_todo_list_mut()).and_then(|(id, todo_list)| todo_list.get_todo_mut(id));
+        if let Some(todo) = todo {
+            if todo.due_date != new_due {
+                let change = (todo.id, todo.due_date, todo.raw_description.clone());
+                todo.set_due_date(new_due);
+                self.mark_dirty();
+                if let Some(workspace_id) = workspace_id {
+                    self.command_history.push_command(Command::BulkSetDue { workspace_id, changes: vec![change], new_due });
+                }
+            }
+            match new_due {
+                Some(due) => self.set_message(format!("Due {}. Press 'u' to undo.", due.format("%Y-%m-%d")), MessageLevel::Success, SHORT),
+                None => self.set_message("Due date cleared. Press 'u' to undo.".to_string(), MessageLevel::Success, SHORT),
+            }
+        }
+        self.exit_due_edit_mode();
+        self.resync_selection();
+    }
+    
+    pub fn exit_due_edit_mode(&mut self) {
+        self.mode = AppMode::Normal;
+        self.clear_input_buffer();
+        self.editing_todo_id = None;
+        self.editing_workspace_id = None;
+    }
+    
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
+    
+    // Anything that changes saved data calls this; the main loop writes it out once the
+    // autosave interval has passed
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.invalidate_visible_rows();
+    }
+    
+    // For changes that don't need saving, such as data just loaded or merged in from disk
+    pub fn invalidate_visible_rows(&mut self) {
+        self.generation += 1;
+    }
+    
+    // Deletions are written straight away rather than risking them on the interval
+    pub fn request_save(&mut self) {
+        self.mark_dirty();
+        self.save_now = true;
+    }
+    
+    pub fn autosave_due(&self, now: Instant) -> bool {
+        self.dirty && (self.save_now || now.duration_since(self.last_saved) >= self.config.autosave_interval)
+    }
+    
+    // A failed write keeps the changes pending and is retried after the interval. A note means
+    // changes made elsewhere were merged in, so the lists on screen may have changed.
+    pub fn finish_autosave(&mut self, now: Instant, result: std::io::Result<Option<String>>) {
+        self.last_saved = now;
+        self.save_now = false;
+        match result {
+            Ok(note) => {
+                self.dirty = false;
+                if let Some(note) = note {
+                    self.invalidate_visible_rows();
+                    self.available_workspaces = self.workspace_manager.get_all_workspaces()
+                        .iter()
+                        .map(|ws| ws.id.clone())
+                        .collect();
+                    self.resync_selection();
+                    self.set_message(note, MessageLevel::Info, LONG);
+                }
+            }
+            Err(e) => self.set_message(format!("Autosave failed: {}", e), MessageLevel::Error, STICKY),
+        }
+    }
+    
+    // A termination signal quits the same way `q` does, so the data is still saved
+    pub fn poll_shutdown_signal(&mut self) {
+        if self.shutdown_signal.load(Ordering::Relaxed) {
+            self.quit();
+        }
+    }
 
+    // Help always opens at the top with no filter
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
+        self.help_scroll = 0;
+        self.help_filter.clear();
+        self.help_filtering = false;
+    }
+
+    // The screen clamps the offset to the help's length when it draws
+    pub fn scroll_help(&mut self, down: bool, rows: usize) {
+        self.help_scroll = if down { self.help_scroll.saturating_add(rows) } else { self.help_scroll.saturating_sub(rows) };
+    }
+
+    pub fn start_help_filter(&mut self) {
+        self.help_filtering = true;
+        self.help_filter.clear();
+        self.help_scroll = 0;
+    }
+
+    pub fn edit_help_filter(&mut self, c: Option<char>) {
+        match c {
+            Some(c) => self.help_filter.push(c),
+            None => {
+                self.help_filter.pop();
+            }
+        }
+        self.help_scroll = 0;
+    }
+
+    // Enter keeps the filter, Esc drops it
+    pub fn finish_help_filter(&mut self, keep: bool) {
+        self.help_filtering = false;
+        if !keep {
+            self.help_filter.clear();
+            self.help_scroll = 0;
+        }
+    }
+    
+    pub fn toggle_time_report(&mut self) {
+        self.show_time_report = !self.show_time_report;
     }
 
     pub fn clear_message(&mut self) {
-        self.message = None;
+        self.messages.clear();
+    }
+
+    // `ttl` of None keeps the message up until it's dismissed or replaced; meant for errors
+    pub fn set_message(&mut self, text: String, level: MessageLevel, ttl: Option<std::time::Duration>) {
+        self.messages.push(text, level, ttl, Instant::now());
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.messages.current().map(|message| message.text.as_str())
     }
 
-    pub fn set_message(&mut self, msg: String) {
-        self.message = Some(msg);
+    // Esc takes down an error before doing anything else
+    pub fn dismiss_error(&mut self) -> bool {
+        if self.messages.current().is_some_and(|message| message.level == MessageLevel::Error) {
+            self.messages.dismiss();
+            true
+        } else {
+            false
+        }
     }
 
     // Workspace helper methods
@@ -378,6 +1088,7 @@ impl App {
         self.workspace_manager.get_current_todo_list()
     }
     
+    // Callers mark the data dirty once they have actually changed something
     pub fn get_current_todo_list_mut(&mut self) -> Option<&mut TodoList> {
         self.workspace_manager.get_current_todo_list_mut()
     }
@@ -388,72 +1099,523 @@ impl App {
             .unwrap_or_else(|| "No Workspace".to_string())
     }
 
-    pub fn get_visible_todos(&self) -> Vec<(&Todo, u32)> {
-        let todo_list = match self.get_current_todo_list() {
-            Some(list) => list,
-            None => return Vec::new(),
-        };
-        
-        match &self.view_mode {
-            ViewMode::All => todo_list.get_flattened_todos(),
-            ViewMode::Pending => todo_list.get_flattened_pending_todos(),
-            ViewMode::Completed => todo_list.get_flattened_completed_todos(),
-            ViewMode::Search(query) => {
-                // Search across all workspaces as requested
-                let search_results = self.workspace_manager.search_all_workspaces(query);
-                let mut all_results = Vec::new();
-                for (_, results) in search_results {
-                    all_results.extend(results);
-                }
-                all_results
-            },
-            ViewMode::FilterByTag(tag) => todo_list.filter_by_tag(tag),
-            ViewMode::FilterByContext(context) => todo_list.filter_by_context(context),
-            ViewMode::FilterByDueDate(filter) => todo_list.filter_by_due_date(*filter),
-        }
+    // Workspace name prefixed with its number, e.g. "2:Work"
+    pub fn get_current_workspace_label(&self) -> String {
+        let name = self.get_current_workspace_name();
+        self.workspace_manager.get_current_workspace_id()
+            .and_then(|id| self.workspace_manager.workspace_number(&id))
+            .map(|number| format!("{}:{}", number, name))
+            .unwrap_or(name)
     }
 
-    pub fn get_selected_todo_id(&self) -> Option<u32> {
-        let todos = self.get_visible_todos();
-        todos.get(self.selected).map(|(todo, _)| todo.id)
+    // Explicit workspace color, or one picked from its position in the list
+    pub fn get_workspace_color(&self, workspace_id: &str) -> ratatui::style::Color {
+        let index = self.workspace_manager.workspaces.get(workspace_id)
+            .and_then(|ws| ws.color)
+            .or_else(|| self.workspace_manager.workspace_number(workspace_id).map(|n| (n - 1) as u8))
+            .unwrap_or(0);
+        self.colors.workspace_color(index)
     }
 
-    pub fn move_selection_up(&mut self) {
-        if self.selected > 0 {
-            self.selected -= 1;
+    // Visible rows as (workspace id, todo, depth); cross-workspace views mix workspaces.
+    // When grouping, rows come group by group and a todo can appear in several groups.
+    pub fn get_visible_todos(&self) -> Vec<(&str, &Todo, u32)> {
+        self.get_grouped_todos().into_iter().map(|(_, row)| row).collect()
+    }
+    
+    // Rows to draw, with a header before each group; `selected` only ever indexes todos
+    pub fn get_list_rows(&self) -> Vec<ListRow> {
+        let grouped = self.get_grouped_todos();
+        let mut rows = Vec::new();
+        let mut current_group: Option<&String> = None;
+        let mut header = 0; // Row of the current group's header, counted up as its todos go by
+        
+        for (index, (group, _)) in grouped.iter().enumerate() {
+            if let Some(group) = group {
+                if current_group != Some(group) {
+                    header = rows.len();
+                    rows.push(ListRow::Header(group.clone(), 0));
+                    current_group = Some(group);
+                }
+                if let ListRow::Header(_, count) = &mut rows[header] {
+                    *count += 1;
+                }
+            }
+            rows.push(ListRow::Todo(index));
         }
+        
+        rows
     }
-
-    pub fn move_selection_down(&mut self) {
-        let todos = self.get_visible_todos();
-        if self.selected < todos.len().saturating_sub(1) {
-            self.selected += 1;
+    
+    fn visible_rows_key(&self) -> VisibleRowsKey {
+        VisibleRowsKey {
+            generation: self.generation,
+            workspace: self.workspace_manager.current_workspace.clone(),
+            view_mode: self.view_mode.clone(),
+            filters: self.get_active_filters(),
+            group_by: self.group_by,
+            search_scope: self.search_scope,
+            minute: chrono::Local::now().timestamp() / 60,
         }
     }
-
-    pub fn go_to_top(&mut self) {
-        self.selected = 0;
+    
+    // Filtered rows paired with their group label, from the cache while nothing they depend on
+    // has changed
+    fn get_grouped_todos(&self) -> Vec<(Option<String>, VisibleRow<'_>)> {
+        let key = self.visible_rows_key();
+        if let Some(cache) = self.visible_rows.borrow().as_ref().filter(|cache| cache.key == key) {
+            return cache.rows.iter()
+                .filter_map(|(group, ws_id, id, depth)| {
+                    let (ws_id, todo_list) = self.workspace_manager.workspace_todos.get_key_value(ws_id)?;
+                    let todo = todo_list.get_todo(*id)?;
+                    Some((group.clone(), (ws_id.as_str(), todo, *depth)))
+                })
+                .collect();
+        }
+        
+        let grouped = self.build_grouped_todos();
+        let rows = grouped.iter()
+            .map(|(group, (ws_id, todo, depth))| (group.clone(), ws_id.to_string(), todo.id, *depth))
+            .collect();
+        *self.visible_rows.borrow_mut() = Some(VisibleRowsCache { key, rows });
+        grouped
     }
-
-    pub fn go_to_bottom(&mut self) {
-        let todos = self.get_visible_todos();
-        self.selected = todos.len().saturating_sub(1);
+    
+    // Groups sorted by name with the ungrouped last
+    fn build_grouped_todos(&self) -> Vec<(Option<String>, VisibleRow<'_>)> {
+        if self.view_mode == ViewMode::Agenda {
+            return self.get_agenda_todos();
+        }
+        
+        let rows = self.get_filtered_todos();
+        if self.group_by == GroupBy::None {
+            return rows.into_iter().map(|row| (None, row)).collect();
+        }
+        
+        let mut groups: BTreeMap<String, Vec<VisibleRow>> = BTreeMap::new();
+        let mut ungrouped = Vec::new();
+        for (ws_id, todo, _) in rows {
+            let labels = self.group_by.labels(todo);
+            if labels.is_empty() {
+                ungrouped.push((ws_id, todo, 0));
+            }
+            for label in labels {
+                groups.entry(label).or_default().push((ws_id, todo, 0));
+            }
+        }
+        
+        let empty_label = self.group_by.empty_label().to_string();
+        groups.into_iter()
+            .flat_map(|(label, rows)| rows.into_iter().map(move |row| (Some(label.clone()), row)))
+            .chain(ungrouped.into_iter().map(|row| (Some(empty_label.clone()), row)))
+            .collect()
     }
-
-    pub fn enter_insert_mode(&mut self) {
-        self.mode = AppMode::Insert;
-        self.clear_input_buffer();
+    
+    // Agenda rows under their bucket name; filters still narrow them
+    fn get_agenda_todos(&self) -> Vec<(Option<String>, VisibleRow<'_>)> {
+        let (Some(ws_id), Some(todo_list)) = (self.workspace_manager.current_workspace.as_deref(), self.get_current_todo_list()) else {
+            return Vec::new();
+        };
+        let filters = self.get_active_filters();
+        let now = chrono::Local::now();
+        
+        todo_list.agenda_buckets_at(now).into_iter()
+            .flat_map(|(bucket, todos)| {
+                todos.into_iter()
+                    .filter(|todo| filters.matches(todo, now))
+                    .map(move |todo| (Some(bucket.name().to_string()), (ws_id, todo, 0)))
+            })
+            .collect()
     }
-
-    pub fn enter_normal_mode(&mut self) {
-        self.mode = AppMode::Normal;
-        self.clear_input_buffer();
-        self.inserting_child_for = None;
+    
+    fn get_filtered_todos(&self) -> Vec<(&str, &Todo, u32)> {
+        let filters = self.get_active_filters();
+        let now = chrono::Local::now();
+        
+        // Views spanning every workspace
+        let global = match (&self.view_mode, &filters.query) {
+            (ViewMode::GlobalDue(filter), _) => Some(self.workspace_manager.collect_due(*filter)),
+            (ViewMode::List, Some(query)) if self.search_scope == SearchScope::All => {
+                Some(self.workspace_manager.search_all_workspaces(query))
+            }
+            _ => None,
+        };
+        if let Some(rows) = global {
+            return rows.into_iter()
+                .filter(|(_, todo)| filters.matches(todo, now))
+                .filter_map(|(ws_id, todo)| {
+                    self.workspace_manager.workspaces.get_key_value(&ws_id).map(|(id, _)| (id.as_str(), todo, 0))
+                })
+                .collect();
+        }
+        
+        let (ws_id, todo_list) = match (self.workspace_manager.current_workspace.as_deref(), self.get_current_todo_list()) {
+            (Some(ws_id), Some(list)) => (ws_id, list),
+            _ => return Vec::new(),
+        };
+        
+        todo_list.filter_todos(&filters).into_iter()
+            .map(|(todo, depth)| (ws_id, todo, depth))
+            .collect()
+    }
+
+    pub fn get_selected_todo(&self) -> Option<&Todo> {
+        self.get_visible_todos().get(self.selected).map(|(_, todo, _)| *todo)
+    }
+
+    pub fn get_selected_todo_id(&self) -> Option<u32> {
+        let todos = self.get_visible_todos();
+        todos.get(self.selected).map(|(_, todo, _)| todo.id)
+    }
+    
+    // Workspace owning the selected row, which may not be the current one
+    pub fn get_selected_workspace_id(&self) -> Option<String> {
+        let todos = self.get_visible_todos();
+        todos.get(self.selected).map(|(ws_id, _, _)| ws_id.to_string())
+    }
+    
+    pub fn get_selected_todo_list(&self) -> Option<&TodoList> {
+        let ws_id = self.get_selected_workspace_id()?;
+        self.workspace_manager.workspace_todos.get(&ws_id)
+    }
+    
+    pub fn get_selected_todo_list_mut(&mut self) -> Option<&mut TodoList> {
+        let ws_id = self.get_selected_workspace_id()?;
+        self.workspace_manager.workspace_todos.get_mut(&ws_id)
+    }
+    
+    // List holding the todo captured when editing, notes or child insertion started
+    pub fn get_editing_todo_list(&self) -> Option<&TodoList> {
+        let ws_id = self.editing_workspace_id.as_ref()?;
+        self.workspace_manager.workspace_todos.get(ws_id)
+    }
+    
+    fn get_editing_todo_list_mut(&mut self) -> Option<&mut TodoList> {
+        let ws_id = self.editing_workspace_id.clone()?;
+        self.workspace_manager.workspace_todos.get_mut(&ws_id)
+    }
+    
+    // The filters being displayed: while typing a search, the in-progress query filters live
+    pub fn get_active_filters(&self) -> FilterSet {
+        let mut filters = self.filters.clone();
+        if self.mode == AppMode::Search {
+            let query = self.search_buffer.content.trim();
+            filters.query = (!query.is_empty()).then(|| query.to_string());
+        }
+        filters
+    }
+    
+    // Whether visible rows can come from workspaces other than the current one
+    pub fn is_cross_workspace_view(&self) -> bool {
+        match self.view_mode {
+            ViewMode::List => self.search_scope == SearchScope::All && self.get_active_filters().query.is_some(),
+            ViewMode::GlobalDue(_) => true,
+            ViewMode::Agenda => false,
+        }
+    }
+    
+    pub fn toggle_search_scope(&mut self) {
+        self.search_scope = match self.search_scope {
+            SearchScope::Workspace => SearchScope::All,
+            SearchScope::All => SearchScope::Workspace,
+        };
+        self.selected = 0;
+        
+        let scope_name = match self.search_scope {
+            SearchScope::Workspace => "current workspace",
+            SearchScope::All => "all workspaces",
+        };
+        self.set_message(format!("Searching {}", scope_name), MessageLevel::Info, SHORT);
+    }
+    
+    pub fn cycle_global_due_view(&mut self) {
+        self.view_mode = match &self.view_mode {
+            ViewMode::GlobalDue(DueDateFilter::Overdue) => ViewMode::GlobalDue(DueDateFilter::Today),
+            ViewMode::GlobalDue(_) => ViewMode::List,
+            ViewMode::List | ViewMode::Agenda => ViewMode::GlobalDue(DueDateFilter::Overdue),
+        };
+        self.selected = 0;
+        
+        let view_name = self.get_view_name();
+        self.set_message(format!("Viewing {}", view_name), MessageLevel::Info, SHORT);
+    }
+    
+    // A: switch between the plain list and the agenda, staying on the selected todo
+    pub fn toggle_agenda_view(&mut self) {
+        let selected_id = self.get_selected_todo_id();
+        self.view_mode = match self.view_mode {
+            ViewMode::Agenda => ViewMode::List,
+            _ => ViewMode::Agenda,
+        };
+        
+        let todos = self.get_visible_todos();
+        self.selected = selected_id
+            .and_then(|id| todos.iter().position(|(_, todo, _)| todo.id == id))
+            .unwrap_or(0);
+        
+        let view_name = self.get_view_name();
+        self.set_message(format!("Viewing {}", view_name), MessageLevel::Info, SHORT);
+    }
+
+    // b: open or close the status board, starting on the todo selected in the list
+    pub fn toggle_board(&mut self) {
+        if self.mode == AppMode::Board {
+            self.mode = AppMode::Normal;
+            self.clear_message();
+            return;
+        }
+        if self.get_current_todo_list().is_none() {
+            self.set_message("No workspace selected".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        
+        let selected_id = self.get_selected_todo_id();
+        self.board_selection = selected_id.and_then(|id| self.board_position(id)).unwrap_or((0, 0));
+        self.mode = AppMode::Board;
+        self.set_message("h/l: column · j/k: todo · H/L: move todo · b/Esc: close".to_string(), MessageLevel::Info, LONG);
+    }
+    
+    // The current workspace's todos under each of BOARD_COLUMNS
+    pub fn get_board_columns(&self) -> Vec<Vec<&Todo>> {
+        BOARD_COLUMNS.iter()
+            .map(|status| self.get_current_todo_list().map(|todo_list| todo_list.todos_with_status(status)).unwrap_or_default())
+            .collect()
+    }
+    
+    pub fn get_board_selected_todo(&self) -> Option<&Todo> {
+        let (column, index) = self.board_selection;
+        self.get_board_columns().get(column).and_then(|todos| todos.get(index).copied())
+    }
+    
+    fn board_position(&self, id: u32) -> Option<(usize, usize)> {
+        self.get_board_columns().iter().enumerate().find_map(|(column, todos)| {
+            todos.iter().position(|todo| todo.id == id).map(|index| (column, index))
+        })
+    }
+    
+    // h/l: move between columns, keeping the row where the column is long enough
+    pub fn move_board_column(&mut self, delta: isize) {
+        let column = self.board_selection.0.saturating_add_signed(delta).min(BOARD_COLUMNS.len() - 1);
+        let count = self.get_board_columns()[column].len();
+        self.board_selection = (column, self.board_selection.1.min(count.saturating_sub(1)));
+    }
+    
+    pub fn move_board_selection(&mut self, delta: isize) {
+        let (column, index) = self.board_selection;
+        let count = self.get_board_columns()[column].len();
+        self.board_selection.1 = index.saturating_add_signed(delta).min(count.saturating_sub(1));
+    }
+    
+    // H/L: move the selected todo to the neighbouring status, following it there
+    pub fn move_board_todo(&mut self, delta: isize) {
+        let Some(id) = self.get_board_selected_todo().map(|todo| todo.id) else { return };
+        let Some(column) = self.board_selection.0.checked_add_signed(delta).filter(|&column| column < BOARD_COLUMNS.len()) else { return };
+        let Some(workspace_id) = self.workspace_manager.current_workspace.clone() else { return };
+        let new_status = BOARD_COLUMNS[column].clone();
+        
+        let Some(todo) = self.get_current_todo_list_mut().and_then(|todo_list| todo_list.get_todo_mut(id)) else { return };
+        let (old_status, old_completed_at) = (todo.status.clone(), todo.completed_at);
+        todo.set_status(new_status.clone());
+        let message = format!("Moved '{}' to {}. Press 'u' to undo.", todo.description, new_status.name());
+        
+        self.mark_dirty();
+        self.command_history.push_command(Command::ChangeStatus { workspace_id, todo_id: id, old_status, old_completed_at, new_status });
+        self.board_selection = self.board_position(id).unwrap_or((column, 0));
+        self.set_message(message, MessageLevel::Success, SHORT);
+    }
+    
+    // c: the week's due todos, a column per day
+    pub fn enter_calendar_mode(&mut self) {
+        if self.get_current_todo_list().is_none() {
+            self.set_message("No workspace selected".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        self.calendar = CalendarState::for_day(chrono::Local::now().date_naive());
+        self.mode = AppMode::Calendar;
+        self.set_message("h/l: day · j/k: todo · [/]: week · Enter: show in list · Esc: close".to_string(), MessageLevel::Info, LONG);
+    }
+    
+    pub fn exit_calendar_mode(&mut self) {
+        self.mode = AppMode::Normal;
+        self.clear_message();
+    }
+    
+    // The overdue gutter, then each day of the calendar's week
+    pub fn get_calendar_columns(&self) -> (Vec<&Todo>, Vec<(NaiveDate, Vec<&Todo>)>) {
+        let now = chrono::Local::now();
+        match self.get_current_todo_list() {
+            Some(todo_list) => (todo_list.overdue_todos(now), todo_list.due_by_day(&self.calendar.days(), now)),
+            None => (Vec::new(), Vec::new()),
+        }
+    }
+    
+    fn get_calendar_focused_todos(&self) -> Vec<&Todo> {
+        let (overdue, days) = self.get_calendar_columns();
+        match self.calendar.focused_day {
+            None => overdue,
+            Some(day) => days.into_iter().nth(day).map(|(_, todos)| todos).unwrap_or_default(),
+        }
+    }
+    
+    pub fn get_calendar_selected_todo(&self) -> Option<&Todo> {
+        self.get_calendar_focused_todos().get(self.calendar.selected_idx).copied()
+    }
+    
+    // h/l: move between days; the overdue gutter sits left of Monday
+    pub fn move_calendar_day(&mut self, delta: isize) {
+        let column = self.calendar.focused_day.map_or(0, |day| day as isize + 1);
+        let column = (column + delta).clamp(0, 7);
+        self.calendar.focused_day = (column > 0).then(|| column as usize - 1);
+        self.calendar.selected_idx = 0;
+    }
+    
+    pub fn move_calendar_selection(&mut self, delta: isize) {
+        let count = self.get_calendar_focused_todos().len();
+        self.calendar.selected_idx = self.calendar.selected_idx.saturating_add_signed(delta).min(count.saturating_sub(1));
+    }
+    
+    // [/]: previous or next week, on the same weekday
+    pub fn page_calendar_week(&mut self, weeks: i64) {
+        self.calendar.week_start += chrono::Duration::weeks(weeks);
+        self.calendar.selected_idx = 0;
+    }
+    
+    // Enter: back to the list with the highlighted todo selected
+    pub fn open_calendar_selection(&mut self) {
+        let Some(id) = self.get_calendar_selected_todo().map(|todo| todo.id) else { return };
+        let Some(ws_id) = self.workspace_manager.current_workspace.clone() else { return };
+        self.exit_calendar_mode();
+        self.view_mode = ViewMode::List;
+        if !self.reveal_todo(&ws_id, id) {
+            // The filters hide it; show everything rather than land somewhere else
+            self.filters = FilterSet::default();
+            self.reveal_todo(&ws_id, id);
+        }
+    }
+    
+    // Select `id` from `workspace_id`, expanding collapsed parents that hide it.
+    // False when the current view leaves it out; the selection is then unchanged.
+    pub fn reveal_todo(&mut self, workspace_id: &str, id: u32) -> bool {
+        let mut expanded = false;
+        if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(workspace_id) {
+            let mut parent = todo_list.get_todo(id).and_then(|todo| todo.parent_id);
+            while let Some(parent_id) = parent {
+                let Some(ancestor) = todo_list.get_todo(parent_id) else { break };
+                parent = ancestor.parent_id;
+                if !ancestor.expanded {
+                    todo_list.toggle_expanded(parent_id);
+                    expanded = true;
+                }
+            }
+        }
+        if expanded {
+            self.mark_dirty();
+        }
+        
+        let position = self.get_visible_todos().iter()
+            .position(|(row_ws_id, todo, _)| *row_ws_id == workspace_id && todo.id == id);
+        match position {
+            Some(index) => {
+                self.selected = index;
+                self.anchor_selection();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn move_selection_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        }
+    }
+
+    pub fn move_selection_down(&mut self) {
+        let todos = self.get_visible_todos();
+        if self.selected < todos.len().saturating_sub(1) {
+            self.selected += 1;
+        }
+    }
+
+    // Move the selection by `delta` todos, stopping at either end
+    pub fn move_selection_by(&mut self, delta: isize) {
+        let last = self.get_visible_todos().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+    
+    // Ctrl+D / Ctrl+U
+    pub fn move_half_page(&mut self, down: bool) {
+        let half = (self.list_view_height / 2).max(1) as isize;
+        self.move_selection_by(if down { half } else { -half });
+    }
+    
+    // PageDown / PageUp
+    pub fn move_page(&mut self, down: bool) {
+        let page = self.list_view_height.max(1) as isize;
+        self.move_selection_by(if down { page } else { -page });
+    }
+
+    pub fn go_to_top(&mut self) {
+        self.selected = 0;
+    }
+
+    pub fn go_to_bottom(&mut self) {
+        let todos = self.get_visible_todos();
+        self.selected = todos.len().saturating_sub(1);
+    }
+
+    pub fn move_to_next_sibling(&mut self) {
+        self.move_in_tree(next_sibling_index);
+    }
+
+    pub fn move_to_previous_sibling(&mut self) {
+        self.move_in_tree(previous_sibling_index);
+    }
+
+    pub fn move_to_parent(&mut self) {
+        self.move_in_tree(parent_index);
+    }
+
+    // Stays put when there is nowhere to go
+    fn move_in_tree(&mut self, motion: fn(&[u32], usize) -> Option<usize>) {
+        let depths: Vec<u32> = self.get_visible_todos().iter().map(|(_, _, depth)| *depth).collect();
+        if let Some(index) = motion(&depths, self.selected) {
+            self.selected = index;
+        }
+    }
+
+    pub fn enter_insert_mode(&mut self) {
+        self.mode = AppMode::Insert;
+        self.clear_input_buffer();
+        self.quick_entry_count = 0;
+    }
+
+    pub fn enter_normal_mode(&mut self) {
+        self.mode = AppMode::Normal;
+        self.clear_input_buffer();
+        self.quick_entry_count = 0;
+        self.inserting_child_for = None;
+        self.editing_workspace_id = None;
     }
 
     pub fn submit_input(&mut self) {
-        if !self.input_buffer.trim().is_empty() {
-            let input_text = self.input_buffer.trim().to_string();
+        self.add_todo_from_input();
+        self.enter_normal_mode();
+    }
+    
+    // Shift+Enter: add the todo but stay in Insert mode for the next one
+    pub fn submit_input_and_continue(&mut self) {
+        if self.add_todo_from_input() {
+            self.quick_entry_count += 1;
+        }
+        self.clear_input_buffer();
+    }
+    
+    // Add the typed todo (or child todo) with its own undo entry; false if nothing was added
+    fn add_todo_from_input(&mut self) -> bool {
+        let mut added = false;
+        if !self.input_buffer.content.trim().is_empty() {
+            let input_text = self.input_buffer.content.trim().to_string();
             match self.mode {
                 AppMode::Insert => {
                     // Get workspace ID before borrowing todo_list mutably
@@ -465,41 +1627,51 @@ impl App {
                         // Clone the todo for undo command after it's created
                         let todo_for_undo = todo_list.get_todo(todo_id).cloned();
                         
-                        self.set_message("Todo added! Press 'u' to undo.".to_string());
+                        self.set_message("Todo added! Press 'u' to undo.".to_string(), MessageLevel::Success, SHORT);
                         
                         // Record command for undo after releasing the mutable borrow
+                        self.mark_dirty();
                         if let (Some(todo), Some(ws_id)) = (todo_for_undo, workspace_id) {
                             let command = Command::AddTodo { workspace_id: ws_id, todo };
                             self.command_history.push_command(command);
                         }
+                        added = true;
                     } else {
-                        self.set_message("No workspace selected".to_string());
+                        self.set_message("No workspace selected".to_string(), MessageLevel::Warning, LONG);
                     }
                 }
                 AppMode::InsertChild => {
                     if let Some(parent_id) = self.inserting_child_for {
-                        if let Some(todo_list) = self.get_current_todo_list_mut() {
-                            if let Some(_) = todo_list.add_child_todo(parent_id, input_text) {
-                                self.set_message("Child todo added!".to_string());
+                        let workspace_id = self.editing_workspace_id.clone();
+                        if let Some(todo_list) = self.get_editing_todo_list_mut() {
+                            let child_todo = todo_list.add_child_todo(parent_id, input_text)
+                                .and_then(|child_id| todo_list.get_todo(child_id).cloned());
+                            if let Some(child_todo) = child_todo {
+                                self.mark_dirty();
+                                self.set_message("Child todo added!".to_string(), MessageLevel::Success, SHORT);
+                                if let Some(ws_id) = workspace_id {
+                                    self.command_history.push_command(Command::AddChildTodo { workspace_id: ws_id, parent_id, child_todo });
+                                }
+                                added = true;
                             } else {
-                                self.set_message("Failed to add child todo".to_string());
+                                self.set_message("Failed to add child todo".to_string(), MessageLevel::Error, STICKY);
                             }
                         } else {
-                            self.set_message("No workspace selected".to_string());
+                            self.set_message("No workspace selected".to_string(), MessageLevel::Warning, LONG);
                         }
                     }
                 }
                 _ => {}
             }
         }
-        self.enter_normal_mode();
+        added
     }
 
     pub fn toggle_todo_complete(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
-            let workspace_id = self.workspace_manager.get_current_workspace_id();
+            let workspace_id = self.get_selected_workspace_id();
             
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
+            if let Some(todo_list) = self.get_selected_todo_list_mut() {
                 if let Some(todo) = todo_list.get_todo_mut(id) {
                     // Record the old status for undo
                     let old_status = todo.status.clone();
@@ -508,90 +1680,146 @@ impl App {
                     let status = if todo.is_completed() { "completed" } else { "pending" };
                     
                     // Record command for undo
+                    self.mark_dirty();
                     if let Some(ws_id) = workspace_id {
                         let command = Command::CompleteTodo { workspace_id: ws_id, todo_id: id, old_status };
                         self.command_history.push_command(command);
                     }
                     
-                    self.set_message(format!("Todo marked as {}. Press 'u' to undo.", status));
+                    self.set_message(format!("Todo marked as {}. Press 'u' to undo.", status), MessageLevel::Success, SHORT);
                 }
             }
+            self.resync_selection();
         }
     }
 
     pub fn delete_selected_todo(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
+            if let Some(todo_list) = self.get_selected_todo_list_mut() {
                 todo_list.remove_todo(id);
-                self.set_message("Todo deleted!".to_string());
-                
-                // Adjust selection if needed
-                let todos = self.get_visible_todos();
-                if self.selected >= todos.len() && todos.len() > 0 {
-                    self.selected = todos.len() - 1;
-                }
+                self.request_save();
+                self.set_message("Todo deleted!".to_string(), MessageLevel::Success, SHORT);
+                self.resync_selection();
             }
         }
     }
 
+    // Cycle the status filter, leaving the other filters in place
     pub fn cycle_view_mode(&mut self) {
-        self.view_mode = match &self.view_mode {
-            ViewMode::All => ViewMode::Pending,
-            ViewMode::Pending => ViewMode::Completed,
-            ViewMode::Completed => ViewMode::All,
-            _ => ViewMode::All, // Reset to All from any filter mode
+        self.filters.status = match self.filters.status {
+            None => Some(StatusFilter::Pending),
+            Some(StatusFilter::Pending) => Some(StatusFilter::InProgress),
+            Some(StatusFilter::InProgress) => Some(StatusFilter::Completed),
+            Some(StatusFilter::Completed) => None,
         };
-        self.selected = 0; // Reset selection when changing view
+        self.resync_selection();
         
         let view_name = self.get_view_name();
-        self.set_message(format!("Viewing {}", view_name));
-    }
-    
-    pub fn get_view_name(&self) -> &str {
-        match &self.view_mode {
-            ViewMode::All => "all todos",
-            ViewMode::Pending => "pending todos", 
-            ViewMode::Completed => "completed todos",
-            ViewMode::Search(_query) => "search results",
-            ViewMode::FilterByTag(_tag) => "filtered by tag",
-            ViewMode::FilterByContext(_context) => "filtered by context",
-            ViewMode::FilterByDueDate(filter) => match filter {
-                DueDateFilter::Overdue => "overdue todos",
-                DueDateFilter::Today => "due today",
-                DueDateFilter::Tomorrow => "due tomorrow",
-                DueDateFilter::ThisWeek => "due this week",
-                DueDateFilter::NoDueDate => "no due date",
-            },
+        self.set_message(format!("Viewing {}", view_name), MessageLevel::Info, SHORT);
+    }
+    
+    pub fn cycle_group_by(&mut self) {
+        self.anchor_selection();
+        self.group_by = self.group_by.next();
+        // Stay on the same todo (its first occurrence when it sits in several groups)
+        self.resync_selection();
+        
+        match self.group_by {
+            GroupBy::None => self.set_message("Grouping off".to_string(), MessageLevel::Info, SHORT),
+            group_by => self.set_message(format!("Grouping by {}", group_by.name()), MessageLevel::Info, SHORT),
+        }
+    }
+    
+    pub fn toggle_detail_pane(&mut self) {
+        let show = !self.display.show_detail_pane;
+        self.display.show_detail_pane = show;
+        self.set_message(if show { "Detail pane on" } else { "Detail pane off" }.to_string(), MessageLevel::Info, SHORT);
+    }
+    
+    // The theme picked at runtime wins over the one in the config file
+    pub fn theme_name(&self) -> ThemeName {
+        self.display.theme.unwrap_or(self.config.theme)
+    }
+    
+    pub fn apply_theme(&mut self) {
+        let theme = self.theme_name().theme();
+        self.colors = if self.no_color {
+            Theme::monochrome()
+        } else if self.true_color {
+            theme
+        } else {
+            theme.to_ansi16()
+        };
+    }
+    
+    pub fn cycle_theme(&mut self) {
+        let next = self.theme_name().next();
+        self.display.theme = Some(next);
+        self.apply_theme();
+        if self.no_color {
+            self.set_message(format!("Theme: {} (colors are off)", next.name()), MessageLevel::Info, SHORT);
+        } else {
+            self.set_message(format!("Theme: {}", next.name()), MessageLevel::Info, SHORT);
+        }
+    }
+    
+    pub fn cycle_sort_mode(&mut self) {
+        self.anchor_selection();
+        let sort_mode = match self.get_current_todo_list_mut() {
+            Some(todo_list) => {
+                todo_list.sort_mode = todo_list.sort_mode.next();
+                todo_list.sort_mode
+            }
+            None => return,
+        };
+        
+        self.mark_dirty();
+        self.resync_selection();
+        self.set_message(format!("Sorting by {}", sort_mode.name()), MessageLevel::Info, SHORT);
+    }
+    
+    // Human readable view, e.g. "Pending · #urgent · This Week"
+    pub fn get_view_name(&self) -> String {
+        let filters = self.get_active_filters();
+        match self.view_mode {
+            ViewMode::List if filters.is_empty() => "All Todos".to_string(),
+            ViewMode::List => filters.describe(),
+            ViewMode::GlobalDue(filter) if filters.is_empty() => format!("{} (All Workspaces)", filter.name()),
+            ViewMode::GlobalDue(filter) => format!("{} (All Workspaces) · {}", filter.name(), filters.describe()),
+            ViewMode::Agenda if filters.is_empty() => "Agenda".to_string(),
+            ViewMode::Agenda => format!("Agenda · {}", filters.describe()),
         }
     }
 
     pub fn increase_priority(&mut self) {
-        if let Some(id) = self.get_selected_todo_id() {
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
-                if let Some(todo) = todo_list.get_todo_mut(id) {
-                    if todo.priority < 5 {
-                        todo.priority += 1;
-                        let priority = todo.priority;
-                        self.set_message(format!("Priority increased to {}", priority));
-                    }
-                }
-            }
+        if let Some(priority) = self.change_selected_priority(1) {
+            self.set_message(format!("Priority increased to {}", priority), MessageLevel::Success, SHORT);
         }
     }
 
     pub fn decrease_priority(&mut self) {
-        if let Some(id) = self.get_selected_todo_id() {
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
-                if let Some(todo) = todo_list.get_todo_mut(id) {
-                    if todo.priority > 0 {
-                        todo.priority -= 1;
-                        let priority = todo.priority;
-                        self.set_message(format!("Priority decreased to {}", priority));
-                    }
-                }
-            }
+        if let Some(priority) = self.change_selected_priority(-1) {
+            self.set_message(format!("Priority decreased to {}", priority), MessageLevel::Success, SHORT);
         }
     }
+    
+    // Step the selected todo's priority within 0-5, recording it for undo; None if it didn't change
+    fn change_selected_priority(&mut self, delta: i8) -> Option<u8> {
+        let id = self.get_selected_todo_id()?;
+        let workspace_id = self.get_selected_workspace_id()?;
+        let todo = self.get_selected_todo_list_mut()?.get_todo_mut(id)?;
+        let old_priority = todo.priority;
+        let new_priority = old_priority.saturating_add_signed(delta).min(5);
+        if new_priority == old_priority {
+            return None;
+        }
+        
+        todo.priority = new_priority;
+        self.mark_dirty();
+        self.command_history.push_command(Command::ChangePriority { workspace_id, todo_id: id, old_priority, new_priority });
+        self.resync_selection();
+        Some(new_priority)
+    }
 
     // Hierarchical methods
     pub fn add_child_todo(&mut self) {
@@ -599,369 +1827,1333 @@ impl App {
             self.mode = AppMode::InsertChild;
             self.clear_input_buffer();
             self.inserting_child_for = Some(parent_id);
+            self.editing_workspace_id = self.get_selected_workspace_id();
         }
     }
 
     pub fn toggle_expansion(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
+            if let Some(todo_list) = self.get_selected_todo_list_mut() {
                 if todo_list.has_children(id) {
                     todo_list.toggle_expanded(id);
                     let expanded = todo_list.get_todo(id).map(|t| t.expanded).unwrap_or(false);
                     let action = if expanded { "expanded" } else { "collapsed" };
-                    self.set_message(format!("Todo {}", action));
+                    self.mark_dirty();
+                    self.set_message(format!("Todo {}", action), MessageLevel::Success, SHORT);
+                    self.resync_selection();
                 }
             }
         }
     }
 
+    pub fn collapse_all(&mut self) {
+        self.refold("Collapsed everything", |todo_list| todo_list.set_all_expanded(false));
+    }
+
+    pub fn expand_all(&mut self) {
+        self.refold("Expanded everything", |todo_list| todo_list.set_all_expanded(true));
+    }
+
+    // Show `levels` levels of the tree, from a count typed before the collapse key
+    pub fn fold_to_level(&mut self, levels: u32) {
+        let depth = levels.saturating_sub(1);
+        let message = if levels == 1 { "Showing 1 level".to_string() } else { format!("Showing {} levels", levels) };
+        self.refold(&message, |todo_list| todo_list.collapse_to_depth(depth));
+    }
+
+    // Apply a folding change to the current workspace, keeping the selection on the same todo or,
+    // when that is now folded away, on its nearest visible ancestor
+    fn refold(&mut self, message: &str, fold: impl FnOnce(&mut TodoList)) {
+        let Some(todo_list) = self.get_current_todo_list_mut() else { return };
+        let folds = |todo_list: &TodoList| todo_list.todos.iter().map(|(id, todo)| (*id, todo.expanded)).collect::<HashMap<_, _>>();
+        let before = folds(todo_list);
+        fold(todo_list);
+        if folds(todo_list) != before {
+            self.mark_dirty();
+        }
+        self.resync_selection();
+        self.set_message(message.to_string(), MessageLevel::Success, SHORT);
+    }
+
     pub fn delete_selected_with_children(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
+            if let Some(todo_list) = self.get_selected_todo_list_mut() {
                 let removed = todo_list.remove_todo_and_children(id);
                 let count = removed.len();
+                self.request_save();
                 if count == 1 {
-                    self.set_message("Todo deleted!".to_string());
+                    self.set_message("Todo deleted!".to_string(), MessageLevel::Success, SHORT);
                 } else {
-                    self.set_message(format!("Todo and {} children deleted!", count - 1));
+                    self.set_message(format!("Todo and {} children deleted!", count - 1), MessageLevel::Success, SHORT);
                 }
                 
                 // Adjust selection if needed
-                let todos = self.get_visible_todos();
-                if self.selected >= todos.len() && todos.len() > 0 {
-                    self.selected = todos.len() - 1;
-                }
+                self.resync_selection();
             }
         }
     }
 
     // Search and filter methods
     pub fn enter_search_mode(&mut self) {
+        self.view_before_search = Some((self.filters.clone(), self.selected));
         self.mode = AppMode::Search;
         self.search_buffer.clear();
-        self.search_cursor_pos = 0;
+        self.search_history_index = None;
+        self.search_draft.clear();
     }
 
     pub fn submit_search(&mut self) {
-        if self.search_buffer.trim().is_empty() {
-            self.view_mode = ViewMode::All;
-        } else {
-            self.view_mode = ViewMode::Search(self.search_buffer.trim().to_string());
+        if self.search_buffer.content.trim().is_empty() {
+            // Nothing typed - drop the text filter and keep the others
+            self.filters.query = None;
+            self.view_before_search = None;
+            self.mode = AppMode::Normal;
+            self.clamp_selection();
+            self.set_message("Search cleared".to_string(), MessageLevel::Info, SHORT);
+            return;
         }
-        self.selected = 0;
+        
+        self.filters.query = Some(self.search_buffer.content.trim().to_string());
+        self.record_search(self.search_buffer.content.trim().to_string());
+        self.last_search = Some(self.search_buffer.content.trim().to_string());
+        self.view_before_search = None;
+        self.clamp_selection();
         self.mode = AppMode::Normal;
-        self.set_message(format!("Searching for: {}", self.search_buffer));
+        self.set_message(format!("Searching for: {}", self.search_buffer.content), MessageLevel::Info, SHORT);
         self.search_buffer.clear();
-        self.search_cursor_pos = 0;
+    }
+    
+    pub fn cancel_search(&mut self) {
+        if let Some((filters, selected)) = self.view_before_search.take() {
+            self.filters = filters;
+            self.selected = selected;
+        }
+        self.mode = AppMode::Normal;
+        self.search_buffer.clear();
+        self.clamp_selection();
+    }
+    
+    fn record_search(&mut self, query: String) {
+        // Collapse repeats of the most recent query
+        if query.is_empty() || self.search_history.last() == Some(&query) {
+            return;
+        }
+        self.search_history.push(query);
+        if self.search_history.len() > MAX_SEARCH_HISTORY {
+            self.search_history.remove(0);
+        }
+    }
+    
+    // Up in the search prompt: step back through earlier queries
+    pub fn search_history_previous(&mut self) {
+        if self.search_history.is_empty() {
+            return;
+        }
+        let index = match self.search_history_index {
+            None => {
+                self.search_draft = self.search_buffer.content.clone();
+                self.search_history.len() - 1
+            }
+            Some(index) => index.saturating_sub(1),
+        };
+        self.search_history_index = Some(index);
+        self.set_search_buffer(self.search_history[index].clone());
+    }
+    
+    // Down in the search prompt: step forward, ending back at the draft
+    pub fn search_history_next(&mut self) {
+        let index = match self.search_history_index {
+            Some(index) => index,
+            None => return,
+        };
+        if index + 1 < self.search_history.len() {
+            self.search_history_index = Some(index + 1);
+            self.set_search_buffer(self.search_history[index + 1].clone());
+        } else {
+            self.search_history_index = None;
+            let draft = std::mem::take(&mut self.search_draft);
+            self.set_search_buffer(draft);
+        }
+    }
+    
+    fn set_search_buffer(&mut self, query: String) {
+        self.search_buffer.set(query);
+        self.clamp_selection();
+    }
+    
+    // Remember which todo is selected, so a change that moves rows around can find it again
+    pub fn anchor_selection(&mut self) {
+        self.selected_anchor = self.get_selected_workspace_id().zip(self.get_selected_todo_id());
+    }
+    
+    // Put the selection back on the anchored todo after the visible rows changed. A todo folded
+    // away under a collapsed parent gives way to its nearest visible ancestor; one that is gone or
+    // filtered out leaves the index where it was, clamped to the list.
+    pub fn resync_selection(&mut self) {
+        let found = match self.selected_anchor.take() {
+            Some((ws_id, id)) => self.select_nearest_visible(&ws_id, id),
+            None => false,
+        };
+        if !found {
+            self.clamp_selection();
+        }
+        self.anchor_selection();
+    }
+    
+    // Select `id`, or when it's folded away the nearest ancestor on screen; false when neither
+    // is, as for a todo that was deleted or filtered out. Only resync_selection needs this.
+    fn select_nearest_visible(&mut self, ws_id: &str, id: u32) -> bool {
+        let Some(todo_list) = self.workspace_manager.workspace_todos.get(ws_id) else { return false };
+        // The todo itself, then ancestors from the first collapsed one up
+        let mut candidates = vec![id];
+        let mut folded = false;
+        let mut parent = todo_list.get_todo(id).and_then(|todo| todo.parent_id);
+        while let Some(parent_id) = parent {
+            let Some(ancestor) = todo_list.get_todo(parent_id) else { break };
+            folded |= !ancestor.expanded;
+            if folded {
+                candidates.push(parent_id);
+            }
+            parent = ancestor.parent_id;
+        }
+        
+        let todos = self.get_visible_todos();
+        let position = candidates.iter().find_map(|&candidate| {
+            todos.iter().position(|(row_ws_id, todo, _)| *row_ws_id == ws_id && todo.id == candidate)
+        });
+        match position {
+            Some(index) => {
+                self.selected = index;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Keep the selection inside the visible rows
+    pub fn clamp_selection(&mut self) {
+        let count = self.get_visible_todos().len();
+        if self.selected >= count {
+            self.selected = count.saturating_sub(1);
+        }
     }
 
     pub fn add_char_to_search(&mut self, c: char) {
-        self.search_buffer.insert(self.search_cursor_pos, c);
-        self.search_cursor_pos += c.len_utf8();
+        self.search_buffer.insert_char(c);
+        self.clamp_selection();
     }
 
     pub fn remove_char_from_search(&mut self) {
-        if self.search_cursor_pos > 0 {
-            // Find the start of the character to remove (handle UTF-8)
-            let mut char_start = self.search_cursor_pos - 1;
-            while char_start > 0 && !self.search_buffer.is_char_boundary(char_start) {
-                char_start -= 1;
-            }
-            
-            self.search_buffer.remove(char_start);
-            self.search_cursor_pos = char_start;
-        }
+        self.search_buffer.delete_char_before();
+        self.clamp_selection();
     }
     
     // Search cursor navigation
     pub fn move_search_cursor_left(&mut self) {
-        if self.search_cursor_pos > 0 {
-            self.search_cursor_pos -= 1;
-            // Ensure we're at a valid character boundary
-            while self.search_cursor_pos > 0 && !self.search_buffer.is_char_boundary(self.search_cursor_pos) {
-                self.search_cursor_pos -= 1;
-            }
-        }
+        self.search_buffer.move_left();
     }
     
     pub fn move_search_cursor_right(&mut self) {
-        if self.search_cursor_pos < self.search_buffer.len() {
-            self.search_cursor_pos += 1;
-            // Ensure we're at a valid character boundary
-            while self.search_cursor_pos < self.search_buffer.len() && !self.search_buffer.is_char_boundary(self.search_cursor_pos) {
-                self.search_cursor_pos += 1;
-            }
-        }
+        self.search_buffer.move_right();
     }
 
     pub fn clear_filters(&mut self) {
-        self.view_mode = ViewMode::All;
+        // A second Esc forgets the search used by n/N
+        if self.view_mode == ViewMode::List && self.filters.is_empty() && self.last_search.is_some() {
+            self.last_search = None;
+            self.set_message("Search cleared".to_string(), MessageLevel::Info, SHORT);
+            return;
+        }
+        
+        self.view_mode = ViewMode::List;
+        self.filters = FilterSet::default();
         self.selected = 0;
-        self.set_message("Filters cleared".to_string());
+        if self.last_search.is_some() {
+            self.set_message("Filters cleared. n/N: jump between search matches".to_string(), MessageLevel::Info, LONG);
+        } else {
+            self.set_message("Filters cleared".to_string(), MessageLevel::Info, SHORT);
+        }
+    }
+    
+    // Move to the next (or previous) visible todo matching the last search, wrapping around
+    pub fn jump_to_search_match(&mut self, forward: bool) {
+        let query = match &self.last_search {
+            Some(query) => query.clone(),
+            None => {
+                self.set_message("No previous search".to_string(), MessageLevel::Warning, LONG);
+                return;
+            }
+        };
+        
+        let matches: Vec<usize> = self.get_visible_todos().iter()
+            .enumerate()
+            .filter(|(_, (_, todo, _))| crate::search::score_todo(todo, &query).is_some())
+            .map(|(index, _)| index)
+            .collect();
+        
+        if matches.is_empty() {
+            self.set_message(format!("No matches for: {}", query), MessageLevel::Warning, LONG);
+            return;
+        }
+        
+        let (target, wrapped) = if forward {
+            match matches.iter().find(|&&index| index > self.selected) {
+                Some(&index) => (index, false),
+                None => (matches[0], true),
+            }
+        } else {
+            match matches.iter().rev().find(|&&index| index < self.selected) {
+                Some(&index) => (index, false),
+                None => (matches[matches.len() - 1], true),
+            }
+        };
+        
+        self.selected = target;
+        let position = matches.iter().position(|&index| index == target).unwrap_or(0) + 1;
+        if wrapped {
+            self.set_message(format!("Search wrapped - match {} of {}", position, matches.len()), MessageLevel::Info, SHORT);
+        } else {
+            self.set_message(format!("Match {} of {}", position, matches.len()), MessageLevel::Info, SHORT);
+        }
     }
 
     pub fn enter_tag_selection(&mut self) {
         if let Some(todo_list) = self.get_current_todo_list() {
-            self.available_tags = todo_list.get_all_tags();
+            // Same order as the popup draws them: most used first
+            self.available_tags = todo_list.get_tag_counts().into_iter().map(|(tag, _)| tag).collect();
             if self.available_tags.is_empty() {
-                self.set_message("No tags found".to_string());
+                self.set_message("No tags found".to_string(), MessageLevel::Warning, LONG);
                 return;
             }
             self.mode = AppMode::TagSelection;
             self.popup_selected = 0;
+            self.popup_exclude = false;
         } else {
-            self.set_message("No workspace selected".to_string());
+            self.set_message("No workspace selected".to_string(), MessageLevel::Warning, LONG);
         }
     }
 
     pub fn enter_context_selection(&mut self) {
         if let Some(todo_list) = self.get_current_todo_list() {
-            self.available_contexts = todo_list.get_all_contexts();
+            self.available_contexts = todo_list.get_context_counts().into_iter().map(|(context, _)| context).collect();
             if self.available_contexts.is_empty() {
-                self.set_message("No contexts found".to_string());
+                self.set_message("No contexts found".to_string(), MessageLevel::Warning, LONG);
                 return;
             }
             self.mode = AppMode::ContextSelection;
             self.popup_selected = 0;
+            self.popup_exclude = false;
         } else {
-            self.set_message("No workspace selected".to_string());
+            self.set_message("No workspace selected".to_string(), MessageLevel::Warning, LONG);
+        }
+    }
+
+    // Filter straight to the selected todo's tag; with several tags, offer just those in the popup
+    pub fn filter_by_selected_todos_tag(&mut self) {
+        let (tags, counts) = match (self.get_selected_todo(), self.get_selected_todo_list()) {
+            (Some(todo), Some(todo_list)) => (todo.tags.clone(), todo_list.get_tag_counts()),
+            _ => return,
+        };
+        
+        match tags.len() {
+            0 => self.set_message("Selected todo has no tags".to_string(), MessageLevel::Warning, LONG),
+            1 => {
+                let tag = tags.into_iter().next().unwrap_or_default();
+                self.apply_filter_keeping_selection(|filters| {
+                    filters.tag = Some(tag.clone());
+                    filters.exclude_tag = false;
+                });
+                self.set_message(format!("Filtering by tag: #{}", tag), MessageLevel::Info, SHORT);
+            }
+            _ => {
+                self.available_tags = counts.into_iter()
+                    .map(|(tag, _)| tag)
+                    .filter(|tag| tags.contains(tag))
+                    .collect();
+                self.mode = AppMode::TagSelection;
+                self.popup_selected = 0;
+                self.popup_exclude = false;
+            }
+        }
+    }
+    
+    pub fn filter_by_selected_todos_context(&mut self) {
+        let (contexts, counts) = match (self.get_selected_todo(), self.get_selected_todo_list()) {
+            (Some(todo), Some(todo_list)) => (todo.contexts.clone(), todo_list.get_context_counts()),
+            _ => return,
+        };
+        
+        match contexts.len() {
+            0 => self.set_message("Selected todo has no contexts".to_string(), MessageLevel::Warning, LONG),
+            1 => {
+                let context = contexts.into_iter().next().unwrap_or_default();
+                self.apply_filter_keeping_selection(|filters| {
+                    filters.context = Some(context.clone());
+                    filters.exclude_context = false;
+                });
+                self.set_message(format!("Filtering by context: @{}", context), MessageLevel::Info, SHORT);
+            }
+            _ => {
+                self.available_contexts = counts.into_iter()
+                    .map(|(context, _)| context)
+                    .filter(|context| contexts.contains(context))
+                    .collect();
+                self.mode = AppMode::ContextSelection;
+                self.popup_selected = 0;
+                self.popup_exclude = false;
+            }
         }
     }
+    
+    // Change the filters while keeping the cursor on the todo it was on
+    fn apply_filter_keeping_selection(&mut self, change: impl FnOnce(&mut FilterSet)) {
+        let selected_id = self.get_selected_todo_id();
+        change(&mut self.filters);
+        let todos = self.get_visible_todos();
+        self.selected = selected_id
+            .and_then(|id| todos.iter().position(|(_, todo, _)| todo.id == id))
+            .unwrap_or(0);
+    }
 
     pub fn move_popup_selection_up(&mut self) {
         if self.popup_selected > 0 {
             self.popup_selected -= 1;
         }
     }
-
-    pub fn move_popup_selection_down(&mut self) {
-        let max_items = match self.mode {
-            AppMode::TagSelection => self.available_tags.len(),
-            AppMode::ContextSelection => self.available_contexts.len(),
-            AppMode::TemplateSelection => self.available_templates.len(),
-            AppMode::RecurrenceSelection => self.available_recurrence.len(),
-            AppMode::WorkspaceSelection => self.available_workspaces.len() + 1, // +1 for Home option
-            _ => 0,
+
+    pub fn move_popup_selection_down(&mut self) {
+        let max_items = match self.mode {
+            AppMode::TagSelection => self.available_tags.len(),
+            AppMode::ContextSelection => self.available_contexts.len(),
+            AppMode::TemplateSelection => self.available_templates.len(),
+            AppMode::RecurrenceSelection => self.available_recurrence.len(),
+            AppMode::WorkspaceSelection => self.available_workspaces.len() + 1, // +1 for Home option
+            AppMode::MoveToWorkspace => self.available_workspaces.len(),
+            AppMode::SavedFilterSelection => self.workspace_manager.saved_filters.len(),
+            AppMode::Export => ExportFormat::ALL.len(),
+            AppMode::TimeTracking => self.get_time_entries_todo().map_or(0, |todo| {
+                todo.time_tracker.entries.len() + usize::from(todo.is_timer_running())
+            }),
+            _ => 0,
+        };
+        if self.popup_selected < max_items.saturating_sub(1) {
+            self.popup_selected += 1;
+        }
+    }
+
+    pub fn select_from_popup(&mut self) {
+        match self.mode {
+            AppMode::TagSelection if self.tag_select_from_visual => {
+                if let Some(tag) = self.available_tags.get(self.popup_selected).cloned() {
+                    self.select_visible_with_tag(&tag);
+                }
+                self.tag_select_from_visual = false;
+                self.available_tags.clear();
+                self.popup_selected = 0;
+                self.mode = AppMode::Visual;
+                return;
+            }
+            AppMode::TagSelection => {
+                // Picking the active tag again (the same way) turns the tag filter off
+                if let Some(tag) = self.available_tags.get(self.popup_selected).cloned() {
+                    self.selected = 0;
+                    if self.filters.tag.as_ref() == Some(&tag) && self.filters.exclude_tag == self.popup_exclude {
+                        self.filters.tag = None;
+                        self.filters.exclude_tag = false;
+                        self.set_message("Tag filter cleared".to_string(), MessageLevel::Info, SHORT);
+                    } else {
+                        self.filters.tag = Some(tag.clone());
+                        self.filters.exclude_tag = self.popup_exclude;
+                        if self.popup_exclude {
+                            self.set_message(format!("Excluding tag: #{}", tag), MessageLevel::Info, SHORT);
+                        } else {
+                            self.set_message(format!("Filtering by tag: #{}", tag), MessageLevel::Info, SHORT);
+                        }
+                    }
+                }
+            }
+            AppMode::ContextSelection => {
+                if let Some(context) = self.available_contexts.get(self.popup_selected).cloned() {
+                    self.selected = 0;
+                    if self.filters.context.as_ref() == Some(&context) && self.filters.exclude_context == self.popup_exclude {
+                        self.filters.context = None;
+                        self.filters.exclude_context = false;
+                        self.set_message("Context filter cleared".to_string(), MessageLevel::Info, SHORT);
+                    } else {
+                        self.filters.context = Some(context.clone());
+                        self.filters.exclude_context = self.popup_exclude;
+                        if self.popup_exclude {
+                            self.set_message(format!("Excluding context: @{}", context), MessageLevel::Info, SHORT);
+                        } else {
+                            self.set_message(format!("Filtering by context: @{}", context), MessageLevel::Info, SHORT);
+                        }
+                    }
+                }
+            }
+            AppMode::TemplateSelection => {
+                self.apply_template();
+                return;
+            }
+            AppMode::RecurrenceSelection => {
+                self.apply_recurrence();
+                return;
+            }
+            AppMode::WorkspaceSelection => {
+                self.switch_workspace();
+                return;
+            }
+            AppMode::MoveToWorkspace => {
+                self.move_to_selected_workspace();
+                return;
+            }
+            AppMode::SavedFilterSelection => {
+                self.apply_saved_filter();
+            }
+            AppMode::Export => {
+                self.enter_export_path_mode();
+                return;
+            }
+            _ => {}
+        }
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn enter_saved_filter_selection(&mut self) {
+        self.mode = AppMode::SavedFilterSelection;
+        self.popup_selected = 0;
+        if self.workspace_manager.saved_filters.is_empty() {
+            self.set_message("No saved filters yet. Press 'n' to save the current view".to_string(), MessageLevel::Info, LONG);
+        }
+    }
+    
+    // Rebuild the view from the highlighted saved filter
+    pub fn apply_saved_filter(&mut self) {
+        let saved = match self.workspace_manager.saved_filters.get(self.popup_selected) {
+            Some(saved) => saved.clone(),
+            None => return,
+        };
+        let mut filters = saved.filters;
+        let mut missing = Vec::new();
+        
+        // Tags and contexts that no longer exist would only ever show an empty list
+        if let Some(todo_list) = self.get_current_todo_list() {
+            if filters.tag.as_ref().is_some_and(|tag| !todo_list.get_all_tags().contains(tag)) {
+                missing.push(format!("#{}", filters.tag.take().unwrap_or_default()));
+                filters.exclude_tag = false;
+            }
+            if filters.context.as_ref().is_some_and(|context| !todo_list.get_all_contexts().contains(context)) {
+                missing.push(format!("@{}", filters.context.take().unwrap_or_default()));
+                filters.exclude_context = false;
+            }
+        }
+        
+        self.view_mode = saved.view_mode;
+        self.group_by = saved.group_by;
+        self.search_scope = saved.search_scope;
+        self.filters = filters;
+        if let Some(todo_list) = self.get_current_todo_list_mut().filter(|todo_list| todo_list.sort_mode != saved.sort_mode) {
+            todo_list.sort_mode = saved.sort_mode;
+            self.mark_dirty();
+        }
+        self.selected = 0;
+        
+        if missing.is_empty() {
+            self.set_message(format!("Applied filter: {}", saved.name), MessageLevel::Success, SHORT);
+        } else {
+            self.set_message(format!("Applied filter: {} (skipped missing {})", saved.name, missing.join(", ")), MessageLevel::Warning, LONG);
+        }
+    }
+    
+    pub fn delete_selected_saved_filter(&mut self) {
+        if let Some(removed) = self.workspace_manager.delete_saved_filter(self.popup_selected) {
+            self.mark_dirty();
+            let count = self.workspace_manager.saved_filters.len();
+            if self.popup_selected >= count && count > 0 {
+                self.popup_selected = count - 1;
+            }
+            self.set_message(format!("Deleted saved filter: {}", removed.name), MessageLevel::Success, SHORT);
+        }
+    }
+    
+    pub fn enter_save_filter_mode(&mut self) {
+        self.mode = AppMode::SaveFilter;
+        self.clear_input_buffer();
+        self.set_message(format!("Save '{}' as:", self.filters.describe()), MessageLevel::Info, LONG);
+    }
+    
+    pub fn submit_save_filter(&mut self) {
+        let name = self.input_buffer.content.trim().to_string();
+        if name.is_empty() {
+            self.set_message("Filter name cannot be empty".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        
+        let sort_mode = self.get_current_todo_list().map(|list| list.sort_mode).unwrap_or_default();
+        self.workspace_manager.save_filter(SavedFilter {
+            name: name.clone(),
+            filters: self.filters.clone(),
+            sort_mode,
+            view_mode: self.view_mode.clone(),
+            group_by: self.group_by,
+            search_scope: self.search_scope,
+        });
+        self.mark_dirty();
+        self.clear_input_buffer();
+        self.mode = AppMode::SavedFilterSelection;
+        self.popup_selected = self.workspace_manager.saved_filters.iter()
+            .position(|saved| saved.name == name)
+            .unwrap_or(0);
+        self.set_message(format!("Saved filter: {}", name), MessageLevel::Success, SHORT);
+    }
+    
+    pub fn cancel_save_filter(&mut self) {
+        self.mode = AppMode::SavedFilterSelection;
+        self.clear_input_buffer();
+    }
+    
+    // Enter in a single-line prompt
+    pub fn submit_prompt(&mut self) {
+        match self.mode {
+            AppMode::SaveFilter => self.submit_save_filter(),
+            AppMode::RenameTag | AppMode::RenameContext => self.submit_rename_label(),
+            AppMode::BulkTag | AppMode::BulkContext => self.submit_bulk_label(),
+            AppMode::BulkDue => self.submit_bulk_due(),
+            AppMode::EditDue => self.submit_due_edit(),
+            AppMode::LogTime => self.submit_log_time(),
+            AppMode::EditTimeEntry => self.submit_time_entry_note(),
+            AppMode::ExportPath => self.submit_export(),
+            AppMode::ImportPath => self.submit_import(),
+            AppMode::ImportWorkspacePath => self.submit_import_workspace(),
+            AppMode::ImportWorkspaceName => self.submit_import_workspace_name(),
+            _ => {}
+        }
+    }
+    
+    // Esc in a single-line prompt goes back to the popup it came from
+    pub fn cancel_prompt(&mut self) {
+        match self.mode {
+            AppMode::SaveFilter => self.cancel_save_filter(),
+            AppMode::RenameTag => {
+                self.mode = AppMode::TagSelection;
+                self.clear_input_buffer();
+            }
+            AppMode::RenameContext => {
+                self.mode = AppMode::ContextSelection;
+                self.clear_input_buffer();
+            }
+            AppMode::BulkTag | AppMode::BulkContext | AppMode::BulkDue => {
+                self.mode = AppMode::Visual;
+                self.clear_input_buffer();
+            }
+            AppMode::EditDue => self.exit_due_edit_mode(),
+            AppMode::LogTime => self.exit_log_time_mode(),
+            AppMode::EditTimeEntry => {
+                self.mode = AppMode::TimeTracking;
+                self.clear_input_buffer();
+            }
+            AppMode::ExportPath => {
+                self.mode = self.return_mode.take().unwrap_or(AppMode::Export);
+                self.export_format = None;
+                self.export_workspace_id = None;
+                self.clear_input_buffer();
+            }
+            AppMode::ImportPath => {
+                self.mode = AppMode::Normal;
+                self.clear_input_buffer();
+            }
+            AppMode::ImportWorkspacePath | AppMode::ImportWorkspaceName => {
+                self.mode = self.return_mode.take().unwrap_or(AppMode::WorkspaceSelection);
+                self.pending_workspace_import = None;
+                self.clear_input_buffer();
+            }
+            _ => {}
+        }
+    }
+    
+    // `r` in the tag or context popup
+    pub fn enter_rename_label_mode(&mut self) {
+        let label = match self.mode {
+            AppMode::TagSelection => self.available_tags.get(self.popup_selected).map(|tag| format!("#{}", tag)),
+            AppMode::ContextSelection => self.available_contexts.get(self.popup_selected).map(|context| format!("@{}", context)),
+            _ => None,
+        };
+        let Some(label) = label else { return };
+        
+        self.mode = if self.mode == AppMode::TagSelection { AppMode::RenameTag } else { AppMode::RenameContext };
+        self.clear_input_buffer();
+        self.set_message(format!("Rename {} to:", label), MessageLevel::Info, LONG);
+    }
+    
+    // The tag or context being renamed, without its marker
+    pub fn get_renaming_label(&self) -> Option<&String> {
+        match self.mode {
+            AppMode::RenameTag => self.available_tags.get(self.popup_selected),
+            AppMode::RenameContext => self.available_contexts.get(self.popup_selected),
+            _ => None,
+        }
+    }
+    
+    pub fn submit_rename_label(&mut self) {
+        let is_tag = self.mode == AppMode::RenameTag;
+        let marker = if is_tag { '#' } else { '@' };
+        let old = match self.get_renaming_label() {
+            Some(old) => old.clone(),
+            None => return,
+        };
+        let new = self.input_buffer.content.trim().trim_start_matches(marker).to_string();
+        if new.is_empty() || !new.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            self.set_message("Use letters, digits and underscores only".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        let count = self.rewrite_label(is_tag, &old, Some(&new));
+        
+        // Keep an active filter on the old name pointing at the renamed one
+        let new_lower = new.to_lowercase();
+        let active = if is_tag { &mut self.filters.tag } else { &mut self.filters.context };
+        if active.as_ref() == Some(&old) {
+            *active = Some(new_lower.clone());
+        }
+        
+        self.mode = AppMode::Normal;
+        self.clear_input_buffer();
+        let plural = if count == 1 { "" } else { "s" };
+        self.set_message(format!("Renamed {}{} to {}{} on {} todo{}", marker, old, marker, new_lower, count, plural), MessageLevel::Success, SHORT);
+    }
+    
+    // Rename (or with no new name, remove) a tag or context in the current workspace as one undoable step
+    fn rewrite_label(&mut self, is_tag: bool, old: &str, new: Option<&str>) -> usize {
+        let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() else { return 0 };
+        let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) else { return 0 };
+        
+        let old = old.to_string();
+        let carries = |todo: &Todo| if is_tag { todo.tags.contains(&old) } else { todo.contexts.contains(&old) };
+        let old_raw_descriptions: Vec<(u32, String)> = todo_list.todos.values()
+            .filter(|todo| carries(todo))
+            .map(|todo| (todo.id, todo.raw_description.clone()))
+            .collect();
+        let count = match (is_tag, new) {
+            (true, Some(new)) => todo_list.rename_tag(&old, new),
+            (false, Some(new)) => todo_list.rename_context(&old, new),
+            (true, None) => todo_list.remove_tag(&old),
+            (false, None) => todo_list.remove_context(&old),
+        };
+        let new_raw_descriptions: Vec<(u32, String)> = old_raw_descriptions.iter()
+            .filter_map(|(id, _)| todo_list.get_todo(*id).map(|todo| (*id, todo.raw_description.clone())))
+            .collect();
+        
+        if count > 0 {
+            self.mark_dirty();
+            self.command_history.push_command(Command::RewriteDescriptions {
+                workspace_id,
+                old_raw_descriptions,
+                new_raw_descriptions,
+            });
+        }
+        count
+    }
+    
+    // `d` in the tag or context popup: ask before stripping it from every todo
+    pub fn request_delete_label(&mut self) {
+        let confirmation = match self.mode {
+            AppMode::TagSelection => self.available_tags.get(self.popup_selected).cloned().map(Confirmation::DeleteTag),
+            AppMode::ContextSelection => self.available_contexts.get(self.popup_selected).cloned().map(Confirmation::DeleteContext),
+            _ => None,
         };
-        if self.popup_selected < max_items.saturating_sub(1) {
-            self.popup_selected += 1;
+        if let Some(confirmation) = confirmation {
+            self.pending_confirmation = Some(confirmation);
+            self.mode = AppMode::Confirm;
         }
     }
-
-    pub fn select_from_popup(&mut self) {
-        match self.mode {
-            AppMode::TagSelection => {
-                if let Some(tag) = self.available_tags.get(self.popup_selected) {
-                    self.view_mode = ViewMode::FilterByTag(tag.clone());
-                    self.selected = 0;
-                    self.set_message(format!("Filtering by tag: #{}", tag));
+    
+    // Question shown in the confirmation popup
+    pub fn get_confirmation_prompt(&self) -> Option<String> {
+        let todo_list = self.get_current_todo_list()?;
+        let (label, count) = match self.pending_confirmation.as_ref()? {
+            Confirmation::DeleteTag(tag) => (format!("#{}", tag), todo_list.todos.values().filter(|todo| todo.tags.contains(tag)).count()),
+            Confirmation::DeleteContext(context) => (format!("@{}", context), todo_list.todos.values().filter(|todo| todo.contexts.contains(context)).count()),
+            Confirmation::PasteTodos(text) => return Some(format!("Create {} todos from the pasted lines?", parse_outline(text).len())),
+            Confirmation::BulkDelete(count) => return Some(format!("Delete {} selected todos?", count)),
+            Confirmation::OverwriteExport(path) => return Some(format!("{} already exists. Replace it?", path.display())),
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        Some(format!("Remove {} from {} todo{}?", label, count, plural))
+    }
+    
+    pub fn confirm_pending(&mut self) {
+        let Some(confirmation) = self.pending_confirmation.take() else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+        
+        match confirmation {
+            Confirmation::DeleteTag(tag) => {
+                let count = self.rewrite_label(true, &tag, None);
+                if self.filters.tag.as_ref() == Some(&tag) {
+                    self.filters.tag = None;
+                    self.filters.exclude_tag = false;
                 }
+                self.enter_tag_selection();
+                self.set_message(format!("Removed #{} from {} todos", tag, count), MessageLevel::Success, SHORT);
             }
-            AppMode::ContextSelection => {
-                if let Some(context) = self.available_contexts.get(self.popup_selected) {
-                    self.view_mode = ViewMode::FilterByContext(context.clone());
-                    self.selected = 0;
-                    self.set_message(format!("Filtering by context: @{}", context));
+            Confirmation::DeleteContext(context) => {
+                let count = self.rewrite_label(false, &context, None);
+                if self.filters.context.as_ref() == Some(&context) {
+                    self.filters.context = None;
+                    self.filters.exclude_context = false;
                 }
+                self.enter_context_selection();
+                self.set_message(format!("Removed @{} from {} todos", context, count), MessageLevel::Success, SHORT);
             }
-            AppMode::TemplateSelection => {
-                self.apply_template();
-                return;
-            }
-            AppMode::RecurrenceSelection => {
-                self.apply_recurrence();
-                return;
+            Confirmation::PasteTodos(text) => {
+                let count = self.add_pasted_todos(&text, None);
+                self.mode = AppMode::Normal;
+                self.set_message(format!("Added {} todos. Press 'u' to undo.", count), MessageLevel::Success, SHORT);
             }
-            AppMode::WorkspaceSelection => {
-                self.switch_workspace();
-                return;
+            Confirmation::BulkDelete(_) => self.delete_bulk_selection(),
+            Confirmation::OverwriteExport(path) => self.write_export(&path),
+        }
+        
+        // The popup closes itself when nothing is left to show
+        if self.mode == AppMode::Confirm {
+            self.mode = AppMode::Normal;
+        }
+        self.clamp_selection();
+    }
+    
+    // Back to wherever the confirmation was asked from
+    pub fn cancel_confirmation(&mut self) {
+        self.mode = match self.pending_confirmation.take() {
+            Some(Confirmation::DeleteTag(_)) => AppMode::TagSelection,
+            Some(Confirmation::DeleteContext(_)) => AppMode::ContextSelection,
+            Some(Confirmation::BulkDelete(_)) => AppMode::Visual,
+            // The path is still typed in, to change or to Esc out of
+            Some(Confirmation::OverwriteExport(_)) => AppMode::ExportPath,
+            Some(Confirmation::PasteTodos(_)) | None => AppMode::Normal,
+        };
+    }
+    
+    // Put back raw descriptions saved by a rename (undo uses the old ones, redo the new)
+    fn apply_raw_descriptions(&mut self, workspace_id: &str, raw_descriptions: &[(u32, String)]) -> bool {
+        let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(workspace_id) else { return false };
+        for (id, raw) in raw_descriptions {
+            if let Some(todo) = todo_list.get_todo_mut(*id) {
+                todo.replace_raw_description(raw.clone());
             }
-            _ => {}
         }
-        self.mode = AppMode::Normal;
+        self.mark_dirty();
+        true
+    }
+    
+    // Space in the tag/context popup: make Enter exclude instead of filter
+    pub fn toggle_popup_exclude(&mut self) {
+        self.popup_exclude = !self.popup_exclude;
+    }
+    
+    // `!` in the tag/context popup: exclude the highlighted entry right away
+    pub fn exclude_from_popup(&mut self) {
+        self.popup_exclude = true;
+        self.select_from_popup();
+        self.popup_exclude = false;
     }
 
     pub fn cancel_popup(&mut self) {
         self.mode = AppMode::Normal;
+        self.editing_todo_id = None;
         self.popup_selected = 0;
+        self.popup_exclude = false;
         self.available_tags.clear();
         self.available_contexts.clear();
         self.available_templates.clear();
         self.available_workspaces.clear();
         self.exit_notes_mode(); // Also handles notes mode cancellation
+        
+        // A bulk move cancelled from its popup goes back to the visual selection
+        if self.move_from_visual || self.tag_select_from_visual {
+            self.move_from_visual = false;
+            self.tag_select_from_visual = false;
+            self.mode = AppMode::Visual;
+        }
     }
 
+    // Step the due date filter through its rotation, ending with no due date filter
     pub fn cycle_due_date_filter(&mut self) {
-        use crate::todo::DueDateFilter;
-        let next_filter = match &self.view_mode {
-            ViewMode::FilterByDueDate(DueDateFilter::Overdue) => DueDateFilter::Today,
-            ViewMode::FilterByDueDate(DueDateFilter::Today) => DueDateFilter::Tomorrow,
-            ViewMode::FilterByDueDate(DueDateFilter::Tomorrow) => DueDateFilter::ThisWeek,
-            ViewMode::FilterByDueDate(DueDateFilter::ThisWeek) => DueDateFilter::NoDueDate,
-            ViewMode::FilterByDueDate(DueDateFilter::NoDueDate) => DueDateFilter::Overdue,
-            _ => DueDateFilter::Overdue,
+        self.filters.due = match self.filters.due {
+            None => Some(DueDateFilter::Overdue),
+            Some(filter) => filter.next(),
         };
-        
-        self.view_mode = ViewMode::FilterByDueDate(next_filter);
         self.selected = 0;
-        let filter_name = self.get_view_name();
-        self.set_message(format!("Filtering by: {}", filter_name));
+        match self.filters.due {
+            Some(filter) => self.set_message(format!("Filtering by: {}", filter.name()), MessageLevel::Info, SHORT),
+            None => self.set_message("Due date filter cleared".to_string(), MessageLevel::Info, SHORT),
+        }
     }
     
     // Advanced feature methods
     
     // Time tracking
+    
+    // Todos with a running timer in any workspace, longest running first
+    pub fn get_active_timers(&self) -> Vec<&Todo> {
+        let mut timers: Vec<&Todo> = self.workspace_manager.workspace_todos.values()
+            .flat_map(|todo_list| todo_list.get_active_timers())
+            .collect();
+        timers.sort_by_key(|todo| todo.time_tracker.current_session);
+        timers
+    }
+    
+    // Stop the timers running in every workspace, returning what each one added
+    pub fn stop_all_timers(&mut self) -> Vec<(String, u64)> {
+        let stopped: Vec<(String, u64)> = self.workspace_manager.workspace_todos.values_mut()
+            .flat_map(|todo_list| todo_list.stop_all_timers())
+            .collect();
+        if !stopped.is_empty() {
+            self.mark_dirty();
+        }
+        stopped
+    }
+    
     pub fn toggle_timer(&mut self) {
-        if let Some(id) = self.get_selected_todo_id() {
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
-                if let Some(todo) = todo_list.get_todo(id) {
-                    if todo.is_timer_running() {
-                        todo_list.stop_timer(id);
-                        self.set_message("Timer stopped".to_string());
-                    } else {
-                        todo_list.start_timer(id);
-                        self.set_message("Timer started".to_string());
-                    }
+        let Some(id) = self.get_selected_todo_id() else { return };
+        let Some(running) = self.get_selected_todo().map(Todo::is_timer_running) else { return };
+        
+        if running {
+            if let Some(todo_list) = self.get_selected_todo_list_mut() {
+                todo_list.stop_timer(id);
+                self.mark_dirty();
+            }
+            self.set_message("Timer stopped".to_string(), MessageLevel::Success, SHORT);
+            self.end_pomodoro_without_timer();
+            return;
+        }
+        
+        // Only one timer runs at a time unless concurrent timers are allowed
+        let stopped = if self.allow_concurrent_timers { Vec::new() } else { self.stop_all_timers() };
+        let Some(todo_list) = self.get_selected_todo_list_mut() else { return };
+        todo_list.start_timer(id);
+        let description = todo_list.get_todo(id).map(|todo| todo.description.clone()).unwrap_or_default();
+        self.mark_dirty();
+        
+        match stopped.as_slice() {
+            [] => self.set_message("Timer started".to_string(), MessageLevel::Success, SHORT),
+            [(previous, seconds)] => self.set_message(format!(
+                "Stopped timer on '{}' (+{}), started on '{}'", previous, format_tracked_time(*seconds), description), MessageLevel::Success, SHORT),
+            _ => self.set_message(format!("Stopped {} timers, started on '{}'", stopped.len(), description), MessageLevel::Success, SHORT),
+        }
+        self.end_pomodoro_without_timer();
+    }
+    
+    // P: run a work session on the selected todo, or cancel the pomodoro in progress
+    pub fn toggle_pomodoro(&mut self) {
+        if let Some(cancelled) = self.pomodoro.take() {
+            // Time worked so far still counts, without the label of a finished session
+            if cancelled.phase == Phase::Work {
+                if let Some(todo) = self.workspace_manager.workspace_todos.get_mut(&cancelled.workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(cancelled.todo_id))
+                    .filter(|todo| todo.is_timer_running()) {
+                    todo.stop_timer_at(chrono::Local::now(), None);
+                    self.mark_dirty();
+                }
+            }
+            self.set_message("Pomodoro cancelled".to_string(), MessageLevel::Info, SHORT);
+            return;
+        }
+        let Some(id) = self.get_selected_todo_id() else { return };
+        let Some(workspace_id) = self.get_selected_workspace_id() else { return };
+        if !self.get_selected_todo().is_some_and(Todo::is_timer_running) {
+            self.toggle_timer();
+        }
+        self.pomodoro = Some(Pomodoro::start(workspace_id, id, chrono::Local::now()));
+        let description = self.get_selected_todo().map(|todo| todo.description.clone()).unwrap_or_default();
+        self.set_message(format!("Pomodoro started on '{}': {} minutes", description, pomodoro::WORK_MINUTES), MessageLevel::Success, SHORT);
+    }
+    
+    // Advance the pomodoro countdown: a finished work session stops its timer and starts the
+    // break, a finished break ends the pomodoro. Both ring the bell.
+    pub fn tick_pomodoro(&mut self, now: chrono::DateTime<chrono::Local>) {
+        if self.end_pomodoro_without_timer() {
+            return;
+        }
+        let Some(pomodoro) = self.pomodoro.as_mut() else { return };
+        match pomodoro.tick(now) {
+            Transition::None => return,
+            Transition::WorkDone { ended_at } => {
+                let (workspace_id, id) = (pomodoro.workspace_id.clone(), pomodoro.todo_id);
+                if let Some(todo) = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(id)) {
+                    todo.stop_timer_at(ended_at, Some(pomodoro::ENTRY_LABEL.to_string()));
+                }
+                self.set_message(format!("Pomodoro done, take a {}-minute break", pomodoro::BREAK_MINUTES), MessageLevel::Info, LONG);
+                self.mark_dirty();
+            }
+            Transition::BreakDone => {
+                self.pomodoro = None;
+                self.set_message("Break over".to_string(), MessageLevel::Info, LONG);
+            }
+        }
+        self.bell_pending = true;
+    }
+    
+    // A work session lasts only as long as its todo's timer: stopped by hand, stopped for
+    // another todo's timer, or gone with its todo, it takes the pomodoro with it
+    fn end_pomodoro_without_timer(&mut self) -> bool {
+        let Some(pomodoro) = self.pomodoro.as_ref().filter(|pomodoro| pomodoro.phase == Phase::Work) else { return false };
+        let timing = self.workspace_manager.workspace_todos.get(&pomodoro.workspace_id)
+            .and_then(|todo_list| todo_list.get_todo(pomodoro.todo_id))
+            .is_some_and(Todo::is_timer_running);
+        if timing {
+            return false;
+        }
+        self.pomodoro = None;
+        self.set_message("Pomodoro cancelled: its timer stopped".to_string(), MessageLevel::Info, SHORT);
+        true
+    }
+    
+    // Todos whose due time or reminder time has passed since the last check, each once. The
+    // first check only starts the clock: what fell due before the app opened isn't announced.
+    pub fn take_due_reminders(&mut self, now: chrono::DateTime<chrono::Local>) -> Vec<Reminder> {
+        let Some(since) = self.reminders_checked_at.replace(now) else { return Vec::new() };
+        let lead = (self.config.reminder_minutes > 0).then(|| chrono::Duration::minutes(self.config.reminder_minutes as i64));
+        let mut reminders = Vec::new();
+        for workspace in self.workspace_manager.get_all_workspaces() {
+            let Some(todo_list) = self.workspace_manager.workspace_todos.get(&workspace.id) else { continue };
+            for (id, kind) in notifications::due_reminders(todo_list, since, now, lead) {
+                let todo = &todo_list.todos[&id];
+                let Some(due) = todo.due_date else { continue };
+                if self.notified.insert((workspace.id.clone(), id, due, kind)) {
+                    reminders.push(Reminder { kind, workspace: workspace.name.clone(), description: todo.description.clone(), due });
+                }
+            }
+        }
+        reminders
+    }
+    
+    // The current workspace's todos for the banner over the status bar: overdue or due
+    // within the hour, unless the banner was dismissed
+    pub fn reminder_banner_todos(&self, now: chrono::DateTime<chrono::Local>) -> Vec<&Todo> {
+        if self.reminder_banner_dismissed {
+            return Vec::new();
+        }
+        self.get_current_todo_list()
+            .map(|todo_list| todo_list.due_soon(chrono::Duration::hours(1), now))
+            .unwrap_or_default()
+    }
+    
+    pub fn dismiss_reminder_banner(&mut self) {
+        if self.reminder_banner_dismissed || self.reminder_banner_todos(chrono::Local::now()).is_empty() {
+            return;
+        }
+        self.reminder_banner_dismissed = true;
+        self.set_message("Due-soon banner hidden until Paperclip is restarted".to_string(), MessageLevel::Info, SHORT);
+    }
+    
+    // Ctrl+T: log time on the selected todo by typing a duration
+    pub fn enter_log_time_mode(&mut self) {
+        let Some(id) = self.get_selected_todo_id() else { return };
+        self.editing_todo_id = Some(id);
+        self.editing_workspace_id = self.get_selected_workspace_id();
+        self.mode = AppMode::LogTime;
+        self.clear_input_buffer();
+        self.set_message("Type 45m or 1h30m (-20m takes time off), optionally followed by a note".to_string(), MessageLevel::Info, LONG);
+    }
+    
+    pub fn submit_log_time(&mut self) {
+        let input = self.input_buffer.content.trim().to_string();
+        let (duration_text, note) = input.split_once(char::is_whitespace).unwrap_or((&input, ""));
+        let Some(seconds) = parse_duration(duration_text) else {
+            self.set_message(format!("Couldn't read '{}' as a duration; try 45m, 1h30m or -20m", duration_text), MessageLevel::Error, STICKY);
+            return;
+        };
+        let note = Some(note.trim().to_string()).filter(|note| !note.is_empty());
+        
+        let Some(id) = self.editing_todo_id else { return };
+        if let Some(todo) = self.get_editing_todo_list_mut().and_then(|todo_list| todo_list.get_todo_mut(id)) {
+            todo.add_manual_time(seconds, note);
+            let amount = format_tracked_time(seconds.unsigned_abs());
+            let message = if seconds > 0 {
+                format!("Logged {} on '{}'", amount, todo.description)
+            } else {
+                format!("Took {} off '{}'", amount, todo.description)
+            };
+            self.mark_dirty();
+            self.set_message(message, MessageLevel::Success, SHORT);
+        }
+        self.exit_log_time_mode();
+    }
+    
+    pub fn exit_log_time_mode(&mut self) {
+        self.mode = AppMode::Normal;
+        self.clear_input_buffer();
+        self.editing_todo_id = None;
+        self.editing_workspace_id = None;
+    }
+    
+    // L: the selected todo's time entries, newest first under any running session
+    pub fn enter_time_entries_mode(&mut self) {
+        let Some(todo) = self.get_selected_todo() else { return };
+        if todo.time_tracker.entries.is_empty() && !todo.is_timer_running() {
+            self.set_message("No time tracked on this todo yet".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        self.editing_todo_id = Some(todo.id);
+        self.editing_workspace_id = self.get_selected_workspace_id();
+        self.mode = AppMode::TimeTracking;
+        self.popup_selected = 0;
+    }
+    
+    pub fn get_time_entries_todo(&self) -> Option<&Todo> {
+        self.get_editing_todo_list()?.get_todo(self.editing_todo_id?)
+    }
+    
+    // Index into `entries` of the highlighted popup row; None for the running session
+    pub fn get_selected_time_entry_index(&self) -> Option<usize> {
+        let todo = self.get_time_entries_todo()?;
+        let row = self.popup_selected.checked_sub(usize::from(todo.is_timer_running()))?;
+        todo.time_tracker.entries.len().checked_sub(row + 1)
+    }
+    
+    pub fn delete_selected_time_entry(&mut self) {
+        let Some(index) = self.get_selected_time_entry_index() else {
+            self.set_message("Stop the timer with t to end the running session".to_string(), MessageLevel::Warning, LONG);
+            return;
+        };
+        let Some(id) = self.editing_todo_id else { return };
+        let Some(todo) = self.get_editing_todo_list_mut().and_then(|todo_list| todo_list.get_todo_mut(id)) else { return };
+        if let Some(removed) = todo.remove_time_entry(index) {
+            let total = todo.get_total_time_formatted();
+            let rows = todo.time_tracker.entries.len() + usize::from(todo.is_timer_running());
+            self.mark_dirty();
+            self.popup_selected = self.popup_selected.min(rows.saturating_sub(1));
+            self.set_message(format!("Deleted a {} entry; {} tracked in total", format_signed_time(removed.duration_seconds()), total), MessageLevel::Success, SHORT);
+            if rows == 0 {
+                self.cancel_popup();
+            }
+        }
+    }
+    
+    // e in the time entries popup: retype the highlighted entry's note
+    pub fn enter_edit_time_entry_mode(&mut self) {
+        let Some(index) = self.get_selected_time_entry_index() else { return };
+        let note = self.get_time_entries_todo()
+            .and_then(|todo| todo.time_tracker.entries[index].description.clone())
+            .unwrap_or_default();
+        self.mode = AppMode::EditTimeEntry;
+        self.input_buffer.set(note);
+    }
+    
+    pub fn submit_time_entry_note(&mut self) {
+        let note = Some(self.input_buffer.content.trim().to_string()).filter(|note| !note.is_empty());
+        if let (Some(index), Some(id)) = (self.get_selected_time_entry_index(), self.editing_todo_id) {
+            if let Some(todo) = self.get_editing_todo_list_mut().and_then(|todo_list| todo_list.get_todo_mut(id)) {
+                if todo.time_tracker.entries[index].description != note {
+                    todo.time_tracker.entries[index].description = note;
+                    self.mark_dirty();
                 }
             }
         }
+        self.mode = AppMode::TimeTracking;
+        self.clear_input_buffer();
     }
     
     // Notes editing
     pub fn enter_notes_mode(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
             self.editing_notes_for = Some(id);
+            self.editing_workspace_id = self.get_selected_workspace_id();
             self.mode = AppMode::EditNotes;
+            self.notes_scroll = 0;
             
             // Load existing notes into buffer
-            if let Some(todo_list) = self.get_current_todo_list() {
+            if let Some(todo_list) = self.get_selected_todo_list() {
                 if let Some(todo) = todo_list.get_todo(id) {
-                    self.notes_buffer = todo.notes.clone().unwrap_or_default();
-                    self.notes_cursor_pos = self.notes_buffer.len();
+                    self.notes_buffer.set(todo.notes.clone().unwrap_or_default());
                 } else {
                     self.notes_buffer.clear();
-                    self.notes_cursor_pos = 0;
                 }
             } else {
                 self.notes_buffer.clear();
-                self.notes_cursor_pos = 0;
             }
         }
     }
     
     pub fn save_notes(&mut self) {
         if let Some(id) = self.editing_notes_for {
-            let notes = if self.notes_buffer.trim().is_empty() {
+            let notes = if self.notes_buffer.content.trim().is_empty() {
                 None
             } else {
-                Some(self.notes_buffer.trim().to_string())
+                Some(self.notes_buffer.content.trim().to_string())
             };
             
-            if let Some(todo_list) = self.get_current_todo_list_mut() {
+            if let Some(todo_list) = self.get_editing_todo_list_mut() {
                 if let Some(todo) = todo_list.get_todo_mut(id) {
-                    todo.set_notes(notes);
-                    self.set_message("Notes saved".to_string());
+                    let old_notes = todo.notes.clone();
+                    todo.set_notes(notes.clone());
+                    self.record_notes_change(id, old_notes, notes);
+                    self.set_message("Notes saved".to_string(), MessageLevel::Success, SHORT);
                 }
             }
         }
         self.exit_notes_mode();
     }
     
+    // Push an undoable notes edit for the todo being edited or viewed, unless nothing changed
+    fn record_notes_change(&mut self, todo_id: u32, old_notes: Option<String>, new_notes: Option<String>) {
+        if old_notes == new_notes {
+            return;
+        }
+        self.mark_dirty();
+        if let Some(workspace_id) = self.editing_workspace_id.clone() {
+            self.command_history.push_command(Command::EditNotes { workspace_id, todo_id, old_notes, new_notes });
+        }
+    }
+    
     pub fn exit_notes_mode(&mut self) {
         self.mode = AppMode::Normal;
         self.notes_buffer.clear();
-        self.notes_cursor_pos = 0;
         self.editing_notes_for = None;
+        self.editing_workspace_id = None;
+    }
+    
+    // Ask the main loop to open the selected todo's notes in $VISUAL/$EDITOR
+    pub fn request_external_notes_edit(&mut self) {
+        let Some(editor) = external_editor::editor_from_env() else {
+            self.set_message("Set $EDITOR or $VISUAL to edit notes in an external editor".to_string(), MessageLevel::Warning, LONG);
+            return;
+        };
+        let Some(id) = self.get_selected_todo_id() else { return };
+        let notes = self.get_selected_todo_list()
+            .and_then(|todo_list| todo_list.get_todo(id))
+            .and_then(|todo| todo.notes.clone())
+            .unwrap_or_default();
+        
+        self.editing_notes_for = Some(id);
+        self.editing_workspace_id = self.get_selected_workspace_id();
+        self.notes_buffer.set(notes);
+        self.external_editor_request = Some(editor);
+    }
+    
+    // Store what the external editor saved, or report why the notes were left alone
+    pub fn finish_external_notes_edit(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(text) => {
+                self.notes_buffer.set(text);
+                self.save_notes();
+            }
+            Err(message) => {
+                self.exit_notes_mode();
+                self.set_message(message, MessageLevel::Error, STICKY);
+            }
+        }
     }
     
     // Notes viewing (read-only)
     pub fn enter_view_notes_mode(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
-            if let Some(todo_list) = self.get_current_todo_list() {
+            if let Some(todo_list) = self.get_selected_todo_list() {
                 if let Some(todo) = todo_list.get_todo(id) {
                     if todo.notes.is_some() && !todo.notes.as_ref().unwrap().trim().is_empty() {
                         let notes = todo.notes.clone().unwrap_or_default();
                         self.editing_notes_for = Some(id);
+                        self.editing_workspace_id = self.get_selected_workspace_id();
                         self.mode = AppMode::ViewNotes;
+                        self.notes_scroll = 0;
+                        self.notes_selected_line = 0;
                         // Load notes into buffer for display purposes only
-                        self.notes_buffer = notes;
+                        self.notes_buffer.set(notes);
                     } else {
-                        self.set_message("This todo has no notes".to_string());
+                        self.set_message("This todo has no notes".to_string(), MessageLevel::Warning, LONG);
                     }
                 }
             }
         }
     }
     
+    // Move the notes viewer's highlighted line; drawing scrolls to keep it on screen
+    pub fn move_notes_selection(&mut self, delta: isize) {
+        let last_line = self.notes_buffer.content.matches('\n').count();
+        self.notes_selected_line = self.notes_selected_line.saturating_add_signed(delta).min(last_line);
+    }
+    
+    pub fn move_notes_selection_page(&mut self, down: bool) {
+        let page = self.notes_view_height.saturating_sub(1).max(1) as isize;
+        self.move_notes_selection(if down { page } else { -page });
+    }
+    
+    // Space in the viewer: tick or untick the highlighted `- [ ]` line and save it to the todo
+    pub fn toggle_notes_checklist_item(&mut self) {
+        let Some(toggled) = toggle_checklist_line(&self.notes_buffer.content, self.notes_selected_line) else {
+            return;
+        };
+        let Some(id) = self.editing_notes_for else { return };
+        if let Some(todo) = self.get_editing_todo_list_mut().and_then(|todo_list| todo_list.get_todo_mut(id)) {
+            let old_notes = todo.notes.clone();
+            todo.set_notes(Some(toggled.clone()));
+            self.notes_buffer.content = toggled.clone();
+            self.record_notes_change(id, old_notes, Some(toggled));
+        }
+    }
+    
     pub fn exit_view_notes_mode(&mut self) {
         self.mode = AppMode::Normal;
         self.notes_buffer.clear();
         self.editing_notes_for = None;
+        self.editing_workspace_id = None;
     }
     
     pub fn add_char_to_notes(&mut self, c: char) {
-        if c == '\n' || c.is_control() {
-            // Handle newlines and control characters at cursor position
-            self.notes_buffer.insert(self.notes_cursor_pos, c);
-            self.notes_cursor_pos += c.len_utf8();
-        } else {
-            // Insert regular characters at cursor position
-            self.notes_buffer.insert(self.notes_cursor_pos, c);
-            self.notes_cursor_pos += c.len_utf8();
-        }
+        self.notes_buffer.insert_char(c);
     }
     
     pub fn remove_char_from_notes(&mut self) {
-        if self.notes_cursor_pos > 0 {
-            // Find the start of the character to remove (handle UTF-8)
-            let mut char_start = self.notes_cursor_pos - 1;
-            while char_start > 0 && !self.notes_buffer.is_char_boundary(char_start) {
-                char_start -= 1;
-            }
-            
-            self.notes_buffer.remove(char_start);
-            self.notes_cursor_pos = char_start;
-        }
+        self.notes_buffer.delete_char_before();
     }
     
     // Todo description editing
     pub fn enter_edit_mode(&mut self) {
         if let Some(id) = self.get_selected_todo_id() {
             self.editing_todo_id = Some(id);
+            self.editing_workspace_id = self.get_selected_workspace_id();
             self.mode = AppMode::EditTodo;
             
             // Load existing raw description into edit buffer
-            if let Some(todo_list) = self.get_current_todo_list() {
+            if let Some(todo_list) = self.get_selected_todo_list() {
                 if let Some(todo) = todo_list.get_todo(id) {
-                    self.edit_buffer = todo.raw_description.clone();
-                    self.edit_cursor_pos = self.edit_buffer.len();
+                    self.edit_buffer.set(todo.raw_description.clone());
                 } else {
                     self.edit_buffer.clear();
-                    self.edit_cursor_pos = 0;
                 }
             } else {
                 self.edit_buffer.clear();
-                self.edit_cursor_pos = 0;
             }
         }
     }
     
     pub fn save_todo_edit(&mut self) {
         if let Some(id) = self.editing_todo_id {
-            if !self.edit_buffer.trim().is_empty() {
-                let new_description = self.edit_buffer.trim().to_string();
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+            if !self.edit_buffer.content.trim().is_empty() {
+                let new_description = self.edit_buffer.content.trim().to_string();
+                let workspace_id = self.editing_workspace_id.clone();
+                if let Some(todo_list) = self.get_editing_todo_list_mut() {
                     if let Some(todo) = todo_list.get_todo_mut(id) {
-                        todo.update_description(new_description);
-                        self.set_message("Todo updated".to_string());
+                        let old_raw_description = todo.raw_description.clone();
+                        todo.update_description(new_description.clone());
+                        if old_raw_description != new_description {
+                            self.mark_dirty();
+                        }
+                        if let Some(ws_id) = workspace_id.filter(|_| old_raw_description != new_description) {
+                            self.command_history.push_command(Command::EditTodo {
+                                workspace_id: ws_id, todo_id: id, old_raw_description, new_raw_description: new_description,
+                            });
+                        }
+                        self.set_message("Todo updated".to_string(), MessageLevel::Success, SHORT);
+                        self.resync_selection();
                     } else {
-                        self.set_message("Failed to find todo for editing".to_string());
+                        self.set_message("Failed to find todo for editing".to_string(), MessageLevel::Error, STICKY);
                     }
                 } else {
-                    self.set_message("No workspace selected".to_string());
+                    self.set_message("No workspace selected".to_string(), MessageLevel::Warning, LONG);
                 }
             } else {
-                self.set_message("Cannot save empty todo description".to_string());
+                self.set_message("Cannot save empty todo description".to_string(), MessageLevel::Warning, LONG);
             }
         }
         self.exit_edit_mode();
@@ -970,139 +3162,252 @@ impl App {
     pub fn exit_edit_mode(&mut self) {
         self.mode = AppMode::Normal;
         self.edit_buffer.clear();
-        self.edit_cursor_pos = 0;
         self.editing_todo_id = None;
+        self.editing_workspace_id = None;
     }
     
     pub fn add_char_to_edit(&mut self, c: char) {
-        self.edit_buffer.insert(self.edit_cursor_pos, c);
-        self.edit_cursor_pos += c.len_utf8();
+        self.edit_buffer.insert_char(c);
     }
     
     pub fn remove_char_from_edit(&mut self) {
-        if self.edit_cursor_pos > 0 {
-            // Find the start of the character to remove (handle UTF-8)
-            let mut char_start = self.edit_cursor_pos - 1;
-            while char_start > 0 && !self.edit_buffer.is_char_boundary(char_start) {
-                char_start -= 1;
-            }
-            
-            self.edit_buffer.remove(char_start);
-            self.edit_cursor_pos = char_start;
-        }
+        self.edit_buffer.delete_char_before();
     }
     
     // Input buffer character manipulation
     pub fn add_char_to_input(&mut self, c: char) {
-        self.input_buffer.insert(self.input_cursor_pos, c);
-        self.input_cursor_pos += c.len_utf8();
+        self.input_buffer.insert_char(c);
     }
     
     pub fn remove_char_from_input(&mut self) {
-        if self.input_cursor_pos > 0 {
-            // Find the start of the character to remove (handle UTF-8)
-            let mut char_start = self.input_cursor_pos - 1;
-            while char_start > 0 && !self.input_buffer.is_char_boundary(char_start) {
-                char_start -= 1;
-            }
-            
-            self.input_buffer.remove(char_start);
-            self.input_cursor_pos = char_start;
-        }
+        self.input_buffer.delete_char_before();
     }
     
     // Cursor navigation for notes
     pub fn move_notes_cursor_left(&mut self) {
-        if self.notes_cursor_pos > 0 {
-            self.notes_cursor_pos -= 1;
-            // Ensure we're at a valid character boundary
-            while self.notes_cursor_pos > 0 && !self.notes_buffer.is_char_boundary(self.notes_cursor_pos) {
-                self.notes_cursor_pos -= 1;
-            }
-        }
+        self.notes_buffer.move_left();
     }
     
     pub fn move_notes_cursor_right(&mut self) {
-        if self.notes_cursor_pos < self.notes_buffer.len() {
-            self.notes_cursor_pos += 1;
-            // Ensure we're at a valid character boundary
-            while self.notes_cursor_pos < self.notes_buffer.len() && !self.notes_buffer.is_char_boundary(self.notes_cursor_pos) {
-                self.notes_cursor_pos += 1;
-            }
-        }
+        self.notes_buffer.move_right();
     }
     
     // Cursor navigation for edit buffer
     pub fn move_edit_cursor_left(&mut self) {
-        if self.edit_cursor_pos > 0 {
-            self.edit_cursor_pos -= 1;
-            // Ensure we're at a valid character boundary
-            while self.edit_cursor_pos > 0 && !self.edit_buffer.is_char_boundary(self.edit_cursor_pos) {
-                self.edit_cursor_pos -= 1;
-            }
-        }
+        self.edit_buffer.move_left();
     }
     
     pub fn move_edit_cursor_right(&mut self) {
-        if self.edit_cursor_pos < self.edit_buffer.len() {
-            self.edit_cursor_pos += 1;
-            // Ensure we're at a valid character boundary
-            while self.edit_cursor_pos < self.edit_buffer.len() && !self.edit_buffer.is_char_boundary(self.edit_cursor_pos) {
-                self.edit_cursor_pos += 1;
-            }
-        }
+        self.edit_buffer.move_right();
     }
     
     // Cursor navigation for input buffer
     pub fn move_input_cursor_left(&mut self) {
-        if self.input_cursor_pos > 0 {
-            self.input_cursor_pos -= 1;
-            // Ensure we're at a valid character boundary
-            while self.input_cursor_pos > 0 && !self.input_buffer.is_char_boundary(self.input_cursor_pos) {
-                self.input_cursor_pos -= 1;
+        self.input_buffer.move_left();
+    }
+    
+    pub fn move_input_cursor_right(&mut self) {
+        self.input_buffer.move_right();
+    }
+    
+    // Tab while typing a todo: complete the `#tag`, `@context` or `due:` before the cursor,
+    // or move to the next candidate if Tab was just pressed. The bulk tag/context prompts
+    // complete the bare name.
+    pub fn complete_input(&mut self) {
+        let buffer = match self.mode {
+            AppMode::EditTodo => &self.edit_buffer,
+            AppMode::Insert | AppMode::InsertChild | AppMode::BulkTag | AppMode::BulkContext => &self.input_buffer,
+            _ => return,
+        };
+        
+        let completion = match self.completion.take() {
+            Some(mut completion) => {
+                completion.advance();
+                completion
+            }
+            None => {
+                let prefix = match self.mode {
+                    AppMode::BulkTag => Some(completion::label_prefix(buffer.before_cursor(), CompletionKind::Tag)),
+                    AppMode::BulkContext => Some(completion::label_prefix(buffer.before_cursor(), CompletionKind::Context)),
+                    _ => completion::completion_prefix(buffer.before_cursor()),
+                };
+                let Some((kind, start, partial)) = prefix else { return };
+                let todo_list = self.get_editing_todo_list().or_else(|| self.get_current_todo_list());
+                let pool = match kind {
+                    CompletionKind::Tag => todo_list.map(|list| list.get_all_tags()).unwrap_or_default(),
+                    CompletionKind::Context => todo_list.map(|list| list.get_all_contexts()).unwrap_or_default(),
+                    CompletionKind::Due => completion::DUE_KEYWORDS.iter().map(|k| k.to_string()).collect(),
+                };
+                let candidates = completion::candidates(partial, &pool);
+                if candidates.is_empty() {
+                    self.set_message("No completions".to_string(), MessageLevel::Warning, LONG);
+                    return;
+                }
+                Completion { kind, start, candidates, index: 0 }
             }
+        };
+        
+        let buffer = if self.mode == AppMode::EditTodo { &mut self.edit_buffer } else { &mut self.input_buffer };
+        buffer.content.replace_range(completion.start..buffer.cursor, completion.current());
+        buffer.cursor = completion.start + completion.current().len();
+        self.completion = Some(completion);
+    }
+    
+    // y copies the selected todo's text; Y copies it with notes and subtasks as a Markdown checklist
+    pub fn copy_selected_todo(&mut self, with_details: bool) {
+        let Some(todo) = self.get_selected_todo() else {
+            self.set_message("No todo selected".to_string(), MessageLevel::Warning, LONG);
+            return;
+        };
+        let text = if with_details {
+            self.get_selected_todo_list().and_then(|list| crate::export::markdown_checklist(list, todo.id))
+        } else {
+            Some(todo.raw_description.clone())
+        };
+        let Some(text) = text else { return };
+        let description = todo.description.clone();
+        
+        match crate::clipboard::copy(&text) {
+            Ok(()) if with_details => self.set_message(format!("Copied '{}' as a Markdown checklist", description), MessageLevel::Success, SHORT),
+            Ok(()) => self.set_message(format!("Copied '{}'", description), MessageLevel::Success, SHORT),
+            Err(e) => self.set_message(format!("Clipboard unavailable: {}", e), MessageLevel::Error, STICKY),
         }
     }
     
-    pub fn move_input_cursor_right(&mut self) {
-        if self.input_cursor_pos < self.input_buffer.len() {
-            self.input_cursor_pos += 1;
-            // Ensure we're at a valid character boundary
-            while self.input_cursor_pos < self.input_buffer.len() && !self.input_buffer.is_char_boundary(self.input_cursor_pos) {
-                self.input_cursor_pos += 1;
+    // Bracketed paste: insert into the open input, or offer to turn pasted lines into todos
+    pub fn paste_text(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        
+        if self.mode == AppMode::EditNotes {
+            self.edit_active_text(|buffer| buffer.insert_str(&text));
+            return;
+        }
+        
+        let lines: Vec<&str> = text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        
+        // A list pasted while adding todos becomes todos right away (under the parent for a child)
+        if lines.len() > 1 && matches!(self.mode, AppMode::Insert | AppMode::InsertChild) {
+            let parent = if self.mode == AppMode::InsertChild { self.inserting_child_for } else { None };
+            let count = self.add_pasted_todos(&text, parent);
+            self.quick_entry_count += count;
+            self.set_message(format!("Added {} todos. Press 'u' to undo.", count), MessageLevel::Success, SHORT);
+            return;
+        }
+        
+        // Single-line inputs get the lines joined with spaces
+        let joined = lines.join(" ");
+        if self.edit_active_text(|buffer| buffer.insert_str(&joined)) || self.mode != AppMode::Normal {
+            return;
+        }
+        
+        match lines.len() {
+            0 => {}
+            1 => {
+                self.enter_insert_mode();
+                self.input_buffer.set(joined);
             }
+            n if n <= self.config.confirm_paste_over => {
+                let count = self.add_pasted_todos(&text, None);
+                self.set_message(format!("Added {} todos. Press 'u' to undo.", count), MessageLevel::Success, SHORT);
+            }
+            _ => {
+                self.pending_confirmation = Some(Confirmation::PasteTodos(text));
+                self.mode = AppMode::Confirm;
+            }
+        }
+    }
+    
+    // Add a pasted outline as todos (indented lines become children) with a single undo entry
+    fn add_pasted_todos(&mut self, text: &str, parent: Option<u32>) -> usize {
+        let workspace_id = match parent {
+            Some(_) => self.editing_workspace_id.clone(),
+            None => self.workspace_manager.get_current_workspace_id(),
+        };
+        let Some(workspace_id) = workspace_id else { return 0 };
+        let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) else { return 0 };
+        
+        let ids = todo_list.add_outline(text, parent);
+        let todos: Vec<Todo> = ids.iter()
+            .filter_map(|id| todo_list.get_todo(*id).cloned())
+            .collect();
+        let count = todos.len();
+        if count > 0 {
+            self.mark_dirty();
+            self.command_history.push_command(Command::AddTodos { workspace_id, todos });
         }
+        self.clamp_selection();
+        count
     }
     
     // Clear input buffer and reset cursor
     pub fn clear_input_buffer(&mut self) {
         self.input_buffer.clear();
-        self.input_cursor_pos = 0;
     }
     
-    // Welcome screen methods
-    pub fn get_welcome_options(&self) -> Vec<(&str, &str)> {
-        if self.is_first_launch {
-            // First time user options
-            vec![
-                ("🚀 Get Started", "Create your first todo and jump right in"),
-                ("📂 Browse Workspaces", "Explore existing workspaces or create new ones"),
-                ("❓ Learn the Basics", "View help and keyboard shortcuts"),
-                ("⚡ Quick Demo", "See Paperclip in action with sample todos"),
-                ("❌ Exit", "Close Paperclip"),
-            ]
+    // The text input the current mode types into, if any
+    pub fn active_text_buffer_mut(&mut self) -> Option<&mut TextBuffer> {
+        match self.mode {
+            AppMode::Insert | AppMode::InsertChild | AppMode::CreateWorkspace
+            | AppMode::SaveFilter | AppMode::RenameTag | AppMode::RenameContext | AppMode::BulkTag
+            | AppMode::BulkContext | AppMode::BulkDue | AppMode::EditDue | AppMode::LogTime | AppMode::EditTimeEntry
+            | AppMode::ExportPath | AppMode::ImportPath | AppMode::ImportWorkspacePath
+            | AppMode::ImportWorkspaceName => Some(&mut self.input_buffer),
+            AppMode::EditTodo => Some(&mut self.edit_buffer),
+            AppMode::Search => Some(&mut self.search_buffer),
+            AppMode::EditNotes => Some(&mut self.notes_buffer),
+            _ => None,
+        }
+    }
+    
+    // Apply a word/line editing command to the active input; false when no input is open
+    pub fn edit_active_text(&mut self, edit: impl FnOnce(&mut TextBuffer)) -> bool {
+        let Some(buffer) = self.active_text_buffer_mut() else { return false };
+        edit(buffer);
+        self.completion = None;
+        if self.mode == AppMode::Search {
+            self.clamp_selection();
+        }
+        true
+    }
+    
+    // Welcome screen methods. First launches lead with Get Started; Remove Demo shows while a
+    // Quick Demo workspace exists.
+    pub fn welcome_choices(&self) -> Vec<WelcomeOption> {
+        let mut choices = if self.is_first_launch {
+            vec![WelcomeOption::GetStarted, WelcomeOption::BrowseWorkspaces, WelcomeOption::LearnBasics, WelcomeOption::QuickDemo]
+        } else {
+            vec![WelcomeOption::BrowseWorkspaces, WelcomeOption::LearnBasics, WelcomeOption::QuickDemo, WelcomeOption::CreateWorkspace]
+        };
+        if self.workspace_manager.demo_workspace_id().is_some() {
+            choices.push(WelcomeOption::RemoveDemo);
+        }
+        choices.push(WelcomeOption::Exit);
+        choices
+    }
+    
+    // Once the saved data is loaded: the welcome screen's options and greeting depend on
+    // whether there was any
+    pub fn set_first_launch(&mut self, first_launch: bool) {
+        self.is_first_launch = first_launch;
+        self.welcome_selected = 0;
+        let total_todos: usize = self.workspace_manager.workspace_todos.values().map(|todo_list| todo_list.total_count()).sum();
+        if first_launch {
+            self.set_message("Welcome to Paperclip! Choose an option below to get started.".to_string(), MessageLevel::Info, LONG);
+        } else if total_todos > 0 {
+            self.set_message(format!("Welcome back! You have {} todos across {} workspaces.", 
+                total_todos, self.workspace_manager.workspaces.len()), MessageLevel::Info, LONG);
         } else {
-            // Returning user options  
-            vec![
-                ("📂 Browse Workspaces", "Select from your existing workspaces"),
-                ("❓ Learn the Basics", "View help and keyboard shortcuts"),
-                ("⚡ Quick Demo", "See Paperclip in action with sample todos"),
-                ("🆕 Create New Workspace", "Start fresh with a new workspace"),
-                ("❌ Exit", "Close Paperclip"),
-            ]
+            self.set_message("Welcome back! Ready to organize your todos?".to_string(), MessageLevel::Info, LONG);
         }
     }
     
+    pub fn get_welcome_options(&self) -> Vec<(&str, &str)> {
+        self.welcome_choices().into_iter().map(|choice| choice.label(self.is_first_launch)).collect()
+    }
+    
     pub fn move_welcome_selection_up(&mut self) {
         if self.welcome_selected > 0 {
             self.welcome_selected -= 1;
@@ -1116,69 +3421,77 @@ impl App {
         }
     }
     
-    pub fn select_welcome_option(&mut self) {
-        if self.is_first_launch {
-            // First time user options: Get Started | Browse Workspaces | Learn | Demo | Exit
-            match self.welcome_selected {
-                0 => {
-                    // Get Started - create Personal workspace and go to insert mode
-                    self.workspace_manager.switch_workspace_by_name("Personal");
-                    self.mode = AppMode::Insert;
-                    self.clear_input_buffer();
-                    self.set_message("Welcome! Type your first todo and press Enter".to_string());
-                }
-                1 => {
-                    // Browse Workspaces
-                    self.enter_workspace_selection();
-                }
-                2 => {
-                    // Learn the Basics
-                    self.show_help = true;
-                    self.set_message("Press ? again to close help".to_string());
-                }
-                3 => {
-                    // Quick Demo
-                    self.create_demo_todos();
-                    self.workspace_manager.switch_workspace_by_name("Personal");
-                    self.mode = AppMode::Normal;
-                    self.set_message("Welcome! Try navigating with j/k, press Space to complete todos".to_string());
-                }
-                4 => {
-                    // Exit
-                    self.should_quit = true;
-                }
-                _ => {}
+    pub fn select_welcome_option(&mut self) {
+        match self.welcome_choices().get(self.welcome_selected) {
+            Some(WelcomeOption::GetStarted) => {
+                // Get Started - into the first workspace (made now if the saved data had none) in insert mode
+                let workspace_id = self.workspace_manager.ensure_workspace();
+                self.workspace_manager.switch_workspace(&workspace_id);
+                self.mark_dirty();
+                self.restore_view_state();
+                self.mode = AppMode::Insert;
+                self.clear_input_buffer();
+                self.set_message("Welcome! Type your first todo and press Enter".to_string(), MessageLevel::Info, LONG);
+            }
+            Some(WelcomeOption::BrowseWorkspaces) => self.enter_workspace_selection(),
+            Some(WelcomeOption::LearnBasics) => {
+                self.show_help = true;
+                self.set_message("Press ? again to close help".to_string(), MessageLevel::Info, LONG);
+            }
+            Some(WelcomeOption::QuickDemo) => self.open_demo_workspace(),
+            Some(WelcomeOption::RemoveDemo) => self.remove_demo_workspace(),
+            Some(WelcomeOption::CreateWorkspace) => self.enter_create_workspace_mode(),
+            Some(WelcomeOption::Exit) => self.should_quit = true,
+            None => {}
+        }
+    }
+    
+    // After loading saved data: open the configured workspace and maybe skip the welcome screen
+    pub fn apply_startup_config(&mut self) {
+        if let Some(name) = self.config.default_workspace.clone() {
+            if self.workspace_manager.switch_workspace_by_name(&name) {
+                self.restore_view_state();
+            } else {
+                self.set_message(format!("{}: no workspace named '{}'", crate::config::CONFIG_FILE, name), MessageLevel::Warning, LONG);
+            }
+        }
+        if self.config.skip_welcome {
+            self.mode = AppMode::Normal;
+        }
+        self.apply_theme();
+    }
+    
+    // Command-line choices of where to start, which win over the config file's
+    pub fn apply_launch_options(&mut self, launch: &LaunchOptions) {
+        if let Some(name) = &launch.workspace {
+            if launch.create && self.workspace_manager.find_workspace_id_by_name(name).is_none() {
+                self.workspace_manager.create_workspace(name.clone(), None);
+                self.mark_dirty();
             }
-        } else {
-            // Returning user options: Browse Workspaces | Learn | Demo | Create New | Exit
-            match self.welcome_selected {
-                0 => {
-                    // Browse Workspaces
-                    self.enter_workspace_selection();
-                }
-                1 => {
-                    // Learn the Basics
-                    self.show_help = true;
-                    self.set_message("Press ? again to close help".to_string());
-                }
-                2 => {
-                    // Quick Demo
-                    self.create_demo_todos();
-                    self.workspace_manager.switch_workspace_by_name("Personal");
-                    self.mode = AppMode::Normal;
-                    self.set_message("Welcome! Try navigating with j/k, press Space to complete todos".to_string());
-                }
-                3 => {
-                    // Create New Workspace
-                    self.enter_create_workspace_mode();
-                }
-                4 => {
-                    // Exit
-                    self.should_quit = true;
-                }
-                _ => {}
+            if !self.workspace_manager.switch_workspace_by_name(name) {
+                self.enter_workspace_selection();
+                self.set_message(format!("No workspace named '{}' (start with --create to make it). Pick one:", name), MessageLevel::Warning, LONG);
+                return;
             }
+            self.restore_view_state();
+            self.mode = AppMode::Normal;
+        }
+        if launch.view.is_some() || launch.add.is_some() {
+            self.workspace_manager.ensure_workspace();
+            self.mode = AppMode::Normal;
+        }
+        match launch.view {
+            Some(LaunchView::Pending) => self.filters.status = Some(StatusFilter::Pending),
+            Some(LaunchView::Completed) => self.filters.status = Some(StatusFilter::Completed),
+            Some(LaunchView::Overdue) => self.view_mode = ViewMode::GlobalDue(DueDateFilter::Overdue),
+            Some(LaunchView::Today) => self.view_mode = ViewMode::GlobalDue(DueDateFilter::Today),
+            None => {}
         }
+        if let Some(text) = &launch.add {
+            self.enter_insert_mode();
+            self.input_buffer.set(text.clone());
+        }
+        self.clamp_selection();
     }
     
     // Return to welcome screen from any mode
@@ -1187,15 +3500,17 @@ impl App {
         self.welcome_selected = 0;
         self.selected = 0;
         self.clear_input_buffer();
-        self.set_message("Returned to welcome screen - Choose an option to continue".to_string());
+        self.set_message("Returned to welcome screen - Choose an option to continue".to_string(), MessageLevel::Info, SHORT);
     }
     
-    fn create_demo_todos(&mut self) {
-        if let Some(todo_list) = self.get_current_todo_list_mut() {
-            // Clear existing todos in Personal workspace for demo
-            todo_list.todos.clear();
-            
-            // Add demo todos
+    // Quick Demo: tutorial todos in a workspace of their own, so none of the user's are touched.
+    // A demo workspace left from before is reopened as it is, or refilled if it was emptied.
+    fn open_demo_workspace(&mut self) {
+        let workspace_id = self.workspace_manager.ensure_demo_workspace();
+        self.save_view_state();
+        self.workspace_manager.switch_workspace(&workspace_id);
+        self.restore_view_state();
+        if let Some(todo_list) = self.get_current_todo_list_mut().filter(|todo_list| todo_list.todos.is_empty()) {
             let demo_todos = vec![
                 "Welcome to Paperclip! #getting-started @demo due:today",
                 "Try pressing Space to mark this todo complete ○ #tutorial",
@@ -1212,6 +3527,20 @@ impl App {
                 todo_list.add_todo(todo_text.to_string());
             }
         }
+        self.mark_dirty();
+        self.mode = AppMode::Normal;
+        self.set_message("Welcome! Try navigating with j/k, press Space to complete todos".to_string(), MessageLevel::Info, LONG);
+    }
+    
+    pub fn remove_demo_workspace(&mut self) {
+        let Some(workspace_id) = self.workspace_manager.demo_workspace_id() else { return };
+        if self.workspace_manager.delete_workspace(&workspace_id) {
+            self.request_save();
+            self.welcome_selected = self.welcome_selected.min(self.get_welcome_options().len() - 1);
+            self.set_message("Removed the demo workspace".to_string(), MessageLevel::Success, SHORT);
+        } else {
+            self.set_message("Cannot delete the last remaining workspace".to_string(), MessageLevel::Warning, LONG);
+        }
     }
     
     // Template management
@@ -1220,7 +3549,7 @@ impl App {
         self.available_templates = templates.iter().map(|t| t.id.clone()).collect();
         
         if self.available_templates.is_empty() {
-            self.set_message("No templates available".to_string());
+            self.set_message("No templates available".to_string(), MessageLevel::Warning, LONG);
             return;
         }
         
@@ -1235,10 +3564,11 @@ impl App {
                 // Clone the template to avoid borrow checker issues
                 if let Some(template) = self.template_manager.get_template(&template_id).cloned() {
                     let template_name = template.name.clone();
-                    if let Some(todo_list) = self.get_current_todo_list_mut() {
+                    if let Some(todo_list) = self.get_selected_todo_list_mut() {
                         if let Some(todo) = todo_list.get_todo_mut(todo_id) {
                             template.apply_to_todo(todo);
-                            self.set_message(format!("Applied template: {}", template_name));
+                            self.mark_dirty();
+                            self.set_message(format!("Applied template: {}", template_name), MessageLevel::Success, SHORT);
                         }
                     }
                 }
@@ -1267,10 +3597,11 @@ impl App {
                     RecurrencePattern::Custom(_days) => "Custom",
                 };
                 
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+                if let Some(todo_list) = self.get_selected_todo_list_mut() {
                     if let Some(todo) = todo_list.get_todo_mut(id) {
                         todo.set_recurrence(pattern);
-                        self.set_message(format!("Recurrence set to: {}", pattern_name));
+                        self.mark_dirty();
+                        self.set_message(format!("Recurrence set to: {}", pattern_name), MessageLevel::Success, SHORT);
                     }
                 }
             }
@@ -1286,22 +3617,24 @@ impl App {
             let new_count = todo_list.total_count();
             
             if new_count > old_count {
-                self.set_message(format!("Generated {} recurring todos", new_count - old_count));
+                self.mark_dirty();
+                self.set_message(format!("Generated {} recurring todos", new_count - old_count), MessageLevel::Success, SHORT);
+                self.resync_selection();
             }
         }
     }
     
     // Workspace management methods
     pub fn enter_workspace_selection(&mut self) {
-        let workspace_names = self.workspace_manager.get_all_workspaces()
+        let workspace_ids = self.workspace_manager.get_all_workspaces()
             .iter()
-            .map(|ws| ws.name.clone())
+            .map(|ws| ws.id.clone())
             .collect();
         
-        self.available_workspaces = workspace_names;
+        self.available_workspaces = workspace_ids;
         
         if self.available_workspaces.is_empty() {
-            self.set_message("No workspaces available".to_string());
+            self.set_message("No workspaces available".to_string(), MessageLevel::Warning, LONG);
             return;
         }
         
@@ -1316,42 +3649,142 @@ impl App {
         } else {
             // Regular workspace selection (subtract 1 to account for Home option)
             let workspace_index = self.popup_selected - 1;
-            if let Some(workspace_name) = self.available_workspaces.get(workspace_index) {
-                if self.workspace_manager.switch_workspace_by_name(workspace_name) {
-                    self.set_message(format!("Switched to workspace: {}", workspace_name));
-                    self.selected = 0; // Reset selection when switching workspaces
-                    self.view_mode = ViewMode::All; // Reset view mode
+            if let Some(workspace_id) = self.available_workspaces.get(workspace_index).cloned() {
+                self.save_view_state();
+                if self.workspace_manager.switch_workspace(&workspace_id) {
+                    self.mark_dirty();
+                    let workspace_name = self.get_current_workspace_name();
+                    self.set_message(format!("Switched to workspace: {}", workspace_name), MessageLevel::Success, SHORT);
+                    self.restore_view_state();
                     self.mode = AppMode::Normal;
                 } else {
-                    self.set_message("Failed to switch workspace".to_string());
+                    self.set_message("Failed to switch workspace".to_string(), MessageLevel::Error, STICKY);
                 }
             }
         }
         self.available_workspaces.clear();
     }
     
+    pub fn switch_to_workspace_number(&mut self, number: usize) {
+        let workspace_id = match self.workspace_manager.workspace_id_at(number) {
+            Some(id) => id,
+            None => {
+                self.set_message(format!("No workspace {}", number), MessageLevel::Warning, LONG);
+                return;
+            }
+        };
+        
+        self.save_view_state();
+        if self.workspace_manager.switch_workspace(&workspace_id) {
+            self.mark_dirty();
+            self.restore_view_state();
+            let workspace_name = self.get_current_workspace_name();
+            self.set_message(format!("Switched to workspace: {}", workspace_name), MessageLevel::Success, SHORT);
+        }
+    }
+    
+    // The workspace highlighted in the selection screen; index 0 is the Home option
+    fn popup_workspace_id(&self) -> Option<String> {
+        self.popup_selected.checked_sub(1).and_then(|i| self.available_workspaces.get(i)).cloned()
+    }
+    
+    // Reorder the workspace highlighted in the selection screen
+    pub fn move_selected_workspace(&mut self, up: bool) {
+        let Some(workspace_id) = self.popup_workspace_id() else { return };
+        
+        if self.workspace_manager.move_workspace(&workspace_id, up) {
+            self.mark_dirty();
+            self.available_workspaces = self.workspace_manager.get_all_workspaces()
+                .iter()
+                .map(|ws| ws.id.clone())
+                .collect();
+            if up {
+                self.popup_selected -= 1;
+            } else {
+                self.popup_selected += 1;
+            }
+        }
+    }
+    
+    pub fn switch_to_previous_workspace(&mut self) {
+        self.save_view_state();
+        if self.workspace_manager.switch_to_previous_workspace() {
+            self.mark_dirty();
+            self.restore_view_state();
+            let workspace_name = self.get_current_workspace_name();
+            self.set_message(format!("Switched to workspace: {}", workspace_name), MessageLevel::Success, SHORT);
+        } else {
+            self.set_message("No previous workspace to switch to".to_string(), MessageLevel::Warning, LONG);
+        }
+    }
+    
+    // Remember selection and view of the current workspace before leaving it
+    pub fn save_view_state(&mut self) {
+        if let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() {
+            let state = WorkspaceViewState {
+                selected: self.selected,
+                selected_todo_id: self.get_selected_todo_id(),
+                view_mode: self.view_mode.clone(),
+                filters: self.filters.clone(),
+            };
+            self.workspace_view_states.insert(workspace_id, state);
+        }
+    }
+    
+    // Restore the remembered state of the current workspace, or start fresh
+    pub fn restore_view_state(&mut self) {
+        let state = self.workspace_manager.get_current_workspace_id()
+            .and_then(|id| self.workspace_view_states.get(&id).cloned());
+        
+        match state {
+            Some(state) => {
+                self.view_mode = state.view_mode;
+                self.filters = state.filters;
+                let todos = self.get_visible_todos();
+                // Fall back to the old position if the remembered todo is gone
+                self.selected = state.selected_todo_id
+                    .and_then(|id| todos.iter().position(|(_, todo, _)| todo.id == id))
+                    .unwrap_or_else(|| state.selected.min(todos.len().saturating_sub(1)));
+            }
+            None => {
+                self.selected = 0;
+                self.view_mode = self.config.default_view.clone();
+                self.filters = FilterSet::default();
+            }
+        }
+    }
+    
     pub fn create_new_workspace(&mut self, name: String, description: Option<String>) {
         let workspace_id = self.workspace_manager.create_workspace(name.clone(), description);
-        self.set_message(format!("Created workspace: {} (ID: {})", name, workspace_id));
+        if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+            todo_list.sort_mode = self.config.default_sort;
+        }
+        self.mark_dirty();
+        self.set_message(format!("Created workspace: {} (ID: {})", name, workspace_id), MessageLevel::Success, SHORT);
     }
     
     pub fn delete_current_workspace(&mut self) {
         let current_name = self.get_current_workspace_name();
+        let current_id = self.workspace_manager.get_current_workspace_id();
         if self.workspace_manager.delete_workspace(&current_name) {
-            self.set_message(format!("Deleted workspace: {}", current_name));
-            self.selected = 0; // Reset selection
-            self.view_mode = ViewMode::All; // Reset view mode
+            self.request_save();
+            self.set_message(format!("Deleted workspace: {}", current_name), MessageLevel::Success, SHORT);
+            if let Some(id) = current_id {
+                self.workspace_view_states.remove(&id);
+            }
+            self.restore_view_state();
         } else {
-            self.set_message("Cannot delete the last remaining workspace".to_string());
+            self.set_message("Cannot delete the last remaining workspace".to_string(), MessageLevel::Warning, LONG);
         }
     }
     
     pub fn rename_current_workspace(&mut self, new_name: String) {
         let current_name = self.get_current_workspace_name();
         if self.workspace_manager.rename_workspace(&current_name, new_name.clone()) {
-            self.set_message(format!("Renamed workspace to: {}", new_name));
+            self.mark_dirty();
+            self.set_message(format!("Renamed workspace to: {}", new_name), MessageLevel::Success, SHORT);
         } else {
-            self.set_message("Failed to rename workspace (name may already exist)".to_string());
+            self.set_message("Failed to rename workspace (name may already exist)".to_string(), MessageLevel::Error, STICKY);
         }
     }
     
@@ -1359,75 +3792,377 @@ impl App {
     pub fn enter_create_workspace_mode(&mut self) {
         self.mode = AppMode::CreateWorkspace;
         self.input_buffer.clear();
-        self.set_message("Enter workspace name:".to_string());
+        self.set_message("Enter workspace name:".to_string(), MessageLevel::Info, LONG);
     }
     
     pub fn submit_workspace_creation(&mut self) {
-        if !self.input_buffer.trim().is_empty() {
-            let workspace_name = self.input_buffer.trim().to_string();
+        if !self.input_buffer.content.trim().is_empty() {
+            let workspace_name = self.input_buffer.content.trim().to_string();
             
             // Check if workspace name already exists
             if self.workspace_manager.get_all_workspaces()
                 .iter()
                 .any(|ws| ws.name == workspace_name) {
-                self.set_message("Workspace with this name already exists".to_string());
+                self.set_message("Workspace with this name already exists".to_string(), MessageLevel::Warning, LONG);
                 return;
             }
             
             // Create the workspace
             let workspace_id = self.workspace_manager.create_workspace(
                 workspace_name.clone(), 
-                Some(format!("Workspace created by user"))
+                Some("Workspace created by user".to_string())
             );
+            self.mark_dirty();
             
             // Refresh available workspaces list
             self.available_workspaces = self.workspace_manager.get_all_workspaces()
                 .iter()
-                .map(|ws| ws.name.clone())
+                .map(|ws| ws.id.clone())
                 .collect();
             
             // Switch to the newly created workspace
+            self.save_view_state();
             if self.workspace_manager.switch_workspace_by_name(&workspace_name) {
-                self.set_message(format!("Created and switched to workspace: {}", workspace_name));
+                self.mark_dirty();
+                self.set_message(format!("Created and switched to workspace: {}", workspace_name), MessageLevel::Success, SHORT);
                 self.mode = AppMode::Normal;
                 self.selected = 0;
-                self.view_mode = ViewMode::All;
+                self.view_mode = ViewMode::List;
+                self.filters = FilterSet::default();
             } else {
-                self.set_message(format!("Created workspace: {} (ID: {}), but failed to switch", workspace_name, workspace_id));
+                self.set_message(format!("Created workspace: {} (ID: {}), but failed to switch", workspace_name, workspace_id), MessageLevel::Warning, LONG);
                 self.mode = AppMode::WorkspaceSelection;
             }
         } else {
-            self.set_message("Workspace name cannot be empty".to_string());
+            self.set_message("Workspace name cannot be empty".to_string(), MessageLevel::Warning, LONG);
         }
         self.input_buffer.clear();
     }
     
+    // X: pick a format, then where to write the current workspace
+    pub fn enter_export_mode(&mut self) {
+        if self.get_current_todo_list().is_none() {
+            return;
+        }
+        self.mode = AppMode::Export;
+        self.popup_selected = 0;
+    }
+    
+    fn enter_export_path_mode(&mut self) {
+        let format = ExportFormat::ALL[self.popup_selected.min(ExportFormat::ALL.len() - 1)];
+        self.popup_selected = 0;
+        self.open_export_prompt(format);
+    }
+    
+    // x in the workspace selection screen: share the highlighted workspace as JSON
+    pub fn enter_export_workspace_mode(&mut self) {
+        let Some(workspace_id) = self.popup_workspace_id() else {
+            self.set_message("Highlight a workspace to export".to_string(), MessageLevel::Warning, LONG);
+            return;
+        };
+        self.export_workspace_id = Some(workspace_id);
+        self.return_mode = Some(AppMode::WorkspaceSelection);
+        self.open_export_prompt(ExportFormat::WorkspaceJson);
+    }
+    
+    fn open_export_prompt(&mut self, format: ExportFormat) {
+        self.export_format = Some(format);
+        self.mode = AppMode::ExportPath;
+        self.input_buffer.set(format.default_file(&self.export_workspace_name()));
+        self.set_message(format!("Save the {} export where? (relative to the directory Paperclip was started in)", format.name()), MessageLevel::Info, LONG);
+    }
+    
+    // The workspace a todo.txt or JSON export writes
+    pub fn export_workspace_name(&self) -> String {
+        match self.export_workspace_id.as_ref().and_then(|id| self.workspace_manager.workspaces.get(id)) {
+            Some(workspace) => workspace.name.clone(),
+            None => self.get_current_workspace_name(),
+        }
+    }
+    
+    pub fn submit_export(&mut self) {
+        let path = expand_path(self.input_buffer.content.trim());
+        // Ask before replacing a file that's already there
+        if path.exists() {
+            self.pending_confirmation = Some(Confirmation::OverwriteExport(path));
+            self.mode = AppMode::Confirm;
+            return;
+        }
+        self.write_export(&path);
+    }
+    
+    fn write_export(&mut self, path: &std::path::Path) {
+        let Some(format) = self.export_format.take() else { return };
+        let written = if format == ExportFormat::WorkspaceJson {
+            self.write_workspace_json(path)
+        } else {
+            match self.export_content(format) {
+                Some((content, count)) => fs::write(path, content).map(|()| count),
+                None => return,
+            }
+        };
+        let what = match format {
+            ExportFormat::TimeCsv => "time entries",
+            ExportFormat::ICalendar => "due todos",
+            _ => "todos",
+        };
+        match written {
+            Ok(count) => self.set_message(format!("Exported {} {} as {} to {}", count, what, format.name(), path.display()), MessageLevel::Success, SHORT),
+            Err(e) => self.set_message(format!("Couldn't write {}: {}", path.display(), e), MessageLevel::Error, STICKY),
+        }
+        self.mode = self.return_mode.take().unwrap_or(AppMode::Normal);
+        self.clear_input_buffer();
+    }
+    
+    fn write_workspace_json(&mut self, path: &std::path::Path) -> io::Result<usize> {
+        let workspace_id = self.export_workspace_id.take()
+            .or_else(|| self.workspace_manager.get_current_workspace_id())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no workspace to export"))?;
+        Storage::export_workspace(&self.workspace_manager, &workspace_id, path)?;
+        Ok(self.workspace_manager.workspace_todos.get(&workspace_id).map_or(0, |todo_list| todo_list.todos.len()))
+    }
+    
+    // The file contents for the text formats and how many things went into them
+    fn export_content(&self, format: ExportFormat) -> Option<(String, usize)> {
+        let exported = match format {
+            ExportFormat::TodoTxt => {
+                let todo_list = self.get_current_todo_list()?;
+                (export::to_todotxt(todo_list), todo_list.todos.len())
+            }
+            ExportFormat::TodosCsv => {
+                let todos = self.workspace_manager.workspace_todos.values().map(|todo_list| todo_list.todos.len()).sum();
+                (export::todos_csv(&self.workspace_manager), todos)
+            }
+            ExportFormat::ICalendar => {
+                let due = self.workspace_manager.workspace_todos.values()
+                    .flat_map(|todo_list| todo_list.todos.values())
+                    .filter(|todo| todo.due_date.is_some())
+                    .count();
+                (export::to_ics(&self.workspace_manager), due)
+            }
+            ExportFormat::TimeCsv => {
+                let entries = self.workspace_manager.workspace_todos.values()
+                    .flat_map(|todo_list| todo_list.todos.values())
+                    .map(|todo| todo.time_tracker.entries.len())
+                    .sum();
+                (export::time_entries_csv(&self.workspace_manager), entries)
+            }
+            ExportFormat::WorkspaceJson => return None, // Written by Storage::export_workspace
+        };
+        Some(exported)
+    }
+    
+    // I on the welcome or workspace selection screen: add a workspace someone shared
+    pub fn enter_import_workspace_mode(&mut self) {
+        self.return_mode = Some(self.mode.clone());
+        self.mode = AppMode::ImportWorkspacePath;
+        self.clear_input_buffer();
+        self.set_message("Path of the shared workspace file to import".to_string(), MessageLevel::Info, LONG);
+    }
+    
+    pub fn submit_import_workspace(&mut self) {
+        let path = expand_path(self.input_buffer.content.trim());
+        let (workspace, todos) = match Storage::import_workspace(&path) {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.set_message(format!("Couldn't import {}: {}", path.display(), e), MessageLevel::Error, STICKY);
+                return;
+            }
+        };
+        if self.workspace_manager.find_workspace_id_by_name(&workspace.name).is_none() {
+            self.finish_workspace_import(workspace, todos);
+            return;
+        }
+        self.set_message(format!("A workspace called '{}' already exists. Name the imported one:", workspace.name), MessageLevel::Info, LONG);
+        self.input_buffer.set(self.workspace_manager.unused_workspace_name(&workspace.name));
+        self.pending_workspace_import = Some((workspace, todos));
+        self.mode = AppMode::ImportWorkspaceName;
+    }
+    
+    pub fn submit_import_workspace_name(&mut self) {
+        let name = self.input_buffer.content.trim().to_string();
+        if name.is_empty() {
+            self.set_message("Workspace name cannot be empty".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        if self.workspace_manager.find_workspace_id_by_name(&name).is_some() {
+            self.set_message(format!("A workspace called '{}' already exists", name), MessageLevel::Warning, LONG);
+            return;
+        }
+        let Some((mut workspace, todos)) = self.pending_workspace_import.take() else { return };
+        workspace.name = name;
+        self.finish_workspace_import(workspace, todos);
+    }
+    
+    fn finish_workspace_import(&mut self, workspace: Workspace, todos: TodoList) {
+        let name = workspace.name.clone();
+        let count = todos.todos.len();
+        self.workspace_manager.import_workspace(workspace, todos);
+        self.mark_dirty();
+        self.available_workspaces = self.workspace_manager.get_all_workspaces()
+            .iter()
+            .map(|ws| ws.id.clone())
+            .collect();
+        self.mode = self.return_mode.take().unwrap_or(AppMode::WorkspaceSelection);
+        self.clear_input_buffer();
+        self.set_message(format!("Imported workspace '{}' with {} todos", name, count), MessageLevel::Success, SHORT);
+    }
+    
+    // I: add the todos from a todo.txt file to the current workspace
+    pub fn enter_import_mode(&mut self) {
+        if self.get_current_todo_list().is_none() {
+            return;
+        }
+        self.mode = AppMode::ImportPath;
+        self.clear_input_buffer();
+        self.set_message("Path of the todo.txt file to import into this workspace".to_string(), MessageLevel::Info, LONG);
+    }
+    
+    pub fn submit_import(&mut self) {
+        let path = expand_path(self.input_buffer.content.trim());
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.set_message(format!("Couldn't read {}: {}", path.display(), e), MessageLevel::Error, STICKY);
+                return;
+            }
+        };
+        let (imported, skipped) = import::from_todotxt(&text);
+        let mut todos: Vec<Todo> = imported.todos.into_values().collect();
+        todos.sort_by_key(|todo| todo.id);
+        let count = todos.len();
+        let Some(workspace_id) = self.workspace_manager.get_current_workspace_id() else { return };
+        if let Some(todo_list) = self.get_current_todo_list_mut() {
+            let todos = todo_list.adopt_todos(todos);
+            if !todos.is_empty() {
+                // Undone in one step, like a pasted batch
+                self.mark_dirty();
+                self.command_history.push_command(Command::AddTodos { workspace_id, todos });
+            }
+        }
+        let mut message = format!("Imported {} todos from {}", count, path.display());
+        if skipped > 0 {
+            message.push_str(&format!(" (skipped {} unreadable line{})", skipped, if skipped == 1 { "" } else { "s" }));
+        }
+        self.set_message(message, MessageLevel::Success, SHORT);
+        self.clamp_selection();
+        self.mode = AppMode::Normal;
+        self.clear_input_buffer();
+    }
+    
+    // Moving todos between workspaces
+    pub fn enter_move_to_workspace(&mut self) {
+        let from_visual = self.mode == AppMode::Visual;
+        if from_visual && self.get_bulk_selection().is_empty() {
+            self.set_message("No todos selected for bulk operation".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        if !from_visual && self.get_selected_todo_id().is_none() {
+            return;
+        }
+        
+        let source_id = if from_visual {
+            self.workspace_manager.get_current_workspace_id()
+        } else {
+            self.get_selected_workspace_id()
+        };
+        self.available_workspaces = self.workspace_manager.get_all_workspaces()
+            .iter()
+            .filter(|ws| Some(&ws.id) != source_id.as_ref())
+            .map(|ws| ws.id.clone())
+            .collect();
+        
+        if self.available_workspaces.is_empty() {
+            self.set_message("No other workspace to move to".to_string(), MessageLevel::Warning, LONG);
+            return;
+        }
+        
+        self.move_from_visual = from_visual;
+        self.mode = AppMode::MoveToWorkspace;
+        self.popup_selected = 0;
+    }
+    
+    pub fn move_to_selected_workspace(&mut self) {
+        let from_visual = self.move_from_visual;
+        self.move_from_visual = false;
+        
+        let target_id = self.available_workspaces.get(self.popup_selected).cloned();
+        let source_id = if from_visual {
+            self.workspace_manager.get_current_workspace_id()
+        } else {
+            self.get_selected_workspace_id()
+        };
+        let mut todo_ids: Vec<u32> = if from_visual {
+            self.get_bulk_selection()
+        } else {
+            self.get_selected_todo_id().into_iter().collect()
+        };
+        todo_ids.sort_unstable();
+        
+        if let (false, Some(target_workspace_id), Some(source_id)) = (todo_ids.is_empty(), target_id, source_id) {
+            let moved = self.workspace_manager.move_todos(&source_id, &target_workspace_id, &todo_ids);
+            
+            if let Some((original_todos, moved_todos)) = moved {
+                let count = moved_todos.len();
+                let target_name = self.workspace_manager.workspaces.get(&target_workspace_id)
+                    .map(|ws| ws.name.clone())
+                    .unwrap_or_default();
+                let command = Command::MoveTodos { workspace_id: source_id, target_workspace_id, original_todos, moved_todos };
+                self.command_history.push_command(command);
+                self.mark_dirty();
+                
+                if from_visual {
+                    self.set_message(format!("Moved {} todos to {}. Press 'u' to undo.", count, target_name), MessageLevel::Success, SHORT);
+                } else if count == 1 {
+                    self.set_message(format!("Moved todo to {}. Press 'u' to undo.", target_name), MessageLevel::Success, SHORT);
+                } else {
+                    self.set_message(format!("Moved todo and {} children to {}. Press 'u' to undo.", count - 1, target_name), MessageLevel::Success, SHORT);
+                }
+                
+                // Adjust selection if needed
+                let todos = self.get_visible_todos();
+                if self.selected >= todos.len() && !todos.is_empty() {
+                    self.selected = todos.len() - 1;
+                }
+            } else {
+                self.set_message("Failed to move todo".to_string(), MessageLevel::Error, STICKY);
+            }
+        }
+        
+        if from_visual {
+            self.visual_start = None;
+            self.selected_todos.clear();
+            self.bulk_operation = None;
+        }
+        self.mode = AppMode::Normal;
+        self.available_workspaces.clear();
+    }
+    
     pub fn cancel_workspace_creation(&mut self) {
         self.mode = AppMode::WorkspaceSelection;
         self.input_buffer.clear();
-        self.set_message("Workspace creation cancelled".to_string());
+        self.set_message("Workspace creation cancelled".to_string(), MessageLevel::Info, SHORT);
     }
     
     pub fn delete_selected_workspace(&mut self) {
         if self.popup_selected == 0 {
             // Can't delete the Home option
-            self.set_message("Cannot delete the Home option".to_string());
+            self.set_message("Cannot delete the Home option".to_string(), MessageLevel::Warning, LONG);
             return;
         }
         
         // Adjust index to account for Home option
         let workspace_index = self.popup_selected - 1;
-        if let Some(workspace_name) = self.available_workspaces.get(workspace_index) {
-            // Find workspace ID by name
-            if let Some((workspace_id, _)) = self.workspace_manager.workspaces.iter().find(|(_, ws)| ws.name == *workspace_name) {
-                let workspace_id = workspace_id.clone();
+        if let Some(workspace_id) = self.available_workspaces.get(workspace_index).cloned() {
+            if let Some(workspace_name) = self.workspace_manager.workspaces.get(&workspace_id).map(|ws| ws.name.clone()) {
                 if self.workspace_manager.delete_workspace(&workspace_id) {
-                    self.set_message(format!("Deleted workspace: {}", workspace_name));
+                    self.set_message(format!("Deleted workspace: {}", workspace_name), MessageLevel::Success, SHORT);
+                    self.request_save();
                     
                     // Refresh available workspaces list
                     self.available_workspaces = self.workspace_manager.get_all_workspaces()
                         .iter()
-                        .map(|ws| ws.name.clone())
+                        .map(|ws| ws.id.clone())
                         .collect();
                     
                     // Adjust popup selection if needed
@@ -1438,12 +4173,12 @@ impl App {
                     // If no workspaces left, exit to normal mode
                     if self.available_workspaces.is_empty() {
                         self.mode = AppMode::Normal;
-                        self.set_message("All workspaces deleted. Creating default workspace.".to_string());
+                        self.set_message("All workspaces deleted. Creating default workspace.".to_string(), MessageLevel::Info, LONG);
                         // Create a default workspace
                         self.workspace_manager.create_workspace("Personal".to_string(), Some("Default workspace".to_string()));
                     }
                 } else {
-                    self.set_message("Cannot delete the last remaining workspace".to_string());
+                    self.set_message("Cannot delete the last remaining workspace".to_string(), MessageLevel::Warning, LONG);
                 }
             }
         }
@@ -1451,33 +4186,62 @@ impl App {
     
     // Undo/Redo functionality
     pub fn undo(&mut self) {
-        if let Some(command) = self.command_history.undo() {
-            self.execute_undo_command(command);
-        } else {
-            self.set_message("Nothing to undo".to_string());
+        match self.command_history.undo() {
+            Some(command) if !self.has_workspace_for(&command) => {
+                self.set_message("Skipped undo: the workspace it was made in no longer exists".to_string(), MessageLevel::Warning, LONG);
+            }
+            Some(command) => {
+                let affected = command.todo_ids();
+                let workspace_id = command.workspace_id().to_string();
+                self.execute_undo_command(command);
+                self.mark_dirty();
+                self.select_affected_todo(&workspace_id, &affected);
+            }
+            None => self.set_message("Nothing to undo".to_string(), MessageLevel::Warning, LONG),
         }
     }
     
     pub fn redo(&mut self) {
-        if let Some(command) = self.command_history.redo() {
-            self.execute_redo_command(command);
-        } else {
-            self.set_message("Nothing to redo".to_string());
+        match self.command_history.redo() {
+            Some(command) if !self.has_workspace_for(&command) => {
+                self.set_message("Skipped redo: the workspace it was made in no longer exists".to_string(), MessageLevel::Warning, LONG);
+            }
+            Some(command) => {
+                let affected = command.todo_ids();
+                let workspace_id = command.workspace_id().to_string();
+                self.execute_redo_command(command);
+                self.mark_dirty();
+                self.select_affected_todo(&workspace_id, &affected);
+            }
+            None => self.set_message("Nothing to redo".to_string(), MessageLevel::Warning, LONG),
+        }
+    }
+    
+    // After an undo or redo, select the first affected todo still on screen so the change is visible
+    fn select_affected_todo(&mut self, workspace_id: &str, todo_ids: &[u32]) {
+        let revealed = todo_ids.iter().any(|&id| self.reveal_todo(workspace_id, id));
+        if !revealed {
+            self.resync_selection();
         }
     }
     
+    // Commands apply to the workspace they were recorded in, which may since have been deleted
+    fn has_workspace_for(&self, command: &Command) -> bool {
+        self.workspace_manager.workspace_todos.contains_key(command.workspace_id())
+    }
+    
     fn execute_undo_command(&mut self, command: Command) {
         match command {
-            Command::AddTodo { workspace_id: _workspace_id, todo } => {
+            Command::AddTodo { workspace_id, todo } => {
                 // Undo add: remove the todo
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     todo_list.remove_todo(todo.id);
-                    self.set_message(format!("Undid: Add todo '{}'", todo.description));
+                    self.set_message(format!("Undid: Add todo '{}'", todo.description), MessageLevel::Success, SHORT);
                 }
             },
-            Command::DeleteTodo { workspace_id: _workspace_id, todo } => {
+            Command::DeleteTodo { workspace_id, todo } => {
                 // Undo delete: restore the todo
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     // Restore parent-child relationships if needed
                     if let Some(parent_id) = todo.parent_id {
                         if let Some(parent) = todo_list.get_todo_mut(parent_id) {
@@ -1487,12 +4251,12 @@ impl App {
                         }
                     }
                     todo_list.todos.insert(todo.id, todo.clone());
-                    self.set_message(format!("Undid: Delete todo '{}'", todo.description));
+                    self.set_message(format!("Undid: Delete todo '{}'", todo.description), MessageLevel::Success, SHORT);
                 }
             },
-            Command::CompleteTodo { workspace_id: _workspace_id, todo_id, old_status } => {
+            Command::CompleteTodo { workspace_id, todo_id, old_status } => {
                 // Undo complete: restore old status
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     if let Some(todo) = todo_list.get_todo_mut(todo_id) {
                         todo.status = old_status.clone();
                         if matches!(old_status, crate::todo::TodoStatus::Completed) {
@@ -1500,43 +4264,50 @@ impl App {
                         } else {
                             todo.completed_at = None;
                         }
-                        self.set_message("Undid: Toggle todo completion".to_string());
+                        self.set_message("Undid: Toggle todo completion".to_string(), MessageLevel::Success, SHORT);
                     }
                 }
             },
-            Command::EditTodo { workspace_id: _workspace_id, todo_id, old_description, old_raw_description } => {
-                // Undo edit: restore old description
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
-                    if let Some(todo) = todo_list.get_todo_mut(todo_id) {
-                        todo.description = old_description;
-                        todo.raw_description = old_raw_description;
-                        self.set_message("Undid: Edit todo".to_string());
-                    }
+            Command::EditTodo { workspace_id, todo_id, old_raw_description, new_raw_description: _ } => {
+                // Undo edit: re-parse the old text so tags, contexts and due date come back with it
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.update_description(old_raw_description);
+                    self.set_message("Undid: Edit todo".to_string(), MessageLevel::Success, SHORT);
                 }
             },
-            Command::ChangePriority { workspace_id: _workspace_id, todo_id, old_priority } => {
+            Command::ChangePriority { workspace_id, todo_id, old_priority, new_priority: _ } => {
                 // Undo priority change: restore old priority
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
-                    if let Some(todo) = todo_list.get_todo_mut(todo_id) {
-                        todo.priority = old_priority;
-                        self.set_message(format!("Undid: Priority change (restored to {})", old_priority));
-                    }
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.priority = old_priority;
+                    self.set_message(format!("Undid: Priority change (restored to {})", old_priority), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::EditNotes { workspace_id, todo_id, old_notes, new_notes: _ } => {
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.set_notes(old_notes);
+                    self.set_message("Undid: Edit notes".to_string(), MessageLevel::Success, SHORT);
                 }
             },
-            Command::AddChildTodo { workspace_id: _workspace_id, parent_id, child_todo } => {
+            Command::AddChildTodo { workspace_id, parent_id, child_todo } => {
                 // Undo add child: remove the child todo
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     // Remove from parent's children list
                     if let Some(parent) = todo_list.get_todo_mut(parent_id) {
                         parent.children.retain(|&id| id != child_todo.id);
                     }
                     todo_list.remove_todo(child_todo.id);
-                    self.set_message(format!("Undid: Add child todo '{}'", child_todo.description));
+                    self.set_message(format!("Undid: Add child todo '{}'", child_todo.description), MessageLevel::Success, SHORT);
                 }
             },
-            Command::DeleteWithChildren { workspace_id: _workspace_id, deleted_todos } => {
+            Command::DeleteWithChildren { workspace_id, deleted_todos } => {
                 // Undo delete with children: restore all todos
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     // Restore all todos
                     for todo in &deleted_todos {
                         todo_list.todos.insert(todo.id, todo.clone());
@@ -1551,7 +4322,78 @@ impl App {
                             }
                         }
                     }
-                    self.set_message(format!("Undid: Delete {} todos with children", deleted_todos.len()));
+                    self.set_message(format!("Undid: Delete {} todos with children", deleted_todos.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::MoveTodos { workspace_id, target_workspace_id, original_todos, moved_todos } => {
+                // Undo move: take the moved todos out of the target and restore the originals
+                if let Some(target_list) = self.workspace_manager.workspace_todos.get_mut(&target_workspace_id) {
+                    for todo in &moved_todos {
+                        target_list.remove_todo(todo.id);
+                    }
+                }
+                if let Some(source_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    source_list.restore_todos(&original_todos);
+                    self.set_message(format!("Undid: Move {} todos to another workspace", original_todos.len()), MessageLevel::Success, SHORT);
+                } else {
+                    self.set_message("Cannot undo move: original workspace no longer exists".to_string(), MessageLevel::Warning, LONG);
+                }
+            },
+            Command::RewriteDescriptions { workspace_id, old_raw_descriptions, new_raw_descriptions: _ } => {
+                if self.apply_raw_descriptions(&workspace_id, &old_raw_descriptions) {
+                    self.set_message(format!("Undid: Rewrite {} todos", old_raw_descriptions.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::AddTodos { workspace_id, todos } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for todo in todos.iter().rev() {
+                        todo_list.remove_todo(todo.id);
+                    }
+                    self.set_message(format!("Undid: Add {} todos", todos.len()), MessageLevel::Success, SHORT);
+                }
+                self.clamp_selection();
+            },
+            Command::ChangeStatus { workspace_id, todo_id, old_status, old_completed_at, new_status: _ } => {
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.status = old_status.clone();
+                    todo.completed_at = old_completed_at;
+                    let message = format!("Undid: Move '{}' back to {}", todo.description, old_status.name());
+                    self.set_message(message, MessageLevel::Success, SHORT);
+                }
+            },
+            Command::BulkComplete { workspace_id, changes } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for (id, old_status, old_completed_at) in &changes {
+                        if let Some(todo) = todo_list.get_todo_mut(*id) {
+                            todo.status = old_status.clone();
+                            todo.completed_at = *old_completed_at;
+                        }
+                    }
+                    self.set_message(format!("Undid: Bulk complete {} todos", changes.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::BulkSetPriority { workspace_id, changes, new_priority: _ } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for (id, old_priority) in &changes {
+                        if let Some(todo) = todo_list.get_todo_mut(*id) {
+                            todo.priority = *old_priority;
+                        }
+                    }
+                    self.set_message(format!("Undid: Set priority for {} todos", changes.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::BulkSetDue { workspace_id, changes, new_due: _ } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for (id, old_due, old_raw) in &changes {
+                        if let Some(todo) = todo_list.get_todo_mut(*id) {
+                            todo.replace_raw_description(old_raw.clone());
+                            todo.due_date = *old_due;
+                        }
+                    }
+                    let plural = if changes.len() == 1 { "" } else { "s" };
+                    self.set_message(format!("Undid: Set due date for {} todo{}", changes.len(), plural), MessageLevel::Success, SHORT);
                 }
             },
         }
@@ -1560,40 +4402,133 @@ impl App {
     fn execute_redo_command(&mut self, command: Command) {
         // Redo is essentially re-executing the original command
         match command {
-            Command::AddTodo { workspace_id: _workspace_id, todo } => {
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+            Command::AddTodo { workspace_id, todo } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     todo_list.todos.insert(todo.id, todo.clone());
-                    self.set_message(format!("Redid: Add todo '{}'", todo.description));
+                    self.set_message(format!("Redid: Add todo '{}'", todo.description), MessageLevel::Success, SHORT);
                 }
             },
-            Command::DeleteTodo { workspace_id: _workspace_id, todo } => {
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+            Command::DeleteTodo { workspace_id, todo } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     todo_list.remove_todo(todo.id);
-                    self.set_message(format!("Redid: Delete todo '{}'", todo.description));
+                    self.set_message(format!("Redid: Delete todo '{}'", todo.description), MessageLevel::Success, SHORT);
                 }
             },
-            Command::CompleteTodo { workspace_id: _workspace_id, todo_id, old_status: _old_status } => {
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
+            Command::CompleteTodo { workspace_id, todo_id, old_status: _old_status } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
                     if let Some(todo) = todo_list.get_todo_mut(todo_id) {
                         todo.toggle_complete();
                         let status = if todo.is_completed() { "completed" } else { "pending" };
-                        self.set_message(format!("Redid: Todo marked as {}", status));
+                        self.set_message(format!("Redid: Todo marked as {}", status), MessageLevel::Success, SHORT);
                     }
                 }
             },
-            Command::ChangePriority { workspace_id: _workspace_id, todo_id, old_priority } => {
-                // For redo, we need to toggle the priority back
-                if let Some(todo_list) = self.get_current_todo_list_mut() {
-                    if let Some(todo) = todo_list.get_todo_mut(todo_id) {
-                        let current_priority = todo.priority;
-                        todo.priority = old_priority;
-                        self.set_message(format!("Redid: Priority change (from {} to {})", current_priority, old_priority));
+            Command::ChangePriority { workspace_id, todo_id, old_priority, new_priority } => {
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.priority = new_priority;
+                    self.set_message(format!("Redid: Priority change (from {} to {})", old_priority, new_priority), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::EditTodo { workspace_id, todo_id, old_raw_description: _, new_raw_description } => {
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.update_description(new_raw_description);
+                    self.set_message("Redid: Edit todo".to_string(), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::EditNotes { workspace_id, todo_id, old_notes: _, new_notes } => {
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.set_notes(new_notes);
+                    self.set_message("Redid: Edit notes".to_string(), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::MoveTodos { workspace_id, target_workspace_id, original_todos, moved_todos } => {
+                if !self.workspace_manager.workspace_todos.contains_key(&target_workspace_id) {
+                    self.set_message("Cannot redo move: target workspace no longer exists".to_string(), MessageLevel::Warning, LONG);
+                    return;
+                }
+                if let Some(source_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for todo in &original_todos {
+                        source_list.remove_todo(todo.id);
                     }
                 }
+                if let Some(target_list) = self.workspace_manager.workspace_todos.get_mut(&target_workspace_id) {
+                    target_list.restore_todos(&moved_todos);
+                    self.set_message(format!("Redid: Move {} todos to another workspace", moved_todos.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::RewriteDescriptions { workspace_id, old_raw_descriptions: _, new_raw_descriptions } => {
+                if self.apply_raw_descriptions(&workspace_id, &new_raw_descriptions) {
+                    self.set_message(format!("Redid: Rewrite {} todos", new_raw_descriptions.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::AddTodos { workspace_id, todos } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    todo_list.restore_todos(&todos);
+                    self.set_message(format!("Redid: Add {} todos", todos.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::ChangeStatus { workspace_id, todo_id, old_status: _, old_completed_at: _, new_status } => {
+                let todo = self.workspace_manager.workspace_todos.get_mut(&workspace_id)
+                    .and_then(|todo_list| todo_list.get_todo_mut(todo_id));
+                if let Some(todo) = todo {
+                    todo.set_status(new_status.clone());
+                    let message = format!("Redid: Move '{}' to {}", todo.description, new_status.name());
+                    self.set_message(message, MessageLevel::Success, SHORT);
+                }
+            },
+            Command::BulkComplete { workspace_id, changes } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for (id, _, _) in &changes {
+                        if let Some(todo) = todo_list.get_todo_mut(*id) {
+                            todo.complete();
+                        }
+                    }
+                    self.set_message(format!("Redid: Bulk complete {} todos", changes.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::BulkSetPriority { workspace_id, changes, new_priority } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for (id, _) in &changes {
+                        if let Some(todo) = todo_list.get_todo_mut(*id) {
+                            todo.priority = new_priority;
+                        }
+                    }
+                    self.set_message(format!("Redid: Set priority to {} for {} todos", new_priority, changes.len()), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::BulkSetDue { workspace_id, changes, new_due } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for (id, _, _) in &changes {
+                        if let Some(todo) = todo_list.get_todo_mut(*id) {
+                            todo.set_due_date(new_due);
+                        }
+                    }
+                    let plural = if changes.len() == 1 { "" } else { "s" };
+                    self.set_message(format!("Redid: Set due date for {} todo{}", changes.len(), plural), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::AddChildTodo { workspace_id, parent_id: _, child_todo } => {
+                // restore_todos reattaches the child to its parent
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    todo_list.restore_todos(std::slice::from_ref(&child_todo));
+                    self.set_message(format!("Redid: Add child todo '{}'", child_todo.description), MessageLevel::Success, SHORT);
+                }
+            },
+            Command::DeleteWithChildren { workspace_id, deleted_todos } => {
+                if let Some(todo_list) = self.workspace_manager.workspace_todos.get_mut(&workspace_id) {
+                    for todo in &deleted_todos {
+                        todo_list.remove_todo(todo.id);
+                    }
+                    self.set_message(format!("Redid: Delete {} todos with children", deleted_todos.len()), MessageLevel::Success, SHORT);
+                }
+                self.clamp_selection();
             },
-            _ => {
-                self.set_message("Redo operation not fully implemented for this command type".to_string());
-            }
         }
     }
 }
@@ -0,0 +1,338 @@
+// Command-line arguments, read before the terminal is set up so mistakes print normally,
+// and the commands that work on the data without opening the full-screen app
+
+use crate::glyphs::Glyphs;
+use crate::storage::Storage;
+use crate::todo::{Todo, TodoList, TodoStatus, WorkspaceManager};
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::io::Read;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: paperclip [options]
+       paperclip [options] add <text> [--workspace <name>]
+       paperclip [options] add --stdin|- [--workspace <name>]
+       paperclip [options] list [--pending] [--json] [--workspace <name>]
+       paperclip [options] done <id> [--workspace <name>]
+
+Commands (without one, the full-screen app opens):
+  add <text>          Add a todo, written as in the app (#tag @context due:friday), and print its id
+  add --stdin, add -  Add a todo per line of standard input, lines indented by two spaces
+                      becoming subtasks of the line above (the default when non-empty
+                      input is piped)
+  list                Print the workspace's todos; --pending leaves out completed ones,
+                      --json prints them as JSON
+  done <id>           Mark a todo completed
+  --workspace <name>  The workspace to use (the last one opened when not given)
+
+Opening the app:
+  --workspace <name>  Go straight to this workspace instead of the welcome screen
+  --create            Create the --workspace if there's none by that name
+  --view <view>       Start in a view: pending, completed, overdue or today
+  --add <text>        Start typing a new todo, with <text> already entered
+
+Options:
+  --data-dir <path>  Keep todos in <path> instead of the default data directory
+                     (also set by PAPERCLIP_DATA_DIR; the flag wins)
+  --force            Start even if another Paperclip seems to be using the data directory
+  -h, --help         Show this message";
+
+// Environment variable naming the data directory when --data-dir isn't given
+pub const DATA_DIR_VAR: &str = "PAPERCLIP_DATA_DIR";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub data_dir: Option<PathBuf>,
+    pub help: bool,
+    pub force: bool, // Break the data directory's lock
+    pub command: Option<Command>, // Run this and exit instead of opening the app
+    pub launch: LaunchOptions,
+}
+
+// Where the app opens, instead of the welcome screen
+#[derive(Debug, Default, PartialEq)]
+pub struct LaunchOptions {
+    pub workspace: Option<String>,
+    pub create: bool, // Create `workspace` when it doesn't exist
+    pub view: Option<LaunchView>,
+    pub add: Option<String>, // Open Insert mode with this text
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LaunchView {
+    Pending,
+    Completed,
+    Overdue,
+    Today,
+}
+
+impl LaunchView {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "pending" => Some(LaunchView::Pending),
+            "completed" | "done" => Some(LaunchView::Completed),
+            "overdue" => Some(LaunchView::Overdue),
+            "today" => Some(LaunchView::Today),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Add { text: String, workspace: Option<String> }, // Empty text when none was given
+    AddLines { workspace: Option<String> }, // A todo per line of standard input
+    List { pending: bool, json: bool, workspace: Option<String> },
+    Done { id: u32, workspace: Option<String> },
+}
+
+impl Args {
+    // Everything after the program name; errors are meant to be printed above USAGE
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut command_words: Option<Vec<String>> = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let text = arg.to_string_lossy();
+            match text.as_ref() {
+                "-h" | "--help" => parsed.help = true,
+                "--force" => parsed.force = true,
+                "--data-dir" => {
+                    let dir = args.next().ok_or("--data-dir needs a path")?;
+                    parsed.data_dir = Some(PathBuf::from(dir));
+                }
+                // After a command word these belong to the command
+                "--workspace" | "-w" if command_words.is_none() => {
+                    let name = args.next().ok_or("--workspace needs a name")?;
+                    parsed.launch.workspace = Some(name.to_string_lossy().into_owned());
+                }
+                "--create" if command_words.is_none() => parsed.launch.create = true,
+                "--view" if command_words.is_none() => {
+                    let name = args.next().ok_or("--view needs pending, completed, overdue or today")?;
+                    let name = name.to_string_lossy();
+                    let view = LaunchView::from_name(&name)
+                        .ok_or_else(|| format!("unknown view '{}' (use pending, completed, overdue or today)", name))?;
+                    parsed.launch.view = Some(view);
+                }
+                "--add" if command_words.is_none() => {
+                    let text = args.next().ok_or("--add needs the todo's text")?;
+                    parsed.launch.add = Some(text.to_string_lossy().into_owned());
+                }
+                _ => match text.strip_prefix("--data-dir=") {
+                    Some(dir) if !dir.is_empty() => parsed.data_dir = Some(PathBuf::from(dir)),
+                    Some(_) => return Err("--data-dir needs a path".to_string()),
+                    // The first word names the command, the rest are its arguments
+                    None => match &mut command_words {
+                        Some(words) => words.push(text.into_owned()),
+                        None if !text.starts_with('-') => command_words = Some(vec![text.into_owned()]),
+                        None => return Err(format!("unknown argument '{}'", text)),
+                    },
+                },
+            }
+        }
+        if parsed.launch.create && parsed.launch.workspace.is_none() {
+            return Err("--create needs --workspace <name>".to_string());
+        }
+        if let Some(words) = command_words {
+            if parsed.launch != LaunchOptions::default() {
+                return Err("--workspace, --create, --view and --add go after a command, or without one".to_string());
+            }
+            parsed.command = Some(Command::parse(words)?);
+        }
+        Ok(parsed)
+    }
+
+    // Whether input piped to a bare `paperclip` or a textless `paperclip add` could be todos
+    pub fn may_use_piped_stdin(&self) -> bool {
+        match &self.command {
+            None => true,
+            Some(Command::Add { text, .. }) => text.is_empty(),
+            Some(_) => false,
+        }
+    }
+
+    // Piped input is todos to add, whether or not `add --stdin` says so, as long as there's
+    // something in it: an empty stdin that just isn't a terminal (a launcher or a service
+    // manager starting the app, say) leaves the command as it was
+    pub fn use_piped_stdin(&mut self, input: &[u8]) {
+        if input.iter().all(u8::is_ascii_whitespace) {
+            return;
+        }
+        match &mut self.command {
+            None => self.command = Some(Command::AddLines { workspace: self.launch.workspace.take() }),
+            Some(Command::Add { text, workspace }) if text.is_empty() => {
+                self.command = Some(Command::AddLines { workspace: workspace.take() });
+            }
+            _ => {}
+        }
+    }
+
+    // The flag, then the environment variable; None means the platform default
+    pub fn resolve_data_dir(&self, env_value: Option<OsString>) -> Option<PathBuf> {
+        self.data_dir.clone().or_else(|| env_value.filter(|value| !value.is_empty()).map(PathBuf::from))
+    }
+}
+
+impl Command {
+    fn parse(words: Vec<String>) -> Result<Self, String> {
+        let mut words = words.into_iter();
+        let name = words.next().unwrap_or_default();
+        let (mut workspace, mut pending, mut json, mut stdin) = (None, false, false, false);
+        let mut positional = Vec::new();
+        while let Some(word) = words.next() {
+            match word.as_str() {
+                "--workspace" | "-w" => workspace = Some(words.next().ok_or("--workspace needs a name")?),
+                "--pending" if name == "list" => pending = true,
+                "--stdin" if name == "add" => stdin = true,
+                "--json" if name == "list" => json = true,
+                _ => match word.strip_prefix("--workspace=") {
+                    Some(value) => workspace = Some(value.to_string()),
+                    None if word.starts_with("--") => return Err(format!("{} doesn't take {}", name, word)),
+                    None => positional.push(word),
+                },
+            }
+        }
+
+        match name.as_str() {
+            "add" if !stdin && positional == ["-"] => Ok(Command::AddLines { workspace }),
+            "add" if stdin => match positional.first() {
+                Some(extra) => Err(format!("add --stdin doesn't take '{}'", extra)),
+                None => Ok(Command::AddLines { workspace }),
+            },
+            "add" => Ok(Command::Add { text: positional.join(" ").trim().to_string(), workspace }),
+            "list" => match positional.first() {
+                Some(extra) => Err(format!("list doesn't take '{}'", extra)),
+                None => Ok(Command::List { pending, json, workspace }),
+            },
+            "done" => match positional.as_slice() {
+                [id] => id.parse()
+                    .map(|id| Command::Done { id, workspace })
+                    .map_err(|_| format!("'{}' isn't a todo id", id)),
+                [] => Err("done needs the id of a todo (see `paperclip list`)".to_string()),
+                [_, extra, ..] => Err(format!("done doesn't take '{}'", extra)),
+            },
+            _ => Err(format!("unknown command '{}'", name)),
+        }
+    }
+
+    // Load, change and save the workspaces; the Ok text goes to stdout and the Err text
+    // to stderr. Saving merges in anything a running app saved meanwhile, as the app does.
+    pub fn run(&self, storage: &Storage, mut stdin: impl Read) -> Result<String, String> {
+        if matches!(self, Command::Add { text, .. } if text.is_empty()) {
+            return Err("add needs the todo's text, or lines piped to it".to_string());
+        }
+        // Listing changes nothing, so it leaves even a damaged file where it is
+        let load = match self {
+            Command::List { .. } => Storage::read_workspace_manager,
+            _ => Storage::load_workspace_manager,
+        };
+        let (mut workspace_manager, warnings) = load(storage)
+            .map_err(|e| format!("can't open {}: {}", storage.get_workspace_file_path().display(), e))?;
+        for warning in warnings {
+            eprintln!("paperclip: {}", warning);
+        }
+
+        let output = match self {
+            Command::Add { text, workspace } => {
+                if workspace.is_none() {
+                    workspace_manager.ensure_workspace();
+                }
+                let todo_list = workspace_todos(&mut workspace_manager, workspace.as_deref())?;
+                format!("{}\n", todo_list.add_todo(text.clone()))
+            }
+            Command::AddLines { workspace } => {
+                // Bytes that aren't UTF-8 spoil their own line, not the rest
+                let mut input = Vec::new();
+                stdin.read_to_end(&mut input).map_err(|e| format!("couldn't read standard input: {}", e))?;
+                if workspace.is_none() {
+                    workspace_manager.ensure_workspace();
+                }
+                let name = match workspace {
+                    Some(name) => name.clone(),
+                    None => workspace_manager.get_current_workspace().map(|ws| ws.name.clone()).unwrap_or_default(),
+                };
+                let todo_list = workspace_todos(&mut workspace_manager, workspace.as_deref())?;
+                let count = todo_list.add_outline(&String::from_utf8_lossy(&input), None).len();
+                // Nothing read means nothing to save
+                if count == 0 {
+                    return Ok(format!("No todos to add to {}\n", name));
+                }
+                format!("Added {} todo{} to {}\n", count, if count == 1 { "" } else { "s" }, name)
+            }
+            Command::List { pending, json, workspace } => {
+                let todo_list = workspace_todos(&mut workspace_manager, workspace.as_deref())?;
+                let todos = all_todos(todo_list, *pending);
+                return if *json {
+                    let todos: Vec<&Todo> = todos.iter().map(|(todo, _)| *todo).collect();
+                    serde_json::to_string_pretty(&todos).map(|json| json + "\n").map_err(|e| e.to_string())
+                } else {
+                    Ok(todo_table(&todos))
+                };
+            }
+            Command::Done { id, workspace } => {
+                let todo_list = workspace_todos(&mut workspace_manager, workspace.as_deref())?;
+                let todo = todo_list.get_todo_mut(*id).ok_or_else(|| format!("no todo with id {}", id))?;
+                if todo.is_completed() {
+                    return Ok(format!("{} was already done: {}\n", id, todo.description));
+                }
+                todo.set_status(TodoStatus::Completed);
+                format!("Completed {}: {}\n", id, todo.description)
+            }
+        };
+
+        storage.sync_workspace_manager(&mut workspace_manager)
+            .map_err(|e| format!("couldn't save {}: {}", storage.get_workspace_file_path().display(), e))?;
+        Ok(output)
+    }
+}
+
+// The named workspace, or the current one
+fn workspace_todos<'a>(workspace_manager: &'a mut WorkspaceManager, name: Option<&str>) -> Result<&'a mut TodoList, String> {
+    let workspace_id = match name {
+        Some(name) => workspace_manager.find_workspace_id_by_name(name).ok_or_else(|| format!("no workspace named '{}'", name))?,
+        None => workspace_manager.get_current_workspace_id().ok_or("there are no workspaces yet")?,
+    };
+    workspace_manager.workspace_todos.get_mut(&workspace_id).ok_or_else(|| format!("workspace '{}' has no todo list", workspace_id))
+}
+
+// Every todo in list order with its depth, subtasks of collapsed todos included
+fn all_todos(todo_list: &TodoList, pending_only: bool) -> Vec<(&Todo, u32)> {
+    let mut rows = Vec::new();
+    let mut stack: Vec<(&Todo, u32)> = todo_list.get_root_todos().into_iter().rev().map(|todo| (todo, 0)).collect();
+    while let Some((todo, depth)) = stack.pop() {
+        if !(pending_only && todo.is_completed()) {
+            rows.push((todo, depth));
+        }
+        let mut children: Vec<&Todo> = todo.children.iter().filter_map(|id| todo_list.todos.get(id)).collect();
+        children.sort_by(|a, b| todo_list.sort_mode.compare(a, b));
+        stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+    }
+    rows
+}
+
+// One line per todo: id, status, priority, due date and text, subtasks indented
+fn todo_table(rows: &[(&Todo, u32)]) -> String {
+    let glyphs = Glyphs::ASCII;
+    let id_width = rows.iter().map(|(todo, _)| todo.id.to_string().len()).max().unwrap_or(1);
+    let mut table = String::new();
+    for (todo, depth) in rows {
+        let status = match todo.status {
+            TodoStatus::Pending => glyphs.pending,
+            TodoStatus::InProgress => glyphs.in_progress,
+            TodoStatus::Completed => glyphs.completed,
+        };
+        let priority = if todo.priority == 0 { "-".to_string() } else { todo.priority.to_string() };
+        let due = todo.due_date.map(|due| due.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+        let mut labels: Vec<String> = todo.tags.iter().map(|tag| format!("#{}", tag))
+            .chain(todo.contexts.iter().map(|context| format!("@{}", context)))
+            .collect();
+        labels.sort();
+        let mut text = format!("{}{}", "  ".repeat(*depth as usize), todo.description);
+        for label in labels {
+            text.push(' ');
+            text.push_str(&label);
+        }
+        let _ = writeln!(table, "{:>id_width$}  {}  {}  {:<16}  {}", todo.id, status, priority, due, text);
+    }
+    table
+}
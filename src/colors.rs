@@ -1,7 +1,10 @@
-use ratatui::style::Color;
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy)]
-pub struct TokyoNightColors {
+// Named palette used by every widget; the field names follow Tokyo Night, which the other
+// themes fill with their closest equivalents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
     // Background colors
     pub bg_dark: Color,
     pub bg: Color,
@@ -32,10 +35,54 @@ pub struct TokyoNightColors {
     pub teal: Color,
     pub red: Color,
     pub red1: Color,
+    pub monochrome: bool, // No colors at all: highlights and badges use reverse video instead
 }
 
-impl TokyoNightColors {
+// Themes the user can pick, by the name used in the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    #[default]
+    TokyoNight,
+    Light,
+    HighContrast,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 3] = [ThemeName::TokyoNight, ThemeName::Light, ThemeName::HighContrast];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeName::TokyoNight => "tokyo_night",
+            ThemeName::Light => "light",
+            ThemeName::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|theme| theme.name() == name.to_lowercase().replace('-', "_"))
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|theme| *theme == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeName::TokyoNight => Theme::tokyo_night(),
+            ThemeName::Light => Theme::light(),
+            ThemeName::HighContrast => Theme::high_contrast(),
+        }
+    }
+}
+
+impl Theme {
     pub fn new() -> Self {
+        Self::tokyo_night()
+    }
+
+    pub fn tokyo_night() -> Self {
         Self {
             bg_dark: Color::Rgb(0x1a, 0x1b, 0x26),
             bg: Color::Rgb(0x24, 0x28, 0x3a),
@@ -66,12 +113,203 @@ impl TokyoNightColors {
             teal: Color::Rgb(0x1a, 0xbc, 0x9c),
             red: Color::Rgb(0xf7, 0x76, 0x8e),
             red1: Color::Rgb(0xdb, 0x4b, 0x4b),
+            monochrome: false,
+        }
+    }
+
+    // Tokyo Night Day, for terminals with a light background
+    pub fn light() -> Self {
+        Self {
+            bg_dark: Color::Rgb(0xe9, 0xe9, 0xed),
+            bg: Color::Rgb(0xe1, 0xe2, 0xe7),
+            bg_highlight: Color::Rgb(0xc4, 0xc8, 0xda),
+            terminal_black: Color::Rgb(0xa1, 0xa6, 0xc5),
+            fg: Color::Rgb(0x37, 0x60, 0xbf),
+            fg_dark: Color::Rgb(0x61, 0x72, 0xb0),
+            fg_gutter: Color::Rgb(0xa8, 0xae, 0xcb),
+            dark3: Color::Rgb(0x89, 0x90, 0xb3),
+            comment: Color::Rgb(0x84, 0x8c, 0xb5),
+            dark5: Color::Rgb(0x68, 0x70, 0x9a),
+            blue0: Color::Rgb(0x78, 0x90, 0xdd),
+            blue: Color::Rgb(0x2e, 0x7d, 0xe9),
+            cyan: Color::Rgb(0x00, 0x71, 0x97),
+            blue1: Color::Rgb(0x18, 0x80, 0x92),
+            blue2: Color::Rgb(0x07, 0x87, 0x9d),
+            blue5: Color::Rgb(0x00, 0x6a, 0x83),
+            blue6: Color::Rgb(0x2e, 0x58, 0x57),
+            blue7: Color::Rgb(0x92, 0xa6, 0xd5),
+            magenta: Color::Rgb(0x98, 0x54, 0xf1),
+            magenta2: Color::Rgb(0xd2, 0x00, 0x65),
+            purple: Color::Rgb(0x78, 0x47, 0xbd),
+            orange: Color::Rgb(0xb1, 0x5c, 0x00),
+            yellow: Color::Rgb(0x8c, 0x6c, 0x3e),
+            green: Color::Rgb(0x58, 0x75, 0x39),
+            green1: Color::Rgb(0x38, 0x70, 0x68),
+            green2: Color::Rgb(0x38, 0x91, 0x9f),
+            teal: Color::Rgb(0x11, 0x8c, 0x74),
+            red: Color::Rgb(0xf5, 0x2a, 0x65),
+            red1: Color::Rgb(0xc6, 0x43, 0x43),
+            monochrome: false,
+        }
+    }
+
+    // White text and saturated accents on black
+    pub fn high_contrast() -> Self {
+        Self {
+            bg_dark: Color::Rgb(0x00, 0x00, 0x00),
+            bg: Color::Rgb(0x00, 0x00, 0x00),
+            bg_highlight: Color::Rgb(0x30, 0x30, 0x30),
+            terminal_black: Color::Rgb(0x80, 0x80, 0x80),
+            fg: Color::Rgb(0xff, 0xff, 0xff),
+            fg_dark: Color::Rgb(0xe0, 0xe0, 0xe0),
+            fg_gutter: Color::Rgb(0x80, 0x80, 0x80),
+            dark3: Color::Rgb(0xa0, 0xa0, 0xa0),
+            comment: Color::Rgb(0xc0, 0xc0, 0xc0),
+            dark5: Color::Rgb(0xc0, 0xc0, 0xc0),
+            blue0: Color::Rgb(0x00, 0x5f, 0xff),
+            blue: Color::Rgb(0x5f, 0xaf, 0xff),
+            cyan: Color::Rgb(0x00, 0xff, 0xff),
+            blue1: Color::Rgb(0x00, 0xd7, 0xff),
+            blue2: Color::Rgb(0x00, 0xaf, 0xff),
+            blue5: Color::Rgb(0x87, 0xd7, 0xff),
+            blue6: Color::Rgb(0xaf, 0xff, 0xff),
+            blue7: Color::Rgb(0x00, 0x00, 0xaf),
+            magenta: Color::Rgb(0xff, 0x5f, 0xff),
+            magenta2: Color::Rgb(0xff, 0x00, 0x87),
+            purple: Color::Rgb(0xd7, 0x87, 0xff),
+            orange: Color::Rgb(0xff, 0x87, 0x00),
+            yellow: Color::Rgb(0xff, 0xff, 0x00),
+            green: Color::Rgb(0x00, 0xff, 0x00),
+            green1: Color::Rgb(0x5f, 0xff, 0xd7),
+            green2: Color::Rgb(0x00, 0xd7, 0xaf),
+            teal: Color::Rgb(0x00, 0xd7, 0xaf),
+            red: Color::Rgb(0xff, 0x00, 0x00),
+            red1: Color::Rgb(0xd7, 0x00, 0x00),
+            monochrome: false,
         }
     }
+
+    // For NO_COLOR: the terminal's own colors everywhere, with selection, badges and
+    // warnings told apart by reverse video, bold and underline
+    pub fn monochrome() -> Self {
+        Self { monochrome: true, ..Self::tokyo_night().map(|_| Color::Reset) }
+    }
+
+    // For terminals without truecolor: every RGB value replaced by the nearest of the 16 ANSI colors
+    pub fn to_ansi16(self) -> Self {
+        self.map(nearest_ansi)
+    }
+
+    fn map(self, f: impl Fn(Color) -> Color) -> Self {
+        Self {
+            bg_dark: f(self.bg_dark),
+            bg: f(self.bg),
+            bg_highlight: f(self.bg_highlight),
+            terminal_black: f(self.terminal_black),
+            fg: f(self.fg),
+            fg_dark: f(self.fg_dark),
+            fg_gutter: f(self.fg_gutter),
+            dark3: f(self.dark3),
+            comment: f(self.comment),
+            dark5: f(self.dark5),
+            blue0: f(self.blue0),
+            blue: f(self.blue),
+            cyan: f(self.cyan),
+            blue1: f(self.blue1),
+            blue2: f(self.blue2),
+            blue5: f(self.blue5),
+            blue6: f(self.blue6),
+            blue7: f(self.blue7),
+            magenta: f(self.magenta),
+            magenta2: f(self.magenta2),
+            purple: f(self.purple),
+            orange: f(self.orange),
+            yellow: f(self.yellow),
+            green: f(self.green),
+            green1: f(self.green1),
+            green2: f(self.green2),
+            teal: f(self.teal),
+            red: f(self.red),
+            red1: f(self.red1),
+            monochrome: self.monochrome,
+        }
+    }
+
+    // Selected rows and the line picked in the notes viewer
+    pub fn selected(&self) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(self.bg_highlight)
+        }
+    }
+
+    // Solid blocks of color such as the mode indicator
+    pub fn badge(&self, color: Color) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().fg(self.bg_dark).bg(color)
+        }
+    }
+
+    // Overdue markers, which otherwise only stand out by being red
+    pub fn alert(&self) -> Modifier {
+        if self.monochrome { Modifier::BOLD | Modifier::UNDERLINED } else { Modifier::empty() }
+    }
+
+    // Accent color for a workspace's color index, shared by every view that tints workspaces
+    pub fn workspace_color(&self, index: u8) -> Color {
+        let palette = [
+            self.magenta,
+            self.blue,
+            self.green,
+            self.orange,
+            self.cyan,
+            self.yellow,
+            self.teal,
+            self.red,
+        ];
+        palette[index as usize % palette.len()]
+    }
 }
 
-impl Default for TokyoNightColors {
+impl Default for Theme {
     fn default() -> Self {
         Self::new()
     }
 }
+
+// The 16 ANSI colors with xterm's default RGB values
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0x00, 0x00, 0x00)),
+    (Color::Red, (0xcd, 0x00, 0x00)),
+    (Color::Green, (0x00, 0xcd, 0x00)),
+    (Color::Yellow, (0xcd, 0xcd, 0x00)),
+    (Color::Blue, (0x00, 0x00, 0xee)),
+    (Color::Magenta, (0xcd, 0x00, 0xcd)),
+    (Color::Cyan, (0x00, 0xcd, 0xcd)),
+    (Color::Gray, (0xe5, 0xe5, 0xe5)),
+    (Color::DarkGray, (0x7f, 0x7f, 0x7f)),
+    (Color::LightRed, (0xff, 0x00, 0x00)),
+    (Color::LightGreen, (0x00, 0xff, 0x00)),
+    (Color::LightYellow, (0xff, 0xff, 0x00)),
+    (Color::LightBlue, (0x5c, 0x5c, 0xff)),
+    (Color::LightMagenta, (0xff, 0x00, 0xff)),
+    (Color::LightCyan, (0x00, 0xff, 0xff)),
+    (Color::White, (0xff, 0xff, 0xff)),
+];
+
+pub fn nearest_ansi(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else { return color };
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+    ANSI_COLORS.iter().min_by_key(|(_, rgb)| distance(*rgb)).map(|(ansi, _)| *ansi).unwrap_or(color)
+}
+
+// Truecolor terminals announce themselves through COLORTERM
+pub fn supports_truecolor() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|value| matches!(value.to_lowercase().as_str(), "truecolor" | "24bit"))
+}
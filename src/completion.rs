@@ -0,0 +1,75 @@
+// Tab completion for `#tag`, `@context` and `due:` tokens while typing a todo
+
+// Keywords understood by the due date parser, offered after `due:`
+pub const DUE_KEYWORDS: &[&str] = &[
+    "today", "tomorrow", "eod", "noon",
+    "monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompletionKind {
+    Tag,
+    Context,
+    Due,
+}
+
+// An active completion: Tab cycles through `candidates`, rewriting the text from `start` to the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    pub start: usize, // Byte offset just after the `#`, `@` or `due:`
+    pub candidates: Vec<String>,
+    pub index: usize,
+}
+
+impl Completion {
+    pub fn current(&self) -> &str {
+        &self.candidates[self.index]
+    }
+
+    pub fn advance(&mut self) {
+        self.index = (self.index + 1) % self.candidates.len();
+    }
+}
+
+// Find the token being typed before the cursor: its kind, where the partial word starts and the partial word
+pub fn completion_prefix(text_before_cursor: &str) -> Option<(CompletionKind, usize, &str)> {
+    let token_start = text_before_cursor
+        .rfind(char::is_whitespace)
+        .map(|i| i + text_before_cursor[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let token = &text_before_cursor[token_start..];
+
+    let (kind, marker_len) = if token.starts_with('#') {
+        (CompletionKind::Tag, 1)
+    } else if token.starts_with('@') {
+        (CompletionKind::Context, 1)
+    } else if token.len() >= 4 && token[..4].eq_ignore_ascii_case("due:") {
+        (CompletionKind::Due, 4)
+    } else {
+        return None;
+    };
+
+    let partial = &token[marker_len..];
+    if !partial.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+        return None;
+    }
+    Some((kind, token_start + marker_len, partial))
+}
+
+// In a prompt for a bare tag or context name the whole input is the partial word,
+// typed with or without its marker
+pub fn label_prefix(text_before_cursor: &str, kind: CompletionKind) -> (CompletionKind, usize, &str) {
+    let marker = if kind == CompletionKind::Context { '@' } else { '#' };
+    let start = if text_before_cursor.starts_with(marker) { 1 } else { 0 };
+    (kind, start, &text_before_cursor[start..])
+}
+
+// Candidates starting with `partial` (case-insensitive), excluding an exact match so Tab always moves on
+pub fn candidates(partial: &str, pool: &[String]) -> Vec<String> {
+    let partial = partial.to_lowercase();
+    pool.iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&partial) && candidate.to_lowercase() != partial)
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,222 @@
+// Startup behaviour and defaults from `config.toml` in the config dir. Every option is
+// optional; a bad value is reported and the default used instead.
+
+use crate::colors::ThemeName;
+use crate::storage::expand_path;
+use crate::todo::{DueDateFilter, SortMode, ViewMode};
+use chrono::format::{Item, StrftimeItems};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+// Written out on first run so the options are easy to find
+const DEFAULT_CONFIG: &str = r#"# Paperclip settings. Delete a line to go back to its default.
+
+# Workspace to open at startup, by name (the last one used when unset)
+# default_workspace = "Personal"
+
+# Go straight to the todo list instead of the welcome screen
+skip_welcome = false
+
+# How often the screen refreshes for timers and the pomodoro countdown, in milliseconds
+tick_rate_ms = 250
+
+# Seconds between saves while there are unsaved changes (deletions are saved at once)
+autosave_secs = 5
+
+# View for a workspace opened for the first time: list, agenda, overdue or today
+default_view = "list"
+
+# Sort order for new workspaces: priority, due, created or name
+default_sort = "priority"
+
+# How due dates a week or more away are shown in the list (strftime)
+date_format = "%m/%d"
+
+# Ask before creating more than this many todos from a paste
+confirm_paste_over = 1
+
+# Ask before deleting more than this many todos in visual mode (0 never asks)
+confirm_bulk_delete_over = 0
+
+# Rows kept visible above and below the selection
+scrolloff = 3
+
+# Color theme: tokyo_night, light or high_contrast (Ctrl+L cycles them and remembers the choice)
+theme = "tokyo_night"
+
+# Draw the list with plain ASCII symbols, for terminals that show Unicode as garbage
+# (turned on automatically for TERM=linux and non-UTF-8 locales)
+ascii = false
+
+# No colors, only bold, underline and reverse video (also turned on by the NO_COLOR variable)
+monochrome = false
+
+# Rewrite this iCalendar file with every todo that has a due date each time Paperclip saves,
+# for a calendar app to subscribe to
+# ics_path = "~/Calendars/paperclip.ics"
+
+# Desktop notifications while Paperclip is open, when a todo falls due
+notifications = true
+
+# Also notify this many minutes before a todo is due (0 for only when it's due)
+reminder_minutes = 15
+"#;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub default_workspace: Option<String>,
+    pub skip_welcome: bool,
+    pub tick_rate: Duration,
+    pub autosave_interval: Duration,
+    pub default_view: ViewMode,
+    pub default_sort: SortMode,
+    pub date_format: String,
+    pub confirm_paste_over: usize,
+    pub confirm_bulk_delete_over: usize,
+    pub scrolloff: usize,
+    pub theme: ThemeName,
+    pub ascii: bool,
+    pub monochrome: bool,
+    pub ics_path: Option<PathBuf>,
+    pub notifications: bool,
+    pub reminder_minutes: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_workspace: None,
+            skip_welcome: false,
+            tick_rate: Duration::from_millis(250),
+            autosave_interval: Duration::from_secs(5),
+            default_view: ViewMode::List,
+            default_sort: SortMode::Priority,
+            date_format: "%m/%d".to_string(),
+            confirm_paste_over: 1,
+            confirm_bulk_delete_over: 0,
+            scrolloff: 3,
+            theme: ThemeName::TokyoNight,
+            ascii: false,
+            monochrome: false,
+            ics_path: None,
+            notifications: true,
+            reminder_minutes: 15,
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("paperclip").join(CONFIG_FILE))
+    }
+
+    // Read the config file, writing the commented defaults first if there is none
+    pub fn load() -> (Self, Vec<String>) {
+        let Some(path) = Self::path() else {
+            return (Self::default(), Vec::new());
+        };
+        if !path.exists() {
+            let written = path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::write(&path, DEFAULT_CONFIG));
+            if let Err(e) = written {
+                return (Self::default(), vec![format!("couldn't create {}: {}", path.display(), e)]);
+            }
+        }
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) => (Self::default(), vec![format!("couldn't read {}: {}", path.display(), e)]),
+        }
+    }
+
+    // Options that don't parse keep their defaults and come back as warnings
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut warnings = Vec::new();
+        let table: toml::Table = match text.parse() {
+            Ok(table) => table,
+            Err(e) => {
+                let e: toml::de::Error = e;
+                return (config, vec![e.message().to_string()]);
+            }
+        };
+
+        for (key, value) in table {
+            let applied = match key.as_str() {
+                "default_workspace" => value.as_str().map(|name| config.default_workspace = Some(name.to_string())),
+                "skip_welcome" => value.as_bool().map(|skip| config.skip_welcome = skip),
+                "tick_rate_ms" => value.as_integer()
+                    .filter(|ms| (10..=5000).contains(ms))
+                    .map(|ms| config.tick_rate = Duration::from_millis(ms as u64)),
+                "autosave_secs" => value.as_integer()
+                    .filter(|secs| (1..=3600).contains(secs))
+                    .map(|secs| config.autosave_interval = Duration::from_secs(secs as u64)),
+                "default_view" => value.as_str().and_then(parse_view).map(|view| config.default_view = view),
+                "default_sort" => value.as_str().and_then(parse_sort).map(|sort| config.default_sort = sort),
+                "date_format" => value.as_str()
+                    .filter(|format| is_valid_date_format(format))
+                    .map(|format| config.date_format = format.to_string()),
+                "confirm_paste_over" => as_count(&value).map(|n| config.confirm_paste_over = n),
+                "confirm_bulk_delete_over" => as_count(&value).map(|n| config.confirm_bulk_delete_over = n),
+                "scrolloff" => as_count(&value).map(|n| config.scrolloff = n),
+                "theme" => value.as_str().and_then(ThemeName::from_name).map(|theme| config.theme = theme),
+                "ascii" => value.as_bool().map(|ascii| config.ascii = ascii),
+                "monochrome" => value.as_bool().map(|monochrome| config.monochrome = monochrome),
+                "ics_path" => value.as_str()
+                    .filter(|path| !path.trim().is_empty())
+                    .map(|path| config.ics_path = Some(expand_path(path.trim()))),
+                "notifications" => value.as_bool().map(|notifications| config.notifications = notifications),
+                "reminder_minutes" => as_count(&value).map(|minutes| config.reminder_minutes = minutes),
+                _ => {
+                    warnings.push(format!("unknown option '{}'", key));
+                    continue;
+                }
+            };
+            if applied.is_none() {
+                warnings.push(format!("invalid value for {}: {}", key, value));
+            }
+        }
+        (config, warnings)
+    }
+}
+
+fn as_count(value: &toml::Value) -> Option<usize> {
+    value.as_integer().and_then(|n| usize::try_from(n).ok())
+}
+
+fn parse_view(name: &str) -> Option<ViewMode> {
+    match name.to_lowercase().as_str() {
+        "list" => Some(ViewMode::List),
+        "agenda" => Some(ViewMode::Agenda),
+        "overdue" => Some(ViewMode::GlobalDue(DueDateFilter::Overdue)),
+        "today" => Some(ViewMode::GlobalDue(DueDateFilter::Today)),
+        _ => None,
+    }
+}
+
+fn parse_sort(name: &str) -> Option<SortMode> {
+    match name.to_lowercase().as_str() {
+        "priority" => Some(SortMode::Priority),
+        "due" | "due_date" => Some(SortMode::DueDate),
+        "created" => Some(SortMode::Created),
+        "name" | "alphabetical" => Some(SortMode::Alphabetical),
+        _ => None,
+    }
+}
+
+// Display choices made in the app and remembered between sessions. They live in their own
+// file next to the workspace data rather than in it, so a synced data file doesn't carry
+// one machine's layout to another.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub show_detail_pane: bool,
+    pub theme: Option<ThemeName>, // Picked with Ctrl+L; overrides the config file once set
+}
+
+// chrono panics when a bad format string is displayed, so check it up front
+fn is_valid_date_format(format: &str) -> bool {
+    !format.is_empty() && StrftimeItems::new(format).all(|item| !matches!(item, Item::Error))
+}
@@ -1,23 +1,37 @@
 use crate::app::{App, AppMode};
+use crate::keymap::{Action, Scope};
+use crate::text_buffer::TextBuffer;
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use std::io;
 
 pub fn handle_event(app: &mut App, event: Event) -> io::Result<()> {
-    // Clear message on any key press
-    if matches!(event, Event::Key(_)) {
-        app.clear_message();
-    }
-
-    match event {
+    let result = match event {
         Event::Key(key_event) => handle_key_event(app, key_event),
+        Event::Paste(text) => {
+            app.paste_text(&text);
+            Ok(())
+        }
         _ => Ok(()),
-    }
+    };
+    // Whatever the key did, the todo now highlighted is the one to keep hold of
+    app.anchor_selection();
+    result
 }
 
 fn handle_key_event(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
-    // Help screen - any key closes it
     if app.show_help {
-        app.toggle_help();
+        handle_help_key(app, key_event);
+        return Ok(());
+    }
+    
+    // Same for the time report
+    if app.show_time_report {
+        app.toggle_time_report();
+        return Ok(());
+    }
+
+    // Word and line editing keys work the same in every text input
+    if handle_text_editing_key(app, key_event) {
         return Ok(());
     }
 
@@ -27,11 +41,14 @@ fn handle_key_event(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
         AppMode::Insert | AppMode::InsertChild => handle_insert_mode(app, key_event)?,
         AppMode::EditTodo => handle_edit_mode(app, key_event)?,
         AppMode::Search => handle_search_mode(app, key_event)?,
-        AppMode::TagSelection | AppMode::ContextSelection | AppMode::TemplateSelection | AppMode::RecurrenceSelection | AppMode::WorkspaceSelection => handle_popup_mode(app, key_event)?,
+        AppMode::TagSelection | AppMode::ContextSelection | AppMode::TemplateSelection | AppMode::RecurrenceSelection | AppMode::WorkspaceSelection | AppMode::MoveToWorkspace | AppMode::SavedFilterSelection | AppMode::TimeTracking | AppMode::Export => handle_popup_mode(app, key_event)?,
         AppMode::EditNotes => handle_notes_mode(app, key_event)?,
         AppMode::ViewNotes => handle_view_notes_mode(app, key_event)?,
-        AppMode::TimeTracking => handle_normal_mode(app, key_event)?, // For now, same as normal
         AppMode::CreateWorkspace => handle_create_workspace_mode(app, key_event)?,
+        AppMode::SaveFilter | AppMode::RenameTag | AppMode::RenameContext | AppMode::BulkTag | AppMode::BulkContext | AppMode::BulkDue | AppMode::EditDue | AppMode::LogTime | AppMode::EditTimeEntry | AppMode::ExportPath | AppMode::ImportPath | AppMode::ImportWorkspacePath | AppMode::ImportWorkspaceName => handle_prompt_mode(app, key_event)?,
+        AppMode::Confirm => handle_confirm_mode(app, key_event)?,
+        AppMode::Calendar => handle_calendar_mode(app, key_event)?,
+        AppMode::Board => handle_board_mode(app, key_event)?,
         AppMode::Visual => handle_visual_mode(app, key_event)?,
         AppMode::BulkOperation => handle_bulk_operation_mode(app, key_event)?,
     }
@@ -39,343 +56,435 @@ fn handle_key_event(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
     Ok(())
 }
 
+// Readline-style bindings shared by all text inputs; returns true if the key was used
+fn handle_text_editing_key(app: &mut App, key_event: KeyEvent) -> bool {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key_event.modifiers.contains(KeyModifiers::ALT);
+    
+    let edit: fn(&mut TextBuffer) = match key_event.code {
+        // Ctrl+W toggles the scope in the search prompt; Alt+Backspace works everywhere
+        KeyCode::Char('w') if ctrl && app.mode != AppMode::Search => TextBuffer::delete_word_before,
+        KeyCode::Backspace if alt || ctrl => TextBuffer::delete_word_before,
+        KeyCode::Delete => TextBuffer::delete_char_after,
+        KeyCode::Char('u') if ctrl => TextBuffer::kill_to_start,
+        KeyCode::Char('k') if ctrl => TextBuffer::kill_to_end,
+        KeyCode::Char('b') if alt => TextBuffer::move_word_left,
+        KeyCode::Char('f') if alt => TextBuffer::move_word_right,
+        KeyCode::Left if ctrl => TextBuffer::move_word_left,
+        KeyCode::Right if ctrl => TextBuffer::move_word_right,
+        KeyCode::Home if ctrl => TextBuffer::move_to_start,
+        KeyCode::End if ctrl => TextBuffer::move_to_end,
+        KeyCode::Home => TextBuffer::move_home,
+        KeyCode::End => TextBuffer::move_end,
+        _ => return false,
+    };
+    app.edit_active_text(edit)
+}
+
+// Scrolling and filtering the help screen; only Esc and the help key close it
+fn handle_help_key(app: &mut App, key_event: KeyEvent) {
+    if app.help_filtering {
+        match key_event.code {
+            KeyCode::Enter => app.finish_help_filter(true),
+            KeyCode::Esc => app.finish_help_filter(false),
+            KeyCode::Backspace => app.edit_help_filter(None),
+            KeyCode::Char(c) if !key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => app.edit_help_filter(Some(c)),
+            _ => {}
+        }
+        return;
+    }
+    let page = app.help_view_height.max(1);
+    match key_event.code {
+        KeyCode::Esc => app.toggle_help(),
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_help(true, 1),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_help(false, 1),
+        KeyCode::PageDown | KeyCode::Char(' ') => app.scroll_help(true, page),
+        KeyCode::PageUp => app.scroll_help(false, page),
+        KeyCode::Char('g') | KeyCode::Home => app.help_scroll = 0,
+        KeyCode::Char('G') | KeyCode::End => app.help_scroll = usize::MAX,
+        KeyCode::Char('/') => app.start_help_filter(),
+        _ if app.keymap.action(Scope::Normal, &key_event) == Some(Action::Help) => app.toggle_help(),
+        _ => {}
+    }
+}
+
 fn handle_normal_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
-    match key_event {
-        // Quit
-        KeyEvent {
-            code: KeyCode::Char('q'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            app.quit();
+    if let Some(prefix) = app.pending_key.take() {
+        return handle_key_sequence(app, prefix, key_event);
+    }
+    
+    // Jump to the Nth workspace (not remappable)
+    if let KeyEvent { code: KeyCode::Char(c @ '1'..='9'), modifiers: KeyModifiers::ALT, .. } = key_event {
+        app.switch_to_workspace_number((c as u8 - b'0') as usize);
+        return Ok(());
+    }
+    
+    // A count for the collapse key, as in `2Z`, unless the digit is bound to something itself
+    if let KeyEvent { code: KeyCode::Char(c @ '1'..='9'), modifiers: KeyModifiers::NONE, .. } = key_event {
+        if app.keymap.action(Scope::Normal, &key_event).is_none() {
+            app.pending_key = Some(c);
+            return Ok(());
         }
+    }
+    
+    let Some(action) = app.keymap.action(Scope::Normal, &key_event) else { return Ok(()) };
+    match action {
+        Action::Quit => app.quit(),
+        Action::ClearFilters if app.dismiss_error() => {}
+        Action::ClearFilters => app.clear_filters(),
+        Action::Help => app.toggle_help(),
         
-        // Clear filters/escape
-        KeyEvent {
-            code: KeyCode::Esc,
-            ..
-        } => {
-            app.clear_filters();
-        }
-
-        // Help
-        KeyEvent {
-            code: KeyCode::Char('?'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            app.toggle_help();
-        }
-
         // Navigation
-        KeyEvent {
-            code: KeyCode::Char('j'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        }
-        | KeyEvent {
-            code: KeyCode::Down,
-            ..
-        } => {
-            app.move_selection_down();
-        }
+        Action::MoveDown => app.move_selection_down(),
+        Action::MoveUp => app.move_selection_up(),
+        Action::GoToBottom => app.go_to_bottom(),
+        Action::PreviousSibling => app.move_to_previous_sibling(),
+        Action::NextSibling => app.move_to_next_sibling(),
+        Action::GoToParent => app.move_to_parent(),
+        Action::HalfPageDown => app.move_half_page(true),
+        Action::HalfPageUp => app.move_half_page(false),
+        Action::PageDown => app.move_page(true),
+        Action::PageUp => app.move_page(false),
+        // Start a `g` sequence (gg, gt, gc, gv)
+        Action::GPrefix => app.pending_key = Some('g'),
+        
+        // Actions
+        Action::AddTodo => app.enter_insert_mode(),
+        Action::EditTodo => app.enter_edit_mode(),
+        Action::SetDue => app.enter_due_edit_mode(),
+        Action::ToggleComplete => app.toggle_todo_complete(),
+        Action::DeleteTodo => app.delete_selected_todo(),
+        Action::Copy => app.copy_selected_todo(false),
+        Action::CopyWithDetails => app.copy_selected_todo(true),
+        Action::Undo => app.undo(),
+        Action::Redo => app.redo(),
+        
+        // Views
+        Action::CycleStatusFilter => app.cycle_view_mode(),
+        Action::ToggleDetailPane => app.toggle_detail_pane(),
+        Action::CycleTheme => app.cycle_theme(),
+        Action::DueOverview => app.cycle_global_due_view(),
+        Action::Agenda => app.toggle_agenda_view(),
+        Action::Board => app.toggle_board(),
+        Action::Calendar => app.enter_calendar_mode(),
+        Action::GroupBy => app.cycle_group_by(),
+        Action::CycleSort => app.cycle_sort_mode(),
+        Action::EnterVisual => app.enter_visual_mode(),
+        
+        // Priority
+        Action::IncreasePriority => app.increase_priority(),
+        Action::DecreasePriority => app.decrease_priority(),
+        
+        // Hierarchical operations
+        Action::AddChild => app.add_child_todo(),
+        Action::ToggleExpand => app.toggle_expansion(),
+        Action::CollapseAll => app.collapse_all(),
+        Action::ExpandAll => app.expand_all(),
+        Action::DeleteWithChildren => app.delete_selected_with_children(),
+        
+        // Search and filtering
+        Action::Search => app.enter_search_mode(),
+        Action::TagFilter => app.enter_tag_selection(),
+        Action::ContextFilter => app.enter_context_selection(),
+        Action::DueFilter => app.cycle_due_date_filter(),
+        Action::FilterByTag => app.filter_by_selected_todos_tag(),
+        Action::SavedFilters => app.enter_saved_filter_selection(),
+        Action::NextMatch => app.jump_to_search_match(true),
+        Action::PreviousMatch => app.jump_to_search_match(false),
+        
+        // Notes, time and templates
+        Action::EditNotes => app.enter_notes_mode(),
+        Action::EditNotesExternal => app.request_external_notes_edit(),
+        Action::ToggleTimer => app.toggle_timer(),
+        Action::LogTime => app.enter_log_time_mode(),
+        Action::Pomodoro => app.toggle_pomodoro(),
+        Action::TimeReport => app.toggle_time_report(),
+        Action::TimeEntries => app.enter_time_entries_mode(),
+        Action::ApplyTemplate => app.enter_template_selection(),
+        Action::SetRecurrence => app.enter_recurrence_selection(),
+        
+        // Workspaces
+        Action::SwitchWorkspace => app.enter_workspace_selection(),
+        Action::PreviousWorkspace => app.switch_to_previous_workspace(),
+        Action::MoveToWorkspace => app.enter_move_to_workspace(),
+        Action::Export => app.enter_export_mode(),
+        Action::Import => app.enter_import_mode(),
+        Action::Welcome => app.return_to_welcome(),
+        Action::DismissReminders => app.dismiss_reminder_banner(),
+        
+        _ => {}
+    }
 
-        KeyEvent {
-            code: KeyCode::Char('k'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        }
-        | KeyEvent {
-            code: KeyCode::Up,
-            ..
-        } => {
-            app.move_selection_up();
+    Ok(())
+}
+
+// Second key of a two-key normal mode sequence; anything unrecognised just cancels it
+fn handle_key_sequence(app: &mut App, prefix: char, key_event: KeyEvent) -> io::Result<()> {
+    // Only the collapse key takes a count; anything else drops it and acts as usual
+    if let Some(levels) = prefix.to_digit(10) {
+        if app.keymap.action(Scope::Normal, &key_event) == Some(Action::CollapseAll) {
+            app.fold_to_level(levels);
+            return Ok(());
         }
+        return handle_normal_mode(app, key_event);
+    }
+    if prefix != 'g' || key_event.modifiers != KeyModifiers::NONE {
+        return Ok(());
+    }
+    
+    match key_event.code {
+        KeyCode::Char('g') => app.go_to_top(),
+        KeyCode::Char('t') => app.filter_by_selected_todos_tag(),
+        KeyCode::Char('c') => app.filter_by_selected_todos_context(),
+        KeyCode::Char('v') => app.enter_view_notes_mode(),
+        _ => {}
+    }
+    
+    Ok(())
+}
 
+fn handle_insert_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    // Any key other than Tab ends the current completion
+    if key_event.code != KeyCode::Tab {
+        app.completion = None;
+    }
+    
+    match key_event {
+        // Complete tag, context or due keyword
         KeyEvent {
-            code: KeyCode::Char('g'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Tab,
             ..
         } => {
-            app.go_to_top();
+            app.complete_input();
         }
 
+        // Submit and keep typing the next todo
         KeyEvent {
-            code: KeyCode::Char('G'),
-            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Enter,
+            modifiers,
             ..
-        } => {
-            app.go_to_bottom();
+        } if modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::CONTROL) => {
+            app.submit_input_and_continue();
         }
 
-        // Actions
+        // Submit
         KeyEvent {
-            code: KeyCode::Char('i'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Enter,
             ..
         } => {
-            app.enter_insert_mode();
+            app.submit_input();
         }
 
+        // Cancel
         KeyEvent {
-            code: KeyCode::Char(' '),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Esc,
             ..
         } => {
-            app.toggle_todo_complete();
+            app.enter_normal_mode();
         }
 
+        // Backspace
         KeyEvent {
-            code: KeyCode::Char('d'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Backspace,
             ..
         } => {
-            app.delete_selected_todo();
+            app.remove_char_from_input();
         }
 
+        // Cursor navigation - left arrow
         KeyEvent {
-            code: KeyCode::Char('v'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Left,
             ..
         } => {
-            app.cycle_view_mode();
+            app.move_input_cursor_left();
         }
         
-        // View notes (read-only)
+        // Cursor navigation - right arrow
         KeyEvent {
-            code: KeyCode::Char('V'),
-            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Right,
             ..
         } => {
-            app.enter_view_notes_mode();
+            app.move_input_cursor_right();
         }
 
-        // Priority
+        // Character input
         KeyEvent {
-            code: KeyCode::Char('+'),
+            code: KeyCode::Char(c),
             modifiers: KeyModifiers::NONE,
             ..
         }
         | KeyEvent {
-            code: KeyCode::Char('='),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::SHIFT,
             ..
         } => {
-            app.increase_priority();
+            app.add_char_to_input(c);
         }
 
-        KeyEvent {
-            code: KeyCode::Char('-'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            app.decrease_priority();
-        }
+        _ => {}
+    }
 
-        // Hierarchical operations
-        KeyEvent {
-            code: KeyCode::Char('a'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            app.add_child_todo();
-        }
+    Ok(())
+}
 
+fn handle_popup_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    match key_event {
+        // Select item
         KeyEvent {
             code: KeyCode::Enter,
             ..
         } => {
-            app.toggle_expansion();
+            app.select_from_popup();
         }
 
+        // Cancel popup
         KeyEvent {
-            code: KeyCode::Char('D'),
-            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Esc,
             ..
         } => {
-            app.delete_selected_with_children();
+            app.cancel_popup();
         }
 
-        // Search and filtering
+        // Navigation
         KeyEvent {
-            code: KeyCode::Char('/'),
+            code: KeyCode::Char('j'),
             modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.enter_search_mode();
         }
-
-        KeyEvent {
-            code: KeyCode::Char('#'),
-            modifiers: KeyModifiers::NONE,
+        | KeyEvent {
+            code: KeyCode::Down,
             ..
         } => {
-            app.enter_tag_selection();
+            app.move_popup_selection_down();
         }
 
         KeyEvent {
-            code: KeyCode::Char('@'),
+            code: KeyCode::Char('k'),
             modifiers: KeyModifiers::NONE,
             ..
+        }
+        | KeyEvent {
+            code: KeyCode::Up,
+            ..
         } => {
-            app.enter_context_selection();
+            app.move_popup_selection_up();
         }
-
+        
+        // Exclusion filters from the tag/context popups
         KeyEvent {
             code: KeyCode::Char('!'),
-            modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.cycle_due_date_filter();
+        } if matches!(app.mode, AppMode::TagSelection | AppMode::ContextSelection) && !app.tag_select_from_visual => {
+            app.exclude_from_popup();
         }
-
-        // Advanced features
+        
         KeyEvent {
-            code: KeyCode::Char('t'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Char(' '),
             ..
-        } => {
-            app.toggle_timer();
+        } if matches!(app.mode, AppMode::TagSelection | AppMode::ContextSelection) && !app.tag_select_from_visual => {
+            app.toggle_popup_exclude();
         }
-
+        
+        // Workspace-specific actions
         KeyEvent {
             code: KeyCode::Char('n'),
             modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.enter_notes_mode();
+        } if app.mode == AppMode::WorkspaceSelection => {
+            // Only allow creating new workspace from workspace selection mode
+            app.enter_create_workspace_mode();
         }
         
-        // Edit todo description
         KeyEvent {
-            code: KeyCode::Char('e'),
+            code: KeyCode::Char('d'),
             modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.enter_edit_mode();
-        }
-
-        KeyEvent {
-            code: KeyCode::Char('T'),
-            modifiers: KeyModifiers::SHIFT,
-            ..
-        } => {
-            app.enter_template_selection();
+        } if app.mode == AppMode::WorkspaceSelection => {
+            // Only allow deleting workspace from workspace selection mode
+            app.delete_selected_workspace();
         }
-
+        
+        // Rename the highlighted tag or context everywhere
         KeyEvent {
             code: KeyCode::Char('r'),
             modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.enter_recurrence_selection();
+        } if matches!(app.mode, AppMode::TagSelection | AppMode::ContextSelection) && !app.tag_select_from_visual => {
+            app.enter_rename_label_mode();
         }
         
-        // Workspace selection
+        // Strip the highlighted tag or context from every todo (asks first)
         KeyEvent {
-            code: KeyCode::Char('w'),
+            code: KeyCode::Char('d'),
             modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.enter_workspace_selection();
+        } if matches!(app.mode, AppMode::TagSelection | AppMode::ContextSelection) && !app.tag_select_from_visual => {
+            app.request_delete_label();
         }
         
-        // Return to welcome screen
+        // Time entry actions
         KeyEvent {
-            code: KeyCode::Char('h'),
-            modifiers: KeyModifiers::CONTROL,
+            code: KeyCode::Char('d'),
+            modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.return_to_welcome();
+        } if app.mode == AppMode::TimeTracking => {
+            app.delete_selected_time_entry();
         }
         
-        // Undo
         KeyEvent {
-            code: KeyCode::Char('u'),
+            code: KeyCode::Char('e'),
             modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.undo();
+        } if app.mode == AppMode::TimeTracking => {
+            app.enter_edit_time_entry_mode();
         }
         
-        // Redo
+        // Saved filter actions
         KeyEvent {
-            code: KeyCode::Char('r'),
-            modifiers: KeyModifiers::CONTROL,
+            code: KeyCode::Char('n'),
+            modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.redo();
+        } if app.mode == AppMode::SavedFilterSelection => {
+            app.enter_save_filter_mode();
         }
         
-        // Visual mode (bulk operations)
-        KeyEvent {
-            code: KeyCode::Char('V'),
-            modifiers: KeyModifiers::SHIFT,
-            ..
-        } => {
-            app.enter_visual_mode();
-        }
-
-        _ => {}
-    }
-
-    Ok(())
-}
-
-fn handle_insert_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
-    match key_event {
-        // Submit
         KeyEvent {
-            code: KeyCode::Enter,
-            ..
-        } => {
-            app.submit_input();
-        }
-
-        // Cancel
-        KeyEvent {
-            code: KeyCode::Esc,
+            code: KeyCode::Char('d'),
+            modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.enter_normal_mode();
+        } if app.mode == AppMode::SavedFilterSelection => {
+            app.delete_selected_saved_filter();
         }
-
-        // Backspace
+        
+        // Share the highlighted workspace, or add one that was shared
         KeyEvent {
-            code: KeyCode::Backspace,
+            code: KeyCode::Char('x'),
+            modifiers: KeyModifiers::NONE,
             ..
-        } => {
-            app.remove_char_from_input();
+        } if app.mode == AppMode::WorkspaceSelection => {
+            app.enter_export_workspace_mode();
         }
-
-        // Cursor navigation - left arrow
+        
         KeyEvent {
-            code: KeyCode::Left,
+            code: KeyCode::Char('I'),
             ..
-        } => {
-            app.move_input_cursor_left();
+        } if app.mode == AppMode::WorkspaceSelection => {
+            app.enter_import_workspace_mode();
         }
         
-        // Cursor navigation - right arrow
+        // Reorder workspaces
         KeyEvent {
-            code: KeyCode::Right,
+            code: KeyCode::Char('J'),
             ..
-        } => {
-            app.move_input_cursor_right();
+        } if app.mode == AppMode::WorkspaceSelection => {
+            app.move_selected_workspace(false);
         }
-
-        // Character input
+        
         KeyEvent {
-            code: KeyCode::Char(c),
-            modifiers: KeyModifiers::NONE,
-            ..
-        }
-        | KeyEvent {
-            code: KeyCode::Char(c),
-            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Char('K'),
             ..
-        } => {
-            app.add_char_to_input(c);
+        } if app.mode == AppMode::WorkspaceSelection => {
+            app.move_selected_workspace(true);
         }
 
         _ => {}
@@ -384,94 +493,46 @@ fn handle_insert_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
     Ok(())
 }
 
-fn handle_popup_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+fn handle_search_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
     match key_event {
-        // Select item
+        // Submit search
         KeyEvent {
             code: KeyCode::Enter,
             ..
         } => {
-            app.select_from_popup();
+            app.submit_search();
         }
 
-        // Cancel popup
+        // Cancel search and restore the previous view
         KeyEvent {
             code: KeyCode::Esc,
             ..
         } => {
-            app.cancel_popup();
+            app.cancel_search();
         }
 
-        // Navigation
+        // Recall earlier searches
         KeyEvent {
-            code: KeyCode::Char('j'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        }
-        | KeyEvent {
-            code: KeyCode::Down,
-            ..
-        } => {
-            app.move_popup_selection_down();
-        }
-
-        KeyEvent {
-            code: KeyCode::Char('k'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        }
-        | KeyEvent {
             code: KeyCode::Up,
             ..
         } => {
-            app.move_popup_selection_up();
+            app.search_history_previous();
         }
-        
-        // Workspace-specific actions
-        KeyEvent {
-            code: KeyCode::Char('n'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            // Only allow creating new workspace from workspace selection mode
-            if app.mode == AppMode::WorkspaceSelection {
-                app.enter_create_workspace_mode();
-            }
-        }
-        
-        KeyEvent {
-            code: KeyCode::Char('d'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            // Only allow deleting workspace from workspace selection mode
-            if app.mode == AppMode::WorkspaceSelection {
-                app.delete_selected_workspace();
-            }
-        }
-
-        _ => {}
-    }
-
-    Ok(())
-}
 
-fn handle_search_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
-    match key_event {
-        // Submit search
         KeyEvent {
-            code: KeyCode::Enter,
+            code: KeyCode::Down,
             ..
         } => {
-            app.submit_search();
+            app.search_history_next();
         }
 
-        // Cancel search
+        // Toggle search scope (current workspace / all workspaces)
         KeyEvent {
-            code: KeyCode::Esc,
+            code: KeyCode::Char('w'),
+            modifiers: KeyModifiers::CONTROL,
             ..
         } => {
-            app.enter_normal_mode();
+            app.toggle_search_scope();
         }
 
         // Backspace
@@ -519,7 +580,20 @@ fn handle_search_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
 }
 
 fn handle_edit_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    // Any key other than Tab ends the current completion
+    if key_event.code != KeyCode::Tab {
+        app.completion = None;
+    }
+    
     match key_event {
+        // Complete tag, context or due keyword
+        KeyEvent {
+            code: KeyCode::Tab,
+            ..
+        } => {
+            app.complete_input();
+        }
+
         // Save edit
         KeyEvent {
             code: KeyCode::Enter,
@@ -634,6 +708,21 @@ fn handle_notes_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
             app.add_char_to_notes('\n');
         }
 
+        // Cursor navigation - previous/next line
+        KeyEvent {
+            code: KeyCode::Up,
+            ..
+        } => {
+            app.notes_buffer.move_up();
+        }
+
+        KeyEvent {
+            code: KeyCode::Down,
+            ..
+        } => {
+            app.notes_buffer.move_down();
+        }
+
         // Cursor navigation - left arrow
         KeyEvent {
             code: KeyCode::Left,
@@ -670,6 +759,41 @@ fn handle_notes_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
     Ok(())
 }
 
+fn handle_calendar_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    match key_event.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('c') => app.exit_calendar_mode(),
+        KeyCode::Char('h') | KeyCode::Left => app.move_calendar_day(-1),
+        KeyCode::Char('l') | KeyCode::Right => app.move_calendar_day(1),
+        KeyCode::Char('j') | KeyCode::Down => app.move_calendar_selection(1),
+        KeyCode::Char('k') | KeyCode::Up => app.move_calendar_selection(-1),
+        KeyCode::Char('[') => app.page_calendar_week(-1),
+        KeyCode::Char(']') => app.page_calendar_week(1),
+        KeyCode::Enter => app.open_calendar_selection(),
+        _ => {}
+    }
+    
+    Ok(())
+}
+
+fn handle_board_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    match key_event.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('b') => app.toggle_board(),
+        KeyCode::Char('h') | KeyCode::Left => app.move_board_column(-1),
+        KeyCode::Char('l') | KeyCode::Right => app.move_board_column(1),
+        KeyCode::Char('j') | KeyCode::Down => app.move_board_selection(1),
+        KeyCode::Char('k') | KeyCode::Up => app.move_board_selection(-1),
+        KeyCode::Char('H') => app.move_board_todo(-1),
+        KeyCode::Char('L') => app.move_board_todo(1),
+        KeyCode::Char('u') => {
+            app.undo();
+            app.move_board_column(0); // Keep the row inside the column after the todo moves back
+        }
+        _ => {}
+    }
+    
+    Ok(())
+}
+
 fn handle_view_notes_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
     match key_event {
         // Close notes viewer
@@ -690,93 +814,98 @@ fn handle_view_notes_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()>
             app.mode = AppMode::EditNotes;
         }
 
-        _ => {}
-    }
-
-    Ok(())
-}
-
-fn handle_visual_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
-    match key_event {
-        // Exit visual mode
+        // Move the highlighted line, scrolling with it
         KeyEvent {
-            code: KeyCode::Esc,
+            code: KeyCode::Char('j') | KeyCode::Down,
             ..
         } => {
-            app.exit_visual_mode();
+            app.move_notes_selection(1);
         }
-        
-        // Navigation in visual mode
+
         KeyEvent {
-            code: KeyCode::Char('j'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        }
-        | KeyEvent {
-            code: KeyCode::Down,
+            code: KeyCode::Char('k') | KeyCode::Up,
             ..
         } => {
-            app.move_selection_down();
-            app.select_range_in_visual();
+            app.move_notes_selection(-1);
         }
 
         KeyEvent {
-            code: KeyCode::Char('k'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        }
-        | KeyEvent {
-            code: KeyCode::Up,
+            code: KeyCode::PageDown,
             ..
         } => {
-            app.move_selection_up();
-            app.select_range_in_visual();
+            app.move_notes_selection_page(true);
         }
-        
-        // Toggle individual selection
+
         KeyEvent {
-            code: KeyCode::Char(' '),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::PageUp,
             ..
         } => {
-            app.toggle_selection_in_visual();
+            app.move_notes_selection_page(false);
         }
-        
-        // Bulk operations
+
         KeyEvent {
-            code: KeyCode::Char('c'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Char('g') | KeyCode::Home,
             ..
         } => {
-            app.bulk_complete_todos();
+            app.notes_selected_line = 0;
         }
-        
+
         KeyEvent {
-            code: KeyCode::Char('d'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Char('G') | KeyCode::End,
             ..
         } => {
-            app.bulk_delete_todos();
+            app.move_notes_selection(isize::MAX);
         }
-        
-        // Priority setting (1-5)
+
+        // Tick or untick a checklist line
         KeyEvent {
-            code: KeyCode::Char(c @ '1'..='5'),
-            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Char(' '),
             ..
         } => {
-            let priority = (c as u8) - b'0';
-            app.bulk_set_priority(priority);
+            app.toggle_notes_checklist_item();
         }
+
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_visual_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    // Priority setting (0-5, not remappable)
+    if let KeyEvent { code: KeyCode::Char(c @ '0'..='5'), modifiers: KeyModifiers::NONE, .. } = key_event {
+        app.bulk_set_priority((c as u8) - b'0');
+        return Ok(());
+    }
+    
+    let Some(action) = app.keymap.action(Scope::Visual, &key_event) else { return Ok(()) };
+    match action {
+        Action::ExitVisual => app.exit_visual_mode(),
         
-        KeyEvent {
-            code: KeyCode::Char('0'),
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            app.bulk_set_priority(0);
+        // Navigation extends the selection
+        Action::MoveDown => {
+            app.move_selection_down();
+            app.select_range_in_visual();
+        }
+        Action::MoveUp => {
+            app.move_selection_up();
+            app.select_range_in_visual();
         }
         
+        // Selection
+        Action::ToggleSelection => app.toggle_selection_in_visual(),
+        Action::SelectAll => app.visual_select_all(),
+        Action::InvertSelection => app.visual_invert_selection(),
+        Action::SelectByTag => app.enter_visual_tag_selection(),
+        
+        // Bulk operations
+        Action::BulkComplete => app.bulk_complete_todos(),
+        Action::BulkDelete => app.bulk_delete_todos(),
+        Action::BulkTag => app.enter_bulk_label_mode(true),
+        Action::BulkContext => app.enter_bulk_label_mode(false),
+        Action::BulkDue => app.enter_bulk_due_mode(),
+        Action::BulkMove => app.enter_move_to_workspace(),
+        
         _ => {}
     }
 
@@ -784,16 +913,13 @@ fn handle_visual_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
 }
 
 fn handle_bulk_operation_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
-    match key_event {
-        // Exit bulk operation mode
-        KeyEvent {
-            code: KeyCode::Esc,
-            ..
-        } => {
-            app.exit_visual_mode();
-        }
-        
-        _ => {}
+    // Exit bulk operation mode
+    if let KeyEvent {
+        code: KeyCode::Esc,
+        ..
+    } = key_event
+    {
+        app.exit_visual_mode();
     }
 
     Ok(())
@@ -844,6 +970,14 @@ fn handle_welcome_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
             app.toggle_help();
         }
 
+        // Import a shared workspace
+        KeyEvent {
+            code: KeyCode::Char('I'),
+            ..
+        } => {
+            app.enter_import_workspace_mode();
+        }
+
         // Quick shortcuts for common actions
         KeyEvent {
             code: KeyCode::Char('1'),
@@ -954,3 +1088,73 @@ fn handle_create_workspace_mode(app: &mut App, key_event: KeyEvent) -> io::Resul
 
     Ok(())
 }
+
+fn handle_confirm_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    match key_event.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => app.confirm_pending(),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.cancel_confirmation(),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// Single-line prompts opened from a popup (saving a filter, renaming a tag or context)
+fn handle_prompt_mode(app: &mut App, key_event: KeyEvent) -> io::Result<()> {
+    // Any key other than Tab ends the current completion
+    if key_event.code != KeyCode::Tab {
+        app.completion = None;
+    }
+    
+    match key_event {
+        // Complete the tag or context name (bulk prompts only)
+        KeyEvent {
+            code: KeyCode::Tab,
+            ..
+        } => {
+            app.complete_input();
+        }
+
+        // Submit
+        KeyEvent {
+            code: KeyCode::Enter,
+            ..
+        } => {
+            app.submit_prompt();
+        }
+
+        // Back to the popup
+        KeyEvent {
+            code: KeyCode::Esc,
+            ..
+        } => {
+            app.cancel_prompt();
+        }
+
+        // Backspace
+        KeyEvent {
+            code: KeyCode::Backspace,
+            ..
+        } => {
+            app.remove_char_from_input();
+        }
+
+        // Character input
+        KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }
+        | KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::SHIFT,
+            ..
+        } => {
+            app.add_char_to_input(c);
+        }
+
+        _ => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use crate::todo::{Todo, TodoList, TodoStatus, WorkspaceManager};
+use crate::todo::RecurrencePattern;
+use chrono::{DateTime, Local, Utc};
+use regex::Regex;
+use std::sync::LazyLock;
+
+// A `due:` token with the space before it, and a #tag, for rewriting text as todo.txt
+static DUE_TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s*\bdue:[\w\-/]+").unwrap());
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"#([a-zA-Z0-9_]+)").unwrap());
+
+// Formats offered by the export popup (X), each written to a path typed after picking it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    TodoTxt, // The current workspace
+    TodosCsv, // Every workspace, one row per todo
+    TimeCsv, // Every workspace, one row per time entry
+    ICalendar, // Every workspace's todos with due dates
+    WorkspaceJson, // The current workspace, for someone else to import
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 5] = [
+        ExportFormat::TodoTxt,
+        ExportFormat::TodosCsv,
+        ExportFormat::TimeCsv,
+        ExportFormat::ICalendar,
+        ExportFormat::WorkspaceJson,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::TodoTxt => "todo.txt",
+            ExportFormat::TodosCsv => "Todos CSV",
+            ExportFormat::TimeCsv => "Time entries CSV",
+            ExportFormat::ICalendar => "iCalendar (due dates)",
+            ExportFormat::WorkspaceJson => "Workspace JSON (for sharing)",
+        }
+    }
+
+    // Suggested file name, prefilled in the path prompt
+    pub fn default_file(self, workspace: &str) -> String {
+        match self {
+            ExportFormat::TodoTxt => format!("{}.todo.txt", file_stem(workspace)),
+            ExportFormat::TodosCsv => "paperclip-todos.csv".to_string(),
+            ExportFormat::TimeCsv => "paperclip-time.csv".to_string(),
+            ExportFormat::ICalendar => "paperclip.ics".to_string(),
+            ExportFormat::WorkspaceJson => format!("{}.paperclip.json", file_stem(workspace)),
+        }
+    }
+}
+
+// A workspace name made safe to use as a file name
+fn file_stem(name: &str) -> String {
+    let stem: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let stem = stem.trim_matches('-');
+    if stem.is_empty() { "paperclip".to_string() } else { stem.to_string() }
+}
+
+// One todo.txt line per todo, in the order they were created; subtasks are flattened and notes
+// left out. Priority 5 (the most important) is (A) down to 1 as (E); #tags become +projects.
+// Completed lines drop their priority, as todo.txt clients do, and always carry a completion
+// date: readers take the first date after `x` as that, so a lone creation date would be misread.
+// A todo completed without a recorded time uses its creation date for both.
+pub fn to_todotxt(todo_list: &TodoList) -> String {
+    let mut todos: Vec<&Todo> = todo_list.todos.values().collect();
+    todos.sort_by_key(|todo| todo.id);
+    todos.iter().map(|todo| todotxt_line(todo) + "\n").collect()
+}
+
+fn todotxt_line(todo: &Todo) -> String {
+    let mut parts = Vec::new();
+    if todo.status == TodoStatus::Completed {
+        let completed_at = todo.completed_at.unwrap_or(todo.created_at);
+        parts.push(format!("x {}", completed_at.format("%Y-%m-%d")));
+    } else if let Some(letter) = todotxt_priority(todo.priority) {
+        parts.push(format!("({})", letter));
+    }
+    parts.push(todo.created_at.format("%Y-%m-%d").to_string());
+
+    // The text as typed, with due: normalised to a date
+    let text = DUE_TOKEN_RE.replace_all(&todo.raw_description, "");
+    let text = TAG_RE.replace_all(text.trim(), "+$1");
+    parts.push(text.into_owned());
+    if let Some(due) = todo.due_date {
+        parts.push(format!("due:{}", due.format("%Y-%m-%d")));
+    }
+    parts.join(" ")
+}
+
+// 5 -> A ... 1 -> E; 0 (no priority) has no letter
+pub fn todotxt_priority(priority: u8) -> Option<char> {
+    (1..=5).contains(&priority).then(|| (b'A' + (5 - priority)) as char)
+}
+
+// A todo with its notes and subtasks as an indented Markdown checklist
+pub fn markdown_checklist(todo_list: &TodoList, id: u32) -> Option<String> {
+    let mut lines = Vec::new();
+    push_markdown(todo_list, id, 0, &mut lines)?;
+    Some(lines.join("\n"))
+}
+
+fn push_markdown(todo_list: &TodoList, id: u32, depth: usize, lines: &mut Vec<String>) -> Option<()> {
+    let todo = todo_list.get_todo(id)?;
+    let indent = "  ".repeat(depth);
+    let check = if todo.status == TodoStatus::Completed { "x" } else { " " };
+    lines.push(format!("{}- [{}] {}", indent, check, todo.raw_description));
+
+    // Notes sit under the item, indented so they stay part of it
+    if let Some(notes) = todo.notes.as_deref().filter(|notes| !notes.trim().is_empty()) {
+        for line in notes.lines() {
+            if line.trim().is_empty() {
+                lines.push(String::new());
+            } else {
+                lines.push(format!("{}  {}", indent, line));
+            }
+        }
+    }
+
+    for &child_id in &todo.children {
+        push_markdown(todo_list, child_id, depth + 1, lines);
+    }
+    Some(())
+}
+
+// Every todo in every workspace for a spreadsheet, workspaces in list order; tags and contexts
+// are space-separated and times are local
+pub fn todos_csv(workspace_manager: &WorkspaceManager) -> String {
+    let mut rows = vec![csv_row(&[
+        "id", "workspace", "description", "status", "priority", "tags", "contexts",
+        "created_at", "completed_at", "due_date", "total_seconds",
+    ])];
+    for (workspace, todo_list) in workspace_lists(workspace_manager) {
+        for todo in sorted_todos(todo_list) {
+            rows.push(csv_row(&[
+                &todo.id.to_string(),
+                workspace,
+                &todo.description,
+                todo.status.name(),
+                &todo.priority.to_string(),
+                &sorted_labels(&todo.tags),
+                &sorted_labels(&todo.contexts),
+                &csv_time(Some(todo.created_at)),
+                &csv_time(todo.completed_at),
+                &csv_time(todo.due_date),
+                &todo.time_tracker.total_seconds.to_string(),
+            ]));
+        }
+    }
+    rows.concat()
+}
+
+// One row per finished time entry in every workspace. A running session isn't an entry yet.
+// Entries that took time off have negative seconds.
+pub fn time_entries_csv(workspace_manager: &WorkspaceManager) -> String {
+    let mut rows = vec![csv_row(&["workspace", "todo_id", "description", "start", "end", "seconds", "note"])];
+    for (workspace, todo_list) in workspace_lists(workspace_manager) {
+        for todo in sorted_todos(todo_list) {
+            for entry in &todo.time_tracker.entries {
+                rows.push(csv_row(&[
+                    workspace,
+                    &todo.id.to_string(),
+                    &todo.description,
+                    &csv_time(Some(entry.start)),
+                    &csv_time(entry.end),
+                    &entry.duration_seconds().to_string(),
+                    entry.description.as_deref().unwrap_or(""),
+                ]));
+            }
+        }
+    }
+    rows.concat()
+}
+
+fn workspace_lists(workspace_manager: &WorkspaceManager) -> Vec<(&str, &TodoList)> {
+    workspace_manager.get_all_workspaces().into_iter()
+        .filter_map(|workspace| {
+            let todo_list = workspace_manager.workspace_todos.get(&workspace.id)?;
+            Some((workspace.name.as_str(), todo_list))
+        })
+        .collect()
+}
+
+fn sorted_todos(todo_list: &TodoList) -> Vec<&Todo> {
+    let mut todos: Vec<&Todo> = todo_list.todos.values().collect();
+    todos.sort_by_key(|todo| todo.id);
+    todos
+}
+
+fn sorted_labels(labels: &std::collections::HashSet<String>) -> String {
+    let mut labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    labels.sort_unstable();
+    labels.join(" ")
+}
+
+fn csv_time(time: Option<DateTime<Local>>) -> String {
+    time.map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default()
+}
+
+// RFC 4180: fields joined by commas, each row ending in CRLF
+pub fn csv_row(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+// Quoted when it holds a comma, quote or line break, or starts or ends with a space,
+// which spreadsheets would otherwise trim; quotes inside are doubled
+pub fn csv_field(field: &str) -> String {
+    let needs_quotes = field.contains([',', '"', '\n', '\r']) || field.starts_with(' ') || field.ends_with(' ');
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// RFC 5545 calendar with a VTODO for every todo that has a due date, so calendar apps can show
+// them. Times are written in UTC; the UID stays the same between exports so updates replace
+// the earlier copy instead of adding another.
+pub fn to_ics(workspace_manager: &WorkspaceManager) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Paperclip//Paperclip todos//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for workspace in workspace_manager.get_all_workspaces() {
+        let Some(todo_list) = workspace_manager.workspace_todos.get(&workspace.id) else { continue };
+        for todo in sorted_todos(todo_list) {
+            let Some(due) = todo.due_date else { continue };
+            lines.push("BEGIN:VTODO".to_string());
+            lines.push(format!("UID:{}-{}-{}@paperclip", workspace.id, todo.id, todo.created_at.timestamp()));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!("SUMMARY:{}", ics_text(&todo.description)));
+            if let Some(notes) = todo.notes.as_deref().filter(|notes| !notes.trim().is_empty()) {
+                lines.push(format!("DESCRIPTION:{}", ics_text(notes)));
+            }
+            lines.push(format!("DUE:{}", ics_time(due)));
+            if let Some(priority) = ics_priority(todo.priority) {
+                lines.push(format!("PRIORITY:{}", priority));
+            }
+            lines.push(format!("STATUS:{}", match todo.status {
+                TodoStatus::Pending => "NEEDS-ACTION",
+                TodoStatus::InProgress => "IN-PROCESS",
+                TodoStatus::Completed => "COMPLETED",
+            }));
+            if let Some(completed_at) = todo.completed_at.filter(|_| todo.status == TodoStatus::Completed) {
+                lines.push(format!("COMPLETED:{}", ics_time(completed_at)));
+            }
+            if !todo.tags.is_empty() {
+                let mut tags: Vec<String> = todo.tags.iter().map(|tag| ics_text(tag)).collect();
+                tags.sort_unstable();
+                lines.push(format!("CATEGORIES:{}", tags.join(",")));
+            }
+            if let Some(rule) = ics_rrule(&todo.recurrence) {
+                lines.push(format!("RRULE:{}", rule));
+            }
+            lines.push("END:VTODO".to_string());
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_ics_line(line) + "\r\n").collect()
+}
+
+fn ics_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// iCalendar priorities run 1 (highest) to 9; ours run 5 (highest) to 1, and 0 is undefined
+pub fn ics_priority(priority: u8) -> Option<u8> {
+    (1..=5).contains(&priority).then(|| 11 - 2 * priority)
+}
+
+fn ics_rrule(recurrence: &RecurrencePattern) -> Option<String> {
+    match recurrence {
+        RecurrencePattern::None => None,
+        RecurrencePattern::Daily => Some("FREQ=DAILY".to_string()),
+        RecurrencePattern::Weekly => Some("FREQ=WEEKLY".to_string()),
+        RecurrencePattern::Monthly => Some("FREQ=MONTHLY".to_string()),
+        RecurrencePattern::Yearly => Some("FREQ=YEARLY".to_string()),
+        RecurrencePattern::Custom(days) => Some(format!("FREQ=DAILY;INTERVAL={}", days)),
+    }
+}
+
+// TEXT values (RFC 5545 3.3.11): backslash, semicolon and comma are escaped and line breaks become \n
+pub fn ics_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Lines longer than 75 octets continue on the next line after a single space (RFC 5545 3.1),
+// breaking between characters so a multi-byte character is never split
+pub fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 37);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
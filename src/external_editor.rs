@@ -0,0 +1,82 @@
+use crossterm::{
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::Backend, Terminal};
+use std::{env, fs, io, io::Write, path::{Path, PathBuf}, process::Command};
+
+// The user's editor command: $VISUAL, then $EDITOR
+pub fn editor_from_env() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+}
+
+// Open `text` in `editor` (which may carry arguments, e.g. "code --wait") via a temp file and
+// return what was saved. Errors are messages for the status bar; the caller keeps the old text.
+pub fn edit_text(editor: &str, text: &str, file_name: &str) -> Result<String, String> {
+    let dir = private_temp_dir().map_err(|e| format!("Couldn't create a temp directory: {}", e))?;
+    let path = dir.join(file_name);
+    let written = new_private_file(&path).and_then(|mut file| file.write_all(text.as_bytes()));
+    if let Err(e) = written {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(format!("Couldn't create a temp file: {}", e));
+    }
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or_default();
+    let status = Command::new(program).args(parts).arg(&path).status();
+
+    let result = match status {
+        Ok(status) if status.success() => {
+            fs::read_to_string(&path).map_err(|e| format!("Couldn't read the edited notes: {}", e))
+        }
+        Ok(status) => Err(format!("{} exited with {}; notes unchanged", program, status)),
+        Err(e) => Err(format!("Couldn't start '{}': {}", program, e)),
+    };
+    // The editor may have left a swap or backup file beside the notes
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+// A new directory in the shared temp dir that only we can use, so nobody else can read the
+// notes or plant a symlink where the file will go
+fn private_temp_dir() -> io::Result<PathBuf> {
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.subsec_nanos());
+    for attempt in 0..100u32 {
+        let dir = env::temp_dir().join(format!("paperclip-{}-{}-{}", std::process::id(), nanos, attempt));
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "no free temp directory name"))
+}
+
+// Fails rather than following anything already at `path`
+fn new_private_file(path: &Path) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+// Hand the terminal back to the shell so a full-screen editor can use it
+pub fn suspend_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)
+}
+
+// Take the terminal back after the editor exits and force a full redraw
+pub fn resume_terminal<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    terminal.clear()
+}
@@ -0,0 +1,122 @@
+// Symbols drawn in the todo list, welcome screen and workspace picker. Terminals without
+// Unicode (the Linux console, non-UTF-8 locales) get the ASCII set instead; both sets are
+// defined side by side so they stay in sync.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyphs {
+    pub pending: &'static str,
+    pub in_progress: &'static str,
+    pub completed: &'static str,
+    pub overdue: &'static str,
+    pub expanded: &'static str,
+    pub collapsed: &'static str,
+    pub branch: &'static str, // Joins a subtask to its parent
+    pub leaf: &'static str,   // After the branch when the subtask has no children
+    pub selection_gutter: &'static str,
+    pub notes_match: &'static str,
+    pub timer: &'static str,
+    pub ellipsis: &'static str,
+    pub pointer: &'static str,
+    pub logo: &'static str,
+    pub first_launch: &'static str,
+    pub welcome_back: &'static str,
+    pub home: &'static str,
+    pub back: &'static str,
+    pub workspace: &'static str,
+    pub up_down: &'static str,
+}
+
+impl Glyphs {
+    pub const UNICODE: Glyphs = Glyphs {
+        pending: "○",
+        in_progress: "◐",
+        completed: "●",
+        overdue: "!",
+        expanded: "▼",
+        collapsed: "▶",
+        branch: "└",
+        leaf: "─",
+        selection_gutter: "▎",
+        notes_match: "[N✓]",
+        timer: "⏱",
+        ellipsis: "…",
+        pointer: "►",
+        logo: "📎 ",
+        first_launch: "🎉 ",
+        welcome_back: "👋 ",
+        home: "🏠 ",
+        back: "←",
+        workspace: "📁 ",
+        up_down: "↓/↑",
+    };
+
+    pub const ASCII: Glyphs = Glyphs {
+        pending: "[ ]",
+        in_progress: "[~]",
+        completed: "[x]",
+        overdue: "[!]",
+        expanded: "-",
+        collapsed: "+",
+        branch: "`",
+        leaf: "-",
+        selection_gutter: "|",
+        notes_match: "[N*]",
+        timer: "t",
+        ellipsis: "...",
+        pointer: ">",
+        logo: "",
+        first_launch: "",
+        welcome_back: "",
+        home: "",
+        back: "<-",
+        workspace: "",
+        up_down: "Down/Up",
+    };
+
+    // Every glyph in field order, for checking the two sets against each other
+    pub fn all(&self) -> [&'static str; 20] {
+        [
+            self.pending,
+            self.in_progress,
+            self.completed,
+            self.overdue,
+            self.expanded,
+            self.collapsed,
+            self.branch,
+            self.leaf,
+            self.selection_gutter,
+            self.notes_match,
+            self.timer,
+            self.ellipsis,
+            self.pointer,
+            self.logo,
+            self.first_launch,
+            self.welcome_back,
+            self.home,
+            self.back,
+            self.workspace,
+            self.up_down,
+        ]
+    }
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self::UNICODE
+    }
+}
+
+// The Linux console can't show most of the Unicode set, and neither can a non-UTF-8 locale
+pub fn unicode_unsupported() -> bool {
+    if std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    locale.is_some_and(|locale| {
+        let locale = locale.to_lowercase();
+        !locale.contains("utf-8") && !locale.contains("utf8")
+    })
+}
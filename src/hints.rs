@@ -0,0 +1,158 @@
+// The key hint bar along the bottom of the screen: the handful of keys that matter most in the
+// current mode. Remappable keys are looked up in the keymap, so the bar shows the keys in effect.
+
+use crate::app::AppMode;
+use crate::keymap::{Action, Keymap};
+use unicode_width::UnicodeWidthStr;
+
+// A key to show: an action's binding, or a fixed key where the keymap doesn't apply
+#[derive(Debug, Clone, Copy)]
+pub enum HintKey {
+    Action(Action),
+    Fixed(&'static str),
+}
+
+use HintKey::{Action as Bound, Fixed};
+
+type Hints = &'static [(HintKey, &'static str)];
+
+const NORMAL: Hints = &[
+    (Bound(Action::AddTodo), "add"),
+    (Bound(Action::EditTodo), "edit"),
+    (Bound(Action::ToggleComplete), "done"),
+    (Bound(Action::DeleteTodo), "del"),
+    (Bound(Action::Search), "search"),
+    (Bound(Action::SwitchWorkspace), "workspace"),
+    (Bound(Action::EnterVisual), "select"),
+    (Bound(Action::Help), "help"),
+];
+const VISUAL: Hints = &[
+    (Bound(Action::ToggleSelection), "toggle"),
+    (Bound(Action::BulkComplete), "complete"),
+    (Bound(Action::BulkDelete), "delete"),
+    (Fixed("0-5"), "priority"),
+    (Bound(Action::BulkTag), "tag"),
+    (Bound(Action::BulkDue), "due"),
+    (Bound(Action::BulkMove), "move"),
+    (Bound(Action::ExitVisual), "cancel"),
+];
+const TYPING_TODO: Hints = &[
+    (Fixed("Enter"), "save"),
+    (Fixed("Shift+Enter"), "save and add another"),
+    (Fixed("Tab"), "complete #tag/@context"),
+    (Fixed("Ctrl+W"), "delete word"),
+    (Fixed("Esc"), "cancel"),
+];
+const EDIT_TODO: Hints = &[
+    (Fixed("Enter"), "save"),
+    (Fixed("Tab"), "complete #tag/@context"),
+    (Fixed("Ctrl+W"), "delete word"),
+    (Fixed("Esc"), "cancel"),
+];
+const SEARCH: Hints = &[
+    (Fixed("Enter"), "keep results"),
+    (Fixed("Ctrl+W"), "this workspace/all"),
+    (Fixed("'"), "exact match"),
+    (Fixed("↑/↓"), "history"),
+    (Fixed("Esc"), "cancel"),
+];
+const PROMPT: Hints = &[(Fixed("Enter"), "confirm"), (Fixed("Ctrl+W"), "delete word"), (Fixed("Esc"), "cancel")];
+const POPUP: Hints = &[(Fixed("j/k"), "move"), (Fixed("Enter"), "select"), (Fixed("Esc"), "cancel")];
+const TAG_POPUP: Hints = &[
+    (Fixed("j/k"), "move"),
+    (Fixed("Enter"), "filter"),
+    (Fixed("!"), "exclude"),
+    (Fixed("r"), "rename"),
+    (Fixed("d"), "remove"),
+    (Fixed("Esc"), "cancel"),
+];
+const WORKSPACES: Hints = &[
+    (Fixed("j/k"), "move"),
+    (Fixed("Enter"), "open"),
+    (Fixed("n"), "new"),
+    (Fixed("d"), "delete"),
+    (Fixed("x"), "export"),
+    (Fixed("I"), "import"),
+    (Fixed("Esc"), "cancel"),
+];
+const SAVED_FILTERS: Hints = &[
+    (Fixed("j/k"), "move"),
+    (Fixed("Enter"), "apply"),
+    (Fixed("n"), "save current"),
+    (Fixed("d"), "delete"),
+    (Fixed("Esc"), "cancel"),
+];
+const TIME_ENTRIES: Hints = &[(Fixed("j/k"), "move"), (Fixed("d"), "delete"), (Fixed("e"), "edit note"), (Fixed("Esc"), "close")];
+const EDIT_NOTES: Hints = &[(Fixed("Ctrl+S"), "save"), (Fixed("Enter"), "new line"), (Fixed("Esc"), "cancel")];
+const VIEW_NOTES: Hints = &[
+    (Fixed("j/k"), "move"),
+    (Fixed("Space"), "tick item"),
+    (Fixed("PgUp/PgDn"), "page"),
+    (Fixed("n"), "edit"),
+    (Fixed("Esc"), "close"),
+];
+const CALENDAR: Hints = &[
+    (Fixed("h/l"), "day"),
+    (Fixed("j/k"), "todo"),
+    (Fixed("[/]"), "week"),
+    (Fixed("Enter"), "show in list"),
+    (Fixed("Esc"), "close"),
+];
+const BOARD: Hints = &[
+    (Fixed("h/l"), "column"),
+    (Fixed("j/k"), "todo"),
+    (Fixed("H/L"), "move todo"),
+    (Fixed("u"), "undo"),
+    (Fixed("Esc"), "close"),
+];
+const CONFIRM: Hints = &[(Fixed("y"), "yes"), (Fixed("n"), "no")];
+const WELCOME: Hints = &[(Fixed("j/k"), "move"), (Fixed("Enter"), "choose"), (Fixed("?"), "help"), (Fixed("q"), "quit")];
+const BULK_OPERATION: Hints = &[(Fixed("Esc"), "cancel")];
+
+pub fn hints_for(mode: &AppMode) -> Hints {
+    match mode {
+        AppMode::Normal => NORMAL,
+        AppMode::Visual => VISUAL,
+        AppMode::Insert | AppMode::InsertChild => TYPING_TODO,
+        AppMode::EditTodo => EDIT_TODO,
+        AppMode::Search => SEARCH,
+        AppMode::SaveFilter | AppMode::RenameTag | AppMode::RenameContext | AppMode::BulkTag | AppMode::BulkContext
+        | AppMode::BulkDue | AppMode::EditDue | AppMode::LogTime | AppMode::EditTimeEntry | AppMode::ExportPath | AppMode::ImportPath
+        | AppMode::ImportWorkspacePath | AppMode::ImportWorkspaceName | AppMode::CreateWorkspace => PROMPT,
+        AppMode::TagSelection | AppMode::ContextSelection => TAG_POPUP,
+        AppMode::TemplateSelection | AppMode::RecurrenceSelection | AppMode::MoveToWorkspace | AppMode::Export => POPUP,
+        AppMode::WorkspaceSelection => WORKSPACES,
+        AppMode::SavedFilterSelection => SAVED_FILTERS,
+        AppMode::TimeTracking => TIME_ENTRIES,
+        AppMode::EditNotes => EDIT_NOTES,
+        AppMode::ViewNotes => VIEW_NOTES,
+        AppMode::Calendar => CALENDAR,
+        AppMode::Board => BOARD,
+        AppMode::Confirm => CONFIRM,
+        AppMode::Welcome => WELCOME,
+        AppMode::BulkOperation => BULK_OPERATION,
+    }
+}
+
+pub const SEPARATOR: &str = " · ";
+
+// The mode's hints as (key, description), as many as fit in `width` columns. Hints are dropped
+// whole from the end rather than cut off part way.
+pub fn visible_hints(mode: &AppMode, keymap: &Keymap, width: usize) -> Vec<(String, &'static str)> {
+    let mut shown = Vec::new();
+    let mut used = 0;
+    for (key, description) in hints_for(mode) {
+        let key = match key {
+            Bound(action) => keymap.label(*action),
+            Fixed(key) => key.to_string(),
+        };
+        let separator = if shown.is_empty() { 0 } else { SEPARATOR.width() };
+        let needed = separator + key.width() + 1 + description.width();
+        if used + needed > width {
+            break;
+        }
+        used += needed;
+        shown.push((key, *description));
+    }
+    shown
+}
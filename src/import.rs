@@ -0,0 +1,76 @@
+// Reading todos written by other tools; the matching writers are in export.rs
+
+use crate::todo::{Todo, TodoList, TodoStatus};
+use chrono::{DateTime, Local, NaiveDate};
+
+// todo.txt lines as flat todos, the reverse of `export::to_todotxt`: (A) is priority 5 down to
+// (E) as 1, +projects become #tags and `due:YYYY-MM-DD` the due date. Returns the todos and
+// how many lines were skipped because they couldn't be read (blank lines don't count).
+pub fn from_todotxt(text: &str) -> (TodoList, usize) {
+    let mut todo_list = TodoList::new();
+    let mut skipped = 0;
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match parse_todotxt_line(line, todo_list.next_id) {
+            Some(todo) => {
+                todo_list.todos.insert(todo.id, todo);
+                todo_list.next_id += 1;
+            }
+            None => skipped += 1,
+        }
+    }
+    (todo_list, skipped)
+}
+
+// `x [completed] [(P)] [created] text`; a line with no text or a due: that isn't a date is unreadable
+fn parse_todotxt_line(line: &str, id: u32) -> Option<Todo> {
+    let mut words = line.split_whitespace().peekable();
+    let completed = words.next_if_eq(&"x").is_some();
+    let completed_at = if completed { words.next_if(|word| parse_date(word).is_some()) } else { None };
+    let priority = words.next_if(|word| parse_priority(word).is_some()).and_then(parse_priority).unwrap_or(0);
+    let created_at = words.next_if(|word| parse_date(word).is_some());
+
+    let mut text = Vec::new();
+    let mut due = None;
+    for word in words {
+        if let Some(date) = word.strip_prefix("due:") {
+            parse_date(date)?;
+            due = Some(date);
+        } else if let Some(project) = word.strip_prefix('+').filter(|project| !project.is_empty()) {
+            // Tags are letters, digits and underscores
+            let tag: String = project.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+            text.push(format!("#{}", tag));
+        } else {
+            text.push(word.to_string());
+        }
+    }
+    if text.is_empty() {
+        return None;
+    }
+    if let Some(due) = due {
+        text.push(format!("due:{}", due));
+    }
+
+    let mut todo = Todo::new(id, text.join(" "));
+    todo.priority = priority;
+    if let Some(created_at) = created_at.and_then(parse_date) {
+        todo.created_at = created_at;
+    }
+    if completed {
+        todo.status = TodoStatus::Completed;
+        todo.completed_at = Some(completed_at.and_then(parse_date).unwrap_or_else(Local::now));
+    }
+    Some(todo)
+}
+
+fn parse_date(word: &str) -> Option<DateTime<Local>> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest()
+}
+
+// (A) -> 5 ... (E) -> 1; letters past E have no equivalent and read as no priority
+fn parse_priority(word: &str) -> Option<u8> {
+    let letter = word.strip_prefix('(')?.strip_suffix(')')?;
+    match letter.as_bytes() {
+        [c @ b'A'..=b'Z'] => Some(5u8.saturating_sub(c - b'A')),
+        _ => None,
+    }
+}
@@ -0,0 +1,435 @@
+// Remappable keys for normal and visual mode. `keybindings.toml` in the config dir replaces
+// the keys of an action by name (`delete_todo = "x"`, `move_down = ["j", "down"]`).
+// Not covered yet, each needing a Scope of its own: normal mode's count digits and Alt+1-9,
+// visual mode's priority digits, text inputs, popups, confirmations, help, the welcome
+// screen, the calendar and the board.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fs;
+
+pub const CONFIG_FILE: &str = "keybindings.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    // Normal and visual mode
+    MoveDown,
+    MoveUp,
+    // Normal mode
+    Quit,
+    Help,
+    ClearFilters,
+    GoToBottom,
+    PreviousSibling,
+    NextSibling,
+    GoToParent,
+    GPrefix, // Starts gg, gt, gc and gv
+    FilterByTag,
+    AddTodo,
+    AddChild,
+    EditTodo,
+    ToggleComplete,
+    DeleteTodo,
+    DeleteWithChildren,
+    CycleStatusFilter,
+    ToggleDetailPane,
+    CycleTheme,
+    DueOverview,
+    Agenda,
+    Board,
+    Calendar,
+    GroupBy,
+    SavedFilters,
+    CycleSort,
+    EditNotesExternal,
+    EnterVisual,
+    IncreasePriority,
+    DecreasePriority,
+    ToggleExpand,
+    CollapseAll, // After a count, shows that many levels instead
+    ExpandAll,
+    Search,
+    TagFilter,
+    ContextFilter,
+    DueFilter,
+    ToggleTimer,
+    LogTime,
+    Pomodoro,
+    TimeReport,
+    TimeEntries,
+    EditNotes,
+    NextMatch,
+    PreviousMatch,
+    ApplyTemplate,
+    SetRecurrence,
+    SetDue,
+    SwitchWorkspace,
+    PreviousWorkspace,
+    Copy,
+    CopyWithDetails,
+    MoveToWorkspace,
+    Export,
+    Import,
+    Welcome,
+    DismissReminders,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    Undo,
+    Redo,
+    // Visual mode
+    ExitVisual,
+    ToggleSelection,
+    SelectAll,
+    InvertSelection,
+    SelectByTag,
+    BulkComplete,
+    BulkDelete,
+    BulkTag,
+    BulkContext,
+    BulkDue,
+    BulkMove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Normal,
+    Visual,
+}
+
+// Every action with its name in the config file and its default keys
+const ACTIONS: &[(Action, &str, &[&str])] = &[
+    (Action::MoveDown, "move_down", &["j", "down"]),
+    (Action::MoveUp, "move_up", &["k", "up"]),
+    (Action::Quit, "quit", &["q", "ctrl+c"]),
+    (Action::Help, "help", &["?"]),
+    (Action::ClearFilters, "clear_filters", &["esc"]),
+    (Action::GoToBottom, "go_to_bottom", &["G"]),
+    (Action::PreviousSibling, "previous_sibling", &["("]),
+    (Action::NextSibling, "next_sibling", &[")"]),
+    (Action::GoToParent, "go_to_parent", &["p"]),
+    (Action::GPrefix, "g_prefix", &["g"]),
+    (Action::FilterByTag, "filter_by_todo_tag", &["]"]),
+    (Action::AddTodo, "add_todo", &["i"]),
+    (Action::AddChild, "add_child", &["a"]),
+    (Action::EditTodo, "edit_todo", &["e"]),
+    (Action::ToggleComplete, "toggle_complete", &["space"]),
+    (Action::DeleteTodo, "delete_todo", &["d"]),
+    (Action::DeleteWithChildren, "delete_with_children", &["D"]),
+    (Action::CycleStatusFilter, "cycle_status_filter", &["v"]),
+    (Action::ToggleDetailPane, "toggle_detail_pane", &["o"]),
+    (Action::CycleTheme, "cycle_theme", &["ctrl+l"]),
+    (Action::DueOverview, "due_overview", &["O"]),
+    (Action::Agenda, "agenda", &["A"]),
+    (Action::Board, "board", &["b"]),
+    (Action::Calendar, "calendar", &["c"]),
+    // Not Shift+G: that is G, already go to bottom
+    (Action::GroupBy, "group_by", &["ctrl+g"]),
+    (Action::SavedFilters, "saved_filters", &["F"]),
+    (Action::CycleSort, "cycle_sort", &["s"]),
+    (Action::EditNotesExternal, "edit_notes_external", &["E"]),
+    (Action::EnterVisual, "enter_visual", &["V"]),
+    (Action::IncreasePriority, "increase_priority", &["+", "="]),
+    (Action::DecreasePriority, "decrease_priority", &["-"]),
+    (Action::ToggleExpand, "toggle_expand", &["enter"]),
+    (Action::CollapseAll, "collapse_all", &["Z"]),
+    (Action::ExpandAll, "expand_all", &["z"]),
+    (Action::Search, "search", &["/"]),
+    (Action::TagFilter, "tag_filter", &["#"]),
+    (Action::ContextFilter, "context_filter", &["@"]),
+    (Action::DueFilter, "due_filter", &["!"]),
+    (Action::ToggleTimer, "toggle_timer", &["t"]),
+    (Action::LogTime, "log_time", &["ctrl+t"]),
+    (Action::Pomodoro, "pomodoro", &["P"]),
+    (Action::TimeReport, "time_report", &["R"]),
+    (Action::TimeEntries, "time_entries", &["L"]),
+    (Action::EditNotes, "edit_notes", &["ctrl+n"]),
+    (Action::NextMatch, "next_match", &["n"]),
+    (Action::PreviousMatch, "previous_match", &["N"]),
+    (Action::ApplyTemplate, "apply_template", &["T"]),
+    (Action::SetRecurrence, "set_recurrence", &["r"]),
+    (Action::SetDue, "set_due", &[","]),
+    (Action::SwitchWorkspace, "switch_workspace", &["w"]),
+    (Action::PreviousWorkspace, "previous_workspace", &["tab", "`"]),
+    (Action::Copy, "copy", &["y"]),
+    (Action::CopyWithDetails, "copy_with_details", &["Y"]),
+    (Action::MoveToWorkspace, "move_to_workspace", &["m"]),
+    (Action::Export, "export", &["X"]),
+    (Action::Import, "import", &["I"]),
+    (Action::Welcome, "welcome", &["ctrl+h"]),
+    (Action::DismissReminders, "dismiss_reminders", &["ctrl+x"]),
+    (Action::HalfPageDown, "half_page_down", &["ctrl+d"]),
+    (Action::HalfPageUp, "half_page_up", &["ctrl+u"]),
+    (Action::PageDown, "page_down", &["pagedown"]),
+    (Action::PageUp, "page_up", &["pageup"]),
+    (Action::Undo, "undo", &["u"]),
+    (Action::Redo, "redo", &["ctrl+r"]),
+    (Action::ExitVisual, "exit_visual", &["esc"]),
+    (Action::ToggleSelection, "toggle_selection", &["space"]),
+    (Action::SelectAll, "select_all", &["a"]),
+    (Action::InvertSelection, "invert_selection", &["i"]),
+    (Action::SelectByTag, "select_by_tag", &["f"]),
+    (Action::BulkComplete, "bulk_complete", &["c"]),
+    (Action::BulkDelete, "bulk_delete", &["d"]),
+    (Action::BulkTag, "bulk_tag", &["t"]),
+    (Action::BulkContext, "bulk_context", &["@"]),
+    (Action::BulkDue, "bulk_due", &["g"]),
+    (Action::BulkMove, "bulk_move", &["m"]),
+];
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        ACTIONS.iter().find(|(action, _, _)| *action == self).map(|(_, name, _)| *name).unwrap_or("")
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().find(|(_, n, _)| *n == name).map(|(action, _, _)| *action)
+    }
+
+    pub fn scopes(self) -> &'static [Scope] {
+        match self {
+            Action::MoveDown | Action::MoveUp => &[Scope::Normal, Scope::Visual],
+            Action::ExitVisual
+            | Action::ToggleSelection
+            | Action::SelectAll
+            | Action::InvertSelection
+            | Action::SelectByTag
+            | Action::BulkComplete
+            | Action::BulkDelete
+            | Action::BulkTag
+            | Action::BulkContext
+            | Action::BulkDue
+            | Action::BulkMove => &[Scope::Visual],
+            _ => &[Scope::Normal],
+        }
+    }
+}
+
+// One key with its modifiers. Shift is folded into the character ("G", not "shift+g").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Terminals disagree on whether shifted characters carry SHIFT, and send Ctrl+letter lowercase
+        let mut modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) => {
+                let c = if modifiers.contains(KeyModifiers::SHIFT) { c.to_ascii_uppercase() } else { c };
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(if modifiers.contains(KeyModifiers::CONTROL) { c.to_ascii_lowercase() } else { c })
+            }
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+
+    pub fn from_event(key_event: &KeyEvent) -> Self {
+        Self::new(key_event.code, key_event.modifiers)
+    }
+
+    // "x", "G", "ctrl+v", "alt+enter", "space", "pagedown", "+"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (prefix, key) = match spec.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None => match spec.rsplit_once('+') {
+                Some((prefix, key)) if !key.is_empty() => (prefix, key),
+                _ => ("", spec),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in prefix.split('+').filter(|part| !part.is_empty()) {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", part, spec)),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", spec)),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    // How the help screen shows the key
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            _ => "?".to_string(),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("Shift+");
+        }
+        if let KeyCode::Char(c) = self.code {
+            if self.modifiers.contains(KeyModifiers::CONTROL) && c.is_ascii_lowercase() {
+                return label + &c.to_ascii_uppercase().to_string();
+            }
+        }
+        label + &key
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<(Scope, KeyBinding), Action>,
+    keys: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::with_overrides(&[]).0
+    }
+}
+
+impl Keymap {
+    // Apply `(action name, keys)` overrides on top of the defaults. Configured keys win over
+    // defaults; anything unknown or clashing is skipped and reported in the returned warnings.
+    pub fn with_overrides(overrides: &[(String, Vec<String>)]) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut configured: HashMap<Action, Vec<KeyBinding>> = HashMap::new();
+        for (name, specs) in overrides {
+            let Some(action) = Action::from_name(name) else {
+                warnings.push(format!("unknown action '{}'", name));
+                continue;
+            };
+            let mut keys = Vec::new();
+            for spec in specs {
+                match KeyBinding::parse(spec) {
+                    Ok(key) => keys.push(key),
+                    Err(e) => warnings.push(format!("{}: {}", name, e)),
+                }
+            }
+            configured.insert(action, keys);
+        }
+
+        let mut keymap = Keymap { bindings: HashMap::new(), keys: HashMap::new() };
+        // Configured actions claim their keys first, in table order
+        for (action, _, _) in ACTIONS {
+            if let Some(keys) = configured.get(action) {
+                for key in keys {
+                    if let Some(other) = keymap.bind(*action, *key) {
+                        warnings.push(format!("{} and {} are both bound to {}", other.name(), action.name(), key.label()));
+                    }
+                }
+            }
+        }
+        for (action, _, defaults) in ACTIONS {
+            if configured.contains_key(action) {
+                continue;
+            }
+            for spec in defaults.iter() {
+                if let Ok(key) = KeyBinding::parse(spec) {
+                    if let Some(other) = keymap.bind(*action, key) {
+                        warnings.push(format!("{} now runs {} instead of {}", key.label(), other.name(), action.name()));
+                    }
+                }
+            }
+        }
+        (keymap, warnings)
+    }
+
+    // Bind `key` in every scope of `action`; returns the action already holding it, if any
+    fn bind(&mut self, action: Action, key: KeyBinding) -> Option<Action> {
+        let taken = action.scopes().iter().find_map(|scope| self.bindings.get(&(*scope, key)).copied());
+        if taken.is_some() {
+            return taken;
+        }
+        for scope in action.scopes() {
+            self.bindings.insert((*scope, key), action);
+        }
+        self.keys.entry(action).or_default().push(key);
+        None
+    }
+
+    // Read `keybindings.toml` from the config dir; a missing file means the defaults
+    pub fn load() -> (Self, Vec<String>) {
+        let Some(path) = dirs::config_dir().map(|dir| dir.join("paperclip").join(CONFIG_FILE)) else {
+            return (Self::default(), Vec::new());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => match parse_config(&text) {
+                Ok(overrides) => Self::with_overrides(&overrides),
+                Err(e) => (Self::default(), vec![e]),
+            },
+            Err(_) => (Self::default(), Vec::new()),
+        }
+    }
+
+    pub fn action(&self, scope: Scope, key_event: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&(scope, KeyBinding::from_event(key_event))).copied()
+    }
+
+    // The keys of an action as the help screen shows them, e.g. "j/↓"
+    pub fn label(&self, action: Action) -> String {
+        match self.keys.get(&action) {
+            Some(keys) if !keys.is_empty() => keys.iter().map(|key| key.label()).collect::<Vec<_>>().join("/"),
+            _ => "(unbound)".to_string(),
+        }
+    }
+}
+
+// `name = "key"` or `name = ["key", ...]` entries
+pub fn parse_config(text: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+    let mut overrides = Vec::new();
+    for (name, value) in table {
+        let keys = match value {
+            toml::Value::String(key) => vec![key],
+            toml::Value::Array(values) => values.into_iter().filter_map(|value| value.as_str().map(str::to_string)).collect(),
+            _ => return Err(format!("'{}' should be a key or a list of keys", name)),
+        };
+        overrides.push((name, keys));
+    }
+    Ok(overrides)
+}
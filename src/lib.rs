@@ -0,0 +1,28 @@
+// Paperclip's todo model, storage and terminal UI. The `paperclip` binary is a thin wrapper
+// that sets up the terminal and runs the event loop; everything else lives here so scripts
+// and tests can drive the app without a real terminal.
+
+pub mod app;
+pub mod cli;
+pub mod clipboard;
+pub mod colors;
+pub mod completion;
+pub mod config;
+pub mod events;
+pub mod export;
+pub mod external_editor;
+pub mod glyphs;
+pub mod hints;
+pub mod import;
+pub mod keymap;
+pub mod markdown;
+pub mod messages;
+pub mod notifications;
+pub mod pomodoro;
+pub mod search;
+pub mod storage;
+pub mod template;
+pub mod text_buffer;
+pub mod todo;
+pub mod ui;
+mod tests;
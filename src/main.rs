@@ -1,87 +1,187 @@
-mod app;
-mod colors;
-mod events;
-mod storage;
-mod template;
-mod todo;
-mod ui;
-mod tests;
-use app::App;
+use paperclip::app::{self, App};
+use paperclip::{cli, colors, config, events, export, external_editor, glyphs, keymap, notifications, storage, ui};
+use paperclip::messages::{MessageLevel, LONG, STICKY};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+    event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::{
-    io::stdout,
+    io::{stdin, stdout, IsTerminal, Read, Stdout, Write},
     time::{Duration, Instant},
 };
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let mut args = match cli::Args::parse(std::env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("paperclip: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    // Whatever is read here to decide goes to the command ahead of the rest of stdin
+    let mut piped = Vec::new();
+    if !stdin().is_terminal() && args.may_use_piped_stdin() && stdin_may_hold_todos() {
+        if !stdin_ready(Duration::from_millis(200)) {
+            eprintln!("paperclip: reading todos from standard input; end it with Ctrl+D, or start with </dev/null to skip");
+        }
+        stdin().lock().read_to_end(&mut piped)?;
+        args.use_piped_stdin(&piped);
+    }
+    let mut storage = match args.resolve_data_dir(std::env::var_os(cli::DATA_DIR_VAR)) {
+        Some(dir) => storage::Storage::new_with_dir(dir)?,
+        None => storage::Storage::new()?,
+    };
+
+    // `add`, `list` and `done` print their result and exit without touching the terminal
+    if let Some(command) = &args.command {
+        match command.run(&storage, piped.as_slice().chain(stdin().lock())) {
+            Ok(output) => print!("{}", output),
+            Err(e) => {
+                eprintln!("paperclip: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Settings come first; a broken file is reported once the app is up rather than stopping it
+    let (config, config_warnings) = config::Config::load();
 
     // Create app and load data
-    let mut app = App::new();
-    let storage = storage::Storage::new()?;
+    let mut app = App::new(config);
+    // Two copies on the same data would each save over the other's changes
+    if let Err(e) = storage.lock(args.force) {
+        eprintln!("paperclip: {}", e);
+        std::process::exit(1);
+    }
+    // The lock is released when `storage` is dropped; a panic may never get that far
+    let lock_path = storage.lock_path();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = std::fs::remove_file(&lock_path);
+        default_hook(info);
+    }));
+    app.data_dir = Some(storage.data_dir().to_path_buf());
     
     // Load workspace manager (this will handle migration from legacy format)
+    let fresh_install = storage.is_fresh_install();
     match storage.load_workspace_manager() {
-        Ok(workspace_manager) => {
+        Ok((workspace_manager, warnings)) => {
             app.workspace_manager = workspace_manager;
+            app.invalidate_visible_rows();
+            if !warnings.is_empty() {
+                app.data_warning = Some(warnings.join("; "));
+            }
             
             // Refresh available workspaces for selection
             app.available_workspaces = app.workspace_manager.get_all_workspaces()
                 .iter()
-                .map(|ws| ws.name.clone())
+                .map(|ws| ws.id.clone())
                 .collect();
                 
             // Always show welcome screen on startup
             app.mode = app::AppMode::Welcome;
-            
-            // Set first launch status based on existing data for welcome message
-            let is_first_launch = app.available_workspaces.len() <= 1 && 
-                app.workspace_manager.workspace_todos.values()
-                    .map(|todo_list| todo_list.total_count())
-                    .sum::<usize>() == 0;
-            
-            app.is_first_launch = is_first_launch;
-            
-            if is_first_launch {
-                app.set_message("Welcome to Paperclip! Choose an option below to get started.".to_string());
-            } else {
-                // Count total todos for welcome message
-                let total_todos: usize = app.workspace_manager.workspace_todos.values()
-                    .map(|todo_list| todo_list.total_count())
-                    .sum();
-                if total_todos > 0 {
-                    app.set_message(format!("Welcome back! You have {} todos across {} workspaces.", 
-                        total_todos, app.workspace_manager.workspaces.len()));
-                } else {
-                    app.set_message("Welcome back! Ready to organize your todos?".to_string());
-                }
-            }
+            app.set_first_launch(fresh_install);
         }
-        Err(_e) => {
-            // No existing data found - this is definitely a first launch
-            app.mode = app::AppMode::Welcome;
-            app.is_first_launch = true;
-            app.set_message("Welcome to Paperclip! Choose an option below to get started.".to_string());
+        Err(e) => {
+            // Data from a newer version, or a file that can't be read at all: carrying on
+            // with empty data would save over it
+            return Err(format!("Can't open {}: {}", storage.get_workspace_file_path().display(), e).into());
         }
     }
 
-    // Main loop
-    let tick_rate = Duration::from_millis(250);
+    let recovered = app.workspace_manager.recover_interrupted_timers();
+    if recovered > 0 {
+        app.mark_dirty();
+        app.set_message(format!("Recovered {} interrupted timer session{}", recovered, if recovered == 1 { "" } else { "s" }), MessageLevel::Info, LONG);
+    }
+
+    app.true_color = colors::supports_truecolor();
+    // https://no-color.org: any non-empty value turns colors off
+    if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        app.no_color = true;
+    }
+    if glyphs::unicode_unsupported() {
+        app.glyphs = glyphs::Glyphs::ASCII;
+    }
+    // Like search history, display settings just start at their defaults if they can't be read
+    app.display = storage.load_display_settings().unwrap_or_default();
+    app.apply_startup_config();
+    app.apply_launch_options(&args.launch);
+    if !config_warnings.is_empty() {
+        app.set_message(format!("{}: {}", config::CONFIG_FILE, config_warnings.join("; ")), MessageLevel::Warning, LONG);
+    }
+
+    // Search history is optional; a missing or unreadable file just starts empty
+    app.search_history = storage.load_search_history().unwrap_or_default();
+
+    // Custom keys; a bad entry is skipped and reported rather than stopping startup
+    let (keymap, warnings) = keymap::Keymap::load();
+    app.keymap = keymap;
+    if !warnings.is_empty() {
+        app.set_message(format!("{}: {}", keymap::CONFIG_FILE, warnings.join("; ")), MessageLevel::Warning, LONG);
+    }
+
+    // Setup terminal, once everything that can stop startup has been loaded
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // SIGTERM and SIGHUP (a closed terminal) exit through the same save as `q`
+    #[cfg(unix)]
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGHUP] {
+        signal_hook::flag::register(signal, std::sync::Arc::clone(&app.shutdown_signal))?;
+    }
+
+    // Errors end the loop but not the program, so the save below always runs
+    let result = run(&mut terminal, &mut app, &storage);
+
+    // Record running timers now rather than counting the time the app is closed
+    app.stop_all_timers();
+
+    // Save workspace manager before exiting
+    // Changes written to the file meanwhile (by another machine, say) are merged in, not lost
+    if let Err(e) = storage.sync_workspace_manager(&mut app.workspace_manager) {
+        eprintln!("Failed to save workspace data: {}", e);
+    } else if let Err(e) = write_calendar(&app) {
+        eprintln!("Failed to update the calendar file: {}", e);
+    }
+    if let Err(e) = storage.save_search_history(&app.search_history) {
+        eprintln!("Failed to save search history: {}", e);
+    }
+    if let Err(e) = storage.save_display_settings(&app.display) {
+        eprintln!("Failed to save display settings: {}", e);
+    }
+
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+// Draw, handle input and tick until the app quits
+fn run(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App, storage: &storage::Storage) -> Result<(), Box<dyn std::error::Error>> {
+    let tick_rate = app.config.tick_rate;
     let mut last_tick = Instant::now();
     
-    let result = loop {
+    loop {
         // Draw UI
-        terminal.draw(|f| ui::draw(f, &mut app))?;
+        terminal.draw(|f| ui::draw(f, app))?;
 
         // Handle events
         let timeout = tick_rate
@@ -89,35 +189,97 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or_else(|| Duration::from_secs(0));
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if let Err(e) = events::handle_event(&mut app, Event::Key(key)) {
-                    break Err(e.into());
-                }
+            let event = event::read()?;
+            if matches!(event, Event::Key(_) | Event::Paste(_)) {
+                events::handle_event(app, event)?;
             }
         }
 
+        // Open notes in $EDITOR, giving the terminal back to the TUI even if the editor fails
+        if let Some(editor) = app.external_editor_request.take() {
+            let result = external_editor::suspend_terminal()
+                .map_err(|e| format!("Couldn't suspend the terminal: {}", e))
+                .and_then(|_| external_editor::edit_text(&editor, app.notes_buffer.as_str(), "notes.md"));
+            external_editor::resume_terminal(terminal)?;
+            app.finish_external_notes_edit(result);
+        }
+
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
+            app.messages.expire(last_tick);
+            app.tick_pomodoro(chrono::Local::now());
+            if app.config.notifications {
+                notify_due_todos(app);
+            }
+            if std::mem::take(&mut app.bell_pending) {
+                let out = terminal.backend_mut();
+                out.write_all(b"\x07")?;
+                out.flush()?;
+            }
         }
 
+        // Deletions are written at once, other changes once the autosave interval has passed
+        if app.autosave_due(Instant::now()) {
+            let result = storage.sync_workspace_manager(&mut app.workspace_manager);
+            let saved = result.is_ok();
+            app.finish_autosave(Instant::now(), result);
+            if let (true, Err(e)) = (saved, write_calendar(app)) {
+                app.set_message(format!("Couldn't update the calendar file: {}", e), MessageLevel::Error, STICKY);
+            }
+        }
+
+        app.poll_shutdown_signal();
         if app.should_quit {
-            break Ok(());
+            return Ok(());
         }
-    };
+    }
+}
 
-    // Save workspace manager before exiting
-    if let Err(e) = storage.save_workspace_manager(&app.workspace_manager) {
-        eprintln!("Failed to save workspace data: {}", e);
+// A notification per todo that has just fallen due; after a failure (no notification
+// service, say) the rest of this batch are dropped and the error shown in the status bar
+fn notify_due_todos(app: &mut App) {
+    for reminder in app.take_due_reminders(chrono::Local::now()) {
+        if let Err(e) = notifications::send(&reminder) {
+            app.set_message(format!("Couldn't show a notification: {}", e), MessageLevel::Error, STICKY);
+            break;
+        }
     }
+}
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+// The `ics_path` calendar, rewritten after every save so calendar apps stay current
+fn write_calendar(app: &App) -> std::io::Result<()> {
+    match &app.config.ics_path {
+        Some(path) => storage::write_atomically(path, export::to_ics(&app.workspace_manager).as_bytes()),
+        None => Ok(()),
+    }
+}
 
-    result
+// Only a pipe or a redirected file can hold todos piped in without `--stdin`. A socket or
+// device handed down by whatever started the app (`ssh host paperclip` without -t, say) may
+// never close, and reading it would hang before the first frame.
+#[cfg(unix)]
+fn stdin_may_hold_todos() -> bool {
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::FileTypeExt;
+    stdin().as_fd().try_clone_to_owned()
+        .and_then(|fd| std::fs::File::from(fd).metadata())
+        .is_ok_and(|metadata| metadata.file_type().is_fifo() || metadata.is_file())
+}
+
+#[cfg(not(unix))]
+fn stdin_may_hold_todos() -> bool {
+    true
+}
+
+// Whether stdin has input, or has been closed, within `timeout`; a pipe an IDE or a supervisor
+// holds open does neither, so the caller says what it is waiting for
+#[cfg(unix)]
+fn stdin_ready(timeout: Duration) -> bool {
+    let mut poll_fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    unsafe { libc::poll(&mut poll_fd, 1, timeout.as_millis() as libc::c_int) > 0 }
+}
+
+#[cfg(not(unix))]
+fn stdin_ready(_timeout: Duration) -> bool {
+    false
 }
@@ -0,0 +1,107 @@
+use crate::colors::Theme;
+use crate::todo::checklist_state;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+// Style notes as lightweight Markdown for the notes viewer: headings, bullets, checkboxes
+// and **bold**/*italic* spans. Anything else is shown as plain text, one line per source line.
+pub fn render_markdown_lines(text: &str, colors: &Theme) -> Vec<Line<'static>> {
+    text.split('\n').map(|line| render_line(line, colors)).collect()
+}
+
+fn render_line(line: &str, colors: &Theme) -> Line<'static> {
+    let base = Style::default().fg(colors.fg);
+
+    // Headings: `# Title` ... `###### Title`
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+        let color = match level {
+            1 => colors.blue,
+            2 => colors.cyan,
+            _ => colors.purple,
+        };
+        let style = base.fg(color).add_modifier(Modifier::BOLD);
+        return Line::from(inline_spans(line[level..].trim(), style));
+    }
+
+    let content = line.trim_start();
+    let indent = &line[..line.len() - content.len()];
+    let bullet = ["- ", "* ", "+ "].iter().find(|bullet| content.starts_with(*bullet));
+    let Some(bullet) = bullet else {
+        return Line::from(inline_spans(line, base));
+    };
+    let item = &content[bullet.len()..];
+
+    // Checkboxes: `- [ ] todo` and `- [x] done`
+    let checkbox = checklist_state(line).map(|checked| if checked {
+        ("☑ ", colors.green)
+    } else {
+        ("☐ ", colors.yellow)
+    });
+
+    let mut spans = vec![Span::raw(indent.to_string())];
+    match checkbox {
+        Some((glyph, color)) => {
+            let style = base.fg(color);
+            spans.push(Span::styled(glyph, style));
+            spans.extend(inline_spans(item[3..].trim_start(), style));
+        }
+        None => {
+            spans.push(Span::styled("• ", base.fg(colors.blue)));
+            spans.extend(inline_spans(item, base));
+        }
+    }
+    Line::from(spans)
+}
+
+// Split text into spans, styling `**bold**` and `*italic*`; unmatched markers stay as typed
+fn inline_spans(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain_start = 0;
+    let mut position = 0;
+
+    while position < text.len() {
+        let rest = &text[position..];
+        let emphasis = if let Some(after) = rest.strip_prefix("**") {
+            closing_marker(after, "**").map(|end| (2, end, Modifier::BOLD))
+        } else if let Some(after) = rest.strip_prefix('*') {
+            closing_marker(after, "*").map(|end| (1, end, Modifier::ITALIC))
+        } else {
+            None
+        };
+
+        match emphasis {
+            Some((marker_len, end, modifier)) => {
+                if plain_start < position {
+                    spans.push(Span::styled(text[plain_start..position].to_string(), base));
+                }
+                let inner = &rest[marker_len..marker_len + end];
+                spans.push(Span::styled(inner.to_string(), base.add_modifier(modifier)));
+                position += 2 * marker_len + end;
+                plain_start = position;
+            }
+            None => {
+                position += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+
+    if plain_start < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[plain_start..].to_string(), base));
+    }
+    spans
+}
+
+// Length of the emphasised text before `marker`, which must hug non-space text on both sides
+fn closing_marker(text: &str, marker: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) || text.starts_with('*') {
+        return None;
+    }
+    let end = text.find(marker)?;
+    if end == 0 || text[..end].ends_with(char::is_whitespace) {
+        return None;
+    }
+    Some(end)
+}
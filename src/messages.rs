@@ -0,0 +1,64 @@
+// Status bar messages. Each has a level, which picks its color, and usually a time to live,
+// checked on every tick of the main loop. Errors have none: they stay until dismissed with Esc
+// or replaced by another error, showing again once anything shown over them expires.
+
+use std::time::{Duration, Instant};
+
+// Confirmations and view changes
+pub const SHORT: Option<Duration> = Some(Duration::from_secs(4));
+// Hints and warnings, which take longer to read
+pub const LONG: Option<Duration> = Some(Duration::from_secs(10));
+// Until dismissed or replaced
+pub const STICKY: Option<Duration> = None;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub text: String,
+    pub level: MessageLevel,
+    pub expires_at: Option<Instant>,
+}
+
+// Newest last. Only an error can be left underneath the message on show.
+#[derive(Debug, Clone, Default)]
+pub struct MessageQueue {
+    messages: Vec<Message>,
+}
+
+impl MessageQueue {
+    pub fn push(&mut self, text: String, level: MessageLevel, ttl: Option<Duration>, now: Instant) {
+        if level == MessageLevel::Error {
+            self.messages.clear();
+        } else {
+            self.messages.retain(|message| message.level == MessageLevel::Error);
+        }
+        self.messages.push(Message { text, level, expires_at: ttl.map(|ttl| now + ttl) });
+    }
+
+    pub fn current(&self) -> Option<&Message> {
+        self.messages.last()
+    }
+
+    // Drop messages whose time is up; true when that changed what's on show
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let before = self.messages.len();
+        self.messages.retain(|message| message.expires_at.is_none_or(|at| now < at));
+        self.messages.len() != before
+    }
+
+    // Esc: take down the message on show, revealing any error under it
+    pub fn dismiss(&mut self) -> Option<Message> {
+        self.messages.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+}
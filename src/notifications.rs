@@ -0,0 +1,71 @@
+// Desktop notifications while the app runs: once when a todo's due time passes and once
+// `reminder_minutes` before it. Sending needs the `notifications` feature (on by default);
+// without it reminders are still worked out but go nowhere.
+
+use crate::todo::TodoList;
+use chrono::{DateTime, Duration, Local};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReminderKind {
+    Upcoming, // The reminder lead time before the due time
+    Due,
+}
+
+// What a notification says
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub kind: ReminderKind,
+    pub workspace: String,
+    pub description: String,
+    pub due: DateTime<Local>,
+}
+
+impl Reminder {
+    pub fn summary(&self) -> String {
+        match self.kind {
+            ReminderKind::Due => format!("Due now: {}", self.description),
+            ReminderKind::Upcoming => format!("Due at {}: {}", self.due.format("%H:%M"), self.description),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        format!("Paperclip · {}", self.workspace)
+    }
+}
+
+// Pending todos whose due time, or due time less `lead`, fell in (since, now]. A todo crossing
+// both in the same check only gets the due one.
+pub fn due_reminders(todo_list: &TodoList, since: DateTime<Local>, now: DateTime<Local>, lead: Option<Duration>) -> Vec<(u32, ReminderKind)> {
+    let crossed = |at: DateTime<Local>| since < at && at <= now;
+    let mut reminders: Vec<(u32, ReminderKind)> = todo_list.todos.values()
+        .filter(|todo| !todo.is_completed())
+        .filter_map(|todo| {
+            let due = todo.due_date?;
+            if crossed(due) {
+                Some((todo.id, ReminderKind::Due))
+            } else if lead.is_some_and(|lead| crossed(due - lead)) {
+                Some((todo.id, ReminderKind::Upcoming))
+            } else {
+                None
+            }
+        })
+        .collect();
+    reminders.sort_by_key(|(id, _)| *id);
+    reminders
+}
+
+#[cfg(feature = "notifications")]
+pub fn send(reminder: &Reminder) -> Result<(), String> {
+    notify_rust::Notification::new()
+        .appname("Paperclip")
+        .summary(&reminder.summary())
+        .body(&reminder.body())
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "notifications"))]
+pub fn send(_reminder: &Reminder) -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Duration, Local};
+
+// Session lengths, kept together for a future config file
+pub const WORK_MINUTES: i64 = 25;
+pub const BREAK_MINUTES: i64 = 5;
+
+// Label on the time entry a finished work session records
+pub const ENTRY_LABEL: &str = "pomodoro";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+// What a tick changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    None,
+    WorkDone { ended_at: DateTime<Local> }, // Now on a break
+    BreakDone,                             // Back to idle
+}
+
+// A running pomodoro: a work session on one todo, then a break
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pomodoro {
+    pub phase: Phase,
+    pub ends_at: DateTime<Local>,
+    pub workspace_id: String,
+    pub todo_id: u32,
+}
+
+impl Pomodoro {
+    pub fn start(workspace_id: String, todo_id: u32, now: DateTime<Local>) -> Self {
+        Self {
+            phase: Phase::Work,
+            ends_at: now + Duration::minutes(WORK_MINUTES),
+            workspace_id,
+            todo_id,
+        }
+    }
+
+    // Move on once `now` passes the end of the current phase. The break is timed from when
+    // the work session was due to end, so a late tick doesn't stretch it.
+    pub fn tick(&mut self, now: DateTime<Local>) -> Transition {
+        if now < self.ends_at {
+            return Transition::None;
+        }
+        match self.phase {
+            Phase::Work => {
+                let ended_at = self.ends_at;
+                self.phase = Phase::Break;
+                self.ends_at = ended_at + Duration::minutes(BREAK_MINUTES);
+                Transition::WorkDone { ended_at }
+            }
+            Phase::Break => Transition::BreakDone,
+        }
+    }
+
+    // Countdown as "MM:SS"
+    pub fn remaining_formatted(&self, now: DateTime<Local>) -> String {
+        let seconds = (self.ends_at - now).num_seconds().max(0);
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
@@ -0,0 +1,152 @@
+use crate::todo::Todo;
+
+// Scoring weights for fuzzy matching
+const SCORE_MATCH: i64 = 16;
+const BONUS_WORD_START: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 24;
+const MAX_LEADING_PENALTY: i64 = 8;
+const NOTES_PENALTY: i64 = 16; // Hits only in the notes rank below description hits
+
+// Prefix a query with ' for exact (substring) matching instead of fuzzy
+pub fn is_exact_query(query: &str) -> bool {
+    query.starts_with('\'')
+}
+
+// Score how well `query` matches `text` as a case-insensitive subsequence.
+// Higher is better; None means the query doesn't match at all.
+pub fn fuzzy_score(text: &str, query: &str) -> Option<i64> {
+    fuzzy_match(text, query).map(|(score, _)| score)
+}
+
+// Character positions in `text` of the best-scoring match, for highlighting
+pub fn fuzzy_positions(text: &str, query: &str) -> Option<Vec<usize>> {
+    fuzzy_match(text, query).map(|(_, positions)| positions)
+}
+
+fn fuzzy_match(text: &str, query: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    if query.len() > lower.len() {
+        return None;
+    }
+
+    // best[j] = best score with the current query char matched at text position j, and
+    // from[i][j] the position the previous query char was matched at to get it
+    let mut best: Vec<Option<i64>> = vec![None; lower.len()];
+    let mut from: Vec<Vec<usize>> = Vec::with_capacity(query.len());
+    for (i, &q) in query.iter().enumerate() {
+        let mut current = vec![None; lower.len()];
+        let mut previous_at = vec![0; lower.len()];
+        // Max of best[k] + k over k < j - 1 (and that k), so a gap of (j - k - 1) costs one point per char
+        let mut best_gapped: Option<(i64, usize)> = None;
+
+        for j in 0..lower.len() {
+            if j >= 2 {
+                if let Some(previous) = best[j - 2] {
+                    let candidate = previous + (j as i64 - 2);
+                    if best_gapped.is_none_or(|(b, _)| candidate > b) {
+                        best_gapped = Some((candidate, j - 2));
+                    }
+                }
+            }
+            if lower[j] != q {
+                continue;
+            }
+
+            let bonus = SCORE_MATCH + if is_word_start(&chars, j) { BONUS_WORD_START } else { 0 };
+            current[j] = if i == 0 {
+                Some(bonus - (j as i64).min(MAX_LEADING_PENALTY))
+            } else {
+                let consecutive = j.checked_sub(1)
+                    .and_then(|k| best[k].map(|score| (score + BONUS_CONSECUTIVE, k)));
+                let gapped = best_gapped.map(|(b, k)| (b - (j as i64 - 1), k));
+                match (consecutive, gapped) {
+                    (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                    (a, b) => a.or(b),
+                }
+                .map(|(score, k)| {
+                    previous_at[j] = k;
+                    score + bonus
+                })
+            };
+        }
+
+        best = current;
+        from.push(previous_at);
+    }
+
+    // Walk back from the best final position to recover where each query char matched
+    let (mut position, score) = best.into_iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by_key(|&(_, score)| score)?;
+    let mut positions = vec![position; query.len()];
+    for i in (1..query.len()).rev() {
+        position = from[i][position];
+        positions[i - 1] = position;
+    }
+    Some((score, positions))
+}
+
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    if index == 0 {
+        return true;
+    }
+    let (previous, current) = (chars[index - 1], chars[index]);
+    !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+}
+
+// Score a todo against a query across description, tags, contexts, and notes
+pub fn score_todo(todo: &Todo, query: &str) -> Option<i64> {
+    let notes_score = best_notes_line(todo, query).map(|(_, score)| score - NOTES_PENALTY);
+    field_score(todo, query).into_iter().chain(notes_score).max()
+}
+
+// Best score from the description, tags, and contexts (everything but notes)
+fn field_score(todo: &Todo, query: &str) -> Option<i64> {
+    let tags = todo.tags.iter().map(|tag| format!("#{}", tag));
+    let contexts = todo.contexts.iter().map(|ctx| format!("@{}", ctx));
+    
+    std::iter::once(todo.description.clone())
+        .chain(tags)
+        .chain(contexts)
+        .filter_map(|text| text_score(&text, query))
+        .max()
+}
+
+// Fuzzy score, or a flat score for exact queries that contain the text
+fn text_score(text: &str, query: &str) -> Option<i64> {
+    match query.strip_prefix('\'') {
+        Some(exact) => text.to_lowercase().contains(&exact.to_lowercase()).then_some(0),
+        None => fuzzy_score(text, query),
+    }
+}
+
+// Notes are matched line by line so a fuzzy query can't stitch letters across lines
+fn best_notes_line<'a>(todo: &'a Todo, query: &str) -> Option<(&'a str, i64)> {
+    let notes = todo.notes.as_deref()?;
+    notes.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| text_score(line, query).map(|score| (line, score)))
+        .max_by_key(|(_, score)| *score)
+}
+
+// The notes line that made a todo match, when the notes matched better than anything else
+pub fn notes_snippet<'a>(todo: &'a Todo, query: &str) -> Option<&'a str> {
+    let (line, score) = best_notes_line(todo, query)?;
+    match field_score(todo, query) {
+        Some(field) if field >= score - NOTES_PENALTY => None,
+        _ => Some(line),
+    }
+}
@@ -1,12 +1,42 @@
-use crate::todo::{TodoList, WorkspaceManager};
-use serde_json;
+use crate::config::DisplaySettings;
+use crate::todo::{MergeSummary, TodoList, Workspace, WorkspaceManager};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
 use std::fs;
-use std::io;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// Bumped whenever the saved data changes shape, with a step added to MIGRATIONS
+pub const SCHEMA_VERSION: u64 = 1;
+
+// Good copies of the workspace file kept beside it, newest first: `workspaces.json.bak.1` and up
+pub const BACKUP_COUNT: usize = 5;
+
+// The least time between backups, so they reach back further than the last few autosaves
+pub const BACKUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+// Held in the data directory while the app runs, with the owner's PID inside
+pub const LOCK_FILE: &str = "paperclip.lock";
+
+// Held only while the workspace file is read, merged and written, so the app and the
+// command-line commands never save over each other's changes
+pub const WRITE_LOCK_FILE: &str = "paperclip.write.lock";
+
+// How long a save waits for another copy's save to finish before giving up
+const WRITE_LOCK_WAIT: Duration = Duration::from_secs(2);
 
 pub struct Storage {
+    data_dir: PathBuf,
     data_file: PathBuf,
     workspace_file: PathBuf,
+    search_history_file: PathBuf,
+    display_file: PathBuf,
+    synced: RefCell<Option<Vec<u8>>>, // The workspace file as this session last loaded or saved it
+    lock: Option<DataLock>,
+    backup_interval: Duration,
 }
 
 impl Storage {
@@ -14,14 +44,25 @@ impl Storage {
         let data_dir = dirs::data_dir()
             .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find data directory"))?
             .join("paperclip");
-        
+        Self::new_with_dir(data_dir)
+    }
+
+    pub fn new_with_dir(data_dir: PathBuf) -> io::Result<Self> {
         // Create data directory if it doesn't exist
         fs::create_dir_all(&data_dir)?;
         
         let data_file = data_dir.join("todos.json");
         let workspace_file = data_dir.join("workspaces.json");
+        let search_history_file = data_dir.join("search_history.json");
+        let display_file = data_dir.join("display.json");
         
-        Ok(Self { data_file, workspace_file })
+        Ok(Self { data_dir, data_file, workspace_file, search_history_file, display_file, synced: RefCell::new(None), lock: None, backup_interval: BACKUP_INTERVAL })
+    }
+
+    // Nothing saved yet, not even by the single-list version before workspaces. Ask before
+    // loading, since the first save creates the files.
+    pub fn is_fresh_install(&self) -> bool {
+        !self.workspace_file.exists() && !self.data_file.exists()
     }
 
     // Legacy method for backward compatibility
@@ -47,47 +88,241 @@ impl Storage {
     }
     
     // New workspace-based methods
-    pub fn load_workspace_manager(&self) -> io::Result<WorkspaceManager> {
+    // A workspace file that doesn't parse is moved aside, so no later save can overwrite it,
+    // and the newest backup that does parse is loaded instead. The warning says where it went.
+    pub fn load_workspace_manager(&self) -> io::Result<(WorkspaceManager, Vec<String>)> {
+        self.load(true)
+    }
+
+    // The same, for reading only: a damaged file is left where it is
+    pub fn read_workspace_manager(&self) -> io::Result<(WorkspaceManager, Vec<String>)> {
+        self.load(false)
+    }
+
+    fn load(&self, move_damaged: bool) -> io::Result<(WorkspaceManager, Vec<String>)> {
         if !self.workspace_file.exists() {
             // If no workspace file exists, try to migrate from old format
-            return self.migrate_from_legacy();
+            return Ok((self.migrate_from_legacy()?, Vec::new()));
         }
 
-        let content = fs::read_to_string(&self.workspace_file)?;
-        let workspace_manager: WorkspaceManager = serde_json::from_str(&content)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let content = fs::read(&self.workspace_file)?;
+        let error = match parse_workspace_manager(&content) {
+            Ok(workspace_manager) => {
+                self.synced.replace(Some(content));
+                return Ok((workspace_manager, Vec::new()));
+            }
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => e,
+            Err(e) => return Err(e),
+        };
+        let moved = match move_damaged {
+            true => self.move_aside(&error)?,
+            false => format!("{} couldn't be read ({})", self.workspace_file.display(), error),
+        };
         
-        Ok(workspace_manager)
+        for n in 1..=BACKUP_COUNT {
+            if let Ok(workspace_manager) = self.restore_from_backup(n) {
+                return Ok((workspace_manager, vec![format!("{}; loaded backup {} instead", moved, n)]));
+            }
+        }
+        Ok((self.migrate_from_legacy()?, vec![format!("{}; no backup could be read either, so this session started empty", moved)]))
     }
 
+    // Rename a damaged workspace file so no save can overwrite it, saying where it went
+    fn move_aside(&self, error: &io::Error) -> io::Result<String> {
+        let corrupt = with_suffix(&self.workspace_file, &format!(".corrupt-{}", Local::now().format("%Y%m%d-%H%M%S")));
+        fs::rename(&self.workspace_file, &corrupt)?;
+        Ok(format!("{} couldn't be read ({}) and was moved to {}", self.workspace_file.display(), error, corrupt.display()))
+    }
+
+    pub fn restore_from_backup(&self, n: usize) -> io::Result<WorkspaceManager> {
+        read_workspace_manager(&self.backup_path(n))
+    }
+
+    pub fn backup_path(&self, n: usize) -> PathBuf {
+        with_suffix(&self.workspace_file, &format!(".bak.{}", n))
+    }
+
+    pub fn set_backup_interval(&mut self, interval: Duration) {
+        self.backup_interval = interval;
+    }
+
+    // Written to a temporary file and renamed over the old one, so a crash mid-save leaves
+    // the previous version intact. Before a change is written the old file becomes the newest
+    // backup, at most once per backup interval; an unchanged file isn't written at all.
     pub fn save_workspace_manager(&self, workspace_manager: &WorkspaceManager) -> io::Result<()> {
-        let content = serde_json::to_string_pretty(workspace_manager)
+        let envelope = Envelope { version: SCHEMA_VERSION, data: workspace_manager };
+        let content = serde_json::to_string_pretty(&envelope)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
         
-        fs::write(&self.workspace_file, content)?;
+        match fs::read(&self.workspace_file) {
+            Ok(previous) if previous == content.as_bytes() => {}
+            previous => {
+                if let Ok(previous) = previous {
+                    if self.backup_due() {
+                        // The save matters more than the backup, so a failure here doesn't stop it
+                        let _ = self.rotate_backups(&previous);
+                    }
+                }
+                write_atomically(&self.workspace_file, content.as_bytes())?;
+            }
+        }
+        self.synced.replace(Some(content.into_bytes()));
         Ok(())
     }
+
+    // Save, first merging in whatever was written to the file since this session loaded or
+    // last saved it (another machine syncing it, say) so those changes aren't saved over.
+    // Returns a note for the status bar when the file had changed.
+    pub fn sync_workspace_manager(&self, workspace_manager: &mut WorkspaceManager) -> io::Result<Option<String>> {
+        let _write_lock = self.write_lock()?;
+        let note = match self.changed_on_disk()? {
+            None => None,
+            Some(content) => match parse_workspace_manager(&content) {
+                Ok(theirs) => {
+                    let base = match self.synced.borrow().as_deref() {
+                        Some(synced) => parse_workspace_manager(synced)?,
+                        None => WorkspaceManager::new(),
+                    };
+                    Some(merged_note(workspace_manager.merge(&base, theirs)))
+                }
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => Some(self.move_aside(&e)?),
+                Err(e) => return Err(e),
+            },
+        };
+        self.save_workspace_manager(workspace_manager)?;
+        Ok(note)
+    }
+
+    // The workspace file's content, when it isn't what this session last loaded or saved
+    fn changed_on_disk(&self) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(&self.workspace_file) {
+            Ok(content) if self.synced.borrow().as_deref() != Some(content.as_slice()) => Ok(Some(content)),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // True when there's no backup yet or the newest is older than the backup interval
+    fn backup_due(&self) -> bool {
+        fs::metadata(self.backup_path(1))
+            .and_then(|metadata| metadata.modified())
+            .map_or(true, |modified| SystemTime::now().duration_since(modified).map_or(true, |age| age >= self.backup_interval))
+    }
+
+    fn rotate_backups(&self, content: &[u8]) -> io::Result<()> {
+        for n in (1..BACKUP_COUNT).rev() {
+            let backup = self.backup_path(n);
+            if backup.exists() {
+                fs::rename(&backup, self.backup_path(n + 1))?;
+            }
+        }
+        write_atomically(&self.backup_path(1), content)
+    }
     
-    // Migration from legacy single TodoList to WorkspaceManager
-    fn migrate_from_legacy(&self) -> io::Result<WorkspaceManager> {
-        let mut workspace_manager = WorkspaceManager::new();
+    // Search history lives in a small sidecar file next to the workspace data
+    pub fn load_search_history(&self) -> io::Result<Vec<String>> {
+        if !self.search_history_file.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.search_history_file)?;
+        let history: Vec<String> = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
         
-        // Create a default workspace
-        let workspace_id = workspace_manager.create_workspace(
-            "Personal".to_string(), 
-            Some("Migrated from legacy todos".to_string())
-        );
+        Ok(history)
+    }
+
+    pub fn save_search_history(&self, history: &[String]) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(history)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
         
-        // If there's a legacy todos.json file, load it into the default workspace
-        if self.data_file.exists() {
-            if let Ok(legacy_todos) = self.load_todos() {
-                if let Some(todo_list) = workspace_manager.workspace_todos.get_mut(&workspace_id) {
-                    *todo_list = legacy_todos;
+        write_atomically(&self.search_history_file, content.as_bytes())
+    }
+    
+    // Display settings have a sidecar file of their own too
+    pub fn load_display_settings(&self) -> io::Result<DisplaySettings> {
+        if !self.display_file.exists() {
+            return Ok(DisplaySettings::default());
+        }
+
+        let content = fs::read_to_string(&self.display_file)?;
+        serde_json::from_str(&content).map_err(invalid_data)
+    }
+
+    pub fn save_display_settings(&self, settings: &DisplaySettings) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(settings).map_err(invalid_data)?;
+        write_atomically(&self.display_file, content.as_bytes())
+    }
+    
+    // Migration from legacy single TodoList to WorkspaceManager
+    // The legacy todos.json is version 0; without one (or if it can't be read) an empty list
+    // goes through the same step so there is still a default workspace
+    fn migrate_from_legacy(&self) -> io::Result<WorkspaceManager> {
+        let legacy_todos = self.load_todos().unwrap_or_else(|_| TodoList::new());
+        let data = serde_json::to_value(legacy_todos).map_err(invalid_data)?;
+        migrate(0, data)
+    }
+
+    // Claim the data directory for this session; released when the Storage is dropped
+    pub fn lock(&mut self, force: bool) -> io::Result<()> {
+        let lock = DataLock::acquire(&self.data_dir, force).map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => e,
+            kind => io::Error::new(kind, format!("couldn't lock {}: {}", self.lock_path().display(), e)),
+        })?;
+        self.lock = Some(lock);
+        Ok(())
+    }
+
+    // Wait for any other copy's save to finish, then hold off the rest until this is dropped
+    fn write_lock(&self) -> io::Result<DataLock> {
+        let path = self.data_dir.join(WRITE_LOCK_FILE);
+        let started = Instant::now();
+        loop {
+            match DataLock::acquire_at(&path, false) {
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists
+                    && started.elapsed() < WRITE_LOCK_WAIT => {
+                    std::thread::sleep(Duration::from_millis(20));
                 }
+                Err(e) => return Err(io::Error::new(e.kind(), format!("couldn't lock {}: {}", path.display(), e))),
+                Ok(lock) => return Ok(lock),
             }
         }
-        
-        Ok(workspace_manager)
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        self.data_dir.join(LOCK_FILE)
+    }
+
+    // One workspace with its todos in a file of its own, for handing to someone else
+    pub fn export_workspace(workspace_manager: &WorkspaceManager, workspace_id: &str, path: &Path) -> io::Result<()> {
+        let (Some(workspace), Some(todos)) = (
+            workspace_manager.workspaces.get(workspace_id),
+            workspace_manager.workspace_todos.get(workspace_id),
+        ) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("no workspace with id {}", workspace_id)));
+        };
+        let shared = SharedWorkspace { version: SCHEMA_VERSION, workspace, todos };
+        let content = serde_json::to_string_pretty(&shared).map_err(invalid_data)?;
+        write_atomically(path, content.as_bytes())
+    }
+
+    // Read a file written by `export_workspace`; the ids in it are the exporter's, so
+    // `WorkspaceManager::import_workspace` gives it fresh ones
+    pub fn import_workspace(path: &Path) -> io::Result<(Workspace, TodoList)> {
+        let content = fs::read(path)?;
+        let shared: SharedWorkspaceFile = serde_json::from_slice(&content).map_err(invalid_data)?;
+        if shared.version > SCHEMA_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("it was exported by a newer Paperclip (data version {}); update Paperclip to import it", shared.version),
+            ));
+        }
+        Ok((shared.workspace, shared.todos))
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
     }
 
     pub fn get_data_file_path(&self) -> &Path {
@@ -98,3 +333,213 @@ impl Storage {
         &self.workspace_file
     }
 }
+
+// Paths typed by the user or in the config file; `~/` is the home directory
+pub fn expand_path(input: &str) -> PathBuf {
+    match (input.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(input),
+    }
+}
+
+// Advisory lock on a data directory, so a second copy of the app can't silently save over
+// the first. The lock file is removed again when this is dropped.
+pub struct DataLock {
+    path: PathBuf,
+}
+
+impl DataLock {
+    // A lock left by a process that has since exited is stale and taken over; one held by
+    // a running process is only broken with `force`. The lock only ever appears complete,
+    // linked or renamed into place from a file holding our PID, and a takeover is read back
+    // to check another copy starting at the same moment didn't take it instead.
+    pub fn acquire(data_dir: &Path, force: bool) -> io::Result<Self> {
+        let path = data_dir.join(LOCK_FILE);
+        Self::acquire_at(&path, force).map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => match read_lock_pid(&path) {
+                Some(pid) if pid != std::process::id() => held_by(pid, data_dir),
+                _ => e,
+            },
+            _ => e,
+        })
+    }
+
+    fn acquire_at(path: &Path, force: bool) -> io::Result<Self> {
+        let ours = std::process::id();
+        let temp = with_suffix(path, &format!(".{}", ours));
+        fs::write(&temp, format!("{}\n", ours))?;
+        let result = Self::claim(path, &temp, ours, force);
+        let _ = fs::remove_file(&temp);
+        result?;
+        Ok(Self { path: path.to_path_buf() })
+    }
+
+    fn claim(path: &Path, temp: &Path, ours: u32, force: bool) -> io::Result<()> {
+        for _ in 0..3 {
+            match fs::hard_link(temp, path) {
+                Ok(()) => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+            let holder = match fs::read_to_string(path) {
+                Ok(text) => text.trim().parse::<u32>().ok(),
+                // Released in the meantime: try again
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            if let Some(pid) = holder.filter(|&pid| !force && process_running(pid)) {
+                return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("held by {}", pid)));
+            }
+            fs::rename(temp, path)?;
+            return match read_lock_pid(path) {
+                Some(pid) if pid == ours => Ok(()),
+                _ => Err(io::Error::new(io::ErrorKind::AlreadyExists, "taken over by another copy")),
+            };
+        }
+        Err(io::Error::new(io::ErrorKind::AlreadyExists, "the lock keeps changing hands"))
+    }
+}
+
+fn read_lock_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok().and_then(|text| text.trim().parse().ok())
+}
+
+impl Drop for DataLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn held_by(pid: u32, data_dir: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!(
+            "Paperclip is already running (PID {}) with the data in {}. Quit it first, or start with --force if it isn't really running.",
+            pid,
+            data_dir.display()
+        ),
+    )
+}
+
+// Signal 0 checks that a process exists without touching it; EPERM means it does but
+// belongs to someone else. Our own PID in a lock file can only be left over from an earlier run.
+#[cfg(unix)]
+fn process_running(pid: u32) -> bool {
+    match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 && pid as u32 != std::process::id() => {
+            let signalled = unsafe { libc::kill(pid, 0) } == 0;
+            signalled || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+        }
+        _ => false,
+    }
+}
+
+// Without a cheap way to ask, any other PID counts as running and --force breaks the lock
+#[cfg(not(unix))]
+fn process_running(pid: u32) -> bool {
+    pid != std::process::id()
+}
+
+fn read_workspace_manager(path: &Path) -> io::Result<WorkspaceManager> {
+    parse_workspace_manager(&fs::read(path)?)
+}
+
+// Invalid UTF-8 counts as damage like bad JSON does, so both come back as InvalidData.
+// Data from a newer build is Unsupported instead: it's intact, just not ours to read.
+fn parse_workspace_manager(content: &[u8]) -> io::Result<WorkspaceManager> {
+    let value: Value = serde_json::from_slice(content).map_err(invalid_data)?;
+    let (version, data) = open_envelope(value)?;
+    migrate(version, data)
+}
+
+fn merged_note(summary: MergeSummary) -> String {
+    if summary.is_empty() {
+        "Data changed on disk; nothing needed merging".to_string()
+    } else {
+        format!(
+            "Merged changes made elsewhere: {} added, {} updated, {} removed",
+            summary.added, summary.updated, summary.removed
+        )
+    }
+}
+
+// What goes on disk: `{"version": N, "data": {...}}`
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u64,
+    data: &'a WorkspaceManager,
+}
+
+// A shared workspace: `{"version": N, "workspace": {...}, "todos": {...}}`
+#[derive(Serialize)]
+struct SharedWorkspace<'a> {
+    version: u64,
+    workspace: &'a Workspace,
+    todos: &'a TodoList,
+}
+
+#[derive(Deserialize)]
+struct SharedWorkspaceFile {
+    version: u64,
+    workspace: Workspace,
+    todos: TodoList,
+}
+
+// Files written before the envelope existed are version 1 as they stand
+fn open_envelope(value: Value) -> io::Result<(u64, Value)> {
+    match value {
+        Value::Object(mut map) if map.contains_key("version") && map.contains_key("data") => {
+            let version = map["version"].as_u64().ok_or_else(|| invalid_data("the version isn't a number"))?;
+            Ok((version, map.remove("data").unwrap_or_default()))
+        }
+        value => Ok((1, value)),
+    }
+}
+
+type Migration = fn(Value) -> io::Result<Value>;
+
+// MIGRATIONS[n] upgrades version n data to version n + 1
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [single_list_to_workspaces];
+
+// Upgrade saved data one version at a time, then read it
+pub fn migrate(version: u64, data: Value) -> io::Result<WorkspaceManager> {
+    if version > SCHEMA_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("data version {} is from a newer Paperclip (this one reads up to {}); update Paperclip to open it", version, SCHEMA_VERSION),
+        ));
+    }
+    let data = MIGRATIONS[version as usize..].iter().try_fold(data, |data, step| step(data))?;
+    serde_json::from_value(data).map_err(invalid_data)
+}
+
+// 0 -> 1: the single todo list becomes the "Personal" workspace
+fn single_list_to_workspaces(data: Value) -> io::Result<Value> {
+    let todo_list: TodoList = serde_json::from_value(data).map_err(invalid_data)?;
+    let mut workspace_manager = WorkspaceManager::new();
+    let workspace_id = workspace_manager.create_workspace(
+        "Personal".to_string(),
+        Some("Migrated from legacy todos".to_string())
+    );
+    workspace_manager.workspace_todos.insert(workspace_id, todo_list);
+    serde_json::to_value(workspace_manager).map_err(invalid_data)
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+// Readers of `path` see the old content or the new, never half of it
+pub fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let temp = with_suffix(path, ".tmp");
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(&temp, path)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
@@ -1,9 +1,55 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use super::*;
-    use crate::todo::{Todo, TodoList, TodoStatus, RecurrencePattern};
+    use crate::app::{scroll_offset, App, AppMode, ListRow};
+    use crate::todo::{checklist_state, TodoStatus, TimeEntry, parse_duration, parse_outline, toggle_checklist_line, DueDateFilter, FilterSet, GroupBy, MergeSummary, SavedFilter, SearchScope, StatusFilter, Todo, TodoList, RecurrencePattern, SortMode, ViewMode, WorkspaceManager};
     use crate::template::TemplateManager;
-    use chrono::Local;
+    use crate::text_buffer::{display_col, line_col, offset_at, row_col, scroll_window, truncate_to_width, truncate_with, wrap_rows, TextBuffer};
+    use crate::glyphs::Glyphs;
+    use crate::clipboard::base64_encode;
+    use crate::export::markdown_checklist;
+    use crate::external_editor::edit_text;
+    use crate::markdown::render_markdown_lines;
+    use crate::colors::{nearest_ansi, Theme, ThemeName};
+    use crate::config::{Config, DisplaySettings};
+    use crate::storage::Storage;
+    use crate::completion::{candidates, completion_prefix, CompletionKind};
+    use crate::search::{fuzzy_score, is_exact_query, notes_snippet};
+    use crate::ui::{fuzzy_highlight_spans, highlight_spans};
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::Span;
+    use std::path::{Path, PathBuf};
+
+    // A fresh directory under the system temp dir, removed again when the test ends or panics
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("paperclip-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl std::ops::Deref for TempDir {
+        type Target = PathBuf;
+
+        fn deref(&self) -> &PathBuf {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempDir {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
 
     #[test]
     fn test_todo_creation_with_advanced_features() {
@@ -88,4 +134,5310 @@ mod tests {
         
         println!("✅ Serialization works correctly");
     }
+    
+    #[test]
+    fn test_move_todo_remaps_subtree_ids() {
+        let mut manager = WorkspaceManager::new();
+        let personal = manager.create_workspace("Personal".to_string(), None);
+        let work = manager.create_workspace("Work".to_string(), None);
+        
+        // Occupy the low ids in the target so the moved todos can't keep theirs
+        {
+            let work_list = manager.workspace_todos.get_mut(&work).unwrap();
+            work_list.add_todo("Existing one".to_string());
+            work_list.add_todo("Existing two".to_string());
+        }
+        
+        let (root, child, grandchild) = {
+            let list = manager.workspace_todos.get_mut(&personal).unwrap();
+            let _other = list.add_todo("Stays behind".to_string());
+            let root = list.add_todo("Project #work".to_string());
+            let child = list.add_child_todo(root, "Step one".to_string()).unwrap();
+            let grandchild = list.add_child_todo(child, "Sub step".to_string()).unwrap();
+            (root, child, grandchild)
+        };
+        
+        assert!(manager.move_todo(&personal, &work, root));
+        
+        let personal_list = &manager.workspace_todos[&personal];
+        assert_eq!(personal_list.total_count(), 1);
+        assert!(personal_list.get_todo(root).is_none());
+        assert!(personal_list.get_todo(child).is_none());
+        assert!(personal_list.get_todo(grandchild).is_none());
+        
+        let work_list = &manager.workspace_todos[&work];
+        assert_eq!(work_list.total_count(), 5);
+        assert_eq!(work_list.next_id, 6);
+        
+        let new_root = work_list.todos.values().find(|t| t.description == "Project work").unwrap();
+        assert_eq!(new_root.id, 3);
+        assert_eq!(new_root.parent_id, None);
+        assert_eq!(new_root.children.len(), 1);
+        
+        let new_child = work_list.get_todo(new_root.children[0]).unwrap();
+        assert_eq!(new_child.description, "Step one");
+        assert_eq!(new_child.parent_id, Some(new_root.id));
+        
+        let new_grandchild = work_list.get_todo(new_child.children[0]).unwrap();
+        assert_eq!(new_grandchild.description, "Sub step");
+        assert_eq!(new_grandchild.parent_id, Some(new_child.id));
+        
+        // Existing todos in the target workspace are untouched
+        assert_eq!(work_list.get_todo(1).unwrap().description, "Existing one");
+        assert_eq!(work_list.get_todo(2).unwrap().description, "Existing two");
+        
+        // Moving a todo to its own workspace or a missing one is refused
+        assert!(!manager.move_todo(&work, &work, new_root.id));
+        assert!(!manager.move_todo(&personal, "ws_missing", 1));
+        
+        println!("✅ Moving a subtree remaps ids correctly");
+    }
+    
+    #[test]
+    fn test_move_todo_undo_restores_original_workspace() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        
+        let (parent, child) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let parent = list.add_todo("Parent".to_string());
+            let child = list.add_child_todo(parent, "Child".to_string()).unwrap();
+            (parent, child)
+        };
+        
+        app.selected = 0;
+        app.enter_move_to_workspace();
+        assert_eq!(app.available_workspaces, vec![work.clone()]);
+        app.select_from_popup();
+        
+        assert_eq!(app.workspace_manager.workspace_todos[&personal].total_count(), 0);
+        assert_eq!(app.workspace_manager.workspace_todos[&work].total_count(), 2);
+        
+        app.undo();
+        let personal_list = &app.workspace_manager.workspace_todos[&personal];
+        assert_eq!(personal_list.get_todo(parent).unwrap().children, vec![child]);
+        assert_eq!(personal_list.get_todo(child).unwrap().parent_id, Some(parent));
+        assert_eq!(app.workspace_manager.workspace_todos[&work].total_count(), 0);
+        
+        app.redo();
+        assert_eq!(app.workspace_manager.workspace_todos[&personal].total_count(), 0);
+        assert_eq!(app.workspace_manager.workspace_todos[&work].total_count(), 2);
+        
+        println!("✅ Moving a todo can be undone and redone");
+    }
+
+    #[test]
+    fn test_workspace_popups_pick_by_id_when_names_repeat() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let first = app.workspace_manager.create_workspace("Work".to_string(), None);
+        let second = app.workspace_manager.create_workspace("Work".to_string(), None);
+        assert_eq!(app.workspace_manager.find_workspace_id_by_name("Work"), Some(first.clone()));
+        
+        app.get_current_todo_list_mut().unwrap().add_todo("Report".to_string());
+        app.mark_dirty();
+        app.selected = 0;
+        app.enter_move_to_workspace();
+        assert_eq!(app.available_workspaces, vec![first.clone(), second.clone()]);
+        app.popup_selected = 1;
+        app.select_from_popup();
+        assert_eq!(app.workspace_manager.workspace_todos[&first].total_count(), 0);
+        assert_eq!(app.workspace_manager.workspace_todos[&second].total_count(), 1);
+        assert_eq!(app.message(), Some("Moved todo to Work. Press 'u' to undo."));
+        
+        // Switching and deleting from the selection screen hit the highlighted one too
+        app.enter_workspace_selection();
+        app.popup_selected = 3; // second Work (index 0 is Home)
+        app.switch_workspace();
+        assert_eq!(app.workspace_manager.get_current_workspace_id(), Some(second.clone()));
+        
+        app.enter_workspace_selection();
+        app.popup_selected = 2;
+        app.delete_selected_workspace();
+        assert!(!app.workspace_manager.workspaces.contains_key(&first));
+        assert_eq!(app.available_workspaces, vec![personal, second]);
+        
+        println!("✅ Workspace popups resolve the highlighted workspace by id");
+    }
+
+    #[test]
+    fn test_bulk_move_deduplicates_selected_subtrees() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        
+        let (parent, child, other, untouched) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let parent = list.add_todo("Parent".to_string());
+            let child = list.add_child_todo(parent, "Child".to_string()).unwrap();
+            let other = list.add_todo("Other".to_string());
+            let untouched = list.add_todo("Untouched".to_string());
+            (parent, child, other, untouched)
+        };
+        
+        app.enter_visual_mode();
+        app.selected_todos.clear();
+        app.selected_todos.extend([parent, child, other]);
+        app.enter_move_to_workspace();
+        assert_eq!(app.mode, AppMode::MoveToWorkspace);
+        
+        // Esc goes back to the visual selection
+        app.cancel_popup();
+        assert_eq!(app.mode, AppMode::Visual);
+        assert_eq!(app.selected_todos.len(), 3);
+        
+        app.enter_move_to_workspace();
+        app.select_from_popup();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.selected_todos.is_empty());
+        
+        // The child travels with its parent and is not moved twice
+        let work_list = &app.workspace_manager.workspace_todos[&work];
+        assert_eq!(work_list.total_count(), 3);
+        let moved_parent = work_list.todos.values().find(|t| t.description == "Parent").unwrap();
+        assert_eq!(moved_parent.children.len(), 1);
+        let personal_list = &app.workspace_manager.workspace_todos[&personal];
+        assert_eq!(personal_list.total_count(), 1);
+        assert!(personal_list.get_todo(untouched).is_some());
+        
+        // One undo restores the whole batch
+        app.undo();
+        let personal_list = &app.workspace_manager.workspace_todos[&personal];
+        assert_eq!(personal_list.total_count(), 4);
+        assert_eq!(personal_list.get_todo(parent).unwrap().children, vec![child]);
+        assert!(personal_list.get_todo(other).is_some());
+        assert_eq!(app.workspace_manager.workspace_todos[&work].total_count(), 0);
+        
+        println!("✅ Bulk move deduplicates subtrees and undoes as one step");
+    }
+
+    #[test]
+    fn test_workspace_view_state_restored_on_switch() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        
+        let (first, second, third) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let first = list.add_todo("First".to_string());
+            let second = list.add_todo("Second".to_string());
+            let third = list.add_todo("Third".to_string());
+            (first, second, third)
+        };
+        
+        app.filters.status = Some(StatusFilter::Pending);
+        app.selected = 1;
+        app.cycle_sort_mode();
+        assert_eq!(app.get_current_todo_list().unwrap().sort_mode, SortMode::DueDate);
+        assert_eq!(app.get_selected_todo_id(), Some(second));
+        
+        // Switch to Work (index 0 is the Home option), which starts fresh
+        app.available_workspaces = vec![personal.clone(), work.clone()];
+        app.popup_selected = 2;
+        app.switch_workspace();
+        assert!(app.filters.is_empty());
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.get_current_todo_list().unwrap().sort_mode, SortMode::Priority);
+        
+        // Back to Personal restores view, sort, and selected todo
+        app.available_workspaces = vec![personal.clone(), work.clone()];
+        app.popup_selected = 1;
+        app.switch_workspace();
+        assert_eq!(app.filters.status, Some(StatusFilter::Pending));
+        assert_eq!(app.get_selected_todo_id(), Some(second));
+        assert_eq!(app.get_current_todo_list().unwrap().sort_mode, SortMode::DueDate);
+        
+        // A deleted todo falls back to the nearest position
+        app.save_view_state();
+        app.workspace_manager.workspace_todos.get_mut(&personal).unwrap().remove_todo(second);
+        app.restore_view_state();
+        assert_eq!(app.get_selected_todo_id(), Some(third));
+        assert!(app.get_current_todo_list().unwrap().get_todo(first).is_some());
+        
+        println!("✅ Workspace view state is remembered across switches");
+    }
+
+    #[test]
+    fn test_switch_to_previous_workspace() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        
+        // Nothing to flip back to yet
+        app.switch_to_previous_workspace();
+        assert_eq!(app.workspace_manager.get_current_workspace_id(), Some(personal.clone()));
+        
+        assert!(app.workspace_manager.switch_workspace_by_name("Work"));
+        app.switch_to_previous_workspace();
+        assert_eq!(app.workspace_manager.get_current_workspace_id(), Some(personal.clone()));
+        assert_eq!(app.workspace_manager.previous_workspace, Some(work.clone()));
+        
+        app.switch_to_previous_workspace();
+        assert_eq!(app.workspace_manager.get_current_workspace_id(), Some(work.clone()));
+        
+        // Switching to the current workspace does not clobber the previous one
+        assert!(app.workspace_manager.switch_workspace(&work));
+        assert_eq!(app.workspace_manager.previous_workspace, Some(personal.clone()));
+        
+        // Deleting the previous workspace forgets it
+        assert!(app.workspace_manager.delete_workspace(&personal));
+        assert_eq!(app.workspace_manager.previous_workspace, None);
+        app.switch_to_previous_workspace();
+        assert_eq!(app.workspace_manager.get_current_workspace_id(), Some(work));
+        
+        println!("✅ Previous workspace quick-switch works");
+    }
+
+    #[test]
+    fn test_workspace_reordering_edge_cases() {
+        let mut manager = WorkspaceManager::new();
+        let personal = manager.create_workspace("Personal".to_string(), None);
+        let work = manager.create_workspace("Work".to_string(), None);
+        let home = manager.create_workspace("Home".to_string(), None);
+        
+        let names = |manager: &WorkspaceManager| -> Vec<String> {
+            manager.get_all_workspaces().iter().map(|ws| ws.name.clone()).collect()
+        };
+        assert_eq!(names(&manager), vec!["Personal", "Work", "Home"]);
+        
+        // First item can't move up, last item can't move down
+        assert!(!manager.move_workspace(&personal, true));
+        assert!(!manager.move_workspace(&home, false));
+        assert!(!manager.move_workspace("missing", true));
+        assert_eq!(names(&manager), vec!["Personal", "Work", "Home"]);
+        
+        assert!(manager.move_workspace(&home, true));
+        assert_eq!(names(&manager), vec!["Personal", "Home", "Work"]);
+        assert!(manager.move_workspace(&personal, false));
+        assert_eq!(names(&manager), vec!["Home", "Personal", "Work"]);
+        assert_eq!(manager.workspace_number(&work), Some(3));
+        assert_eq!(manager.workspace_id_at(1), Some(home.clone()));
+        assert_eq!(manager.workspace_id_at(0), None);
+        assert_eq!(manager.workspace_id_at(4), None);
+        
+        // Order survives a save/load round-trip
+        let json = serde_json::to_string(&manager).unwrap();
+        let loaded: WorkspaceManager = serde_json::from_str(&json).unwrap();
+        assert_eq!(names(&loaded), vec!["Home", "Personal", "Work"]);
+        
+        println!("✅ Workspace reordering handles edge cases and persists");
+    }
+    
+    #[test]
+    fn test_switch_to_workspace_by_number() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        
+        app.switch_to_workspace_number(2);
+        assert_eq!(app.workspace_manager.get_current_workspace_id(), Some(work.clone()));
+        assert_eq!(app.get_current_workspace_label(), "2:Work");
+        
+        // Out of range numbers leave the current workspace alone
+        app.switch_to_workspace_number(9);
+        assert_eq!(app.get_current_workspace_label(), "2:Work");
+        
+        // Reordering from the selection screen changes the numbers
+        app.enter_workspace_selection();
+        app.popup_selected = 2; // Work (index 0 is Home)
+        app.move_selected_workspace(true);
+        assert_eq!(app.available_workspaces, vec![work, personal]);
+        assert_eq!(app.popup_selected, 1);
+        assert_eq!(app.get_current_workspace_label(), "1:Work");
+        
+        println!("✅ Alt+N workspace switching follows workspace order");
+    }
+
+    #[test]
+    fn test_global_due_view_routes_to_owning_workspace() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        let yesterday = chrono::Local::now() - chrono::Duration::days(1);
+        
+        // Same numeric id (1) in both workspaces, only the Work one is overdue
+        let personal_id = app.workspace_manager.workspace_todos.get_mut(&personal).unwrap()
+            .add_todo("Personal chore".to_string());
+        let work_list = app.workspace_manager.workspace_todos.get_mut(&work).unwrap();
+        let work_id = work_list.add_todo("Late report".to_string());
+        work_list.add_todo("Not due".to_string());
+        work_list.get_todo_mut(work_id).unwrap().due_date = Some(yesterday);
+        assert_eq!(personal_id, work_id);
+        
+        let due = app.workspace_manager.collect_due(DueDateFilter::Overdue);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, work);
+        
+        app.cycle_global_due_view();
+        assert_eq!(app.view_mode, ViewMode::GlobalDue(DueDateFilter::Overdue));
+        assert_eq!(app.get_selected_workspace_id(), Some(work.clone()));
+        
+        app.toggle_todo_complete();
+        assert!(app.workspace_manager.workspace_todos[&work].get_todo(work_id).unwrap().is_completed());
+        assert!(!app.workspace_manager.workspace_todos[&personal].get_todo(personal_id).unwrap().is_completed());
+        
+        app.cycle_global_due_view();
+        assert_eq!(app.view_mode, ViewMode::GlobalDue(DueDateFilter::Today));
+        app.cycle_global_due_view();
+        assert_eq!(app.view_mode, ViewMode::List);
+        
+        println!("✅ Global due view routes actions to the owning workspace");
+    }
+
+    #[test]
+    fn test_search_actions_apply_to_owning_workspace() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        
+        // Both workspaces contain a todo with id 1
+        let personal_id = app.workspace_manager.workspace_todos.get_mut(&personal).unwrap()
+            .add_todo("Buy milk".to_string());
+        let work_id = app.workspace_manager.workspace_todos.get_mut(&work).unwrap()
+            .add_todo("Quarterly report".to_string());
+        assert_eq!(personal_id, 1);
+        assert_eq!(work_id, 1);
+        
+        // Searching all workspaces from Personal finds the Work todo only
+        app.search_scope = SearchScope::All;
+        app.filters.query = Some("report".to_string());
+        app.selected = 0;
+        assert_eq!(app.get_selected_workspace_id(), Some(work.clone()));
+        
+        app.toggle_todo_complete();
+        app.increase_priority();
+        assert!(app.workspace_manager.workspace_todos[&work].get_todo(1).unwrap().is_completed());
+        assert_eq!(app.workspace_manager.workspace_todos[&work].get_todo(1).unwrap().priority, 1);
+        let milk = app.workspace_manager.workspace_todos[&personal].get_todo(1).unwrap();
+        assert!(!milk.is_completed());
+        assert_eq!(milk.priority, 0);
+        
+        // Editing saves into the owning workspace
+        app.enter_edit_mode();
+        app.edit_buffer.set("Quarterly report draft".to_string());
+        app.save_todo_edit();
+        assert_eq!(app.workspace_manager.workspace_todos[&work].get_todo(1).unwrap().description, "Quarterly report draft");
+        assert_eq!(app.workspace_manager.workspace_todos[&personal].get_todo(1).unwrap().description, "Buy milk");
+        
+        // Deleting removes the match, not the todo sharing its id
+        app.delete_selected_todo();
+        assert!(app.workspace_manager.workspace_todos[&work].get_todo(1).is_none());
+        assert!(app.workspace_manager.workspace_todos[&personal].get_todo(1).is_some());
+        
+        println!("✅ Search actions apply to the workspace that owns the todo");
+    }
+
+    #[test]
+    fn test_search_scope_toggle() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        app.workspace_manager.workspace_todos.get_mut(&personal).unwrap().add_todo("Write notes".to_string());
+        app.workspace_manager.workspace_todos.get_mut(&work).unwrap().add_todo("Write report".to_string());
+        
+        // Defaults to the current workspace
+        app.filters.query = Some("write".to_string());
+        assert_eq!(app.search_scope, SearchScope::Workspace);
+        assert_eq!(app.get_visible_todos().len(), 1);
+        assert!(!app.is_cross_workspace_view());
+        
+        app.toggle_search_scope();
+        assert_eq!(app.search_scope, SearchScope::All);
+        let rows = app.get_visible_todos();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().any(|(ws_id, _, _)| *ws_id == work));
+        assert!(app.is_cross_workspace_view());
+        
+        // The scope sticks for later searches
+        app.enter_search_mode();
+        app.search_buffer.set("report".to_string());
+        app.submit_search();
+        assert_eq!(app.search_scope, SearchScope::All);
+        assert_eq!(app.get_selected_workspace_id(), Some(work));
+        
+        println!("✅ Search scope toggles between workspace and all");
+    }
+
+    #[test]
+    fn test_live_search_and_cancel_restores_view() {
+        let mut app = App::new(Config::default());
+        {
+            let list = app.get_current_todo_list_mut().unwrap();
+            list.add_todo("Buy milk".to_string());
+            list.add_todo("Buy bread".to_string());
+            list.add_todo("Call mom".to_string());
+        }
+        
+        app.filters.status = Some(StatusFilter::Pending);
+        app.selected = 2;
+        app.enter_search_mode();
+        
+        // Results narrow as the query is typed, and selection stays in range
+        for c in "buy".chars() {
+            app.add_char_to_search(c);
+        }
+        assert_eq!(app.get_visible_todos().len(), 2);
+        assert_eq!(app.selected, 1);
+        app.add_char_to_search(' ');
+        app.add_char_to_search('m');
+        assert_eq!(app.get_visible_todos().len(), 1);
+        assert_eq!(app.selected, 0);
+        
+        // Esc brings back the view that was active before searching
+        app.cancel_search();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.filters.status, Some(StatusFilter::Pending));
+        assert_eq!(app.selected, 2);
+        assert_eq!(app.get_visible_todos().len(), 3);
+        
+        // Enter commits the live query
+        app.enter_search_mode();
+        for c in "call".chars() {
+            app.add_char_to_search(c);
+        }
+        app.submit_search();
+        assert_eq!(app.filters.query.as_deref(), Some("call"));
+        assert_eq!(app.get_visible_todos().len(), 1);
+        
+        println!("✅ Live search filters while typing and Esc restores the view");
+    }
+
+    #[test]
+    fn test_fuzzy_search_scoring() {
+        // Word-initial abbreviations beat scattered matches
+        let fix_bug = fuzzy_score("Fix Bug", "fb").unwrap();
+        let feedback = fuzzy_score("feedback", "fb").unwrap();
+        assert!(fix_bug > feedback);
+        
+        // Typos with dropped letters still match, missing letters don't
+        assert!(fuzzy_score("Clean up workspace", "wrkspce").is_some());
+        assert!(fuzzy_score("scaffolding", "fb").is_none());
+        
+        // Contiguous matches beat spread-out ones
+        assert!(fuzzy_score("report", "rep").unwrap() > fuzzy_score("r e p", "rep").unwrap());
+        
+        let mut list = TodoList::new();
+        let scaffold = list.add_todo("Update scaffolding".to_string());
+        let fix = list.add_todo("Fix Bug in parser".to_string());
+        let feedback_id = list.add_todo("Read feedback".to_string());
+        let tagged = list.add_todo("Something #frontend-bugs".to_string());
+        
+        let results: Vec<u32> = list.search_todos("fb").iter().map(|(todo, _)| todo.id).collect();
+        assert_eq!(results[0], fix);
+        assert!(results.contains(&feedback_id));
+        assert!(results.contains(&tagged));
+        assert!(!results.contains(&scaffold));
+        
+        // Ties are broken by priority
+        list.get_todo_mut(feedback_id).unwrap().priority = 3;
+        let dup = list.add_todo("Read feedback".to_string());
+        let results: Vec<u32> = list.search_todos("read feedback").iter().map(|(todo, _)| todo.id).collect();
+        assert_eq!(results, vec![feedback_id, dup]);
+        
+        // A leading ' switches to exact substring matching
+        assert!(is_exact_query("'fix"));
+        let exact: Vec<u32> = list.search_todos("'fb").iter().map(|(todo, _)| todo.id).collect();
+        assert!(exact.is_empty());
+        let exact: Vec<u32> = list.search_todos("'bug in").iter().map(|(todo, _)| todo.id).collect();
+        assert_eq!(exact, vec![fix]);
+        
+        println!("✅ Fuzzy search ranks obvious matches first");
+    }
+
+    #[test]
+    fn test_search_matches_notes() {
+        let mut list = TodoList::new();
+        let notes_only = list.add_todo("Plan trip".to_string());
+        list.get_todo_mut(notes_only).unwrap().set_notes(Some("Flights\nBook the HOTEL near the station".to_string()));
+        let description_only = list.add_todo("Call hotel about parking".to_string());
+        let both = list.add_todo("Hotel invoice".to_string());
+        list.get_todo_mut(both).unwrap().set_notes(Some("Forward the hotel invoice to accounting".to_string()));
+        
+        let results: Vec<u32> = list.search_todos("'hotel").iter().map(|(todo, _)| todo.id).collect();
+        assert_eq!(results.len(), 3);
+        let results: Vec<u32> = list.search_todos("hotel").iter().map(|(todo, _)| todo.id).collect();
+        assert_eq!(results.len(), 3);
+        // Description hits rank above notes-only hits
+        assert_eq!(*results.last().unwrap(), notes_only);
+        
+        // Only the notes-only hit gets a snippet, and it's the matching line
+        assert_eq!(notes_snippet(list.get_todo(notes_only).unwrap(), "hotel"), Some("Book the HOTEL near the station"));
+        assert_eq!(notes_snippet(list.get_todo(description_only).unwrap(), "hotel"), None);
+        assert_eq!(notes_snippet(list.get_todo(both).unwrap(), "hotel"), None);
+        
+        // Fuzzy matches don't stitch letters together across note lines
+        let mut list = TodoList::new();
+        let id = list.add_todo("Groceries".to_string());
+        list.get_todo_mut(id).unwrap().set_notes(Some("ab\ncd".to_string()));
+        assert!(list.search_todos("abcd").is_empty());
+        assert_eq!(list.search_todos("cd").len(), 1);
+        
+        println!("✅ Search matches notes line by line");
+    }
+
+    #[test]
+    fn test_highlight_spans() {
+        let base = Style::default();
+        let hl = Style::default().add_modifier(Modifier::BOLD);
+        let render = |spans: Vec<Span>| -> Vec<(String, bool)> {
+            spans.iter().map(|span| (span.content.to_string(), span.style == hl)).collect()
+        };
+        
+        // Every occurrence, case-insensitively
+        assert_eq!(render(highlight_spans("Fix bug, then FIX docs", "fix", base, hl)), vec![
+            ("Fix".to_string(), true),
+            (" bug, then ".to_string(), false),
+            ("FIX".to_string(), true),
+            (" docs".to_string(), false),
+        ]);
+        
+        // Empty query or no match leaves the text alone
+        assert_eq!(render(highlight_spans("Plain", "", base, hl)), vec![("Plain".to_string(), false)]);
+        assert_eq!(render(highlight_spans("Plain", "xyz", base, hl)), vec![("Plain".to_string(), false)]);
+        
+        // Multi-byte characters around and inside the match
+        assert_eq!(render(highlight_spans("Café résumé ☕", "RÉSUMÉ", base, hl)), vec![
+            ("Café ".to_string(), false),
+            ("résumé".to_string(), true),
+            (" ☕".to_string(), false),
+        ]);
+        assert_eq!(render(highlight_spans("☕☕", "☕", base, hl)), vec![
+            ("☕".to_string(), true),
+            ("☕".to_string(), true),
+        ]);
+        
+        // Tag spans highlight the matching tag text
+        assert_eq!(render(highlight_spans(" #urgent", "#urg", base, hl)), vec![
+            (" ".to_string(), false),
+            ("#urg".to_string(), true),
+            ("ent".to_string(), false),
+        ]);
+        
+        // Fuzzy queries highlight the letters the matcher picked, preferring word starts
+        assert_eq!(crate::search::fuzzy_positions("Buy milk", "bmk"), Some(vec![0, 4, 7]));
+        assert_eq!(crate::search::fuzzy_positions("Buy milk", "xyz"), None);
+        assert_eq!(render(fuzzy_highlight_spans("Buy milk", "bmi", base, hl)), vec![
+            ("B".to_string(), true),
+            ("uy ".to_string(), false),
+            ("mi".to_string(), true),
+            ("lk".to_string(), false),
+        ]);
+        assert_eq!(render(fuzzy_highlight_spans("Café ☕", "é☕", base, hl)), vec![
+            ("Caf".to_string(), false),
+            ("é".to_string(), true),
+            (" ".to_string(), false),
+            ("☕".to_string(), true),
+        ]);
+        assert_eq!(render(fuzzy_highlight_spans("Plain", "xyz", base, hl)), vec![("Plain".to_string(), false)]);
+        
+        println!("✅ Highlight spans split matches on character boundaries");
+    }
+
+    #[test]
+    fn test_search_history_recall() {
+        let mut app = App::new(Config::default());
+        
+        for query in ["milk", "milk", "", "report", "bread"] {
+            app.enter_search_mode();
+            app.search_buffer.set(query.to_string());
+            app.submit_search();
+        }
+        // Consecutive duplicates collapse and empty submissions are skipped
+        assert_eq!(app.search_history, vec!["milk", "report", "bread"]);
+        
+        app.enter_search_mode();
+        app.add_char_to_search('x');
+        app.search_history_previous();
+        assert_eq!(app.search_buffer.content, "bread");
+        assert_eq!(app.search_buffer.cursor, "bread".len());
+        app.search_history_previous();
+        app.search_history_previous();
+        app.search_history_previous(); // Stops at the oldest entry
+        assert_eq!(app.search_buffer.content, "milk");
+        
+        app.search_history_next();
+        assert_eq!(app.search_buffer.content, "report");
+        app.search_history_next();
+        app.search_history_next(); // Past the newest entry restores the draft
+        assert_eq!(app.search_buffer.content, "x");
+        assert_eq!(app.search_history_index, None);
+        
+        // History is capped
+        for i in 0..60 {
+            app.enter_search_mode();
+            app.search_buffer.set(format!("query {}", i));
+            app.submit_search();
+        }
+        assert_eq!(app.search_history.len(), 50);
+        assert_eq!(app.search_history.last().unwrap(), "query 59");
+        
+        println!("✅ Search history recalls earlier queries");
+    }
+
+    #[test]
+    fn test_search_match_navigation() {
+        let mut app = App::new(Config::default());
+        {
+            let list = app.get_current_todo_list_mut().unwrap();
+            list.add_todo("Buy milk".to_string());
+            list.add_todo("Call mom".to_string());
+            let tagged = list.add_todo("Weekly review".to_string());
+            list.get_todo_mut(tagged).unwrap().tags.insert("milk".to_string());
+            list.add_todo("Water plants".to_string());
+        }
+        
+        app.jump_to_search_match(true);
+        assert_eq!(app.message(), Some("No previous search"));
+        
+        app.enter_search_mode();
+        app.search_buffer.set("'milk".to_string());
+        app.submit_search();
+        assert_eq!(app.get_visible_todos().len(), 2);
+        
+        // Back to the All view, keeping the query for n/N
+        app.clear_filters();
+        assert!(app.filters.is_empty());
+        assert_eq!(app.selected, 0);
+        
+        app.jump_to_search_match(true);
+        assert_eq!(app.selected, 2); // Matched through its tag
+        app.jump_to_search_match(true);
+        assert_eq!(app.selected, 0);
+        assert!(app.message().unwrap().starts_with("Search wrapped"));
+        app.jump_to_search_match(false);
+        assert_eq!(app.selected, 2);
+        
+        // Second Esc forgets the query
+        app.clear_filters();
+        assert_eq!(app.last_search, None);
+        app.jump_to_search_match(true);
+        assert_eq!(app.message(), Some("No previous search"));
+        
+        app.last_search = Some("'nothing here".to_string());
+        app.jump_to_search_match(true);
+        assert_eq!(app.message(), Some("No matches for: 'nothing here"));
+        
+        println!("✅ n/N jump between search matches");
+    }
+
+    #[test]
+    fn test_combined_filters() {
+        let mut app = App::new(Config::default());
+        let tomorrow = chrono::Local::now() + chrono::Duration::days(1);
+        {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let report = list.add_todo("Send report #urgent @office".to_string());
+            list.get_todo_mut(report).unwrap().due_date = Some(tomorrow);
+            let invoice = list.add_todo("Pay invoice #urgent".to_string());
+            list.get_todo_mut(invoice).unwrap().due_date = Some(tomorrow);
+            list.get_todo_mut(invoice).unwrap().complete();
+            list.add_todo("Book flights #urgent @home".to_string());
+            let slides = list.add_todo("Prepare slides @office".to_string());
+            list.get_todo_mut(slides).unwrap().due_date = Some(tomorrow);
+        }
+        let descriptions = |app: &App| -> Vec<String> {
+            let mut names: Vec<String> = app.get_visible_todos().iter()
+                .map(|(_, todo, _)| todo.description.clone())
+                .collect();
+            names.sort();
+            names
+        };
+        
+        // Status and tag stack instead of replacing each other
+        app.cycle_view_mode();
+        app.available_tags = vec!["urgent".to_string()];
+        app.mode = AppMode::TagSelection;
+        app.popup_selected = 0;
+        app.select_from_popup();
+        assert_eq!(app.filters.status, Some(StatusFilter::Pending));
+        assert_eq!(descriptions(&app), vec!["Book flights urgent home", "Send report urgent office"]);
+        
+        // Adding a due date filter narrows further
+        for _ in 0..4 {
+            app.cycle_due_date_filter();
+        }
+        assert_eq!(app.filters.due, Some(DueDateFilter::ThisWeek));
+        assert_eq!(descriptions(&app), vec!["Send report urgent office"]);
+        assert_eq!(app.get_view_name(), "Pending · #urgent · This Week");
+        
+        // Re-selecting the tag clears only that component
+        app.mode = AppMode::TagSelection;
+        app.select_from_popup();
+        assert_eq!(app.filters.tag, None);
+        assert_eq!(descriptions(&app), vec!["Prepare slides office", "Send report urgent office"]);
+        
+        // Context plus a text query
+        app.available_contexts = vec!["office".to_string()];
+        app.mode = AppMode::ContextSelection;
+        app.popup_selected = 0;
+        app.select_from_popup();
+        app.enter_search_mode();
+        app.search_buffer.set("slides".to_string());
+        app.submit_search();
+        assert_eq!(descriptions(&app), vec!["Prepare slides office"]);
+        assert_eq!(app.get_view_name(), "Pending · @office · This Week · \"slides\"");
+        
+        // The due rotation ends with no due filter; other components stay
+        app.cycle_due_date_filter();
+        app.cycle_due_date_filter();
+        assert_eq!(app.filters.due, None);
+        assert_eq!(app.filters.context.as_deref(), Some("office"));
+        
+        // Esc clears everything at once
+        app.clear_filters();
+        assert_eq!(app.filters, FilterSet::default());
+        assert_eq!(app.get_view_name(), "All Todos");
+        assert_eq!(app.get_visible_todos().len(), 4);
+        
+        println!("✅ Filters combine and clear independently");
+    }
+
+    #[test]
+    fn test_exclusion_filters() {
+        let mut app = App::new(Config::default());
+        {
+            let list = app.get_current_todo_list_mut().unwrap();
+            list.add_todo("Email landlord #home #admin @waiting".to_string());
+            list.add_todo("File taxes #admin @desk".to_string());
+            list.add_todo("Water plants #home".to_string());
+            list.add_todo("Plan trip".to_string());
+        }
+        
+        let list = app.get_current_todo_list().unwrap();
+        // Excluding one tag drops todos carrying it even alongside other tags
+        let without_home: Vec<&str> = list.filter_excluding_tag("home").iter()
+            .map(|(todo, _)| todo.description.as_str())
+            .collect();
+        assert_eq!(without_home.len(), 2);
+        assert!(without_home.iter().all(|d| !d.contains("landlord") && !d.contains("plants")));
+        assert_eq!(list.filter_excluding_context("waiting").len(), 3);
+        
+        // `!` in the tag popup applies the exclusion directly
+        app.enter_tag_selection();
+        app.popup_selected = app.available_tags.iter().position(|t| t == "admin").unwrap();
+        app.exclude_from_popup();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.filters.exclude_tag);
+        assert_eq!(app.get_visible_todos().len(), 2);
+        assert_eq!(app.get_view_name(), "not #admin");
+        
+        // Space then Enter does the same for contexts, stacking with the tag exclusion
+        app.enter_context_selection();
+        app.popup_selected = app.available_contexts.iter().position(|c| c == "waiting").unwrap();
+        app.toggle_popup_exclude();
+        app.select_from_popup();
+        assert_eq!(app.get_view_name(), "not #admin · not @waiting");
+        assert_eq!(app.get_visible_todos().len(), 2);
+        
+        // Including the same tag replaces the exclusion instead of clearing it
+        app.enter_tag_selection();
+        app.popup_selected = app.available_tags.iter().position(|t| t == "admin").unwrap();
+        app.select_from_popup();
+        assert!(!app.filters.exclude_tag);
+        assert_eq!(app.get_visible_todos().len(), 1); // File taxes: #admin without @waiting
+        
+        app.clear_filters();
+        assert!(app.filters.is_empty());
+        assert_eq!(app.get_visible_todos().len(), 4);
+        
+        println!("✅ Tag and context exclusion filters");
+    }
+
+    #[test]
+    fn test_saved_filters() {
+        let mut app = App::new(Config::default());
+        {
+            let list = app.get_current_todo_list_mut().unwrap();
+            list.add_todo("Fix bug #urgent @work".to_string());
+            list.add_todo("Call plumber #urgent @home".to_string());
+            list.add_todo("Read book @home".to_string());
+        }
+        
+        // Save the current filters and sort under a name
+        app.filters.status = Some(StatusFilter::Pending);
+        app.filters.tag = Some("urgent".to_string());
+        app.filters.context = Some("home".to_string());
+        app.get_current_todo_list_mut().unwrap().sort_mode = SortMode::Alphabetical;
+        app.group_by = GroupBy::Context;
+        app.enter_saved_filter_selection();
+        app.enter_save_filter_mode();
+        app.input_buffer.set("Urgent chores".to_string());
+        app.submit_save_filter();
+        assert_eq!(app.mode, AppMode::SavedFilterSelection);
+        assert_eq!(app.workspace_manager.saved_filters.len(), 1);
+        
+        // Saved filters survive a round trip through the workspace file format
+        let json = serde_json::to_string(&app.workspace_manager).unwrap();
+        let restored: WorkspaceManager = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.saved_filters, app.workspace_manager.saved_filters);
+        
+        // Filters saved before views were remembered still load, into the plain list
+        let old = r#"{"name":"Old","filters":{"tag":"urgent"},"sort_mode":"Priority"}"#;
+        let old: SavedFilter = serde_json::from_str(old).unwrap();
+        assert_eq!((old.view_mode, old.group_by, old.search_scope), (ViewMode::List, GroupBy::None, SearchScope::Workspace));
+        
+        // Applying rebuilds filters, sort and view from scratch
+        app.cancel_popup();
+        app.clear_filters();
+        app.get_current_todo_list_mut().unwrap().sort_mode = SortMode::Priority;
+        app.group_by = GroupBy::None;
+        app.view_mode = ViewMode::Agenda;
+        app.enter_saved_filter_selection();
+        app.select_from_popup();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.filters.tag.as_deref(), Some("urgent"));
+        assert_eq!(app.get_current_todo_list().unwrap().sort_mode, SortMode::Alphabetical);
+        assert_eq!((app.view_mode.clone(), app.group_by, app.search_scope), (ViewMode::List, GroupBy::Context, SearchScope::Workspace));
+        assert_eq!(app.get_visible_todos().len(), 1);
+        
+        // A tag that no longer exists is skipped rather than emptying the list
+        app.workspace_manager.save_filter(SavedFilter {
+            name: "Gone".to_string(),
+            filters: FilterSet { tag: Some("archived".to_string()), context: Some("home".to_string()), ..FilterSet::default() },
+            sort_mode: SortMode::Priority,
+            view_mode: ViewMode::List,
+            group_by: GroupBy::None,
+            search_scope: SearchScope::All,
+        });
+        app.enter_saved_filter_selection();
+        app.popup_selected = 1;
+        app.select_from_popup();
+        assert_eq!(app.filters.tag, None);
+        assert_eq!(app.search_scope, SearchScope::All);
+        assert_eq!(app.get_visible_todos().len(), 2);
+        assert!(app.message().unwrap().contains("#archived"));
+        
+        // Deleting from the popup
+        app.enter_saved_filter_selection();
+        app.delete_selected_saved_filter();
+        assert_eq!(app.workspace_manager.saved_filters.len(), 1);
+        assert_eq!(app.workspace_manager.saved_filters[0].name, "Gone");
+        
+        println!("✅ Saved filters save, apply, and delete");
+    }
+
+    #[test]
+    fn test_group_by_tag_and_context() {
+        let mut app = App::new(Config::default());
+        let (shared, plain) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            list.add_todo("Draft slides #work".to_string());
+            let shared = list.add_todo("Expense report #work #admin @office".to_string());
+            let plain = list.add_todo("Go for a run".to_string());
+            (shared, plain)
+        };
+        
+        // Select the untagged todo, then group by tag: selection follows the todo
+        app.selected = app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == plain).unwrap();
+        app.cycle_group_by();
+        assert_eq!(app.group_by, GroupBy::Tag);
+        assert_eq!(app.get_selected_todo_id(), Some(plain));
+        
+        // Todos with several tags appear under each; untagged ones go last
+        let rows = app.get_list_rows();
+        assert_eq!(rows, vec![
+            ListRow::Header("#admin".to_string(), 1),
+            ListRow::Todo(0),
+            ListRow::Header("#work".to_string(), 2),
+            ListRow::Todo(1),
+            ListRow::Todo(2),
+            ListRow::Header("(untagged)".to_string(), 1),
+            ListRow::Todo(3),
+        ]);
+        let visible = app.get_visible_todos();
+        assert_eq!(visible.len(), 4);
+        assert_eq!(visible[0].1.id, shared);
+        assert_eq!(app.selected, 3);
+        
+        // Navigation moves over todos only, so headers are never selected
+        app.go_to_top();
+        app.move_selection_down();
+        assert_eq!(app.selected, 1);
+        assert!(matches!(app.get_list_rows()[3], ListRow::Todo(1)));
+        app.go_to_bottom();
+        assert_eq!(app.get_selected_todo_id(), Some(plain));
+        
+        // Actions on a grouped row reach the real todo
+        app.go_to_top();
+        app.toggle_todo_complete();
+        assert!(app.get_current_todo_list().unwrap().get_todo(shared).unwrap().is_completed());
+        
+        // Context grouping, then back to the plain list
+        app.cycle_group_by();
+        assert_eq!(app.group_by, GroupBy::Context);
+        let rows = app.get_list_rows();
+        assert_eq!(rows[0], ListRow::Header("@office".to_string(), 1));
+        assert_eq!(rows[2], ListRow::Header("(no context)".to_string(), 2));
+        app.cycle_group_by();
+        assert_eq!(app.group_by, GroupBy::None);
+        assert!(app.get_list_rows().iter().all(|row| matches!(row, ListRow::Todo(_))));
+        
+        println!("✅ Group-by renders headers and keeps selection on todos");
+    }
+
+    #[test]
+    fn test_filter_from_selected_todo() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+        let press = |app: &mut App, c: char| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))).unwrap();
+        };
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let (single, multi, bare) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let single = list.add_todo("Fix login #urgent @work".to_string());
+            let multi = list.add_todo("Plan offsite #work #travel #budget".to_string());
+            list.add_todo("Book hotel #travel".to_string());
+            let bare = list.add_todo("Stretch".to_string());
+            (single, multi, bare)
+        };
+        let select = |app: &mut App, id: u32| {
+            app.selected = app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == id).unwrap();
+        };
+        
+        // `g t` filters by the only tag right away and keeps the cursor on the todo
+        select(&mut app, single);
+        press(&mut app, 'g');
+        press(&mut app, 't');
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.filters.tag.as_deref(), Some("urgent"));
+        assert_eq!(app.get_selected_todo_id(), Some(single));
+        
+        // `g c` does the same for its context
+        press(&mut app, 'g');
+        press(&mut app, 'c');
+        assert_eq!(app.filters.context.as_deref(), Some("work"));
+        app.clear_filters();
+        
+        // Several tags: the popup offers only this todo's tags
+        select(&mut app, multi);
+        press(&mut app, ']');
+        assert_eq!(app.mode, AppMode::TagSelection);
+        assert_eq!(app.available_tags, vec!["travel", "budget", "work"]);
+        app.popup_selected = 0;
+        app.select_from_popup();
+        assert_eq!(app.filters.tag.as_deref(), Some("travel"));
+        assert_eq!(app.get_visible_todos().len(), 2);
+        app.clear_filters();
+        
+        // No tags: just a message, nothing changes
+        select(&mut app, bare);
+        app.filter_by_selected_todos_tag();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.filters.is_empty());
+        assert_eq!(app.message(), Some("Selected todo has no tags"));
+        
+        // `g g` still goes to the top
+        press(&mut app, 'g');
+        press(&mut app, 'g');
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.pending_key, None);
+        
+        println!("✅ Filters can be applied from the selected todo");
+    }
+
+    #[test]
+    fn test_rename_tag_and_context() {
+        let mut app = App::new(Config::default());
+        let (gym, run, desk) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let gym = list.add_todo("Gym session #workout #Work".to_string());
+            let run = list.add_todo("Morning run #workout due:tomorrow".to_string());
+            let desk = list.add_todo("Clean desk #work @offce".to_string());
+            (gym, run, desk)
+        };
+        let run_due = app.get_current_todo_list().unwrap().get_todo(run).unwrap().due_date;
+        
+        // #work is a prefix of #workout; only the exact tag is rewritten
+        let list = app.get_current_todo_list_mut().unwrap();
+        assert_eq!(list.rename_tag("work", "job"), 2);
+        let gym_todo = list.get_todo(gym).unwrap();
+        assert_eq!(gym_todo.raw_description, "Gym session #workout #job");
+        assert!(gym_todo.tags.contains("workout") && gym_todo.tags.contains("job"));
+        assert_eq!(list.get_todo(desk).unwrap().raw_description, "Clean desk #job @offce");
+        assert_eq!(list.get_todo(run).unwrap().raw_description, "Morning run #workout due:tomorrow");
+        assert_eq!(list.rename_tag("missing", "x"), 0);
+        
+        // Contexts work the same way
+        assert_eq!(list.rename_context("offce", "office"), 1);
+        assert!(list.get_todo(desk).unwrap().contexts.contains("office"));
+        
+        // From the tag popup: r, type the name, Enter; undo restores every todo
+        app.enter_tag_selection();
+        app.popup_selected = app.available_tags.iter().position(|t| t == "workout").unwrap();
+        app.enter_rename_label_mode();
+        assert_eq!(app.mode, AppMode::RenameTag);
+        for c in "#training".chars() {
+            app.add_char_to_input(c);
+        }
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.message(), Some("Renamed #workout to #training on 2 todos"));
+        let list = app.get_current_todo_list().unwrap();
+        assert_eq!(list.get_todo(run).unwrap().raw_description, "Morning run #training due:tomorrow");
+        assert_eq!(list.get_todo(run).unwrap().due_date, run_due);
+        
+        app.undo();
+        let list = app.get_current_todo_list().unwrap();
+        assert_eq!(list.get_todo(gym).unwrap().raw_description, "Gym session #workout #job");
+        assert!(list.get_todo(run).unwrap().tags.contains("workout"));
+        app.redo();
+        assert!(app.get_current_todo_list().unwrap().get_todo(run).unwrap().tags.contains("training"));
+        
+        // Invalid names are rejected and keep the prompt open
+        app.enter_tag_selection();
+        app.enter_rename_label_mode();
+        app.input_buffer.set("two words".to_string());
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::RenameTag);
+        app.cancel_prompt();
+        assert_eq!(app.mode, AppMode::TagSelection);
+        
+        println!("✅ Tags and contexts rename across the workspace");
+    }
+
+    #[test]
+    fn test_delete_tag_and_context() {
+        let mut app = App::new(Config::default());
+        let (gym, desk) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let gym = list.add_todo("Gym session #workout #work".to_string());
+            let desk = list.add_todo("Clean #work desk @office".to_string());
+            (gym, desk)
+        };
+        
+        // d asks first, showing how many todos it will touch; n backs out to the popup
+        app.enter_tag_selection();
+        app.popup_selected = app.available_tags.iter().position(|t| t == "work").unwrap();
+        app.request_delete_label();
+        assert_eq!(app.mode, AppMode::Confirm);
+        assert_eq!(app.get_confirmation_prompt().as_deref(), Some("Remove #work from 2 todos?"));
+        app.cancel_confirmation();
+        assert_eq!(app.mode, AppMode::TagSelection);
+        assert_eq!(app.get_current_todo_list().unwrap().get_tag_counts().len(), 2);
+        
+        // Confirming strips only the exact tag and refreshes the popup
+        app.filters.tag = Some("work".to_string());
+        app.request_delete_label();
+        app.confirm_pending();
+        assert_eq!(app.mode, AppMode::TagSelection);
+        assert_eq!(app.message(), Some("Removed #work from 2 todos"));
+        assert_eq!(app.available_tags, vec!["workout".to_string()]);
+        assert_eq!(app.filters.tag, None);
+        let list = app.get_current_todo_list().unwrap();
+        assert_eq!(list.get_todo(gym).unwrap().raw_description, "Gym session #workout");
+        assert_eq!(list.get_todo(desk).unwrap().raw_description, "Clean desk @office");
+        assert!(!list.get_todo(desk).unwrap().description.contains('#'));
+        assert!(!list.get_tag_counts().iter().any(|(tag, _)| tag == "work"));
+        
+        app.undo();
+        let list = app.get_current_todo_list().unwrap();
+        assert_eq!(list.get_todo(desk).unwrap().raw_description, "Clean #work desk @office");
+        assert!(list.get_tag_counts().contains(&("work".to_string(), 2)));
+        app.redo();
+        assert!(!app.get_current_todo_list().unwrap().get_todo(gym).unwrap().tags.contains("work"));
+        
+        // Removing the last context closes the popup
+        app.enter_context_selection();
+        app.request_delete_label();
+        assert_eq!(app.get_confirmation_prompt().as_deref(), Some("Remove @office from 1 todo?"));
+        app.confirm_pending();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.get_current_todo_list().unwrap().get_todo(desk).unwrap().contexts.is_empty());
+        
+        println!("✅ Tags and contexts can be removed from every todo");
+    }
+
+    #[test]
+    fn test_tab_completion() {
+        // Prefix extraction looks at the token right before the cursor
+        assert_eq!(completion_prefix("Fix bug #pr"), Some((CompletionKind::Tag, 9, "pr")));
+        assert_eq!(completion_prefix("Call mum @ho"), Some((CompletionKind::Context, 10, "ho")));
+        assert_eq!(completion_prefix("Pay rent due:"), Some((CompletionKind::Due, 13, "")));
+        assert_eq!(completion_prefix("#"), Some((CompletionKind::Tag, 1, "")));
+        assert_eq!(completion_prefix("Fix bug"), None);
+        assert_eq!(completion_prefix("Fix #bug "), None);
+        assert_eq!(completion_prefix("mail me@home"), None);
+        
+        let pool = vec!["project".to_string(), "prod".to_string(), "home".to_string()];
+        assert_eq!(candidates("PR", &pool), vec!["project".to_string(), "prod".to_string()]);
+        assert!(candidates("x", &pool).is_empty());
+        
+        // Tab completes inline and repeated Tab cycles back round
+        let mut app = App::new(Config::default());
+        app.get_current_todo_list_mut().unwrap().add_todo("Ship it #project #prod @home".to_string());
+        app.enter_insert_mode();
+        for c in "Fix bug #pr".chars() {
+            app.add_char_to_input(c);
+        }
+        app.complete_input();
+        assert_eq!(app.input_buffer.content, "Fix bug #prod");
+        app.complete_input();
+        assert_eq!(app.input_buffer.content, "Fix bug #project");
+        assert_eq!(app.input_buffer.cursor, app.input_buffer.content.len());
+        app.complete_input();
+        assert_eq!(app.input_buffer.content, "Fix bug #prod");
+        
+        // Text after the cursor is kept
+        app.completion = None;
+        app.input_buffer.set("Pay rent due:to soon".to_string());
+        app.input_buffer.cursor = "Pay rent due:to".len();
+        app.complete_input();
+        assert_eq!(app.input_buffer.content, "Pay rent due:today soon");
+        app.complete_input();
+        assert_eq!(app.input_buffer.content, "Pay rent due:tomorrow soon");
+        
+        // Nothing to offer leaves the text alone
+        app.completion = None;
+        app.input_buffer.set("Call @xyz".to_string());
+        app.complete_input();
+        assert_eq!(app.input_buffer.content, "Call @xyz");
+        assert!(app.completion.is_none());
+        assert_eq!(app.message(), Some("No completions"));
+        
+        println!("✅ Tab completes tags, contexts and due keywords");
+    }
+
+    #[test]
+    fn test_parse_preview() {
+        let preview = Todo::parse_preview("Ship release #launch #Beta @work due:tomorrow");
+        assert_eq!(preview.tags, vec!["beta".to_string(), "launch".to_string()]);
+        assert_eq!(preview.contexts, vec!["work".to_string()]);
+        assert_eq!(preview.due_token.as_deref(), Some("tomorrow"));
+        let tomorrow = chrono::Local::now().date_naive() + chrono::Duration::days(1);
+        assert_eq!(preview.due_date.map(|due| due.date_naive()), Some(tomorrow));
+        assert!(!preview.has_unrecognized_due());
+        
+        // A due: token the parser doesn't understand is flagged
+        let preview = Todo::parse_preview("Pay rent due:someday");
+        assert!(preview.has_unrecognized_due());
+        assert!(!preview.is_empty());
+        
+        assert!(Todo::parse_preview("Plain text").is_empty());
+        
+        println!("✅ Parse preview reports tags, contexts and due dates");
+    }
+
+    #[test]
+    fn test_quick_entry_stays_in_insert_mode() {
+        let mut app = App::new(Config::default());
+        app.enter_insert_mode();
+        for text in ["Buy milk", "Buy eggs", "   "] {
+            for c in text.chars() {
+                app.add_char_to_input(c);
+            }
+            app.submit_input_and_continue();
+        }
+        assert_eq!(app.mode, AppMode::Insert);
+        assert_eq!(app.quick_entry_count, 2);
+        assert!(app.input_buffer.content.is_empty());
+        assert_eq!(app.input_buffer.cursor, 0);
+        assert_eq!(app.get_current_todo_list().unwrap().total_count(), 2);
+        
+        // Plain Enter still adds and leaves Insert mode
+        for c in "Buy bread".chars() {
+            app.add_char_to_input(c);
+        }
+        app.submit_input();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.quick_entry_count, 0);
+        
+        // Each todo is its own undo step
+        app.undo();
+        app.undo();
+        assert_eq!(app.get_current_todo_list().unwrap().total_count(), 1);
+        
+        // Children can be rattled off under one parent the same way
+        app.selected = 0;
+        app.add_child_todo();
+        assert_eq!(app.mode, AppMode::InsertChild);
+        for text in ["Whole milk", "Oat milk"] {
+            for c in text.chars() {
+                app.add_char_to_input(c);
+            }
+            app.submit_input_and_continue();
+        }
+        assert_eq!(app.mode, AppMode::InsertChild);
+        let list = app.get_current_todo_list().unwrap();
+        let parent = list.todos.values().find(|t| t.description == "Buy milk").unwrap();
+        assert_eq!(parent.children.len(), 2);
+        let parent_id = parent.id;
+        
+        app.enter_normal_mode();
+        app.undo();
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(parent_id).unwrap().children.len(), 1);
+        app.redo();
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(parent_id).unwrap().children.len(), 2);
+        
+        println!("✅ Shift+Enter adds todos without leaving Insert mode");
+    }
+
+    #[test]
+    fn test_text_buffer_word_editing() {
+        let mut buffer = TextBuffer::new();
+        for c in "fix the café  sign".chars() {
+            buffer.insert_char(c);
+        }
+        assert_eq!(buffer.cursor, buffer.content.len());
+        
+        // Word movement skips runs of spaces and handles multi-byte text
+        buffer.move_word_left();
+        assert_eq!(buffer.before_cursor(), "fix the café  ");
+        buffer.move_word_left();
+        assert_eq!(buffer.before_cursor(), "fix the ");
+        buffer.move_word_right();
+        assert_eq!(buffer.before_cursor(), "fix the café");
+        buffer.move_left();
+        assert_eq!(buffer.before_cursor(), "fix the caf");
+        buffer.move_right();
+        
+        // Ctrl+W deletes the previous word, Ctrl+K the rest of the line
+        buffer.delete_word_before();
+        assert_eq!(buffer.content, "fix the   sign");
+        assert_eq!(buffer.before_cursor(), "fix the ");
+        buffer.kill_to_end();
+        assert_eq!(buffer.content, "fix the ");
+        buffer.delete_word_before();
+        assert_eq!(buffer.content, "fix ");
+        
+        // Ctrl+U clears back to the start
+        buffer.set("one two".to_string());
+        buffer.move_word_left();
+        buffer.kill_to_start();
+        assert_eq!(buffer.content, "two");
+        assert_eq!(buffer.cursor, 0);
+        
+        // Home/End and the kills stay on the current line in multi-line notes
+        buffer.set("first line\nsecond line".to_string());
+        buffer.move_home();
+        assert_eq!(buffer.before_cursor(), "first line\n");
+        buffer.move_right();
+        buffer.kill_to_start();
+        assert_eq!(buffer.content, "first line\necond line");
+        buffer.move_end();
+        assert_eq!(buffer.cursor, buffer.content.len());
+        
+        // Nothing to delete or move at the edges
+        buffer.clear();
+        buffer.delete_char_before();
+        buffer.delete_word_before();
+        buffer.move_left();
+        buffer.move_word_right();
+        assert_eq!(buffer, TextBuffer::new());
+        
+        // Word edits apply to whichever input is open
+        let mut app = App::new(Config::default());
+        app.enter_search_mode();
+        app.search_buffer.set("milk bread".to_string());
+        assert!(app.edit_active_text(TextBuffer::delete_word_before));
+        assert_eq!(app.search_buffer.content, "milk ");
+        app.enter_normal_mode();
+        assert!(!app.edit_active_text(TextBuffer::kill_to_start));
+        
+        println!("✅ Text buffers support word and line editing");
+    }
+
+    #[test]
+    fn test_text_buffer_delete_forward() {
+        let mut buffer = TextBuffer::new();
+        buffer.set("naïve 日本".to_string());
+        
+        // At the end there is nothing to delete
+        buffer.delete_char_after();
+        assert_eq!(buffer.content, "naïve 日本");
+        
+        // Multi-byte characters are removed whole
+        buffer.move_home();
+        buffer.move_right();
+        buffer.move_right();
+        buffer.delete_char_after();
+        assert_eq!(buffer.content, "nave 日本");
+        assert_eq!(buffer.cursor, 2);
+        buffer.move_end();
+        buffer.move_left();
+        buffer.move_left();
+        buffer.delete_char_after();
+        assert_eq!(buffer.content, "nave 本");
+        buffer.delete_char_after();
+        assert_eq!(buffer.content, "nave ");
+        assert_eq!(buffer.cursor, buffer.content.len());
+        
+        // Delete works in every input, including workspace names
+        let mut app = App::new(Config::default());
+        app.enter_create_workspace_mode();
+        app.input_buffer.set("Home".to_string());
+        app.input_buffer.move_home();
+        assert!(app.edit_active_text(TextBuffer::delete_char_after));
+        assert_eq!(app.input_buffer.content, "ome");
+        
+        println!("✅ Delete removes the character under the cursor");
+    }
+
+    #[test]
+    fn test_display_col() {
+        // ASCII: one column per byte
+        assert_eq!(display_col("hello", 0), 0);
+        assert_eq!(display_col("hello", 3), 3);
+        
+        // Accented letters are two bytes but one column
+        let cafe = "café au lait";
+        assert_eq!(display_col(cafe, "café".len()), 4);
+        assert_eq!(display_col(cafe, cafe.len()), 12);
+        
+        // CJK characters are three bytes and two columns
+        let japanese = "日本語 ok";
+        assert_eq!(display_col(japanese, "日本".len()), 4);
+        assert_eq!(display_col(japanese, japanese.len()), 9);
+        
+        let mut buffer = TextBuffer::new();
+        buffer.set("Buy 牛乳".to_string());
+        assert_eq!(buffer.cursor_col(), 8);
+        buffer.move_left();
+        assert_eq!(buffer.cursor_col(), 6);
+        
+        println!("✅ Cursor columns account for multi-byte and wide characters");
+    }
+
+    #[test]
+    fn test_input_scroll_window() {
+        // Short text is shown as is
+        assert_eq!(scroll_window("short", 5, 10), ("short".to_string(), 5));
+        
+        // Cursor at the end of long text: the start is clipped, leaving a cell for the cursor
+        let text = "abcdefghijklmnop";
+        assert_eq!(scroll_window(text, text.len(), 10), ("…ijklmnop".to_string(), 9));
+        
+        // Cursor at the start: the end is clipped
+        assert_eq!(scroll_window(text, 0, 10), ("abcdefghi…".to_string(), 0));
+        
+        // Cursor in the middle: clipped on both sides, cursor kept on screen
+        let (visible, col) = scroll_window(text, 12, 10);
+        assert_eq!(visible, "…fghijklm…");
+        assert_eq!(col, 8);
+        assert_eq!(visible.chars().nth(col), Some('m'));
+        
+        // Wide characters are never split
+        let wide = "日本語のテキストです";
+        let (visible, col) = scroll_window(wide, wide.len(), 10);
+        assert_eq!(visible, "…ストです");
+        assert_eq!(col, 9);
+        assert!(display_col(&visible, visible.len()) <= 10);
+        
+        println!("✅ Long input text scrolls to keep the cursor visible");
+    }
+
+    #[test]
+    fn test_paste_into_inputs_and_normal_mode() {
+        let mut app = App::new(Config::default());
+        
+        // Single-line inputs get newlines turned into spaces, at the cursor
+        app.enter_search_mode();
+        for c in "Buy  #shop".chars() {
+            app.add_char_to_search(c);
+        }
+        app.search_buffer.cursor = 4;
+        app.paste_text("oat milk\r\nand eggs");
+        assert_eq!(app.search_buffer.content, "Buy oat milk and eggs #shop");
+        assert_eq!(app.search_buffer.cursor, "Buy oat milk and eggs".len());
+        app.cancel_search();
+        
+        // The notes editor keeps the line breaks
+        app.get_current_todo_list_mut().unwrap().add_todo("Plan trip".to_string());
+        app.mark_dirty();
+        app.enter_notes_mode();
+        app.paste_text("day one\nday two");
+        assert_eq!(app.notes_buffer.content, "day one\nday two");
+        app.exit_notes_mode();
+        
+        // In normal mode one line starts a new todo with it
+        app.paste_text("  Call the bank  ");
+        assert_eq!(app.mode, AppMode::Insert);
+        assert_eq!(app.input_buffer.content, "Call the bank");
+        app.enter_normal_mode();
+        
+        // Several lines ask before creating one todo per line
+        app.paste_text("Pack bags #trip\n\nBook taxi @phone\n");
+        assert_eq!(app.mode, AppMode::Confirm);
+        assert_eq!(app.get_confirmation_prompt().as_deref(), Some("Create 2 todos from the pasted lines?"));
+        app.cancel_confirmation();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.get_current_todo_list().unwrap().total_count(), 1);
+        
+        app.paste_text("Pack bags #trip\nBook taxi @phone");
+        app.confirm_pending();
+        assert_eq!(app.mode, AppMode::Normal);
+        let list = app.get_current_todo_list().unwrap();
+        assert_eq!(list.total_count(), 3);
+        assert!(list.todos.values().any(|t| t.description == "Book taxi phone" && t.contexts.contains("phone")));
+        
+        println!("✅ Pasted text goes into the open input or becomes todos");
+    }
+
+    #[test]
+    fn test_parse_outline_hierarchy() {
+        let outline = |text: &str| -> Vec<(usize, String)> { parse_outline(text) };
+        let expect = |rows: &[(usize, &str)]| -> Vec<(usize, String)> {
+            rows.iter().map(|(d, t)| (*d, t.to_string())).collect()
+        };
+        
+        // Two-space and tab indentation, blank lines skipped
+        assert_eq!(outline("Trip\n  Pack\n    Socks\n\n  Book taxi\nGroceries"), expect(&[
+            (0, "Trip"), (1, "Pack"), (2, "Socks"), (1, "Book taxi"), (0, "Groceries"),
+        ]));
+        assert_eq!(outline("Trip\n\tPack"), expect(&[(0, "Trip"), (1, "Pack")]));
+        
+        // Markdown bullets and dash nesting
+        assert_eq!(outline("- Trip #travel\n  - Pack\n-- Book taxi\n* Groceries\n-5 degrees"), expect(&[
+            (0, "Trip #travel"), (1, "Pack"), (1, "Book taxi"), (0, "Groceries"), (0, "-5 degrees"),
+        ]));
+        
+        // Everything indented the same is top level; jumps deeper are capped at one level
+        assert_eq!(outline("    a\n    b"), expect(&[(0, "a"), (0, "b")]));
+        assert_eq!(outline("a\n      b\n  c"), expect(&[(0, "a"), (1, "b"), (1, "c")]));
+        assert!(outline("\n   \n").is_empty());
+        
+        // add_outline builds the tree, each line parsed for tags and due dates
+        let mut list = TodoList::new();
+        let ids = list.add_outline("Trip #travel\n  Pack @home\n    Socks\n  Taxi due:tomorrow\nGroceries", None);
+        assert_eq!(ids.len(), 5);
+        let trip = list.get_todo(ids[0]).unwrap();
+        assert_eq!(trip.children, vec![ids[1], ids[3]]);
+        assert!(trip.tags.contains("travel"));
+        assert_eq!(list.get_todo(ids[2]).unwrap().parent_id, Some(ids[1]));
+        assert!(list.get_todo(ids[1]).unwrap().contexts.contains("home"));
+        assert!(list.get_todo(ids[3]).unwrap().due_date.is_some());
+        assert_eq!(list.get_todo(ids[4]).unwrap().parent_id, None);
+        
+        // Pasting a list in Insert mode adds it at once as a single undo step
+        let mut app = App::new(Config::default());
+        app.enter_insert_mode();
+        app.paste_text("Trip\n  Pack\nGroceries");
+        assert_eq!(app.mode, AppMode::Insert);
+        assert_eq!(app.quick_entry_count, 3);
+        assert_eq!(app.get_current_todo_list().unwrap().total_count(), 3);
+        app.enter_normal_mode();
+        app.undo();
+        assert_eq!(app.get_current_todo_list().unwrap().total_count(), 0);
+        app.redo();
+        let list = app.get_current_todo_list().unwrap();
+        let trip = list.todos.values().find(|t| t.description == "Trip").unwrap();
+        assert_eq!(trip.children.len(), 1);
+        
+        // Under a parent in InsertChild mode, top-level lines become its children
+        app.selected = app.get_visible_todos().iter().position(|(_, t, _)| t.description == "Groceries").unwrap();
+        app.add_child_todo();
+        app.paste_text("Milk\nEggs");
+        let list = app.get_current_todo_list().unwrap();
+        assert_eq!(list.todos.values().find(|t| t.description == "Groceries").unwrap().children.len(), 2);
+        
+        println!("✅ Pasted outlines become nested todos");
+    }
+
+    #[test]
+    fn test_markdown_checklist_and_clipboard_encoding() {
+        let mut list = TodoList::new();
+        let trip = list.add_todo("Plan trip #travel due:2030-06-01".to_string());
+        let pack = list.add_child_todo(trip, "Pack bags".to_string()).unwrap();
+        let socks = list.add_child_todo(pack, "Socks".to_string()).unwrap();
+        list.add_child_todo(trip, "Book taxi @phone".to_string()).unwrap();
+        list.get_todo_mut(trip).unwrap().set_notes(Some("Passport!\n\nCheck visa".to_string()));
+        list.get_todo_mut(socks).unwrap().toggle_complete();
+        
+        assert_eq!(markdown_checklist(&list, trip).unwrap(), [
+            "- [ ] Plan trip #travel due:2030-06-01",
+            "  Passport!",
+            "",
+            "  Check visa",
+            "  - [ ] Pack bags",
+            "    - [x] Socks",
+            "  - [ ] Book taxi @phone",
+        ].join("\n"));
+        assert_eq!(markdown_checklist(&list, socks).unwrap(), "- [x] Socks");
+        assert!(markdown_checklist(&list, 999).is_none());
+        
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode("café".as_bytes()), "Y2Fmw6k=");
+        
+        // Without a terminal there is no clipboard: the message says so instead of failing
+        let mut app = App::new(Config::default());
+        app.get_current_todo_list_mut().unwrap().add_todo("Call mum".to_string());
+        app.copy_selected_todo(false);
+        let message = app.message().map(str::to_string).unwrap();
+        assert!(message == "Copied 'Call mum'" || message.starts_with("Clipboard unavailable"));
+        
+        println!("✅ Todos copy as text or Markdown checklists");
+    }
+
+    #[test]
+    fn test_notes_line_navigation() {
+        let text = "first line\nab\n\n日本語 text";
+        
+        // Byte offset <-> (line, display column)
+        assert_eq!(line_col(text, 0), (0, 0));
+        assert_eq!(line_col(text, 5), (0, 5));
+        assert_eq!(line_col(text, "first line\nab".len()), (1, 2));
+        assert_eq!(line_col(text, "first line\nab\n".len()), (2, 0));
+        assert_eq!(line_col(text, "first line\nab\n\n日本".len()), (3, 4));
+        assert_eq!(offset_at(text, 1, 1), "first line\na".len());
+        assert_eq!(offset_at(text, 1, 9), "first line\nab".len());
+        assert_eq!(offset_at(text, 3, 3), "first line\nab\n\n日".len());
+        assert_eq!(offset_at(text, 9, 0), text.len());
+        
+        // Up/Down keep the column, clamped to shorter lines
+        let mut buffer = TextBuffer::new();
+        buffer.set(text.to_string());
+        buffer.move_up();
+        assert_eq!(buffer.cursor, "first line\nab\n".len());
+        buffer.cursor = text.len();
+        buffer.move_up();
+        buffer.move_up();
+        buffer.move_down();
+        buffer.move_down();
+        assert_eq!(line_col(text, buffer.cursor), (3, 0));
+        buffer.cursor = 7;
+        buffer.move_down();
+        assert_eq!(buffer.cursor, "first line\nab".len());
+        buffer.move_up();
+        assert_eq!(buffer.cursor, 2);
+        buffer.move_up();
+        assert_eq!(buffer.cursor, 2);
+        buffer.cursor = "first line\nab\n\n日本".len();
+        buffer.move_down();
+        assert_eq!(line_col(text, buffer.cursor), (3, 4));
+        buffer.move_to_start();
+        assert_eq!(buffer.cursor, 0);
+        buffer.move_to_end();
+        assert_eq!(buffer.cursor, text.len());
+        
+        // Wrapped rows and where the cursor lands on them
+        let rows = wrap_rows("abcdefgh\n\nxy", 3);
+        assert_eq!(rows, vec![0..3, 3..6, 6..8, 9..9, 10..12]);
+        assert_eq!(row_col("abcdefgh\n\nxy", &rows, 4), (1, 1));
+        assert_eq!(row_col("abcdefgh\n\nxy", &rows, 8), (2, 2));
+        assert_eq!(row_col("abcdefgh\n\nxy", &rows, 9), (3, 0));
+        assert_eq!(row_col("abcdefgh\n\nxy", &rows, 12), (4, 2));
+        assert_eq!(wrap_rows("日本語", 4), vec![0..6, 6..9]);
+        
+        println!("✅ Notes editor moves between lines by column");
+    }
+
+    #[test]
+    fn test_notes_scroll_follows_cursor_and_viewer_keys() {
+        use ratatui::{backend::TestBackend, Terminal};
+        
+        let mut app = App::new(Config::default());
+        app.get_current_todo_list_mut().unwrap().add_todo("Long notes".to_string());
+        let notes: Vec<String> = (1..=40).map(|n| format!("line {}", n)).collect();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        
+        // The editor scrolls down to the cursor at the end, and back up when it moves to the top
+        app.enter_notes_mode();
+        app.paste_text(&notes.join("\n"));
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let height = app.notes_view_height;
+        assert!(height > 0 && height < 40);
+        assert_eq!(app.notes_scroll, 40 - height);
+        let screen = |terminal: &Terminal<TestBackend>| terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>();
+        assert!(screen(&terminal).contains(" line 40/40 "));
+        
+        // A new empty last line counts, so the cursor on it isn't past the total
+        app.paste_text("\n");
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        assert!(screen(&terminal).contains(" line 41/41 "));
+        app.notes_buffer.delete_char_before();
+        app.notes_buffer.move_to_start();
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        assert_eq!(app.notes_scroll, 0);
+        app.save_notes();
+        
+        // The viewer's highlighted line moves with j/k and pages, scrolling with it to the last line
+        app.enter_view_notes_mode();
+        assert_eq!(app.mode, AppMode::ViewNotes);
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let height = app.notes_view_height;
+        app.move_notes_selection(1);
+        assert_eq!(app.notes_selected_line, 1);
+        app.move_notes_selection(-5);
+        assert_eq!(app.notes_selected_line, 0);
+        app.move_notes_selection_page(true);
+        assert_eq!(app.notes_selected_line, height - 1);
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        assert_eq!(app.notes_scroll, 0);
+        app.move_notes_selection(1);
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        assert_eq!(app.notes_scroll, 1);
+        app.move_notes_selection(100);
+        assert_eq!(app.notes_selected_line, 39);
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        assert_eq!(app.notes_scroll, 40 - height);
+        
+        println!("✅ Long notes scroll in the editor and viewer");
+    }
+
+    #[test]
+    fn test_render_markdown_lines() {
+        let colors = Theme::new();
+        let text_of = |line: &ratatui::text::Line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>();
+        let notes = "# Plan\n## Steps\n- pack\n  * [ ] book hotel\n- [x] buy tickets\nSome **bold** and *italic* words\n#hashtag\n2 * 3 * 4";
+        let lines = render_markdown_lines(notes, &colors);
+        assert_eq!(lines.len(), 8);
+        
+        // Headings drop the `#` and are bold, coloured by level
+        assert_eq!(text_of(&lines[0]), "Plan");
+        assert_eq!(lines[0].spans[0].style.fg, Some(colors.blue));
+        assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(lines[1].spans[0].style.fg, Some(colors.cyan));
+        
+        // Bullets get a glyph and keep their indent; checkboxes are coloured by state
+        assert_eq!(text_of(&lines[2]), "• pack");
+        assert_eq!(text_of(&lines[3]), "  ☐ book hotel");
+        assert_eq!(lines[3].spans[2].style.fg, Some(colors.yellow));
+        assert_eq!(text_of(&lines[4]), "☑ buy tickets");
+        assert_eq!(lines[4].spans[2].style.fg, Some(colors.green));
+        
+        // Inline emphasis
+        let spans = &lines[5].spans;
+        assert_eq!(text_of(&lines[5]), "Some bold and italic words");
+        assert_eq!(spans[1].content, "bold");
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(spans[3].content, "italic");
+        assert!(spans[3].style.add_modifier.contains(Modifier::ITALIC));
+        
+        // Anything that isn't quite Markdown stays as typed
+        assert_eq!(text_of(&lines[6]), "#hashtag");
+        assert_eq!(text_of(&lines[7]), "2 * 3 * 4");
+        assert_eq!(lines[7].spans.len(), 1);
+        
+        println!("✅ Notes render as lightweight Markdown");
+    }
+
+    #[test]
+    fn test_external_notes_editor() {
+        let mut app = App::new(Config::default());
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        let id = todo_list.add_todo("Write report".to_string());
+        todo_list.get_todo_mut(id).unwrap().set_notes(Some("old notes".to_string()));
+        
+        // Saved text replaces the notes
+        app.editing_notes_for = Some(id);
+        app.editing_workspace_id = app.get_selected_workspace_id();
+        app.finish_external_notes_edit(Ok("new notes\n".to_string()));
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(id).unwrap().notes.as_deref(), Some("new notes"));
+        assert_eq!(app.editing_notes_for, None);
+        
+        // A failed editor leaves them alone and says why
+        app.editing_notes_for = Some(id);
+        app.editing_workspace_id = app.get_selected_workspace_id();
+        app.finish_external_notes_edit(Err("vim exited with exit status: 1; notes unchanged".to_string()));
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(id).unwrap().notes.as_deref(), Some("new notes"));
+        assert!(app.message().unwrap().contains("notes unchanged"));
+        
+        // The process helper reports missing editors and nonzero exits
+        assert!(edit_text("paperclip-no-such-editor", "text", "test.md").unwrap_err().starts_with("Couldn't start"));
+        if cfg!(unix) {
+            assert_eq!(edit_text("true", "kept as is", "test.md"), Ok("kept as is".to_string()));
+            assert!(edit_text("false", "text", "test.md").unwrap_err().contains("notes unchanged"));
+        }
+        
+        // The temp file is private to us and cleaned up with its directory
+        if cfg!(target_os = "linux") {
+            let script_dir = TempDir::new("editor-test");
+            std::fs::create_dir_all(&script_dir).unwrap();
+            let report = script_dir.join("report");
+            let script = script_dir.join("editor.sh");
+            std::fs::write(&script, format!(
+                "#!/bin/sh\nstat -c %a \"$1\" \"$(dirname \"$1\")\" > {}\necho \"$1\" >> {}\n",
+                report.display(), report.display()
+            )).unwrap();
+            std::process::Command::new("chmod").arg("+x").arg(&script).status().unwrap();
+            assert_eq!(edit_text(script.to_str().unwrap(), "private", "notes.md"), Ok("private".to_string()));
+            let report = std::fs::read_to_string(&report).unwrap();
+            let lines: Vec<&str> = report.lines().collect();
+            assert_eq!(&lines[..2], &["600", "700"]);
+            assert!(!std::path::Path::new(lines[2]).parent().unwrap().exists());
+        }
+        
+        println!("✅ Notes round-trip through an external editor");
+    }
+
+    #[test]
+    fn test_notes_checklist_progress_and_toggle() {
+        // Only bulleted `[ ]` / `[x]` lines count, at any indent
+        assert_eq!(checklist_state("- [ ] book flights"), Some(false));
+        assert_eq!(checklist_state("  * [x] pack"), Some(true));
+        assert_eq!(checklist_state("+ [X]"), Some(true));
+        assert_eq!(checklist_state("- [] nope"), None);
+        assert_eq!(checklist_state("[ ] no bullet"), None);
+        assert_eq!(checklist_state("- [x]nope"), None);
+        assert_eq!(checklist_state("- plain bullet"), None);
+        
+        let notes = "# Trip\nSome prose about it.\n- [x] book flights\n- [ ] pack\n  - [ ] passport\n- regular bullet\n* [X] visa";
+        let mut todo = Todo::new(1, "Holiday".to_string());
+        assert_eq!(todo.notes_checklist_progress(), None);
+        todo.set_notes(Some("Just prose\n- and a bullet".to_string()));
+        assert_eq!(todo.notes_checklist_progress(), None);
+        todo.set_notes(Some(notes.to_string()));
+        assert_eq!(todo.notes_checklist_progress(), Some((2, 4)));
+        
+        // Toggling rewrites just the mark, and only on checklist lines
+        let toggled = toggle_checklist_line(notes, 4).unwrap();
+        assert!(toggled.contains("\n  - [x] passport\n"));
+        assert_eq!(toggle_checklist_line(&toggled, 4).unwrap(), notes);
+        assert_eq!(toggle_checklist_line(notes, 1), None);
+        assert_eq!(toggle_checklist_line(notes, 99), None);
+        
+        // Space in the viewer ticks the highlighted line and saves it to the todo
+        let mut app = App::new(Config::default());
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        let id = todo_list.add_todo("Holiday".to_string());
+        todo_list.get_todo_mut(id).unwrap().set_notes(Some(notes.to_string()));
+        app.enter_view_notes_mode();
+        app.move_notes_selection(3);
+        app.toggle_notes_checklist_item();
+        app.move_notes_selection(-2);
+        app.toggle_notes_checklist_item();
+        let todo = app.get_current_todo_list().unwrap().get_todo(id).unwrap();
+        assert_eq!(todo.notes_checklist_progress(), Some((3, 4)));
+        assert!(todo.notes.as_deref().unwrap().contains("- [x] pack"));
+        assert!(app.notes_buffer.content.contains("- [x] pack"));
+        
+        println!("✅ Checklists in notes count toward the todo and toggle from the viewer");
+    }
+
+    #[test]
+    fn test_detail_pane_toggle_and_layout() {
+        use ratatui::{backend::TestBackend, Terminal};
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        let id = todo_list.add_todo("Renew passport #admin @town due:tomorrow".to_string());
+        todo_list.get_todo_mut(id).unwrap().set_notes(Some("Bring two photos".to_string()));
+        let screen = |app: &mut App, width: u16| {
+            let mut terminal = Terminal::new(TestBackend::new(width, 30)).unwrap();
+            terminal.draw(|f| crate::ui::draw(f, app)).unwrap();
+            terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>()
+        };
+        assert!(!screen(&mut app, 120).contains("Details"));
+        
+        // Wide enough: the pane shows the fields and the start of the notes
+        app.toggle_detail_pane();
+        let wide = screen(&mut app, 120);
+        assert!(wide.contains("Details"));
+        assert!(wide.contains("#admin"));
+        assert!(wide.contains("Bring two photos"));
+        
+        // Too narrow: the list keeps the whole width
+        assert!(!screen(&mut app, 80).contains("Details"));
+        
+        // The setting is saved in its own file, not with the workspace data
+        let dir = TempDir::new("display");
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+        assert_eq!(storage.load_display_settings().unwrap(), DisplaySettings::default());
+        storage.save_display_settings(&app.display).unwrap();
+        assert!(storage.load_display_settings().unwrap().show_detail_pane);
+        assert!(!serde_json::to_string(&app.workspace_manager).unwrap().contains("detail"));
+        app.toggle_detail_pane();
+        assert!(!app.display.show_detail_pane);
+        
+        println!("✅ Detail pane toggles, persists and hides on narrow terminals");
+    }
+
+    #[test]
+    fn test_list_scrolloff_and_paging() {
+        // The offset stays put until the selection comes within the margin of an edge
+        assert_eq!(scroll_offset(0, 0, 100, 10, 3), 0);
+        assert_eq!(scroll_offset(0, 6, 100, 10, 3), 0);
+        assert_eq!(scroll_offset(0, 7, 100, 10, 3), 1);
+        assert_eq!(scroll_offset(20, 23, 100, 10, 3), 20);
+        assert_eq!(scroll_offset(20, 22, 100, 10, 3), 19);
+        // ...never scrolls past the end, and copes with tiny or empty viewports
+        assert_eq!(scroll_offset(0, 99, 100, 10, 3), 90);
+        assert_eq!(scroll_offset(50, 5, 8, 10, 3), 0);
+        assert_eq!(scroll_offset(0, 4, 100, 3, 3), 3);
+        assert_eq!(scroll_offset(7, 4, 100, 0, 3), 0);
+        
+        let mut app = App::new(Config::default());
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        for n in 1..=50 {
+            todo_list.add_todo(format!("Task {}", n));
+        }
+        app.list_view_height = 10;
+        app.move_half_page(true);
+        assert_eq!(app.selected, 5);
+        app.move_page(true);
+        assert_eq!(app.selected, 15);
+        app.move_half_page(false);
+        assert_eq!(app.selected, 10);
+        app.move_page(false);
+        app.move_page(false);
+        assert_eq!(app.selected, 0);
+        app.move_selection_by(1000);
+        assert_eq!(app.selected, 49);
+        
+        println!("✅ List scrolling keeps a margin and pages move the selection");
+    }
+
+    #[test]
+    fn test_long_descriptions_are_truncated() {
+        use ratatui::{backend::TestBackend, Terminal};
+        
+        assert_eq!(truncate_to_width("short", 10), "short");
+        assert_eq!(truncate_to_width("exactly ten", 11), "exactly ten");
+        assert_eq!(truncate_to_width("a longer sentence", 8), "a longe…");
+        assert_eq!(truncate_to_width("日本語のテキスト", 7), "日本語…");
+        assert_eq!(truncate_to_width("cafe\u{301}s and more", 5), "cafe\u{301}…");
+        assert_eq!(truncate_to_width("anything", 1), "…");
+        assert_eq!(truncate_to_width("anything", 0), "");
+        
+        // The tags and due date stay on screen; the description gives way
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        app.get_current_todo_list_mut().unwrap().add_todo(
+            "Write up the quarterly planning notes for the whole team and circulate them #work due:tomorrow".to_string());
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        let row = rows.iter().find(|row| row.contains("Write up")).unwrap();
+        assert!(row.contains('…'));
+        assert!(row.contains("#work"));
+        assert!(row.contains("[tomorrow]"));
+        
+        // ...and the selected row's text is repeated along the bottom border
+        assert!(rows.iter().any(|row| row.contains("Write up the quarterly planning notes for") && !row.contains("#work")));
+        
+        println!("✅ Long descriptions are shortened so the row's details stay visible");
+    }
+
+    #[test]
+    fn test_running_timer_is_displayed() {
+        use ratatui::{backend::TestBackend, Terminal};
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        let running = todo_list.add_todo("Fix login bug".to_string());
+        let tracked = todo_list.add_todo("Write docs".to_string());
+        todo_list.get_todo_mut(running).unwrap().time_tracker.current_session =
+            Some(chrono::Local::now() - chrono::Duration::seconds(42 * 60 + 13));
+        todo_list.get_todo_mut(tracked).unwrap().time_tracker.total_seconds = 3900;
+        
+        let todo = app.get_current_todo_list().unwrap().get_todo(running).unwrap();
+        assert!(todo.get_current_session_formatted().unwrap().starts_with("00:42:1"));
+        assert_eq!(app.get_active_timers().len(), 1);
+        
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        assert!(rows.iter().any(|row| row.contains("Fix login bug") && row.contains("⏱ 42m")));
+        assert!(rows.iter().any(|row| row.contains("Write docs") && row.contains("⏱ 1h 5m")));
+        assert!(rows.iter().any(|row| row.contains("Timer: Fix login bug 00:42:1")));
+        
+        println!("✅ Running and tracked time show in the rows and status bar");
+    }
+
+    #[test]
+    fn test_only_one_timer_runs_at_a_time() {
+        let mut app = App::new(Config::default());
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        for description in ["Fix bug", "Write docs", "Review PR"] {
+            todo_list.add_todo(description.to_string());
+        }
+        let running_count = |app: &App| app.workspace_manager.workspace_todos.values()
+            .flat_map(|todo_list| todo_list.todos.values())
+            .filter(|todo| todo.time_tracker.current_session.is_some())
+            .count();
+        
+        // Each start stops the previous timer and records its session
+        for selected in [0, 1, 2, 0] {
+            app.selected = selected;
+            app.toggle_timer();
+            assert_eq!(running_count(&app), 1);
+        }
+        assert_eq!(app.get_selected_todo().unwrap().description, "Fix bug");
+        assert!(app.message().unwrap().starts_with("Stopped timer on 'Review PR' (+0m), started on 'Fix bug'"));
+        let entries: usize = app.get_current_todo_list().unwrap().todos.values()
+            .map(|todo| todo.time_tracker.entries.len())
+            .sum();
+        assert_eq!(entries, 3);
+        
+        // Timers in other workspaces are stopped too
+        let work_id = app.workspace_manager.workspace_todos.get_mut(&work).unwrap().add_todo("Standup".to_string());
+        app.workspace_manager.workspace_todos.get_mut(&work).unwrap().start_timer(work_id);
+        assert_eq!(running_count(&app), 2);
+        app.toggle_timer();
+        app.toggle_timer();
+        assert_eq!(running_count(&app), 1);
+        
+        // Toggling the running timer just stops it; concurrent timers can be allowed
+        app.toggle_timer();
+        assert_eq!(running_count(&app), 0);
+        app.allow_concurrent_timers = true;
+        app.toggle_timer();
+        app.selected = 1;
+        app.toggle_timer();
+        assert_eq!(running_count(&app), 2);
+        
+        println!("✅ Starting a timer stops the one already running");
+    }
+
+    #[test]
+    fn test_interrupted_timers_are_recovered_on_load() {
+        let mut manager = WorkspaceManager::new();
+        let personal = manager.create_workspace("Personal".to_string(), None);
+        let work = manager.create_workspace("Work".to_string(), None);
+        let started = chrono::Local::now() - chrono::Duration::hours(30);
+        for ws_id in [&personal, &work] {
+            let todo_list = manager.workspace_todos.get_mut(ws_id).unwrap();
+            let id = todo_list.add_todo("Left running".to_string());
+            let todo = todo_list.get_todo_mut(id).unwrap();
+            todo.time_tracker.total_seconds = 600;
+            todo.time_tracker.current_session = Some(started);
+        }
+        manager.workspace_todos.get_mut(&work).unwrap().add_todo("Never timed".to_string());
+        
+        // A crash leaves the sessions in the saved file; loading closes them without the lost time
+        let saved = serde_json::to_string(&manager).unwrap();
+        let mut loaded: WorkspaceManager = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.recover_interrupted_timers(), 2);
+        for todo in loaded.workspace_todos.values().flat_map(|todo_list| todo_list.todos.values()) {
+            assert!(todo.time_tracker.current_session.is_none());
+            assert!(todo.time_tracker.total_seconds == 600 || todo.time_tracker.total_seconds == 0);
+        }
+        assert_eq!(loaded.recover_interrupted_timers(), 0);
+        
+        // Quitting stops timers properly, so nothing is left to recover
+        let mut app = App::new(Config::default());
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Timed".to_string());
+        app.toggle_timer();
+        assert_eq!(app.stop_all_timers().len(), 1);
+        let todo = app.get_current_todo_list().unwrap().get_todo(id).unwrap();
+        assert!(!todo.is_timer_running());
+        assert_eq!(todo.time_tracker.entries.len(), 1);
+        assert_eq!(app.workspace_manager.recover_interrupted_timers(), 0);
+        
+        println!("✅ Interrupted timer sessions are closed on load without inflating totals");
+    }
+
+    #[test]
+    fn test_manual_time_entry() {
+        assert_eq!(parse_duration("45m"), Some(45 * 60));
+        assert_eq!(parse_duration("1h30m"), Some(90 * 60));
+        assert_eq!(parse_duration("2H"), Some(7200));
+        assert_eq!(parse_duration("-20m"), Some(-20 * 60));
+        assert_eq!(parse_duration("+90s"), Some(90));
+        assert_eq!(parse_duration("45"), None);
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("1x"), None);
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("99999999999999999h"), None);
+        
+        // Entries span the logged time; corrections never take the total below zero
+        let mut todo = Todo::new(1, "Client call".to_string());
+        todo.add_manual_time(45 * 60, Some("forgot the timer".to_string()));
+        assert_eq!(todo.time_tracker.total_seconds, 2700);
+        let entry = &todo.time_tracker.entries[0];
+        assert_eq!((entry.end.unwrap() - entry.start).num_seconds(), 2700);
+        assert_eq!(entry.description.as_deref(), Some("forgot the timer"));
+        todo.add_manual_time(-20 * 60, None);
+        assert_eq!(todo.time_tracker.total_seconds, 1500);
+        todo.add_manual_time(-3600, None);
+        assert_eq!(todo.time_tracker.total_seconds, 0);
+        assert_eq!(todo.time_tracker.entries.len(), 3);
+        
+        // The input bar flow: bad input keeps the prompt open with a message
+        let mut app = App::new(Config::default());
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Write report".to_string());
+        app.enter_log_time_mode();
+        assert_eq!(app.mode, AppMode::LogTime);
+        app.paste_text("soon");
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::LogTime);
+        assert!(app.message().unwrap().starts_with("Couldn't read 'soon'"));
+        app.clear_input_buffer();
+        app.paste_text("1h30m  drafting yesterday");
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.message(), Some("Logged 1h 30m on 'Write report'"));
+        let todo = app.get_current_todo_list().unwrap().get_todo(id).unwrap();
+        assert_eq!(todo.time_tracker.total_seconds, 5400);
+        assert_eq!(todo.time_tracker.entries[0].description.as_deref(), Some("drafting yesterday"));
+        
+        println!("✅ Time can be logged and corrected by hand");
+    }
+
+    #[test]
+    fn test_time_entries_popup() {
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Client work".to_string());
+        app.enter_time_entries_mode();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.message(), Some("No time tracked on this todo yet"));
+        
+        {
+            let todo = app.get_current_todo_list_mut().unwrap().get_todo_mut(id).unwrap();
+            todo.add_manual_time(3600, Some("first".to_string()));
+            todo.add_manual_time(1800, Some("second".to_string()));
+            todo.add_manual_time(-600, None);
+            todo.start_timer();
+        }
+        
+        // The running session sits on top and can't be deleted; entries follow newest first
+        app.enter_time_entries_mode();
+        assert_eq!(app.mode, AppMode::TimeTracking);
+        assert_eq!(app.get_selected_time_entry_index(), None);
+        app.delete_selected_time_entry();
+        assert_eq!(app.get_time_entries_todo().unwrap().time_tracker.entries.len(), 3);
+        app.move_popup_selection_down();
+        assert_eq!(app.get_selected_time_entry_index(), Some(2));
+        for _ in 0..10 {
+            app.move_popup_selection_down();
+        }
+        assert_eq!(app.popup_selected, 3);
+        assert_eq!(app.get_selected_time_entry_index(), Some(0));
+        
+        // Deleting recounts the total from what's left
+        app.popup_selected = 1;
+        app.delete_selected_time_entry();
+        let todo = app.get_time_entries_todo().unwrap();
+        assert_eq!(todo.time_tracker.total_seconds, 5400);
+        assert_eq!(todo.time_tracker.entries.len(), 2);
+        
+        // e retypes the highlighted entry's note and comes back to the popup
+        app.popup_selected = 2;
+        app.enter_edit_time_entry_mode();
+        assert_eq!(app.mode, AppMode::EditTimeEntry);
+        assert_eq!(app.input_buffer.content, "first");
+        app.clear_input_buffer();
+        app.paste_text("kickoff call");
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::TimeTracking);
+        assert_eq!(app.get_time_entries_todo().unwrap().time_tracker.entries[0].description.as_deref(), Some("kickoff call"));
+        
+        app.cancel_popup();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.editing_todo_id, None);
+        
+        println!("✅ Time entries can be reviewed, deleted and annotated");
+    }
+
+    #[test]
+    fn test_time_report_aggregation() {
+        use chrono::{Local, NaiveDate, TimeZone};
+        
+        let at = |day: u32, hour: u32| Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap();
+        let entry = |day: u32, from: u32, to: u32| TimeEntry { start: at(day, from), end: Some(at(day, to)), description: None };
+        let mut list = TodoList::new();
+        let client = list.add_todo("Client call #clientA @phone".to_string());
+        let docs = list.add_todo("Write docs #clientA #docs".to_string());
+        let misc = list.add_todo("Inbox zero".to_string());
+        list.get_todo_mut(client).unwrap().time_tracker.entries = vec![entry(4, 9, 10), entry(5, 9, 11)];
+        list.get_todo_mut(docs).unwrap().time_tracker.entries = vec![entry(5, 13, 14), entry(20, 9, 17)];
+        // A correction taking 30m off on the 5th
+        list.get_todo_mut(misc).unwrap().time_tracker.entries = vec![
+            entry(5, 15, 16),
+            TimeEntry { start: at(5, 18) + chrono::Duration::minutes(30), end: Some(at(5, 18)), description: None },
+        ];
+        
+        let week = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()..=NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        assert_eq!(list.time_by_tag(&week), vec![
+            ("#clienta".to_string(), 4 * 3600),
+            ("@phone".to_string(), 3 * 3600),
+            ("#docs".to_string(), 3600),
+            ("(untagged)".to_string(), 1800),
+        ]);
+        
+        let by_day = list.time_by_day(&week);
+        assert_eq!(by_day.len(), 7);
+        assert_eq!(by_day[0], (NaiveDate::from_ymd_opt(2024, 3, 4).unwrap(), 3600));
+        assert_eq!(by_day[1].1, 2 * 3600 + 3600 + 1800);
+        assert!(by_day[2..].iter().all(|(_, seconds)| *seconds == 0));
+        
+        // Entries outside the range are left out
+        let later = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap()..=NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        assert_eq!(list.time_by_tag(&later), vec![("#clienta".to_string(), 8 * 3600), ("#docs".to_string(), 8 * 3600)]);
+        
+        println!("✅ Tracked time adds up per tag and per day");
+    }
+
+    #[test]
+    fn test_pomodoro_transitions() {
+        use crate::pomodoro::{self, Phase, Pomodoro, Transition};
+        use chrono::Duration;
+        
+        // The state machine on its own: work -> break -> idle, with the break timed from the
+        // scheduled end of work even when the tick comes late
+        let start = chrono::Local::now();
+        let mut session = Pomodoro::start("ws".to_string(), 1, start);
+        assert_eq!(session.remaining_formatted(start), "25:00");
+        assert_eq!(session.tick(start + Duration::minutes(10)), Transition::None);
+        assert_eq!(session.remaining_formatted(start + Duration::seconds(1499)), "00:01");
+        let work_end = start + Duration::minutes(pomodoro::WORK_MINUTES);
+        assert_eq!(session.tick(work_end + Duration::seconds(3)), Transition::WorkDone { ended_at: work_end });
+        assert_eq!(session.phase, Phase::Break);
+        assert_eq!(session.ends_at, work_end + Duration::minutes(pomodoro::BREAK_MINUTES));
+        assert_eq!(session.tick(work_end + Duration::minutes(4)), Transition::None);
+        assert_eq!(session.tick(work_end + Duration::minutes(5)), Transition::BreakDone);
+        
+        // Through the app: P starts the timer, the end of work records a labelled entry
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Deep work".to_string());
+        let other = app.get_current_todo_list_mut().unwrap().add_todo("Other".to_string());
+        app.get_current_todo_list_mut().unwrap().start_timer(other);
+        app.selected = app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == id).unwrap();
+        app.toggle_pomodoro();
+        let list = app.get_current_todo_list().unwrap();
+        assert!(list.get_todo(id).unwrap().is_timer_running());
+        assert!(!list.get_todo(other).unwrap().is_timer_running());
+        
+        let ends_at = app.pomodoro.as_ref().unwrap().ends_at;
+        app.tick_pomodoro(ends_at - Duration::seconds(1));
+        assert!(!app.bell_pending);
+        app.tick_pomodoro(ends_at);
+        assert!(std::mem::take(&mut app.bell_pending));
+        assert_eq!(app.pomodoro.as_ref().unwrap().phase, Phase::Break);
+        let todo = app.get_current_todo_list().unwrap().get_todo(id).unwrap();
+        assert!(!todo.is_timer_running());
+        let entry = todo.time_tracker.entries.last().unwrap();
+        assert_eq!(entry.description.as_deref(), Some(pomodoro::ENTRY_LABEL));
+        assert_eq!(entry.end, Some(ends_at));
+        
+        let break_end = app.pomodoro.as_ref().unwrap().ends_at;
+        app.tick_pomodoro(break_end);
+        assert!(app.pomodoro.is_none());
+        assert!(app.bell_pending);
+        assert_eq!(app.message(), Some("Break over"));
+        
+        // P again cancels a pomodoro in progress, stopping its timer with the time so far
+        app.toggle_pomodoro();
+        assert!(app.pomodoro.is_some());
+        let entries = app.get_current_todo_list().unwrap().get_todo(id).unwrap().time_tracker.entries.len();
+        app.toggle_pomodoro();
+        assert!(app.pomodoro.is_none());
+        let todo = app.get_current_todo_list().unwrap().get_todo(id).unwrap();
+        assert!(!todo.is_timer_running());
+        assert_eq!(todo.time_tracker.entries.len(), entries + 1);
+        assert_eq!(todo.time_tracker.entries.last().unwrap().description, None);
+        
+        // Starting another todo's timer, or stopping this one's, ends the work session too
+        app.toggle_pomodoro();
+        app.selected = app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == other).unwrap();
+        app.toggle_timer();
+        assert!(app.pomodoro.is_none());
+        assert_eq!(app.message(), Some("Pomodoro cancelled: its timer stopped"));
+        app.toggle_pomodoro();
+        app.toggle_timer();
+        assert!(app.pomodoro.is_none());
+        
+        // ...as does its todo going away, noticed on the next tick
+        app.toggle_pomodoro();
+        app.delete_selected_todo();
+        app.tick_pomodoro(chrono::Local::now());
+        assert!(app.pomodoro.is_none());
+        
+        println!("✅ Pomodoro work, break and idle transitions work correctly!");
+    }
+
+    #[test]
+    fn test_agenda_buckets() {
+        use crate::app::ListRow;
+        use crate::todo::AgendaBucket;
+        use chrono::{Local, TimeZone};
+        
+        // Half an hour before midnight on a Monday
+        let now = Local.with_ymd_and_hms(2024, 3, 4, 23, 30, 0).unwrap();
+        let at = |day: u32, hour: u32, minute: u32| Some(Local.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap());
+        assert_eq!(AgendaBucket::for_due(at(4, 23, 0), now), AgendaBucket::Overdue);
+        assert_eq!(AgendaBucket::for_due(at(4, 23, 59), now), AgendaBucket::Today);
+        assert_eq!(AgendaBucket::for_due(at(5, 0, 0), now), AgendaBucket::Tomorrow);
+        assert_eq!(AgendaBucket::for_due(at(5, 23, 59), now), AgendaBucket::Tomorrow);
+        assert_eq!(AgendaBucket::for_due(at(6, 0, 0), now), AgendaBucket::ThisWeek);
+        assert_eq!(AgendaBucket::for_due(at(10, 23, 59), now), AgendaBucket::ThisWeek);
+        assert_eq!(AgendaBucket::for_due(at(11, 0, 0), now), AgendaBucket::Later);
+        assert_eq!(AgendaBucket::for_due(None, now), AgendaBucket::NoDate);
+        
+        // Buckets come in order, sorted by due time inside, without completed todos
+        let mut list = TodoList::new();
+        let friday = list.add_todo("Friday".to_string());
+        let wednesday = list.add_todo("Wednesday".to_string());
+        let late = list.add_todo("Late".to_string());
+        let done = list.add_todo("Done".to_string());
+        let someday = list.add_todo("Someday".to_string());
+        list.get_todo_mut(friday).unwrap().due_date = at(8, 9, 0);
+        list.get_todo_mut(wednesday).unwrap().due_date = at(6, 9, 0);
+        list.get_todo_mut(late).unwrap().due_date = at(1, 9, 0);
+        list.get_todo_mut(done).unwrap().due_date = at(5, 9, 0);
+        list.get_todo_mut(done).unwrap().toggle_complete();
+        let buckets: Vec<(AgendaBucket, Vec<u32>)> = list.agenda_buckets_at(now).into_iter()
+            .map(|(bucket, todos)| (bucket, todos.iter().map(|todo| todo.id).collect()))
+            .collect();
+        assert_eq!(buckets, vec![
+            (AgendaBucket::Overdue, vec![late]),
+            (AgendaBucket::ThisWeek, vec![wednesday, friday]),
+            (AgendaBucket::NoDate, vec![someday]),
+        ]);
+        
+        // The app view puts headers between buckets and actions work on the selected row
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let list = app.get_current_todo_list_mut().unwrap();
+        let undated = list.add_todo("Undated".to_string());
+        let overdue = list.add_todo("Overdue".to_string());
+        list.get_todo_mut(overdue).unwrap().due_date = Some(Local::now() - chrono::Duration::days(2));
+        app.selected = app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == undated).unwrap();
+        app.toggle_agenda_view();
+        assert_eq!(app.view_mode, ViewMode::Agenda);
+        assert_eq!(app.get_selected_todo_id(), Some(undated));
+        assert_eq!(app.get_list_rows(), vec![
+            ListRow::Header("Overdue".to_string(), 1),
+            ListRow::Todo(0),
+            ListRow::Header("No date".to_string(), 1),
+            ListRow::Todo(1),
+        ]);
+        app.selected = 0;
+        app.toggle_todo_complete();
+        assert!(app.get_current_todo_list().unwrap().get_todo(overdue).unwrap().is_completed());
+        assert_eq!(app.get_visible_todos().len(), 1);
+        app.toggle_agenda_view();
+        assert_eq!(app.view_mode, ViewMode::List);
+        
+        println!("✅ Agenda buckets and view work correctly!");
+    }
+
+    #[test]
+    fn test_week_calendar() {
+        use crate::app::CalendarState;
+        use chrono::{Datelike, Local, NaiveDate, TimeZone, Weekday};
+        
+        // Wednesday afternoon; the calendar week runs Monday to Sunday
+        let now = Local.with_ymd_and_hms(2024, 3, 6, 15, 0, 0).unwrap();
+        let at = |day: u32, hour: u32| Some(Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap());
+        let state = CalendarState::for_day(now.date_naive());
+        assert_eq!(state.week_start, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+        assert_eq!(state.week_start.weekday(), Weekday::Mon);
+        assert_eq!(state.focused_day, Some(2));
+        
+        let mut list = TodoList::new();
+        let missed = list.add_todo("Missed".to_string());
+        let earlier_today = list.add_todo("Earlier today".to_string());
+        let later_today = list.add_todo("Later today".to_string());
+        let done = list.add_todo("Done Monday".to_string());
+        let sunday = list.add_todo("Sunday".to_string());
+        let next_week = list.add_todo("Next week".to_string());
+        list.get_todo_mut(missed).unwrap().due_date = at(1, 9);
+        list.get_todo_mut(earlier_today).unwrap().due_date = at(6, 9);
+        list.get_todo_mut(later_today).unwrap().due_date = at(6, 17);
+        list.get_todo_mut(done).unwrap().due_date = at(4, 9);
+        list.get_todo_mut(done).unwrap().toggle_complete();
+        list.get_todo_mut(sunday).unwrap().due_date = at(10, 23);
+        list.get_todo_mut(next_week).unwrap().due_date = at(11, 9);
+        
+        // Overdue todos go to the gutter instead of their day
+        let ids = |todos: Vec<&Todo>| todos.iter().map(|todo| todo.id).collect::<Vec<u32>>();
+        assert_eq!(ids(list.overdue_todos(now)), vec![missed, earlier_today]);
+        let days = list.due_by_day(&state.days(), now);
+        assert_eq!(days.len(), 7);
+        let by_day: Vec<Vec<u32>> = days.into_iter().map(|(_, todos)| ids(todos)).collect();
+        assert_eq!(by_day, vec![vec![done], vec![], vec![later_today], vec![], vec![], vec![], vec![sunday]]);
+        
+        // Navigation in the app, then Enter shows the todo in a list whose filters hid it
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let today = Local::now().date_naive();
+        let list = app.get_current_todo_list_mut().unwrap();
+        let parent = list.add_todo("Project".to_string());
+        let child = list.add_child_todo(parent, "Step due today".to_string()).unwrap();
+        list.get_todo_mut(child).unwrap().due_date = Some(Local.from_local_datetime(&today.and_hms_opt(23, 59, 59).unwrap()).unwrap());
+        list.get_todo_mut(parent).unwrap().expanded = false;
+        app.filters.status = Some(StatusFilter::Completed);
+        
+        app.enter_calendar_mode();
+        assert_eq!(app.mode, AppMode::Calendar);
+        assert_eq!(app.get_calendar_selected_todo().map(|todo| todo.id), Some(child));
+        app.move_calendar_day(-10);
+        assert_eq!(app.calendar.focused_day, None);
+        assert!(app.get_calendar_selected_todo().is_none());
+        app.move_calendar_day(10);
+        assert_eq!(app.calendar.focused_day, Some(6));
+        let week_start = app.calendar.week_start;
+        app.page_calendar_week(1);
+        assert_eq!(app.calendar.week_start, week_start + chrono::Duration::days(7));
+        app.page_calendar_week(-1);
+        app.calendar.focused_day = Some(today.weekday().num_days_from_monday() as usize);
+        app.open_calendar_selection();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.view_mode, ViewMode::List);
+        assert!(app.filters.is_empty());
+        assert_eq!(app.get_selected_todo_id(), Some(child));
+        
+        println!("✅ Week calendar buckets and navigation work correctly!");
+    }
+
+    #[test]
+    fn test_board_status_transitions() {
+        use crate::todo::TodoStatus;
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let list = app.get_current_todo_list_mut().unwrap();
+        let write = list.add_todo("Write".to_string());
+        let review = list.add_todo("Review".to_string());
+        list.get_todo_mut(review).unwrap().set_status(TodoStatus::InProgress);
+        
+        // Opening the board starts on the todo selected in the list
+        app.selected = app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == review).unwrap();
+        app.toggle_board();
+        assert_eq!(app.mode, AppMode::Board);
+        assert_eq!(app.board_selection, (1, 0));
+        let column_ids = |app: &App| -> Vec<Vec<u32>> {
+            app.get_board_columns().iter().map(|todos| todos.iter().map(|todo| todo.id).collect()).collect()
+        };
+        assert_eq!(column_ids(&app), vec![vec![write], vec![review], vec![]]);
+        
+        // Moving right into Completed stamps completed_at, and the selection follows the todo
+        app.move_board_todo(1);
+        assert_eq!(app.board_selection, (2, 0));
+        let todo = app.get_current_todo_list().unwrap().get_todo(review).unwrap();
+        assert_eq!(todo.status, TodoStatus::Completed);
+        assert!(todo.completed_at.is_some());
+        app.move_board_todo(1);
+        assert_eq!(app.board_selection, (2, 0));
+        
+        // Moving back out of Completed clears it again
+        app.move_board_todo(-1);
+        let todo = app.get_current_todo_list().unwrap().get_todo(review).unwrap();
+        assert_eq!(todo.status, TodoStatus::InProgress);
+        assert!(todo.completed_at.is_none());
+        
+        // Undo restores the previous status and completion time, redo reapplies it
+        app.undo();
+        let completed_at = app.get_current_todo_list().unwrap().get_todo(review).unwrap().completed_at;
+        assert!(completed_at.is_some());
+        app.undo();
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(review).unwrap().status, TodoStatus::InProgress);
+        app.redo();
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(review).unwrap().status, TodoStatus::Completed);
+        
+        // Column and row movement stay in range
+        app.move_board_column(-5);
+        assert_eq!(app.board_selection, (0, 0));
+        app.move_board_selection(3);
+        assert_eq!(app.board_selection, (0, 0));
+        app.move_board_todo(-1);
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(write).unwrap().status, TodoStatus::Pending);
+        app.toggle_board();
+        assert_eq!(app.mode, AppMode::Normal);
+        
+        println!("✅ Board status transitions work correctly!");
+    }
+
+    #[test]
+    fn test_undo_edit_priority_notes_and_child() {
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Plan trip #travel @home due:2030-06-01".to_string());
+        let original = app.get_current_todo_list().unwrap().get_todo(id).unwrap().clone();
+        let todo = |app: &App| app.get_current_todo_list().unwrap().get_todo(id).unwrap().clone();
+        
+        // Editing the text; undo brings back the tags, context and due date parsed from the old text
+        app.enter_edit_mode();
+        app.edit_buffer.set("Book flights #work due:2031-01-02".to_string());
+        app.save_todo_edit();
+        assert!(todo(&app).tags.contains("work") && !todo(&app).tags.contains("travel"));
+        assert!(todo(&app).contexts.is_empty());
+        app.undo();
+        let restored = todo(&app);
+        assert_eq!(restored.raw_description, original.raw_description);
+        assert_eq!(restored.description, original.description);
+        assert_eq!(restored.tags, original.tags);
+        assert_eq!(restored.contexts, original.contexts);
+        assert_eq!(restored.due_date, original.due_date);
+        app.redo();
+        assert_eq!(todo(&app).raw_description, "Book flights #work due:2031-01-02");
+        app.undo();
+        
+        // Priority steps, each undone separately; no command when already at the limit
+        app.increase_priority();
+        app.increase_priority();
+        assert_eq!(todo(&app).priority, 2);
+        app.undo();
+        assert_eq!(todo(&app).priority, 1);
+        app.redo();
+        assert_eq!(todo(&app).priority, 2);
+        app.undo();
+        app.undo();
+        assert_eq!(todo(&app).priority, 0);
+        app.decrease_priority();
+        app.undo();
+        assert_eq!(app.message(), Some("Nothing to undo"));
+        
+        // Notes written in the editor, then a checklist item ticked in the viewer
+        app.enter_notes_mode();
+        app.notes_buffer.set("- [ ] passports".to_string());
+        app.save_notes();
+        app.enter_view_notes_mode();
+        app.toggle_notes_checklist_item();
+        app.exit_view_notes_mode();
+        assert_eq!(todo(&app).notes.as_deref(), Some("- [x] passports"));
+        app.undo();
+        assert_eq!(todo(&app).notes.as_deref(), Some("- [ ] passports"));
+        app.undo();
+        assert_eq!(todo(&app).notes, None);
+        app.redo();
+        assert_eq!(todo(&app).notes.as_deref(), Some("- [ ] passports"));
+        app.undo();
+        
+        // Adding a child through the InsertChild prompt
+        app.add_child_todo();
+        app.input_buffer.set("Renew passport".to_string());
+        app.submit_input();
+        let child = todo(&app).children[0];
+        app.undo();
+        let list = app.get_current_todo_list().unwrap();
+        assert!(list.get_todo(child).is_none());
+        assert!(todo(&app).children.is_empty());
+        
+        let final_state = todo(&app);
+        assert_eq!(final_state.raw_description, original.raw_description);
+        assert_eq!(final_state.priority, original.priority);
+        assert_eq!(final_state.notes, original.notes);
+        
+        println!("✅ Undo restores edits, priority, notes and child additions!");
+    }
+
+    #[test]
+    fn test_undo_applies_to_the_command_workspace() {
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let work = app.workspace_manager.get_current_workspace_id().unwrap();
+        let personal = app.workspace_manager.create_workspace("Personal".to_string(), None);
+        
+        // Todo ids are per workspace, so both lists have a todo with the same id
+        app.enter_insert_mode();
+        app.input_buffer.set("Send report".to_string());
+        app.submit_input();
+        app.increase_priority();
+        let report = app.get_selected_todo_id().unwrap();
+        app.workspace_manager.switch_workspace(&personal);
+        let groceries = app.get_current_todo_list_mut().unwrap().add_todo("Buy groceries".to_string());
+        assert_eq!(report, groceries);
+        
+        // Undoing from Personal changes Work, not the same id here
+        app.undo();
+        assert_eq!(app.workspace_manager.workspace_todos[&work].get_todo(report).unwrap().priority, 0);
+        app.undo();
+        assert!(app.workspace_manager.workspace_todos[&work].get_todo(report).is_none());
+        let groceries_todo = app.workspace_manager.workspace_todos[&personal].get_todo(groceries).unwrap();
+        assert_eq!(groceries_todo.description, "Buy groceries");
+        app.redo();
+        assert!(app.workspace_manager.workspace_todos[&work].get_todo(report).is_some());
+        assert_eq!(app.workspace_manager.workspace_todos[&personal].total_count(), 1);
+        
+        // Once Work is gone its commands are skipped rather than applied to Personal
+        assert!(app.workspace_manager.delete_workspace(&work));
+        app.undo();
+        assert_eq!(app.message(), Some("Skipped undo: the workspace it was made in no longer exists"));
+        assert_eq!(app.workspace_manager.workspace_todos[&personal].total_count(), 1);
+        
+        println!("✅ Undo applies to the workspace each command was recorded in!");
+    }
+
+    #[test]
+    fn test_undo_selects_the_affected_todo() {
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let (first, _second, third) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            (list.add_todo("First".to_string()), list.add_todo("Second".to_string()), list.add_todo("Third".to_string()))
+        };
+        let index_of = |app: &App, id: u32| app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == id).unwrap();
+        
+        // Undo moves the selection back to the todo it changed
+        app.selected = index_of(&app, third);
+        app.increase_priority();
+        app.selected = index_of(&app, first);
+        app.undo();
+        assert_eq!(app.get_selected_todo_id(), Some(third));
+        app.selected = index_of(&app, first);
+        app.redo();
+        assert_eq!(app.get_selected_todo_id(), Some(third));
+        
+        // A child hidden under a collapsed parent is revealed; once it's gone the parent is selected
+        app.selected = index_of(&app, first);
+        app.add_child_todo();
+        app.input_buffer.set("Child".to_string());
+        app.submit_input();
+        let child = app.get_current_todo_list().unwrap().get_todo(first).unwrap().children[0];
+        app.undo();
+        assert_eq!(app.get_selected_todo_id(), Some(first));
+        app.get_current_todo_list_mut().unwrap().get_todo_mut(first).unwrap().expanded = false;
+        app.selected = index_of(&app, third);
+        app.redo();
+        assert!(app.get_current_todo_list().unwrap().get_todo(first).unwrap().expanded);
+        assert_eq!(app.get_selected_todo_id(), Some(child));
+        
+        // Removing the last row clamps the selection
+        app.selected = app.get_visible_todos().len() - 1;
+        app.delete_selected_todo();
+        app.selected = 0;
+        app.enter_insert_mode();
+        app.input_buffer.set("Fourth".to_string());
+        app.submit_input();
+        app.selected = app.get_visible_todos().len() - 1;
+        app.undo();
+        assert!(app.selected < app.get_visible_todos().len());
+        
+        println!("✅ Undo and redo select the todo they changed!");
+    }
+
+    #[test]
+    fn test_bulk_complete_and_priority_undo() {
+        use crate::todo::TodoStatus;
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let finished_at = chrono::Local::now() - chrono::Duration::days(3);
+        let ids: Vec<u32> = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let ids: Vec<u32> = (1..=5).map(|n| list.add_todo(format!("Task {}", n))).collect();
+            list.get_todo_mut(ids[1]).unwrap().status = TodoStatus::InProgress;
+            list.get_todo_mut(ids[2]).unwrap().complete();
+            list.get_todo_mut(ids[2]).unwrap().completed_at = Some(finished_at);
+            list.get_todo_mut(ids[3]).unwrap().priority = 4;
+            list.get_todo_mut(ids[4]).unwrap().status = TodoStatus::InProgress;
+            ids
+        };
+        let snapshot = |app: &App| -> Vec<(TodoStatus, Option<chrono::DateTime<chrono::Local>>, u8)> {
+            let list = app.get_current_todo_list().unwrap();
+            ids.iter().map(|id| {
+                let todo = list.get_todo(*id).unwrap();
+                (todo.status.clone(), todo.completed_at, todo.priority)
+            }).collect()
+        };
+        let before = snapshot(&app);
+        let select_all = |app: &mut App| {
+            app.enter_visual_mode();
+            app.selected_todos = ids.iter().copied().collect();
+        };
+        
+        // One undo puts back every status and completion time, including the already completed one
+        select_all(&mut app);
+        app.bulk_complete_todos();
+        assert!(snapshot(&app).iter().all(|(status, _, _)| *status == TodoStatus::Completed));
+        assert_eq!(snapshot(&app)[2].1, Some(finished_at));
+        app.undo();
+        assert_eq!(snapshot(&app), before);
+        app.redo();
+        assert!(snapshot(&app).iter().all(|(status, completed_at, _)| *status == TodoStatus::Completed && completed_at.is_some()));
+        app.undo();
+        assert_eq!(snapshot(&app), before);
+        
+        // Same for priority
+        select_all(&mut app);
+        app.bulk_set_priority(2);
+        assert!(snapshot(&app).iter().all(|(_, _, priority)| *priority == 2));
+        app.undo();
+        assert_eq!(snapshot(&app), before);
+        app.redo();
+        assert!(snapshot(&app).iter().all(|(_, _, priority)| *priority == 2));
+        
+        println!("✅ Bulk complete and bulk priority undo in one step!");
+    }
+
+    #[test]
+    fn test_visual_selection_highlight() {
+        use ratatui::{backend::TestBackend, Terminal};
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let (alpha, beta, gamma) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            (list.add_todo("Alpha #work".to_string()), list.add_todo("Beta".to_string()), list.add_todo("Gamma #work".to_string()))
+        };
+        app.enter_visual_mode();
+        app.selected_todos = [alpha, gamma].into_iter().collect();
+        
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let row_of = |text: &str| (0..20).find(|&y| {
+            (0..80).map(|x| buffer[(x, y)].symbol()).collect::<String>().contains(text)
+        }).unwrap();
+        let screen: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("2 selected"));
+        for (text, selected) in [("Alpha", true), ("Beta", false), ("Gamma", true)] {
+            let y = row_of(text);
+            assert_eq!(buffer[(1, y)].symbol() == "▎", selected, "{}", text);
+            assert_eq!(buffer[(40, y)].bg == app.colors.blue7, selected, "{}", text);
+        }
+        
+        // Filtering Gamma out drops it from the count and from what bulk operations touch
+        app.filters.tag = Some("work".to_string());
+        app.filters.exclude_tag = false;
+        app.get_current_todo_list_mut().unwrap().get_todo_mut(gamma).unwrap().update_description("Gamma".to_string());
+        assert_eq!(app.get_bulk_selection(), vec![alpha]);
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("1 selected"));
+        app.bulk_complete_todos();
+        let list = app.get_current_todo_list().unwrap();
+        assert!(list.get_todo(alpha).unwrap().is_completed());
+        assert!(!list.get_todo(beta).unwrap().is_completed());
+        assert!(!list.get_todo(gamma).unwrap().is_completed());
+        
+        println!("✅ Visual mode highlights the bulk selection!");
+    }
+
+    #[test]
+    fn test_visual_selection_set_operations() {
+        use crate::app::{invert_visible_selection, select_all_visible};
+        use std::collections::HashSet;
+        
+        // Id 9 is selected but hidden: neither operation should keep it
+        let visible = [1, 2, 3, 4];
+        let mut selected: HashSet<u32> = [2, 9].into_iter().collect();
+        invert_visible_selection(&mut selected, &visible);
+        assert_eq!(selected, [1, 3, 4].into_iter().collect());
+        invert_visible_selection(&mut selected, &visible);
+        assert_eq!(selected, [2].into_iter().collect());
+        
+        selected.insert(9);
+        select_all_visible(&mut selected, &visible);
+        assert_eq!(selected, visible.into_iter().collect());
+        invert_visible_selection(&mut selected, &visible);
+        assert!(selected.is_empty());
+        
+        // Select-by-tag only adds todos the current filter shows
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let (home, errand, done) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let home = list.add_todo("Water plants #home".to_string());
+            let errand = list.add_todo("Buy milk #errand".to_string());
+            let done = list.add_todo("Fix tap #home".to_string());
+            list.get_todo_mut(done).unwrap().complete();
+            (home, errand, done)
+        };
+        app.filters.status = Some(StatusFilter::Pending);
+        app.enter_visual_mode();
+        app.selected_todos.clear();
+        app.enter_visual_tag_selection();
+        assert_eq!(app.mode, AppMode::TagSelection);
+        app.popup_selected = app.available_tags.iter().position(|t| t == "home").unwrap();
+        app.select_from_popup();
+        assert_eq!(app.mode, AppMode::Visual);
+        assert!(app.filters.tag.is_none());
+        assert_eq!(app.get_bulk_selection(), vec![home]);
+        assert!(!app.selected_todos.contains(&done));
+        
+        app.visual_invert_selection();
+        assert_eq!(app.get_bulk_selection(), vec![errand]);
+        app.visual_select_all();
+        assert_eq!(app.get_bulk_selection().len(), 2);
+        
+        // Cancelling the popup keeps visual mode and the selection
+        app.enter_visual_tag_selection();
+        app.cancel_popup();
+        assert_eq!(app.mode, AppMode::Visual);
+        assert!(!app.tag_select_from_visual);
+        assert_eq!(app.selected_todos.len(), 2);
+        
+        println!("✅ Visual select-all, invert and select-by-tag stay within the visible todos");
+    }
+
+    #[test]
+    fn test_bulk_add_tag_and_context() {
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let (tagged, plain) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            (list.add_todo("Paint fence #Home".to_string()), list.add_todo("Call plumber @phone".to_string()))
+        };
+        let raw = |app: &App, id: u32| app.get_current_todo_list().unwrap().get_todo(id).unwrap().raw_description.clone();
+        
+        app.enter_visual_mode();
+        app.visual_select_all();
+        app.enter_bulk_label_mode(true);
+        assert_eq!(app.mode, AppMode::BulkTag);
+        
+        // Tab completes the bare name from existing tags, with or without the marker
+        app.add_char_to_input('#');
+        app.add_char_to_input('h');
+        app.complete_input();
+        assert_eq!(app.input_buffer.content, "#home");
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.message().unwrap_or("").contains("to 1 todo."));
+        
+        // The todo already tagged (in any case) gets no second token
+        assert_eq!(raw(&app, tagged), "Paint fence #Home");
+        assert_eq!(raw(&app, plain), "Call plumber @phone #home");
+        let todo = app.get_current_todo_list().unwrap().get_todo(plain).unwrap();
+        assert!(todo.tags.contains("home"));
+        
+        app.enter_visual_mode();
+        app.visual_select_all();
+        app.enter_bulk_label_mode(false);
+        for c in "phone".chars() {
+            app.add_char_to_input(c);
+        }
+        app.submit_prompt();
+        assert_eq!(raw(&app, tagged), "Paint fence #Home @phone");
+        assert_eq!(raw(&app, plain), "Call plumber @phone #home");
+        
+        // Each batch undoes in one step
+        app.undo();
+        assert_eq!(raw(&app, tagged), "Paint fence #Home");
+        app.undo();
+        assert_eq!(raw(&app, plain), "Call plumber @phone");
+        app.redo();
+        assert_eq!(raw(&app, plain), "Call plumber @phone #home");
+        
+        // Esc goes back to the selection
+        app.enter_visual_mode();
+        app.enter_bulk_label_mode(true);
+        app.cancel_prompt();
+        assert_eq!(app.mode, AppMode::Visual);
+        
+        println!("✅ Bulk tag and context are added once per todo and undo as a batch");
+    }
+
+    #[test]
+    fn test_bulk_set_and_clear_due_date() {
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let (a, b) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            (list.add_todo("Renew passport due:tomorrow".to_string()), list.add_todo("Book dentist".to_string()))
+        };
+        let todo = |app: &App, id: u32| app.get_current_todo_list().unwrap().get_todo(id).unwrap().clone();
+        let original_due = todo(&app, a).due_date;
+        let type_due = |app: &mut App, text: &str| {
+            app.enter_visual_mode();
+            app.visual_select_all();
+            app.enter_bulk_due_mode();
+            for c in text.chars() {
+                app.add_char_to_input(c);
+            }
+            app.submit_prompt();
+        };
+        
+        // Anything the `due:` grammar can't read keeps the prompt open
+        type_due(&mut app, "someday");
+        assert_eq!(app.mode, AppMode::BulkDue);
+        app.cancel_prompt();
+        app.exit_visual_mode();
+        
+        type_due(&mut app, "in 3 days");
+        assert_eq!(app.mode, AppMode::Normal);
+        let expected = (chrono::Local::now() + chrono::Duration::days(3)).date_naive();
+        for id in [a, b] {
+            let todo = todo(&app, id);
+            assert_eq!(todo.due_date.unwrap().date_naive(), expected);
+            // The raw text carries the new date, so editing it later keeps the same day
+            assert!(todo.raw_description.ends_with(&format!("due:{}", expected.format("%Y-%m-%d"))));
+            assert_eq!(todo.raw_description.matches("due:").count(), 1);
+        }
+        
+        type_due(&mut app, "none");
+        assert!(todo(&app, a).due_date.is_none());
+        assert_eq!(todo(&app, a).raw_description, "Renew passport");
+        assert_eq!(todo(&app, b).raw_description, "Book dentist");
+        
+        // Each batch undoes in one step, back to the prior dates and text
+        app.undo();
+        assert_eq!(todo(&app, b).due_date.unwrap().date_naive(), expected);
+        app.undo();
+        assert_eq!(todo(&app, a).due_date, original_due);
+        assert_eq!(todo(&app, a).raw_description, "Renew passport due:tomorrow");
+        assert!(todo(&app, b).due_date.is_none());
+        app.redo();
+        assert_eq!(todo(&app, a).due_date.unwrap().date_naive(), expected);
+        
+        println!("✅ Bulk due dates apply, clear and undo as a batch");
+    }
+
+    #[test]
+    fn test_shift_v_enters_visual_mode() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+        let press = |app: &mut App, code: KeyCode, modifiers: KeyModifiers| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(code, modifiers))).unwrap();
+        };
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Write report".to_string());
+        app.get_current_todo_list_mut().unwrap().get_todo_mut(id).unwrap().set_notes(Some("Outline first".to_string()));
+        
+        press(&mut app, KeyCode::Char('V'), KeyModifiers::SHIFT);
+        assert_eq!(app.mode, AppMode::Visual);
+        assert!(app.selected_todos.contains(&id));
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Normal);
+        
+        // Viewing notes moved to `g v`
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('v'), KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::ViewNotes);
+        
+        println!("✅ Shift+V enters visual mode and g v views notes");
+    }
+
+    #[test]
+    fn test_keymap_parsing_and_conflicts() {
+        use crate::keymap::{parse_config, Action, KeyBinding, Keymap, Scope};
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+        
+        // Key specs: shift folds into the character, ctrl letters are case-insensitive
+        assert_eq!(KeyBinding::parse("x"), Ok(KeyBinding { code: KeyCode::Char('x'), modifiers: KeyModifiers::NONE }));
+        assert_eq!(KeyBinding::parse("shift+v"), KeyBinding::parse("V"));
+        assert_eq!(KeyBinding::parse("Ctrl+V"), Ok(KeyBinding { code: KeyCode::Char('v'), modifiers: KeyModifiers::CONTROL }));
+        assert_eq!(KeyBinding::parse("+").unwrap().code, KeyCode::Char('+'));
+        assert_eq!(KeyBinding::parse("ctrl++").unwrap().modifiers, KeyModifiers::CONTROL);
+        assert_eq!(KeyBinding::parse("pagedown").unwrap().code, KeyCode::PageDown);
+        assert_eq!(KeyBinding::parse("space").unwrap().label(), "Space");
+        assert_eq!(KeyBinding::parse("ctrl+g").unwrap().label(), "Ctrl+G");
+        assert!(KeyBinding::parse("hyper+x").is_err());
+        assert!(KeyBinding::parse("nope").is_err());
+        
+        let overrides = parse_config("delete_todo = \"x\"\nenter_visual = \"ctrl+v\"\nmove_down = [\"n\", \"down\"]\n").unwrap();
+        assert_eq!(overrides.len(), 3);
+        assert!(parse_config("quit = 5").is_err());
+        assert!(parse_config("quit = ").is_err());
+        
+        let (keymap, warnings) = Keymap::with_overrides(&overrides);
+        let key = |code: KeyCode, modifiers: KeyModifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(keymap.action(Scope::Normal, &key(KeyCode::Char('x'), KeyModifiers::NONE)), Some(Action::DeleteTodo));
+        assert_eq!(keymap.action(Scope::Normal, &key(KeyCode::Char('d'), KeyModifiers::NONE)), None);
+        assert_eq!(keymap.action(Scope::Normal, &key(KeyCode::Char('v'), KeyModifiers::CONTROL)), Some(Action::EnterVisual));
+        // Remapped movement applies in visual mode too, and took `n` from next_match
+        assert_eq!(keymap.action(Scope::Visual, &key(KeyCode::Char('n'), KeyModifiers::NONE)), Some(Action::MoveDown));
+        assert_eq!(keymap.label(Action::MoveDown), "n/↓");
+        assert_eq!(keymap.label(Action::NextMatch), "(unbound)");
+        assert_eq!(warnings, vec!["n now runs move_down instead of next_match".to_string()]);
+        
+        // Unknown actions, bad keys and two configured actions on one key are all reported
+        let overrides = vec![
+            ("launch_rockets".to_string(), vec!["Q".to_string()]),
+            ("quit".to_string(), vec!["meh+q".to_string()]),
+            ("undo".to_string(), vec!["Q".to_string()]),
+            ("redo".to_string(), vec!["Q".to_string()]),
+        ];
+        let (keymap, warnings) = Keymap::with_overrides(&overrides);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("launch_rockets"));
+        assert!(warnings[1].contains("meh"));
+        assert_eq!(warnings[2], "undo and redo are both bound to Q");
+        assert_eq!(keymap.action(Scope::Normal, &key(KeyCode::Char('Q'), KeyModifiers::NONE)), Some(Action::Undo));
+        
+        // Scopes are separate: `c` is still bulk complete in visual mode after remapping the calendar
+        let (keymap, warnings) = Keymap::with_overrides(&[("bulk_complete".to_string(), vec!["x".to_string()])]);
+        assert!(warnings.is_empty());
+        assert_eq!(keymap.action(Scope::Normal, &key(KeyCode::Char('c'), KeyModifiers::NONE)), Some(Action::Calendar));
+        
+        // The event handlers go through the app's keymap
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        app.keymap = Keymap::with_overrides(&[("enter_visual".to_string(), vec!["ctrl+v".to_string()])]).0;
+        app.get_current_todo_list_mut().unwrap().add_todo("Water plants".to_string());
+        crate::events::handle_event(&mut app, Event::Key(key(KeyCode::Char('V'), KeyModifiers::SHIFT))).unwrap();
+        assert_eq!(app.mode, AppMode::Normal);
+        crate::events::handle_event(&mut app, Event::Key(key(KeyCode::Char('v'), KeyModifiers::CONTROL))).unwrap();
+        assert_eq!(app.mode, AppMode::Visual);
+        
+        println!("✅ Keybinding config parses, reports conflicts and drives the handlers");
+    }
+
+    #[test]
+    fn test_config_file_sets_startup_state() {
+        use crate::todo::SortMode;
+        use std::time::Duration;
+        
+        let sample = r#"
+default_workspace = "Work"
+skip_welcome = true
+tick_rate_ms = 100
+default_view = "agenda"
+default_sort = "due"
+date_format = "%d.%m."
+confirm_paste_over = 3
+confirm_bulk_delete_over = 2
+scrolloff = 5
+"#;
+        let (config, warnings) = Config::parse(sample);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(config.tick_rate, Duration::from_millis(100));
+        
+        let mut app = App::new(config);
+        assert_eq!(app.view_mode, ViewMode::Agenda);
+        assert_eq!(app.scrolloff, 5);
+        assert_eq!(app.get_current_todo_list().unwrap().sort_mode, SortMode::DueDate);
+        
+        // Startup opens the configured workspace and skips the welcome screen
+        app.create_new_workspace("Work".to_string(), None);
+        let work_id = app.workspace_manager.find_workspace_id_by_name("Work").unwrap();
+        assert_eq!(app.workspace_manager.workspace_todos[&work_id].sort_mode, SortMode::DueDate);
+        app.mode = AppMode::Welcome;
+        app.apply_startup_config();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.get_current_workspace_name(), "Work");
+        assert_eq!(app.view_mode, ViewMode::Agenda);
+        
+        // Pastes up to the threshold go straight in; bigger ones ask
+        app.view_mode = ViewMode::List;
+        app.paste_text("One\nTwo\nThree");
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.get_current_todo_list().unwrap().total_count(), 3);
+        app.paste_text("Four\nFive\nSix\nSeven");
+        assert_eq!(app.mode, AppMode::Confirm);
+        app.cancel_confirmation();
+        
+        // Deleting more than two selected todos asks first; cancelling keeps the selection
+        app.enter_visual_mode();
+        app.visual_select_all();
+        app.bulk_delete_todos();
+        assert_eq!(app.mode, AppMode::Confirm);
+        assert_eq!(app.get_confirmation_prompt().as_deref(), Some("Delete 3 selected todos?"));
+        app.cancel_confirmation();
+        assert_eq!(app.mode, AppMode::Visual);
+        app.bulk_delete_todos();
+        app.confirm_pending();
+        assert_eq!(app.get_current_todo_list().unwrap().total_count(), 0);
+        
+        // Bad values keep their defaults and are reported one by one
+        let (config, warnings) = Config::parse("scrolloff = -1\ndefault_view = \"kanban\"\ndate_format = \"%Q\"\ncolour = \"red\"\ntick_rate_ms = 50");
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+        assert_eq!(config.scrolloff, Config::default().scrolloff);
+        assert_eq!(config.date_format, "%m/%d");
+        assert_eq!(config.tick_rate, Duration::from_millis(50));
+        let (_, warnings) = Config::parse("skip_welcome = ");
+        assert_eq!(warnings.len(), 1);
+        
+        println!("✅ config.toml options reach the app state they control");
+    }
+
+    #[test]
+    fn test_themes_cycle_persist_and_fall_back_to_ansi() {
+        // The config file picks the starting theme
+        let (config, warnings) = Config::parse("theme = \"high-contrast\"");
+        assert!(warnings.is_empty());
+        assert_eq!(config.theme, ThemeName::HighContrast);
+        assert_eq!(Config::parse("theme = \"solarized\"").1.len(), 1);
+        let mut app = App::new(config);
+        assert_eq!(app.colors, Theme::high_contrast());
+        
+        // Cycling wraps around and is remembered with the display settings, ahead of the config
+        app.cycle_theme();
+        assert_eq!(app.colors, Theme::tokyo_night());
+        app.cycle_theme();
+        assert_eq!(app.colors, Theme::light());
+        assert!(!app.dirty);
+        let saved = serde_json::to_string(&app.display).unwrap();
+        let mut restarted = App::new(Config { theme: ThemeName::HighContrast, ..Config::default() });
+        restarted.display = serde_json::from_str(&saved).unwrap();
+        restarted.apply_startup_config();
+        assert_eq!(restarted.colors, Theme::light());
+        
+        // Without truecolor every RGB value becomes its nearest ANSI color
+        assert_eq!(nearest_ansi(Color::Rgb(0xdb, 0x4b, 0x4b)), Color::Red);
+        assert_eq!(nearest_ansi(Color::Rgb(0x1a, 0x1b, 0x26)), Color::Black);
+        assert_eq!(nearest_ansi(Color::Cyan), Color::Cyan);
+        restarted.true_color = false;
+        restarted.apply_theme();
+        assert_eq!(restarted.colors.fg, nearest_ansi(Theme::light().fg));
+        assert!(!matches!(restarted.colors.bg, Color::Rgb(..)));
+        
+        println!("✅ Themes cycle, persist and map to ANSI colors without truecolor");
+    }
+
+    #[test]
+    fn test_ascii_glyphs() {
+        // Every Unicode glyph has a plain ASCII counterpart
+        for (unicode, ascii) in Glyphs::UNICODE.all().into_iter().zip(Glyphs::ASCII.all()) {
+            assert!(ascii.is_ascii(), "{:?} has no ASCII replacement ({:?})", unicode, ascii);
+            assert!(!ascii.is_empty() || unicode.ends_with(' '), "{:?} would leave a gap", unicode);
+        }
+        assert_eq!(truncate_with("a longer sentence", 8, "..."), "a lon...");
+        assert_eq!(truncate_with("anything", 2, "..."), "..");
+        
+        // The config flag switches the list, welcome screen and workspace picker to ASCII
+        let (config, warnings) = Config::parse("ascii = true");
+        assert!(warnings.is_empty());
+        let mut app = App::new(config);
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        let parent = todo_list.add_todo("Plan trip".to_string());
+        todo_list.add_child_todo(parent, "Buy adapter".to_string());
+        let done = todo_list.add_todo("Pack bags".to_string());
+        todo_list.get_todo_mut(done).unwrap().complete();
+        let screen = |app: &mut App| {
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+            terminal.draw(|f| crate::ui::draw(f, app)).unwrap();
+            terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>()
+        };
+        let welcome = screen(&mut app);
+        assert!(welcome.contains("> "));
+        assert!(!welcome.chars().any(|c| matches!(c, '📎' | '🎉' | '👋' | '►')));
+        
+        app.mode = AppMode::Normal;
+        let list = screen(&mut app);
+        assert!(list.contains("- [ ] Plan trip"));
+        assert!(list.contains("[x] Pack bags"));
+        assert!(list.contains("`- [ ] Buy adapter"));
+        assert!(!list.chars().any(|c| matches!(c, '○' | '●' | '▼' | '▶' | '└')));
+        
+        app.mode = AppMode::WorkspaceSelection;
+        let picker = screen(&mut app);
+        assert!(picker.contains("<- Return to Welcome Screen"));
+        assert!(!picker.chars().any(|c| matches!(c, '🏠' | '📁' | '←')));
+        
+        println!("✅ ASCII mode replaces every glyph");
+    }
+
+    #[test]
+    fn test_monochrome_renders_without_colors() {
+        use ratatui::{backend::TestBackend, Terminal};
+        
+        let (config, warnings) = Config::parse("monochrome = true\nskip_welcome = true");
+        assert!(warnings.is_empty());
+        let mut app = App::new(config);
+        app.apply_startup_config();
+        assert_eq!(app.mode, AppMode::Normal);
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        let overdue = todo_list.add_todo("Pay rent due:yesterday".to_string());
+        todo_list.add_todo("Water plants #home".to_string());
+        let done = todo_list.add_todo("Book dentist".to_string());
+        todo_list.get_todo_mut(done).unwrap().complete();
+        assert!(todo_list.get_todo(overdue).unwrap().is_overdue());
+        
+        // Cycling themes keeps the colors off
+        app.cycle_theme();
+        assert!(app.colors.monochrome);
+        
+        let render = |app: &mut App| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+            terminal.draw(|f| crate::ui::draw(f, app)).unwrap();
+            terminal.backend().buffer().clone()
+        };
+        for mode in [AppMode::Normal, AppMode::Visual, AppMode::Welcome, AppMode::WorkspaceSelection] {
+            app.mode = mode.clone();
+            let buffer = render(&mut app);
+            for cell in buffer.content() {
+                assert!(!matches!(cell.fg, Color::Rgb(..)) && !matches!(cell.bg, Color::Rgb(..)), "{:?} drew a color", mode);
+            }
+        }
+        
+        // Selection, the mode badge and the overdue marker stand out by modifiers alone
+        app.mode = AppMode::Normal;
+        let buffer = render(&mut app);
+        let row_of = |text: &str| (0..buffer.area.height).find(|y| {
+            (0..buffer.area.width).map(|x| buffer[(x, *y)].symbol()).collect::<String>().contains(text)
+        }).unwrap();
+        let reversed = |y: u16| (0..buffer.area.width).any(|x| buffer[(x, y)].modifier.contains(Modifier::REVERSED));
+        let selected = app.get_selected_todo().unwrap().description.clone();
+        assert!(reversed(row_of(&selected)));
+        assert!(reversed(row_of(" NORMAL ")));
+        let rent = row_of("Pay rent");
+        let marker = (0..buffer.area.width).find(|x| buffer[(*x, rent)].symbol() == "!").unwrap();
+        assert!(buffer[(marker, rent)].modifier.contains(Modifier::UNDERLINED));
+        
+        println!("✅ Monochrome mode draws no colors and keeps selection and warnings visible");
+    }
+
+    #[test]
+    fn test_ctrl_c_and_shutdown_signal_quit() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+        use std::sync::atomic::Ordering;
+        
+        // Ctrl+C takes the same path as `q`
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        crate::events::handle_event(&mut app, Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))).unwrap();
+        assert!(app.should_quit);
+        
+        // A signal only sets the shared flag; the loop notices it on its next pass
+        let mut app = App::new(Config::default());
+        let handler_flag = std::sync::Arc::clone(&app.shutdown_signal);
+        app.poll_shutdown_signal();
+        assert!(!app.should_quit);
+        handler_flag.store(true, Ordering::Relaxed);
+        app.poll_shutdown_signal();
+        assert!(app.should_quit);
+        
+        println!("✅ Ctrl+C and termination signals quit through the normal exit");
+    }
+
+    #[test]
+    fn test_autosave_dirty_tracking() {
+        use std::time::{Duration, Instant};
+        
+        let mut app = App::new(Config::default());
+        app.mode = AppMode::Normal;
+        let start = app.last_saved;
+        let interval = app.config.autosave_interval;
+        assert!(!app.dirty);
+        assert!(!app.autosave_due(start + interval));
+        
+        // Representative edits each leave unsaved changes behind
+        let saved = |app: &mut App| app.finish_autosave(Instant::now(), Ok(None));
+        type Edit = (&'static str, fn(&mut App));
+        let edits: [Edit; 6] = [
+            ("add", |app| { app.mode = AppMode::Insert; app.input_buffer.set("Call bank #admin".to_string()); app.submit_input(); }),
+            ("complete", |app| app.toggle_todo_complete()),
+            ("undo", |app| app.undo()),
+            ("notes", |app| { app.enter_notes_mode(); app.notes_buffer.set("Ask about fees".to_string()); app.save_notes(); }),
+            ("timer", |app| app.toggle_timer()),
+            ("workspace", |app| app.create_new_workspace("Errands".to_string(), None)),
+        ];
+        for (name, edit) in edits {
+            saved(&mut app);
+            edit(&mut app);
+            assert!(app.dirty, "{} didn't mark the data dirty", name);
+        }
+        
+        // ...while borrowing a list, an edit with nothing to act on, or a display setting
+        // (saved in its own file) leaves it clean
+        let home = app.workspace_manager.get_current_workspace_id().unwrap();
+        assert!(app.workspace_manager.switch_workspace_by_name("Errands"));
+        saved(&mut app);
+        let generation = app.generation;
+        let _ = app.get_current_todo_list_mut();
+        let _ = app.get_selected_todo_list_mut();
+        app.delete_selected_todo();
+        app.increase_priority();
+        app.toggle_todo_complete();
+        app.toggle_detail_pane();
+        assert!(!app.dirty && !app.save_now);
+        assert_eq!(app.generation, generation);
+        app.workspace_manager.switch_workspace(&home);
+        
+        // Ordinary changes wait for the interval; deletions are saved on the next pass
+        saved(&mut app);
+        app.mark_dirty();
+        let now = app.last_saved;
+        assert!(!app.autosave_due(now + Duration::from_millis(10)));
+        assert!(app.autosave_due(now + interval));
+        app.delete_selected_todo();
+        assert!(app.autosave_due(now));
+        
+        // A failed write keeps the changes and reports it
+        app.finish_autosave(now, Err(std::io::Error::other("disk full")));
+        assert!(app.dirty && !app.save_now);
+        assert!(app.message().unwrap().contains("disk full"));
+        
+        println!("✅ Edits mark the data dirty and autosave waits or saves at once");
+    }
+
+    #[test]
+    fn test_atomic_saves_keep_rotating_backups() {
+        let dir = TempDir::new("backups");
+        let mut storage = Storage::new_with_dir(dir.clone()).unwrap();
+        let workspace_count = |workspace_manager: &WorkspaceManager| workspace_manager.workspaces.len();
+        
+        // Within the backup interval changes are saved but the backups stay as they are
+        let mut workspace_manager = WorkspaceManager::new();
+        workspace_manager.create_workspace("Workspace 1".to_string(), None);
+        storage.save_workspace_manager(&workspace_manager).unwrap();
+        assert!(!storage.backup_path(1).exists());
+        workspace_manager.create_workspace("Workspace 2".to_string(), None);
+        storage.save_workspace_manager(&workspace_manager).unwrap();
+        assert_eq!(workspace_count(&storage.restore_from_backup(1).unwrap()), 1);
+        workspace_manager.create_workspace("Workspace 3".to_string(), None);
+        storage.save_workspace_manager(&workspace_manager).unwrap();
+        assert_eq!(workspace_count(&storage.restore_from_backup(1).unwrap()), 1);
+        assert!(!storage.backup_path(2).exists());
+        
+        // Once it has passed, the file as it was before the save becomes backup 1, pushing older
+        // ones down until five are kept; saving unchanged data doesn't count
+        storage.set_backup_interval(std::time::Duration::ZERO);
+        for n in 4..=8 {
+            workspace_manager.create_workspace(format!("Workspace {}", n), None);
+            storage.save_workspace_manager(&workspace_manager).unwrap();
+            storage.save_workspace_manager(&workspace_manager).unwrap();
+        }
+        assert_eq!(workspace_count(&storage.restore_from_backup(1).unwrap()), 7);
+        assert_eq!(workspace_count(&storage.restore_from_backup(5).unwrap()), 3);
+        assert!(!storage.backup_path(6).exists());
+        assert!(!dir.join("workspaces.json.tmp").exists());
+        
+        // A backup that can't be rotated doesn't stop the save
+        std::fs::remove_file(storage.backup_path(5)).unwrap();
+        std::fs::create_dir_all(storage.backup_path(5).join("in the way")).unwrap();
+        workspace_manager.create_workspace("Workspace 9".to_string(), None);
+        storage.save_workspace_manager(&workspace_manager).unwrap();
+        assert_eq!(workspace_count(&storage.load_workspace_manager().unwrap().0), 9);
+        std::fs::remove_dir_all(storage.backup_path(5)).unwrap();
+        
+        // A damaged main file loads the newest backup that parses, with a warning
+        std::fs::write(storage.get_workspace_file_path(), "{\"workspaces\": {").unwrap();
+        std::fs::write(storage.backup_path(1), [0xff, 0xfe]).unwrap();
+        let (loaded, warnings) = storage.load_workspace_manager().unwrap();
+        assert_eq!(workspace_count(&loaded), 6);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("backup 2"));
+        
+        // An intact file loads without warnings
+        storage.save_workspace_manager(&workspace_manager).unwrap();
+        let (loaded, warnings) = storage.load_workspace_manager().unwrap();
+        assert_eq!(workspace_count(&loaded), 9);
+        assert!(warnings.is_empty());
+        
+        println!("✅ Saves are atomic and a damaged file falls back to the newest good backup");
+    }
+
+    #[test]
+    fn test_schema_versions_migrate_or_refuse() {
+        use crate::storage::SCHEMA_VERSION;
+        use crate::todo::TodoStatus;
+        
+        let dir = TempDir::new("schema");
+        let load = |file: &str, contents: &str| {
+            let _ = std::fs::remove_dir_all(&dir);
+            let storage = Storage::new_with_dir(dir.clone()).unwrap();
+            std::fs::write(dir.join(file), contents).unwrap();
+            storage.load_workspace_manager().map(|(workspace_manager, _)| workspace_manager)
+        };
+        let only_list = |workspace_manager: &WorkspaceManager| workspace_manager.get_current_todo_list().unwrap().clone();
+        
+        // Version 0: the single legacy list becomes the Personal workspace, missing fields defaulted
+        let v0 = load("todos.json", include_str!("../tests/fixtures/todos-v0.json")).unwrap();
+        assert_eq!(v0.get_current_workspace().unwrap().name, "Personal");
+        let todos = only_list(&v0);
+        assert_eq!(todos.get_todo(1).unwrap().children, vec![2]);
+        let child = todos.get_todo(2).unwrap();
+        assert_eq!(child.status, TodoStatus::Completed);
+        assert!(child.expanded && child.tags.is_empty() && child.notes.is_none());
+        
+        // Version 1 from before the envelope: read as it stands
+        let bare = load("workspaces.json", include_str!("../tests/fixtures/workspaces-v1-bare.json")).unwrap();
+        assert_eq!(bare.workspaces.len(), 2);
+        assert_eq!(bare.get_current_workspace().unwrap().name, "Work");
+        assert!(bare.saved_filters.is_empty());
+        assert_eq!(bare.workspace_todos["ws_1"].get_todo(1).unwrap().recurrence, RecurrencePattern::Weekly);
+        
+        // Version 1 in its envelope, with a field this build doesn't know about
+        let v1 = load("workspaces.json", include_str!("../tests/fixtures/workspaces-v1.json")).unwrap();
+        let todos = only_list(&v1);
+        assert_eq!(todos.sort_mode, SortMode::DueDate);
+        assert_eq!(todos.get_todo(1).unwrap().notes.as_deref(), Some("Ask about the boiler too"));
+        
+        // Saving writes the current version in the envelope, and reads back the same
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+        storage.save_workspace_manager(&v1).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("workspaces.json")).unwrap()).unwrap();
+        assert_eq!(saved["version"], SCHEMA_VERSION);
+        assert_eq!(saved["data"]["current_workspace"], "ws_1");
+        assert_eq!(only_list(&storage.load_workspace_manager().unwrap().0).get_todo(1).unwrap().priority, 3);
+        
+        // A newer version is refused with an error that says why, not treated as damage
+        let error = load("workspaces.json", include_str!("../tests/fixtures/workspaces-v99.json")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+        assert!(error.to_string().contains("newer Paperclip"));
+        
+        println!("✅ Every data version loads through the migrations; newer ones are refused");
+    }
+
+    #[test]
+    fn test_corrupt_workspace_file_is_preserved() {
+        let dir = TempDir::new("corrupt");
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+        let damaged = "{\"version\": 1, \"data\": {\"workspaces\": {\"ws_1\": ";
+        std::fs::write(storage.get_workspace_file_path(), damaged).unwrap();
+        
+        // With no backups the session starts empty, and the warning names where the file went
+        let (workspace_manager, warnings) = storage.load_workspace_manager().unwrap();
+        assert_eq!(workspace_manager.workspaces.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("started empty"));
+        let moved: Vec<_> = std::fs::read_dir(&dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with("workspaces.json.corrupt-"))
+            .collect();
+        assert_eq!(moved.len(), 1);
+        assert!(warnings[0].contains(&moved[0].display().to_string()));
+        
+        // Saving the new session leaves the damaged file exactly as it was
+        let mut app = App::new(Config::default());
+        app.workspace_manager = workspace_manager;
+        app.data_warning = warnings.first().cloned();
+        app.get_current_todo_list_mut().unwrap().add_todo("Start over".to_string());
+        storage.save_workspace_manager(&app.workspace_manager).unwrap();
+        assert_eq!(std::fs::read_to_string(&moved[0]).unwrap(), damaged);
+        assert_eq!(storage.load_workspace_manager().unwrap().0.get_current_todo_list().unwrap().todos.len(), 1);
+        
+        // The warning stays up after other messages are cleared
+        app.mode = AppMode::Normal;
+        app.clear_message();
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(400, 30)).unwrap();
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("couldn't be read"));
+        
+        println!("✅ A damaged data file is moved aside, never overwritten, and reported");
+    }
+
+    #[test]
+    fn test_cli_data_dir_flag_and_env() {
+        use crate::cli::Args;
+        use std::ffi::OsString;
+        use std::path::PathBuf;
+
+        let parse = |args: &[&str]| Args::parse(args.iter().map(OsString::from));
+
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+        assert!(parse(&["--help"]).unwrap().help);
+        assert_eq!(parse(&["--data-dir", "/tmp/a"]).unwrap().data_dir, Some(PathBuf::from("/tmp/a")));
+        assert_eq!(parse(&["--data-dir=/tmp/b"]).unwrap().data_dir, Some(PathBuf::from("/tmp/b")));
+        assert!(parse(&["--data-dir"]).is_err());
+        assert!(parse(&["--data-dir="]).is_err());
+        assert!(parse(&["--bogus"]).unwrap_err().contains("--bogus"));
+
+        // The flag beats the environment, and an empty variable counts as unset
+        let flagged = parse(&["--data-dir", "/tmp/flag"]).unwrap();
+        assert_eq!(flagged.resolve_data_dir(Some(OsString::from("/tmp/env"))), Some(PathBuf::from("/tmp/flag")));
+        let plain = Args::default();
+        assert_eq!(plain.resolve_data_dir(Some(OsString::from("/tmp/env"))), Some(PathBuf::from("/tmp/env")));
+        assert_eq!(plain.resolve_data_dir(Some(OsString::new())), None);
+        assert_eq!(plain.resolve_data_dir(None), None);
+
+        println!("✅ --data-dir and PAPERCLIP_DATA_DIR resolve in the right order");
+    }
+
+    #[test]
+    fn test_storage_custom_data_dir_is_created_and_used() {
+        let dir = TempDir::new("datadir");
+        let nested = dir.join("synced").join("paperclip");
+
+        let storage = Storage::new_with_dir(nested.clone()).unwrap();
+        assert!(nested.is_dir());
+        assert_eq!(storage.data_dir(), nested.as_path());
+        assert!(storage.get_workspace_file_path().starts_with(&nested));
+
+        let mut manager = WorkspaceManager::new();
+        manager.create_workspace("Elsewhere".to_string(), None);
+        storage.save_workspace_manager(&manager).unwrap();
+        assert!(nested.join("workspaces.json").exists());
+
+        let (loaded, warnings) = Storage::new_with_dir(nested.clone()).unwrap().load_workspace_manager().unwrap();
+        assert!(warnings.is_empty());
+        assert!(loaded.workspaces.values().any(|w| w.name == "Elsewhere"));
+
+        println!("✅ Storage keeps its files in a custom data directory");
+    }
+
+    #[test]
+    fn test_merge_todo_list_additions_and_conflicts() {
+        let mut base = TodoList::new();
+        let shared = base.add_todo("Shared".to_string());
+        let edited_here = base.add_todo("Edited here".to_string());
+        let edited_there = base.add_todo("Edited there".to_string());
+        let mut ours = base.clone();
+        let mut theirs = base.clone();
+
+        // Each side adds a todo and both get the next id; they are different todos
+        let mine = ours.add_todo("Added here".to_string());
+        let other = theirs.add_todo("Added there".to_string());
+        assert_eq!(mine, other);
+        theirs.todos.get_mut(&other).unwrap().created_at -= chrono::Duration::seconds(30);
+
+        ours.todos.get_mut(&edited_here).unwrap().priority = 4;
+        theirs.todos.get_mut(&edited_there).unwrap().priority = 2;
+        // Changed on both sides: completing it there is the newer change
+        ours.todos.get_mut(&shared).unwrap().priority = 1;
+        theirs.todos.get_mut(&shared).unwrap().complete();
+
+        let summary = ours.merge(&base, theirs);
+        assert_eq!(summary, MergeSummary { added: 1, updated: 2, removed: 0 });
+        assert_eq!(ours.todos.len(), 5);
+        assert_eq!(ours.todos[&mine].description, "Added here");
+        let renumbered = ours.todos.values().find(|todo| todo.description == "Added there").unwrap();
+        assert_ne!(renumbered.id, mine);
+        assert!(ours.next_id > renumbered.id);
+        assert_eq!(ours.todos[&edited_here].priority, 4);
+        assert_eq!(ours.todos[&edited_there].priority, 2);
+        assert!(ours.todos[&shared].completed_at.is_some());
+
+        println!("✅ Merging keeps todos added on both sides and the newer of conflicting edits");
+    }
+
+    #[test]
+    fn test_merge_todo_list_removals_and_subtasks() {
+        let mut base = TodoList::new();
+        let parent = base.add_todo("Parent".to_string());
+        let gone_there = base.add_todo("Removed there".to_string());
+        let gone_here = base.add_todo("Removed here".to_string());
+        let kept = base.add_todo("Removed there, edited here".to_string());
+        let mut ours = base.clone();
+        let mut theirs = base.clone();
+
+        theirs.remove_todo(gone_there);
+        theirs.remove_todo(kept);
+        ours.remove_todo(gone_here);
+        ours.todos.get_mut(&kept).unwrap().priority = 3;
+        let child = theirs.add_child_todo(parent, "Subtask from elsewhere".to_string()).unwrap();
+
+        let summary = ours.merge(&base, theirs);
+        // The parent counts as updated: its children changed there
+        assert_eq!(summary, MergeSummary { added: 1, updated: 1, removed: 1 });
+        assert!(!ours.todos.contains_key(&gone_there));
+        assert!(!ours.todos.contains_key(&gone_here));
+        assert!(ours.todos.contains_key(&kept));
+        assert_eq!(ours.todos[&child].parent_id, Some(parent));
+        assert_eq!(ours.todos[&parent].children, vec![child]);
+
+        // Merging the same copy again changes nothing
+        let again = ours.clone();
+        assert!(ours.merge(&again.clone(), again).is_empty());
+
+        println!("✅ Merging applies removals from both sides and links new subtasks");
+    }
+
+    #[test]
+    fn test_merge_workspace_manager() {
+        let mut base = WorkspaceManager::new();
+        let home = base.create_workspace("Home".to_string(), None);
+        let old = base.create_workspace("Old".to_string(), None);
+        let mut ours = base.clone();
+        let mut theirs = base.clone();
+
+        // Both create a workspace with the next id
+        let here = ours.create_workspace("Here".to_string(), None);
+        let there = theirs.create_workspace("There".to_string(), None);
+        assert_eq!(here, there);
+        theirs.workspaces.get_mut(&there).unwrap().created_at -= chrono::Duration::seconds(30);
+        theirs.workspace_todos.get_mut(&there).unwrap().add_todo("Todo there".to_string());
+        theirs.workspace_todos.get_mut(&home).unwrap().add_todo("New at home".to_string());
+        theirs.rename_workspace(&home, "House".to_string());
+        theirs.delete_workspace(&old);
+
+        let summary = ours.merge(&base, theirs);
+        assert_eq!(summary, MergeSummary { added: 2, updated: 1, removed: 1 });
+        assert_eq!(ours.workspaces[&home].name, "House");
+        assert_eq!(ours.workspace_todos[&home].total_count(), 1);
+        assert!(!ours.workspaces.contains_key(&old));
+        let moved = ours.find_workspace_id_by_name("There").unwrap();
+        assert_ne!(moved, here);
+        assert_eq!(ours.workspace_todos[&moved].total_count(), 1);
+        assert_eq!(ours.workspaces[&here].name, "Here");
+        assert_eq!(ours.current_workspace, Some(home));
+
+        println!("✅ Merging workspaces adds, renames and removes what changed elsewhere");
+    }
+
+    #[test]
+    fn test_storage_sync_merges_external_changes() {
+        let dir = TempDir::new("sync");
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+        let mut manager = WorkspaceManager::new();
+        let id = manager.create_workspace("Shared".to_string(), None);
+        storage.save_workspace_manager(&manager).unwrap();
+
+        // Nothing changed on disk: a plain save
+        manager.workspace_todos.get_mut(&id).unwrap().add_todo("Mine".to_string());
+        assert_eq!(storage.sync_workspace_manager(&mut manager).unwrap(), None);
+
+        // Another machine writes the file
+        let elsewhere = Storage::new_with_dir(dir.clone()).unwrap();
+        let (mut theirs, _) = elsewhere.load_workspace_manager().unwrap();
+        let mut added = Todo::new(2, "Theirs".to_string());
+        added.created_at -= chrono::Duration::seconds(5);
+        theirs.workspace_todos.get_mut(&id).unwrap().todos.insert(2, added);
+        elsewhere.save_workspace_manager(&theirs).unwrap();
+
+        manager.workspace_todos.get_mut(&id).unwrap().add_todo("Also mine".to_string());
+        let note = storage.sync_workspace_manager(&mut manager).unwrap().unwrap();
+        assert!(note.contains("1 added"), "{}", note);
+        let (saved, _) = Storage::new_with_dir(dir.clone()).unwrap().load_workspace_manager().unwrap();
+        let mut descriptions: Vec<String> = saved.workspace_todos[&id].todos.values().map(|todo| todo.description.clone()).collect();
+        descriptions.sort();
+        assert_eq!(descriptions, ["Also mine", "Mine", "Theirs"]);
+
+        // A file damaged elsewhere is moved aside rather than merged or saved over
+        std::fs::write(storage.get_workspace_file_path(), "{ not json").unwrap();
+        let note = storage.sync_workspace_manager(&mut manager).unwrap().unwrap();
+        assert!(note.contains("moved to"), "{}", note);
+        assert!(Storage::new_with_dir(dir.clone()).unwrap().load_workspace_manager().is_ok());
+
+        println!("✅ Saving merges in changes written to the file elsewhere");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_data_lock_refuses_live_owner_and_breaks_with_force() {
+        use crate::storage::{DataLock, LOCK_FILE};
+
+        let dir = TempDir::new("lock-live");
+        std::fs::create_dir_all(&dir).unwrap();
+        let lock_file = dir.join(LOCK_FILE);
+
+        // PID 1 is always running
+        std::fs::write(&lock_file, "1\n").unwrap();
+        let err = DataLock::acquire(&dir, false).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert!(err.to_string().contains("PID 1"));
+        assert_eq!(std::fs::read_to_string(&lock_file).unwrap(), "1\n");
+
+        let lock = DataLock::acquire(&dir, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&lock_file).unwrap().trim(), std::process::id().to_string());
+        drop(lock);
+        assert!(!lock_file.exists());
+
+        println!("✅ A lock held by a running process stops startup unless forced");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_data_lock_takes_over_stale_locks() {
+        use crate::storage::{DataLock, LOCK_FILE};
+
+        let dir = TempDir::new("lock-stale");
+        let lock_file = dir.join(LOCK_FILE);
+
+        // A process that has exited, a damaged file and our own PID from an earlier run are all stale
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let exited = child.id();
+        child.wait().unwrap();
+        let mut storage = Storage::new_with_dir(dir.clone()).unwrap();
+        for stale in [exited.to_string(), "not a pid".to_string(), std::process::id().to_string()] {
+            std::fs::write(&lock_file, &stale).unwrap();
+            let lock = DataLock::acquire(&dir, false).unwrap();
+            assert_eq!(std::fs::read_to_string(&lock_file).unwrap().trim(), std::process::id().to_string());
+            drop(lock);
+        }
+        // Nothing is left beside the lock once it's taken
+        let lock = DataLock::acquire(&dir, false).unwrap();
+        let mut names: Vec<String> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        names.sort();
+        assert_eq!(names, vec![LOCK_FILE.to_string()]);
+        drop(lock);
+
+        // The Storage holds its lock until it is dropped
+        storage.lock(false).unwrap();
+        assert_eq!(storage.lock_path(), lock_file);
+        assert!(lock_file.exists());
+        drop(storage);
+        assert!(!lock_file.exists());
+
+        println!("✅ Stale locks are taken over and released on drop");
+    }
+
+    #[test]
+    fn test_cli_force_flag() {
+        use crate::cli::Args;
+        let args = Args::parse(["--force", "--data-dir", "/tmp/x"].iter().map(std::ffi::OsString::from)).unwrap();
+        assert!(args.force);
+        assert!(!Args::default().force);
+        println!("✅ --force is parsed");
+    }
+
+    #[test]
+    fn test_todotxt_round_trip() {
+        use crate::export::to_todotxt;
+        use crate::import::from_todotxt;
+
+        let text = include_str!("../tests/fixtures/todo.txt");
+        let (todo_list, skipped) = from_todotxt(text);
+        // A line of only metadata, a due: that isn't a date and a bare `x` can't be read
+        assert_eq!(skipped, 3);
+        assert_eq!(todo_list.todos.len(), 5);
+
+        let bank = &todo_list.todos[&1];
+        assert_eq!(bank.description, "Call the bank about the mortgage finance phone");
+        assert_eq!(bank.priority, 5);
+        assert!(bank.tags.contains("finance"));
+        assert!(bank.contexts.contains("phone"));
+        assert_eq!(bank.due_date.unwrap().format("%Y-%m-%d").to_string(), "2024-03-08");
+        assert_eq!(bank.created_at.format("%Y-%m-%d").to_string(), "2024-03-01");
+        assert_eq!(todo_list.todos[&3].priority, 0);
+        let dentist = &todo_list.todos[&4];
+        assert_eq!(dentist.status, TodoStatus::Completed);
+        assert_eq!(dentist.completed_at.unwrap().format("%Y-%m-%d").to_string(), "2024-03-05");
+        assert_eq!(todo_list.todos[&5].priority, 1);
+
+        // Every readable line comes back out exactly as it went in
+        let readable: String = text.lines()
+            .filter(|line| !line.is_empty())
+            .take(5)
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert_eq!(to_todotxt(&todo_list), readable);
+
+        println!("✅ todo.txt files survive an import and export unchanged");
+    }
+
+    #[test]
+    fn test_todotxt_export_from_typed_todos() {
+        use crate::export::{to_todotxt, todotxt_priority};
+
+        assert_eq!(todotxt_priority(5), Some('A'));
+        assert_eq!(todotxt_priority(1), Some('E'));
+        assert_eq!(todotxt_priority(0), None);
+
+        let mut todo_list = TodoList::new();
+        let id = todo_list.add_todo("Fix bug #urgent @work due:2024-12-25".to_string());
+        todo_list.get_todo_mut(id).unwrap().priority = 4;
+        let done = todo_list.add_todo("Ship it".to_string());
+        todo_list.get_todo_mut(done).unwrap().complete();
+        todo_list.add_child_todo(id, "Write the test".to_string());
+
+        let created = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let lines: Vec<String> = to_todotxt(&todo_list).lines().map(str::to_string).collect();
+        assert_eq!(lines, [
+            format!("(B) {} Fix bug +urgent @work due:2024-12-25", created),
+            format!("x {} {} Ship it", created, created),
+            format!("{} Write the test", created),
+        ]);
+
+        println!("✅ todo.txt export writes priorities, projects, contexts and due dates");
+    }
+
+    #[test]
+    fn test_todotxt_completed_without_completion_time() {
+        use crate::export::to_todotxt;
+        use crate::import::from_todotxt;
+        use chrono::TimeZone;
+
+        let mut todo_list = TodoList::new();
+        let id = todo_list.add_todo("Renew passport".to_string());
+        let todo = todo_list.get_todo_mut(id).unwrap();
+        todo.created_at = chrono::Local.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        todo.status = TodoStatus::Completed;
+        todo.completed_at = None;
+
+        // The creation date is written as the completion date too, not in its place
+        let text = to_todotxt(&todo_list);
+        assert_eq!(text, "x 2024-03-01 2024-03-01 Renew passport\n");
+
+        let (imported, skipped) = from_todotxt(&text);
+        assert_eq!(skipped, 0);
+        let todo = &imported.todos[&1];
+        assert_eq!(todo.status, TodoStatus::Completed);
+        assert_eq!(todo.completed_at.unwrap().format("%Y-%m-%d").to_string(), "2024-03-01");
+        assert_eq!(todo.created_at.format("%Y-%m-%d").to_string(), "2024-03-01");
+        assert_eq!(todo.description, "Renew passport");
+        assert_eq!(to_todotxt(&imported), text);
+
+        println!("✅ A completed todo without a completion time keeps its dates through todo.txt");
+    }
+
+    #[test]
+    fn test_import_todotxt_into_workspace() {
+        let dir = TempDir::new("todotxt");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("todo.txt");
+        std::fs::write(&file, "(A) Imported first\nnot due:valid\nImported second +home\n").unwrap();
+
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let existing = app.get_current_todo_list_mut().unwrap().add_todo("Already here".to_string());
+
+        app.enter_import_mode();
+        assert_eq!(app.mode, AppMode::ImportPath);
+        app.input_buffer.set(file.display().to_string());
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.message().unwrap().contains("Imported 2 todos"));
+        assert!(app.message().unwrap().contains("skipped 1 unreadable line"));
+
+        let todo_list = app.get_current_todo_list().unwrap();
+        assert_eq!(todo_list.todos.len(), 3);
+        assert_eq!(todo_list.todos[&existing].description, "Already here");
+        let first = todo_list.todos.values().find(|todo| todo.description == "Imported first").unwrap();
+        assert_ne!(first.id, existing);
+        assert_eq!(first.priority, 5);
+
+        // One undo takes the whole import back out
+        app.undo();
+        assert_eq!(app.get_current_todo_list().unwrap().todos.len(), 1);
+
+        // Export writes the workspace where the prompt says
+        app.enter_export_mode();
+        app.select_from_popup();
+        assert_eq!(app.mode, AppMode::ExportPath);
+        assert_eq!(app.input_buffer.content, "personal.todo.txt");
+        let out = dir.join("out.txt");
+        app.input_buffer.set(out.display().to_string());
+        app.submit_prompt();
+        assert!(std::fs::read_to_string(&out).unwrap().contains("Already here"));
+
+        // Exporting over that file asks first; no goes back to the path
+        app.get_current_todo_list_mut().unwrap().add_todo("Added later".to_string());
+        app.enter_export_mode();
+        app.select_from_popup();
+        app.input_buffer.set(out.display().to_string());
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::Confirm);
+        assert!(app.get_confirmation_prompt().unwrap().contains("already exists"));
+        app.cancel_confirmation();
+        assert_eq!(app.mode, AppMode::ExportPath);
+        assert_eq!(app.input_buffer.content, out.display().to_string());
+        assert!(!std::fs::read_to_string(&out).unwrap().contains("Added later"));
+        app.submit_prompt();
+        app.confirm_pending();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(std::fs::read_to_string(&out).unwrap().contains("Added later"));
+
+        println!("✅ todo.txt files import into the workspace with fresh ids and export back out");
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        use crate::export::{csv_field, csv_row};
+
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+        assert_eq!(csv_field(" padded "), "\" padded \"");
+        assert_eq!(csv_field("\""), "\"\"\"\"");
+        assert_eq!(csv_row(&["1", "x,y", ""]), "1,\"x,y\",\r\n");
+
+        println!("✅ CSV fields are quoted and escaped per RFC 4180");
+    }
+
+    #[test]
+    fn test_todos_and_time_entries_csv() {
+        use crate::export::{time_entries_csv, todos_csv};
+
+        let mut manager = WorkspaceManager::new();
+        let work = manager.create_workspace("Work, Inc".to_string(), None);
+        let home = manager.create_workspace("Home".to_string(), None);
+        let work_list = manager.workspace_todos.get_mut(&work).unwrap();
+        let id = work_list.add_todo("Quote \"the\" client, then invoice #billing @office".to_string());
+        let todo = work_list.get_todo_mut(id).unwrap();
+        todo.priority = 3;
+        todo.add_manual_time(1800, Some("call, then\nemail".to_string()));
+        todo.add_manual_time(-600, None);
+        manager.workspace_todos.get_mut(&home).unwrap().add_todo("Water plants".to_string());
+
+        let csv = todos_csv(&manager);
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(rows[0], "id,workspace,description,status,priority,tags,contexts,created_at,completed_at,due_date,total_seconds");
+        assert!(rows[1].starts_with("1,\"Work, Inc\",\"Quote \"\"the\"\" client, then invoice billing office\",Pending,3,billing,office,"));
+        assert!(rows[1].ends_with(",,,1200"));
+        assert!(rows[2].starts_with("1,Home,Water plants,Pending,0,,,"));
+        assert_eq!(rows.len(), 4); // Header, two todos and the empty string after the last CRLF
+
+        let csv = time_entries_csv(&manager);
+        assert!(csv.starts_with("workspace,todo_id,description,start,end,seconds,note\r\n"));
+        assert!(csv.contains(",1800,\"call, then\nemail\"\r\n"));
+        assert!(csv.contains(",-600,\r\n"));
+        assert_eq!(csv.matches("\"Work, Inc\",1,").count(), 2);
+
+        println!("✅ Todos and time entries export as CSV");
+    }
+
+    #[test]
+    fn test_ics_text_escaping() {
+        use crate::export::ics_text;
+
+        assert_eq!(ics_text("plain"), "plain");
+        assert_eq!(ics_text("a,b;c"), "a\\,b\\;c");
+        assert_eq!(ics_text("back\\slash"), "back\\\\slash");
+        assert_eq!(ics_text("two\nlines"), "two\\nlines");
+        assert_eq!(ics_text("windows\r\nline"), "windows\\nline");
+        assert_eq!(ics_text("old mac\rline"), "old mac\\nline");
+        assert_eq!(ics_text("colon: fine"), "colon: fine");
+
+        println!("✅ iCalendar text values are escaped per RFC 5545");
+    }
+
+    #[test]
+    fn test_ics_line_folding() {
+        use crate::export::fold_ics_line;
+
+        let short = "SUMMARY:short";
+        assert_eq!(fold_ics_line(short), short);
+        let exact = format!("SUMMARY:{}", "x".repeat(67));
+        assert_eq!(exact.len(), 75);
+        assert_eq!(fold_ics_line(&exact), exact);
+
+        let long = format!("SUMMARY:{}", "y".repeat(200));
+        let folded = fold_ics_line(&long);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        assert_eq!(lines[0].len(), 75);
+        let unfolded: String = lines.iter().enumerate().map(|(i, line)| if i == 0 { *line } else { &line[1..] }).collect();
+        assert_eq!(unfolded, long);
+
+        // Three-byte characters are never split across lines
+        let wide = format!("SUMMARY:{}", "€".repeat(60));
+        let folded = fold_ics_line(&wide);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert_eq!(lines[0].len(), 8 + 3 * 22);
+        assert_eq!(folded.replace("\r\n ", ""), wide);
+
+        println!("✅ iCalendar lines fold at 75 octets without splitting characters");
+    }
+
+    #[test]
+    fn test_to_ics_exports_due_todos() {
+        use crate::export::{ics_priority, to_ics};
+
+        assert_eq!(ics_priority(5), Some(1));
+        assert_eq!(ics_priority(3), Some(5));
+        assert_eq!(ics_priority(1), Some(9));
+        assert_eq!(ics_priority(0), None);
+
+        let mut manager = WorkspaceManager::new();
+        let id = manager.create_workspace("Work".to_string(), None);
+        let todo_list = manager.workspace_todos.get_mut(&id).unwrap();
+        let due = todo_list.add_todo("Pay rent, then relax #home #money due:2030-01-31".to_string());
+        let todo = todo_list.get_todo_mut(due).unwrap();
+        todo.priority = 4;
+        todo.recurrence = RecurrencePattern::Monthly;
+        todo.set_notes(Some("Bank; transfer\nby noon".to_string()));
+        let done = todo_list.add_todo("Filed taxes due:2030-01-15".to_string());
+        todo_list.get_todo_mut(done).unwrap().complete();
+        todo_list.add_todo("No due date".to_string());
+
+        let ics = to_ics(&manager);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VTODO").count(), 2);
+        assert!(!ics.contains("No due date"));
+        assert!(ics.contains("SUMMARY:Pay rent\\, then relax home money\r\n"));
+        assert!(ics.contains("DESCRIPTION:Bank\\; transfer\\nby noon\r\n"));
+        assert!(ics.contains("PRIORITY:3\r\n"));
+        assert!(ics.contains("CATEGORIES:home,money\r\n"));
+        assert!(ics.contains("RRULE:FREQ=MONTHLY\r\n"));
+        assert!(ics.contains("STATUS:NEEDS-ACTION\r\n"));
+        assert!(ics.contains("STATUS:COMPLETED\r\nCOMPLETED:"));
+        assert!(ics.contains(&format!("UID:{}-{}-", id, due)));
+        let due_utc = manager.workspace_todos[&id].todos[&due].due_date.unwrap().with_timezone(&chrono::Utc);
+        assert!(ics.contains(&format!("DUE:{}\r\n", due_utc.format("%Y%m%dT%H%M%SZ"))));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+
+        // The file can be written on every save
+        let (config, warnings) = Config::parse("ics_path = \"/tmp/paperclip.ics\"");
+        assert!(warnings.is_empty());
+        assert_eq!(config.ics_path, Some(std::path::PathBuf::from("/tmp/paperclip.ics")));
+
+        println!("✅ Due todos export as iCalendar VTODOs");
+    }
+
+    #[test]
+    fn test_shared_workspace_gets_fresh_ids() {
+        let dir = TempDir::new("share");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("garden.paperclip.json");
+
+        let mut theirs = WorkspaceManager::new();
+        let garden = theirs.create_workspace("Garden".to_string(), Some("Allotment".to_string()));
+        let todo_list = theirs.workspace_todos.get_mut(&garden).unwrap();
+        let parent = todo_list.add_todo("Plant beans #spring".to_string());
+        let child = todo_list.add_child_todo(parent, "Buy seeds".to_string()).unwrap();
+        todo_list.get_todo_mut(child).unwrap().set_notes(Some("Runner beans".to_string()));
+        Storage::export_workspace(&theirs, &garden, &file).unwrap();
+        assert!(Storage::export_workspace(&theirs, "ws_missing", &dir.join("none.json")).is_err());
+
+        let (workspace, todos) = Storage::import_workspace(&file).unwrap();
+        assert_eq!(workspace.name, "Garden");
+        assert_eq!(workspace.description.as_deref(), Some("Allotment"));
+        assert_eq!(todos.todos.len(), 2);
+
+        // Ours already uses the exporter's workspace id and todo ids
+        let mut ours = WorkspaceManager::new();
+        let first = ours.create_workspace("Work".to_string(), None);
+        assert_eq!(first, garden);
+        let mine = ours.workspace_todos.get_mut(&first).unwrap().add_todo("Mine".to_string());
+        assert_eq!(mine, parent);
+
+        let imported = ours.import_workspace(workspace, todos);
+        assert_ne!(imported, first);
+        assert_eq!(ours.workspaces[&imported].id, imported);
+        assert_eq!(ours.workspaces[&imported].order, ours.workspaces[&first].order + 1);
+        assert_eq!(ours.workspace_todos[&first].todos.len(), 1);
+        let todo_list = &ours.workspace_todos[&imported];
+        let beans = todo_list.todos.values().find(|todo| todo.description.starts_with("Plant beans")).unwrap();
+        let seeds = todo_list.todos.values().find(|todo| todo.description == "Buy seeds").unwrap();
+        assert_eq!(beans.children, vec![seeds.id]);
+        assert_eq!(seeds.parent_id, Some(beans.id));
+        assert_eq!(seeds.notes.as_deref(), Some("Runner beans"));
+        assert_eq!(todo_list.next_id, 3);
+        assert_eq!(ours.unused_workspace_name("Garden"), "Garden (2)");
+        assert_eq!(ours.unused_workspace_name("Home"), "Home");
+
+        // Files that aren't a shared workspace, or come from a newer version, are refused
+        let mut newer: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        newer["version"] = serde_json::json!(999);
+        std::fs::write(&file, newer.to_string()).unwrap();
+        assert_eq!(Storage::import_workspace(&file).unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+        std::fs::write(&file, "{\"todos\": []}").unwrap();
+        assert_eq!(Storage::import_workspace(&file).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+
+        println!("✅ Shared workspaces import with fresh ids");
+    }
+
+    #[test]
+    fn test_import_shared_workspace_with_taken_name() {
+        let dir = TempDir::new("share-app");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("work.paperclip.json");
+
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        let current = app.workspace_manager.get_current_workspace_id().unwrap();
+        let name = app.get_current_workspace_name();
+        app.get_current_todo_list_mut().unwrap().add_todo("Shared todo".to_string());
+
+        // x on the workspace in the selection screen writes it and comes back there
+        app.enter_workspace_selection();
+        app.popup_selected = 1;
+        app.enter_export_workspace_mode();
+        assert_eq!(app.mode, AppMode::ExportPath);
+        assert!(app.input_buffer.content.ends_with(".paperclip.json"));
+        app.input_buffer.set(file.display().to_string());
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::WorkspaceSelection);
+        assert!(app.message().unwrap().contains("Exported 1 todos"));
+
+        // Importing it back collides with its own name
+        app.enter_import_workspace_mode();
+        assert_eq!(app.mode, AppMode::ImportWorkspacePath);
+        app.input_buffer.set(file.display().to_string());
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::ImportWorkspaceName);
+        assert_eq!(app.input_buffer.content, format!("{} (2)", name));
+
+        app.input_buffer.set(name.clone());
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::ImportWorkspaceName);
+        assert!(app.message().unwrap().contains("already exists"));
+
+        app.input_buffer.set("From a friend".to_string());
+        app.submit_prompt();
+        assert_eq!(app.mode, AppMode::WorkspaceSelection);
+        assert_eq!(app.message(), Some("Imported workspace 'From a friend' with 1 todos"));
+        let imported = app.workspace_manager.find_workspace_id_by_name("From a friend").unwrap();
+        assert!(app.available_workspaces.contains(&imported));
+        assert_ne!(imported, current);
+        assert_eq!(app.workspace_manager.workspace_todos[&imported].todos.len(), 1);
+
+        // Esc from the welcome screen's prompt goes back to it
+        app.mode = AppMode::Welcome;
+        app.enter_import_workspace_mode();
+        app.cancel_prompt();
+        assert_eq!(app.mode, AppMode::Welcome);
+
+        println!("✅ Shared workspaces with a taken name ask for another");
+    }
+
+    #[test]
+    fn test_cli_command_arguments() {
+        use crate::cli::{Args, Command};
+        use std::ffi::OsString;
+
+        let parse = |args: &[&str]| Args::parse(args.iter().map(OsString::from));
+
+        assert_eq!(
+            parse(&["add", "Fix bug #urgent due:friday", "--workspace", "Work"]).unwrap().command,
+            Some(Command::Add { text: "Fix bug #urgent due:friday".to_string(), workspace: Some("Work".to_string()) }),
+        );
+        // Unquoted words are joined, and global options can come anywhere
+        let args = parse(&["add", "Call", "mum", "--data-dir", "/tmp/cli"]).unwrap();
+        assert_eq!(args.command, Some(Command::Add { text: "Call mum".to_string(), workspace: None }));
+        assert!(args.data_dir.is_some());
+        assert_eq!(
+            parse(&["list", "--pending", "--json", "--workspace=Home"]).unwrap().command,
+            Some(Command::List { pending: true, json: true, workspace: Some("Home".to_string()) }),
+        );
+        assert_eq!(parse(&["done", "7"]).unwrap().command, Some(Command::Done { id: 7, workspace: None }));
+
+        assert_eq!(parse(&["add"]).unwrap().command, Some(Command::Add { text: String::new(), workspace: None }));
+        assert!(parse(&["done", "seven"]).unwrap_err().contains("'seven' isn't a todo id"));
+        assert!(parse(&["done"]).is_err());
+        assert!(parse(&["done", "1", "2"]).is_err());
+        assert!(parse(&["list", "extra"]).is_err());
+        assert!(parse(&["add", "x", "--pending"]).unwrap_err().contains("--pending"));
+        assert!(parse(&["add", "x", "--workspace"]).is_err());
+        assert!(parse(&["frobnicate"]).unwrap_err().contains("unknown command"));
+
+        println!("✅ CLI commands parse, and bad ones are refused");
+    }
+
+    #[test]
+    fn test_cli_commands_against_data_dir() {
+        use crate::cli::Command;
+
+        let dir = TempDir::new("cli");
+        let run = |command: Command| Command::run(&command, &Storage::new_with_dir(dir.clone()).unwrap(), std::io::empty());
+
+        // The first add creates the default workspace, like opening the app does
+        let id = run(Command::Add { text: "Fix bug #urgent @desk due:2030-01-31".to_string(), workspace: None }).unwrap();
+        assert_eq!(id, "1\n");
+        run(Command::Add { text: "Write changelog".to_string(), workspace: None }).unwrap();
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+        let (manager, _) = storage.load_workspace_manager().unwrap();
+        let todo = &manager.get_current_todo_list().unwrap().todos[&1];
+        assert_eq!(todo.description, "Fix bug urgent desk");
+        assert!(todo.tags.contains("urgent") && todo.contexts.contains("desk"));
+        assert!(todo.due_date.is_some());
+
+        let listed = run(Command::List { pending: false, json: false, workspace: None }).unwrap();
+        assert_eq!(listed.lines().count(), 2);
+        assert!(listed.contains("1  [ ]  -  2030-01-31 "));
+        assert!(listed.contains("Fix bug urgent desk #urgent @desk"));
+
+        assert_eq!(run(Command::Done { id: 1, workspace: None }).unwrap(), "Completed 1: Fix bug urgent desk\n");
+        assert!(run(Command::Done { id: 1, workspace: None }).unwrap().contains("already done"));
+        let pending = run(Command::List { pending: true, json: false, workspace: None }).unwrap();
+        assert_eq!(pending.lines().count(), 1);
+        assert!(pending.contains("Write changelog"));
+
+        let json = run(Command::List { pending: false, json: true, workspace: Some("Personal".to_string()) }).unwrap();
+        let todos: Vec<Todo> = serde_json::from_str(&json).unwrap();
+        assert_eq!(todos.len(), 2);
+        assert!(todos.iter().any(|todo| todo.id == 1 && todo.status == TodoStatus::Completed));
+
+        // Mistakes are errors and leave the data alone
+        assert!(run(Command::Done { id: 99, workspace: None }).unwrap_err().contains("no todo with id 99"));
+        assert!(run(Command::Add { text: "Lost".to_string(), workspace: Some("Nowhere".to_string()) }).unwrap_err().contains("Nowhere"));
+        let (manager, _) = storage.load_workspace_manager().unwrap();
+        assert_eq!(manager.get_current_todo_list().unwrap().todos.len(), 2);
+
+        println!("✅ CLI commands add, list and complete todos in the data directory");
+    }
+
+    #[test]
+    fn test_cli_add_lines_from_stdin() {
+        use crate::cli::{Args, Command};
+        use std::ffi::OsString;
+
+        let parse = |args: &[&str]| Args::parse(args.iter().map(OsString::from)).unwrap();
+        let inbox = Some("Inbox".to_string());
+        assert_eq!(parse(&["add", "--stdin", "-w", "Inbox"]).command, Some(Command::AddLines { workspace: inbox.clone() }));
+        assert!(Args::parse(["add", "--stdin", "text"].iter().map(OsString::from)).is_err());
+
+        assert_eq!(parse(&["add", "-"]).command, Some(Command::AddLines { workspace: None }));
+
+        // Piped input needs no flag; text given on the command line still wins
+        let mut piped = parse(&["add", "--workspace", "Inbox"]);
+        assert!(piped.may_use_piped_stdin());
+        piped.use_piped_stdin(b"Call bank\n");
+        assert_eq!(piped.command, Some(Command::AddLines { workspace: inbox.clone() }));
+        let mut bare = parse(&[]);
+        bare.use_piped_stdin(b"Call bank\n");
+        assert_eq!(bare.command, Some(Command::AddLines { workspace: None }));
+        let text = parse(&["add", "Buy milk"]);
+        assert!(!text.may_use_piped_stdin());
+        assert!(!parse(&["list"]).may_use_piped_stdin());
+
+        // An empty stdin that isn't a terminal still opens the app
+        let mut empty = parse(&[]);
+        empty.use_piped_stdin(b" \n\n");
+        assert_eq!(empty.command, None);
+
+        let dir = TempDir::new("cli-stdin");
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+        assert!(Command::Add { text: String::new(), workspace: None }.run(&storage, std::io::empty()).unwrap_err().contains("piped"));
+
+        let input = b"Plan trip #travel\n  Book flights due:2030-03-01\n  Book hotel\n\n\xff broken bytes\nPack\n";
+        let summary = Command::AddLines { workspace: None }.run(&storage, &input[..]).unwrap();
+        assert_eq!(summary, "Added 5 todos to Personal\n");
+
+        let (manager, _) = storage.load_workspace_manager().unwrap();
+        let todo_list = manager.get_current_todo_list().unwrap();
+        assert_eq!(todo_list.todos.len(), 5);
+        let trip = todo_list.todos.values().find(|todo| todo.description.starts_with("Plan trip")).unwrap();
+        assert!(trip.tags.contains("travel"));
+        assert_eq!(trip.children.len(), 2);
+        let flights = todo_list.todos.values().find(|todo| todo.description == "Book flights").unwrap();
+        assert_eq!(flights.parent_id, Some(trip.id));
+        assert!(flights.due_date.is_some());
+        assert!(todo_list.todos.values().any(|todo| todo.description.ends_with("broken bytes") && todo.parent_id.is_none()));
+
+        // Blank input adds nothing and leaves the file alone
+        let before = std::fs::read(storage.get_workspace_file_path()).unwrap();
+        let summary = Command::AddLines { workspace: None }.run(&storage, &b"\n  \n"[..]).unwrap();
+        assert_eq!(summary, "No todos to add to Personal\n");
+        assert_eq!(std::fs::read(storage.get_workspace_file_path()).unwrap(), before);
+
+        let missing = Command::AddLines { workspace: inbox }.run(&storage, &b"Lost"[..]);
+        assert!(missing.unwrap_err().contains("no workspace named 'Inbox'"));
+
+        println!("✅ Piped lines become todos, indented ones subtasks");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cli_saves_take_the_write_lock_and_list_changes_nothing() {
+        use crate::cli::Command;
+        use crate::storage::WRITE_LOCK_FILE;
+
+        let dir = TempDir::new("cli-lock");
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+        let write_lock = dir.join(WRITE_LOCK_FILE);
+
+        // A save in progress elsewhere (PID 1 is always running) is waited for, not written over
+        std::fs::write(&write_lock, "1\n").unwrap();
+        let release = {
+            let write_lock = write_lock.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                std::fs::remove_file(write_lock).unwrap();
+            })
+        };
+        let add = Command::Add { text: "Call bank".to_string(), workspace: None };
+        assert_eq!(add.run(&storage, std::io::empty()).unwrap(), "1\n");
+        release.join().unwrap();
+        assert!(!write_lock.exists());
+
+        // Listing a damaged file shows a backup but leaves the file where it is
+        let workspace_file = storage.get_workspace_file_path().to_path_buf();
+        std::fs::write(&workspace_file, "{ not json").unwrap();
+        let list = Command::List { pending: false, json: false, workspace: None };
+        list.run(&storage, std::io::empty()).unwrap();
+        assert_eq!(std::fs::read_to_string(&workspace_file).unwrap(), "{ not json");
+        assert!(std::fs::read_dir(&dir).unwrap().all(|entry| !entry.unwrap().file_name().to_string_lossy().contains(".corrupt-")));
+
+        println!("✅ CLI saves wait for each other and listing changes nothing");
+    }
+
+    #[test]
+    fn test_launch_options() {
+        use crate::cli::{Args, LaunchOptions, LaunchView};
+        use std::ffi::OsString;
+
+        let parse = |args: &[&str]| Args::parse(args.iter().map(OsString::from));
+        let args = parse(&["--workspace", "Errands", "--create", "--view", "Overdue", "--add", "Buy stamps"]).unwrap();
+        assert_eq!(args.launch, LaunchOptions {
+            workspace: Some("Errands".to_string()),
+            create: true,
+            view: Some(LaunchView::Overdue),
+            add: Some("Buy stamps".to_string()),
+        });
+        assert!(args.command.is_none());
+        assert!(parse(&["--view", "sideways"]).unwrap_err().contains("sideways"));
+        assert!(parse(&["--create"]).unwrap_err().contains("--workspace"));
+        assert!(parse(&["--workspace", "Work", "list"]).is_err());
+        assert!(parse(&["--add"]).is_err());
+
+        let launch = |launch: LaunchOptions| {
+            let mut app = App::new(Config::default());
+            app.workspace_manager.ensure_workspace();
+            app.workspace_manager.create_workspace("Work".to_string(), None);
+            app.mode = AppMode::Welcome;
+            app.apply_launch_options(&launch);
+            app
+        };
+
+        // An existing workspace opens in Normal mode
+        let app = launch(LaunchOptions { workspace: Some("Work".to_string()), view: Some(LaunchView::Pending), ..Default::default() });
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.get_current_workspace_name(), "Work");
+        assert_eq!(app.filters.status, Some(StatusFilter::Pending));
+
+        // A missing one falls back to the picker, unless --create makes it
+        let app = launch(LaunchOptions { workspace: Some("Errands".to_string()), add: Some("Buy stamps".to_string()), ..Default::default() });
+        assert_eq!(app.mode, AppMode::WorkspaceSelection);
+        assert!(app.message().unwrap().contains("No workspace named 'Errands'"));
+        assert!(app.input_buffer.is_empty());
+        let app = launch(LaunchOptions { workspace: Some("Errands".to_string()), create: true, add: Some("Buy stamps".to_string()), ..Default::default() });
+        assert_eq!(app.get_current_workspace_name(), "Errands");
+        assert_eq!(app.mode, AppMode::Insert);
+        assert_eq!(app.input_buffer.content, "Buy stamps");
+
+        let app = launch(LaunchOptions { view: Some(LaunchView::Today), ..Default::default() });
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.view_mode, ViewMode::GlobalDue(DueDateFilter::Today));
+
+        // Nothing given leaves the welcome screen alone
+        assert_eq!(launch(LaunchOptions::default()).mode, AppMode::Welcome);
+
+        println!("✅ Launch flags open a workspace, view or new todo directly");
+    }
+
+    #[test]
+    fn test_due_reminders() {
+        use crate::notifications::{due_reminders, ReminderKind};
+        use chrono::{Duration, Local, TimeZone};
+
+        let at = |h: u32, m: u32| Local.with_ymd_and_hms(2030, 5, 1, h, m, 0).unwrap();
+        let mut todo_list = TodoList::new();
+        let standup = todo_list.add_todo("Standup".to_string());
+        todo_list.get_todo_mut(standup).unwrap().due_date = Some(at(10, 0));
+        let review = todo_list.add_todo("Review".to_string());
+        todo_list.get_todo_mut(review).unwrap().due_date = Some(at(10, 10));
+        let done = todo_list.add_todo("Already done".to_string());
+        todo_list.get_todo_mut(done).unwrap().due_date = Some(at(10, 0));
+        todo_list.get_todo_mut(done).unwrap().complete();
+        todo_list.add_todo("Whenever".to_string());
+        let lead = Some(Duration::minutes(15));
+
+        // 9:45 is standup's reminder; review's (9:55) hasn't come yet
+        assert_eq!(due_reminders(&todo_list, at(9, 44), at(9, 45), lead), vec![(standup, ReminderKind::Upcoming)]);
+        assert_eq!(due_reminders(&todo_list, at(9, 45), at(9, 54), lead), vec![]);
+        assert_eq!(due_reminders(&todo_list, at(9, 54), at(10, 0), lead), vec![(standup, ReminderKind::Due), (review, ReminderKind::Upcoming)]);
+        // Without a lead time only due times count, and crossing both at once is just "due"
+        assert_eq!(due_reminders(&todo_list, at(9, 0), at(9, 59), None), vec![]);
+        assert_eq!(due_reminders(&todo_list, at(9, 0), at(10, 30), lead), vec![(standup, ReminderKind::Due), (review, ReminderKind::Due)]);
+
+        // The app announces each once, and nothing that was due before it started
+        let mut app = App::new(Config::default());
+        let workspace_id = app.workspace_manager.ensure_workspace();
+        app.workspace_manager.workspace_todos.insert(workspace_id, todo_list);
+        assert!(app.take_due_reminders(at(10, 5)).is_empty());
+        let reminders = app.take_due_reminders(at(10, 6));
+        assert_eq!(reminders.len(), 0);
+        let reminders = app.take_due_reminders(at(10, 10));
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].summary(), "Due now: Review");
+        assert_eq!(reminders[0].body(), "Paperclip · Personal");
+        app.reminders_checked_at = Some(at(10, 0));
+        assert!(app.take_due_reminders(at(10, 20)).is_empty());
+
+        let (config, warnings) = Config::parse("notifications = false\nreminder_minutes = 0");
+        assert!(warnings.is_empty());
+        assert!(!config.notifications);
+        assert_eq!(config.reminder_minutes, 0);
+
+        println!("✅ Due and upcoming reminders fire once each");
+    }
+
+    #[test]
+    fn test_due_soon_banner() {
+        use chrono::{Duration, Local, TimeZone};
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let now = Local.with_ymd_and_hms(2030, 5, 1, 12, 0, 0).unwrap();
+        let mut todo_list = TodoList::new();
+        let mut add = |text: &str, due: Option<chrono::DateTime<Local>>| {
+            let id = todo_list.add_todo(text.to_string());
+            todo_list.get_todo_mut(id).unwrap().due_date = due;
+            id
+        };
+        let soon = add("Soon", Some(now + Duration::minutes(30)));
+        let overdue = add("Overdue", Some(now - Duration::days(1)));
+        let edge = add("On the hour", Some(now + Duration::hours(1)));
+        add("Later", Some(now + Duration::minutes(61)));
+        add("No date", None);
+        let done = add("Done", Some(now - Duration::minutes(5)));
+        todo_list.get_todo_mut(done).unwrap().complete();
+
+        let ids: Vec<u32> = todo_list.due_soon(Duration::hours(1), now).iter().map(|todo| todo.id).collect();
+        assert_eq!(ids, vec![overdue, soon, edge]);
+        assert_eq!(todo_list.due_soon(Duration::zero(), now).len(), 1);
+
+        // Ctrl+X hides the banner for the session, but only once there is one
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let ctrl_x = || Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL));
+        crate::events::handle_event(&mut app, ctrl_x()).unwrap();
+        assert!(!app.reminder_banner_dismissed);
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Call back".to_string());
+        app.get_current_todo_list_mut().unwrap().get_todo_mut(id).unwrap().due_date = Some(Local::now() + Duration::minutes(10));
+        assert_eq!(app.reminder_banner_todos(Local::now()).len(), 1);
+        crate::events::handle_event(&mut app, ctrl_x()).unwrap();
+        assert!(app.reminder_banner_dismissed);
+        assert!(app.reminder_banner_todos(Local::now()).is_empty());
+
+        println!("✅ Overdue and due-within-the-hour todos show in the banner until dismissed");
+    }
+
+    #[test]
+    fn test_message_queue_expiry() {
+        use crate::messages::{MessageLevel, MessageQueue, LONG, SHORT, STICKY};
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let text = |queue: &MessageQueue| queue.current().map(|message| message.text.clone());
+        let mut queue = MessageQueue::default();
+        queue.push("Todo deleted!".to_string(), MessageLevel::Success, SHORT, start);
+        assert!(!queue.expire(start + Duration::from_secs(3)));
+        assert_eq!(text(&queue).as_deref(), Some("Todo deleted!"));
+        assert!(queue.expire(start + SHORT.unwrap()));
+        assert_eq!(queue.current(), None);
+
+        // An error outlives anything shown over it and comes back when that expires
+        queue.push("Autosave failed".to_string(), MessageLevel::Error, STICKY, start);
+        queue.push("Viewing all".to_string(), MessageLevel::Info, SHORT, start);
+        queue.push("Sorting by name".to_string(), MessageLevel::Info, LONG, start);
+        assert_eq!(text(&queue).as_deref(), Some("Sorting by name"));
+        assert!(queue.expire(start + Duration::from_secs(60)));
+        assert_eq!(text(&queue).as_deref(), Some("Autosave failed"));
+        assert!(!queue.expire(start + Duration::from_secs(3600)));
+
+        // A newer error replaces it, and dismissing leaves nothing behind
+        queue.push("Couldn't write out.md".to_string(), MessageLevel::Error, STICKY, start);
+        assert_eq!(queue.dismiss().map(|message| message.text).as_deref(), Some("Couldn't write out.md"));
+        assert_eq!(queue.current(), None);
+
+        // Keys no longer clear messages; Esc takes down an error before clearing filters
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        app.filters.status = Some(StatusFilter::Pending);
+        app.set_message("Couldn't read notes.txt".to_string(), MessageLevel::Error, STICKY);
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        crate::events::handle_event(&mut app, key(KeyCode::Char('j'))).unwrap();
+        assert_eq!(app.message(), Some("Couldn't read notes.txt"));
+        crate::events::handle_event(&mut app, key(KeyCode::Esc)).unwrap();
+        assert_eq!(app.message(), None);
+        assert_eq!(app.filters.status, Some(StatusFilter::Pending));
+        crate::events::handle_event(&mut app, key(KeyCode::Esc)).unwrap();
+        assert_eq!(app.filters.status, None);
+
+        println!("✅ Messages expire on their own and errors stay until dismissed");
+    }
+
+    #[test]
+    fn test_help_scrolls_and_filters() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+        let mut screen = |app: &mut App| {
+            terminal.draw(|f| crate::ui::draw(f, app)).unwrap();
+            terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>()
+        };
+        let press = |app: &mut App, code| crate::events::handle_event(app, Event::Key(KeyEvent::new(code, KeyModifiers::NONE))).unwrap();
+
+        press(&mut app, KeyCode::Char('?'));
+        assert!(app.show_help);
+        assert!(screen(&mut app).contains("Help · 1-22 of"));
+
+        // Scrolling stops at the end, and keys other than Esc and ? leave help open
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.help_scroll, 1 + app.help_view_height);
+        press(&mut app, KeyCode::Char('G'));
+        let text = screen(&mut app);
+        assert!(text.contains("Quit"));
+        let end = app.help_scroll;
+        press(&mut app, KeyCode::Char('j'));
+        screen(&mut app);
+        assert_eq!(app.help_scroll, end);
+        press(&mut app, KeyCode::Char('x'));
+        assert!(app.show_help);
+
+        // `/` narrows to matching lines under their headings
+        press(&mut app, KeyCode::Char('/'));
+        for c in "pomodoro".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        let text = screen(&mut app);
+        assert!(text.contains("/pomodoro"));
+        assert!(text.contains("Advanced Features:"));
+        assert!(text.contains("25m work on the selected todo"));
+        assert!(!text.contains("Move down"));
+        press(&mut app, KeyCode::Char('/'));
+        for c in "zzz".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert!(screen(&mut app).contains("No help lines match"));
+
+        // Esc drops the filter, then closes; reopening starts at the top
+        press(&mut app, KeyCode::Esc);
+        assert!(app.help_filter.is_empty());
+        assert!(app.show_help);
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.show_help);
+        press(&mut app, KeyCode::Char('?'));
+        assert_eq!(app.help_scroll, 0);
+        press(&mut app, KeyCode::Char('?'));
+        assert!(!app.show_help);
+
+        println!("✅ Help scrolls, filters and only closes on Esc or ?");
+    }
+
+    #[test]
+    fn test_key_hint_bar() {
+        use crate::hints::visible_hints;
+        use crate::keymap::Keymap;
+
+        let keymap = Keymap::default();
+        let normal = visible_hints(&AppMode::Normal, &keymap, 200);
+        assert_eq!(normal[0], ("i".to_string(), "add"));
+        assert!(normal.contains(&("Space".to_string(), "done")));
+        let visual = visible_hints(&AppMode::Visual, &keymap, 200);
+        assert!(visual.contains(&("0-5".to_string(), "priority")));
+        assert_eq!(visual.last().unwrap().1, "cancel");
+
+        // Remapped keys show as bound
+        let (keymap, _) = Keymap::with_overrides(&[("add_todo".to_string(), vec!["ctrl+n".to_string()])]);
+        assert_eq!(visible_hints(&AppMode::Normal, &keymap, 200)[0], ("Ctrl+N".to_string(), "add"));
+
+        // Narrow terminals drop whole hints from the end
+        let keymap = Keymap::default();
+        assert_eq!(visible_hints(&AppMode::Normal, &keymap, 14), vec![("i".to_string(), "add"), ("e".to_string(), "edit")]);
+        assert_eq!(visible_hints(&AppMode::Normal, &keymap, 13).len(), 1);
+        assert!(visible_hints(&AppMode::Normal, &keymap, 3).is_empty());
+
+        // The bar follows the mode on screen
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Board;
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let bottom: String = terminal.backend().buffer().content().iter().skip(29 * 100).map(|cell| cell.symbol()).collect();
+        assert!(bottom.contains("H/L move todo"));
+
+        println!("✅ The hint bar shows the mode's keys as bound, fitted to the width");
+    }
+
+    #[test]
+    fn test_quick_demo_uses_its_own_workspace() {
+        use crate::app::WelcomeOption;
+
+        let mut app = App::new(Config::default());
+        app.is_first_launch = false;
+        let personal = app.workspace_manager.ensure_workspace();
+        app.get_current_todo_list_mut().unwrap().add_todo("Pay rent #home".to_string());
+        assert!(!app.welcome_choices().contains(&WelcomeOption::RemoveDemo));
+
+        let demo_index = app.welcome_choices().iter().position(|choice| *choice == WelcomeOption::QuickDemo).unwrap();
+        app.welcome_selected = demo_index;
+        app.select_welcome_option();
+        assert_eq!(app.mode, AppMode::Normal);
+        let demo = app.workspace_manager.current_workspace.clone().unwrap();
+        assert_ne!(demo, personal);
+        assert_eq!(app.get_current_workspace_name(), "Demo");
+        let tutorial = app.get_current_todo_list().unwrap().todos.len();
+        assert!(tutorial > 1);
+
+        // Personal is untouched, and a second demo reuses the workspace without doubling it up
+        let personal_list = &app.workspace_manager.workspace_todos[&personal];
+        assert_eq!(personal_list.todos.len(), 1);
+        assert!(personal_list.todos.values().next().unwrap().description.starts_with("Pay rent"));
+        app.welcome_selected = demo_index;
+        app.select_welcome_option();
+        assert_eq!(app.workspace_manager.workspaces.len(), 2);
+        assert_eq!(app.get_current_todo_list().unwrap().todos.len(), tutorial);
+
+        // Removing it leaves Personal and takes the option away again
+        let remove = app.welcome_choices().iter().position(|choice| *choice == WelcomeOption::RemoveDemo).unwrap();
+        app.welcome_selected = remove;
+        app.select_welcome_option();
+        assert!(app.workspace_manager.demo_workspace_id().is_none());
+        assert_eq!(app.workspace_manager.workspaces.len(), 1);
+        assert_eq!(app.workspace_manager.workspace_todos[&personal].todos.len(), 1);
+        assert!(!app.welcome_choices().contains(&WelcomeOption::RemoveDemo));
+        assert!(app.welcome_selected < app.welcome_choices().len());
+
+        println!("✅ Quick Demo fills a Demo workspace of its own and can remove it");
+    }
+
+    #[test]
+    fn test_first_launch_comes_from_stored_data() {
+        use crate::app::WelcomeOption;
+
+        let dir = TempDir::new("first-launch");
+        let load = |storage: &Storage| {
+            let fresh = storage.is_fresh_install();
+            let mut app = App::new(Config::default());
+            app.workspace_manager = storage.load_workspace_manager().unwrap().0;
+            app.set_first_launch(fresh);
+            app
+        };
+
+        // Fresh install
+        let storage = Storage::new_with_dir(dir.join("fresh")).unwrap();
+        let app = load(&storage);
+        assert!(app.is_first_launch);
+        assert_eq!(app.welcome_choices()[0], WelcomeOption::GetStarted);
+        assert!(app.message().unwrap().starts_with("Welcome to Paperclip!"));
+
+        // A todos.json from before workspaces is migrated, and isn't a first launch
+        let storage = Storage::new_with_dir(dir.join("legacy")).unwrap();
+        let mut legacy = TodoList::new();
+        legacy.add_todo("Old todo".to_string());
+        storage.save_todos(&legacy).unwrap();
+        let app = load(&storage);
+        assert!(!app.is_first_launch);
+        assert_eq!(app.welcome_choices()[0], WelcomeOption::BrowseWorkspaces);
+        assert_eq!(app.message(), Some("Welcome back! You have 1 todos across 1 workspaces."));
+
+        // One workspace, saved before: still a returning user, with or without todos
+        let storage = Storage::new_with_dir(dir.join("single")).unwrap();
+        let mut workspace_manager = WorkspaceManager::new();
+        workspace_manager.ensure_workspace();
+        storage.save_workspace_manager(&workspace_manager).unwrap();
+        let app = load(&storage);
+        assert!(!app.is_first_launch);
+        assert_eq!(app.message(), Some("Welcome back! Ready to organize your todos?"));
+
+        println!("✅ First launch means nothing was saved before");
+    }
+
+    #[test]
+    fn test_get_started_after_loading_empty_data() {
+        use crate::app::WelcomeOption;
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let dir = TempDir::new("get-started");
+        let storage = Storage::new_with_dir(dir.clone()).unwrap();
+
+        // As main.rs does: the App's own workspaces are replaced by whatever was loaded
+        for loaded in [storage.load_workspace_manager().unwrap().0, WorkspaceManager::new()] {
+            let fresh = storage.is_fresh_install();
+            let mut app = App::new(Config::default());
+            app.workspace_manager = loaded;
+            app.mode = AppMode::Welcome;
+            app.set_first_launch(fresh);
+            assert_eq!(app.welcome_choices()[0], WelcomeOption::GetStarted);
+
+            crate::events::handle_event(&mut app, Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+            assert_eq!(app.mode, AppMode::Insert);
+            for c in "First todo".chars() {
+                crate::events::handle_event(&mut app, Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))).unwrap();
+            }
+            crate::events::handle_event(&mut app, Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+            let todo_list = app.get_current_todo_list().unwrap();
+            assert_eq!(todo_list.todos.len(), 1);
+            assert_eq!(todo_list.todos.values().next().unwrap().description, "First todo");
+            assert_ne!(app.message(), Some("No workspace selected"));
+        }
+
+        println!("✅ Get Started always has a workspace for the first todo");
+    }
+
+    #[test]
+    fn test_status_bar_counts() {
+        use crate::messages::{MessageLevel, SHORT};
+        use crate::ui::{status_segments, CountSegment, StatusCounts};
+
+        let counts = StatusCounts { total: 10, pending: 6, in_progress: 0, completed: 4, overdue: 2, due_today: 1 };
+        let all = vec![CountSegment::Total(10), CountSegment::Pending(6), CountSegment::Completed(4), CountSegment::Overdue(2), CountSegment::DueToday(1)];
+        assert_eq!(status_segments(counts, 200), all);
+        // "Total: 10 | Pending: 6 | Completed: 4 | Overdue: 2 | Today: 1" is 61 columns
+        assert_eq!(status_segments(counts, 61), all);
+        assert_eq!(status_segments(counts, 60), all[1..].to_vec());
+        assert_eq!(status_segments(counts, 35), vec![CountSegment::Pending(6), CountSegment::Overdue(2), CountSegment::DueToday(1)]);
+        assert_eq!(status_segments(counts, 22), vec![CountSegment::Overdue(2), CountSegment::DueToday(1)]);
+        assert_eq!(status_segments(counts, 10), vec![CountSegment::Overdue(2)]);
+        // "Overdue: 2" alone is 10 columns; one fewer and no count fits
+        assert!(status_segments(counts, 9).is_empty());
+        assert!(status_segments(counts, 5).is_empty());
+
+        // Nothing overdue or due today: only the totals
+        let quiet = StatusCounts { total: 3, pending: 3, ..StatusCounts::default() };
+        assert_eq!(status_segments(quiet, 200), vec![CountSegment::Total(3), CountSegment::Pending(3), CountSegment::Completed(0)]);
+
+        // Started todos get a count after pending, dropped just after the completed one
+        let started = StatusCounts { total: 3, pending: 3, in_progress: 1, ..StatusCounts::default() };
+        assert_eq!(status_segments(started, 200), vec![CountSegment::Total(3), CountSegment::Pending(3), CountSegment::InProgress(1), CountSegment::Completed(0)]);
+        assert_eq!(status_segments(started, 30), vec![CountSegment::Pending(3), CountSegment::InProgress(1)]);
+        assert_eq!(status_segments(started, 20), vec![CountSegment::Pending(3)]);
+
+        // A message covers the counts until it expires
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Late".to_string());
+        app.get_current_todo_list_mut().unwrap().get_todo_mut(id).unwrap().due_date = Some(chrono::Local::now() - chrono::Duration::days(2));
+        app.set_message("Sorting by name".to_string(), MessageLevel::Info, SHORT);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(120, 30)).unwrap();
+        let mut screen = |app: &mut App| {
+            terminal.draw(|f| crate::ui::draw(f, app)).unwrap();
+            terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>()
+        };
+        assert!(!screen(&mut app).contains("Overdue: 1"));
+        app.messages.expire(std::time::Instant::now() + SHORT.unwrap());
+        assert!(screen(&mut app).contains("Overdue: 1"));
+
+        println!("✅ The status bar adds overdue and due-today counts, dropping the least useful when narrow");
+    }
+
+    #[test]
+    fn test_header_workspace_color_and_counts() {
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        app.workspace_manager.workspaces.get_mut(&work).unwrap().color = Some(3);
+        app.workspace_manager.switch_workspace(&work);
+        let todo_list = app.get_current_todo_list_mut().unwrap();
+        todo_list.add_todo("Report due:today".to_string());
+        todo_list.add_todo("Slides".to_string());
+        let done = todo_list.add_todo("Email".to_string());
+        todo_list.get_todo_mut(done).unwrap().complete();
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+        let mut draw = |app: &mut App| {
+            terminal.draw(|f| crate::ui::draw(f, app)).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            let header: String = buffer.content().iter().skip(100).take(100).map(|cell| cell.symbol()).collect();
+            (header, buffer[(0, 0)].fg)
+        };
+        let (header, border) = draw(&mut app);
+        assert!(header.contains("2:Work (2) | "));
+        assert!(!header.contains("result"));
+        assert_eq!(border, app.colors.workspace_color(3));
+        assert_eq!(app.get_workspace_color(&work), app.colors.workspace_color(3));
+
+        // A due date filter adds the number of todos it leaves
+        app.cycle_due_date_filter();
+        assert!(app.filters.due.is_some());
+        let (header, _) = draw(&mut app);
+        let shown = app.get_visible_todos().len();
+        assert!(header.contains(&format!("| {} result", shown)));
+
+        println!("✅ The header shows the workspace in its color with pending and result counts");
+    }
+
+    #[test]
+    fn test_relative_due_dates() {
+        use crate::ui::format_due;
+        use chrono::{Local, TimeZone};
+
+        let colors = Theme::tokyo_night();
+        let at = |y, m, d, h, min, s| Local.with_ymd_and_hms(y, m, d, h, min, s).unwrap();
+        let cases = [
+            // (now, due, text, color)
+            (at(2030, 5, 10, 12, 0, 0), at(2030, 5, 7, 23, 59, 59), "3d overdue", colors.red),
+            (at(2030, 5, 10, 12, 0, 0), at(2030, 5, 9, 9, 0, 0), "1d overdue", colors.red),
+            // Just past midnight, yesterday's date is overdue and today's isn't yet
+            (at(2030, 5, 10, 0, 0, 1), at(2030, 5, 9, 23, 59, 59), "1d overdue", colors.red),
+            (at(2030, 5, 10, 0, 0, 1), at(2030, 5, 10, 23, 59, 59), "today", colors.yellow),
+            (at(2030, 5, 10, 12, 0, 0), at(2030, 5, 10, 17, 0, 0), "today 17:00", colors.yellow),
+            (at(2030, 5, 10, 18, 0, 0), at(2030, 5, 10, 17, 0, 0), "today 17:00", colors.red),
+            (at(2030, 5, 10, 23, 59, 0), at(2030, 5, 11, 0, 30, 0), "tomorrow 00:30", colors.blue),
+            (at(2030, 5, 10, 12, 0, 0), at(2030, 5, 11, 23, 59, 59), "tomorrow", colors.blue),
+            (at(2030, 5, 10, 12, 0, 0), at(2030, 5, 14, 23, 59, 59), "in 4d", colors.blue),
+            (at(2030, 5, 10, 12, 0, 0), at(2030, 5, 16, 23, 59, 59), "in 6d", colors.blue),
+            // Exactly a week out is a date again
+            (at(2030, 5, 10, 12, 0, 0), at(2030, 5, 17, 12, 0, 0), "05/17", colors.blue),
+            // Leap years: the 29th of February counts as a day
+            (at(2028, 2, 28, 12, 0, 0), at(2028, 2, 29, 23, 59, 59), "tomorrow", colors.blue),
+            (at(2028, 2, 28, 12, 0, 0), at(2028, 3, 1, 23, 59, 59), "in 2d", colors.blue),
+            (at(2028, 3, 1, 12, 0, 0), at(2028, 2, 28, 23, 59, 59), "2d overdue", colors.red),
+            (at(2030, 12, 30, 12, 0, 0), at(2031, 1, 2, 23, 59, 59), "in 3d", colors.blue),
+        ];
+        for (now, due, text, color) in cases {
+            assert_eq!(format_due(due, now, "%m/%d", &colors), (text.to_string(), color), "due {} seen at {}", due, now);
+        }
+        assert_eq!(format_due(at(2030, 6, 1, 0, 0, 0), at(2030, 5, 10, 0, 0, 0), "%Y-%m-%d", &colors).0, "2030-06-01");
+
+        println!("✅ Due dates show relative to now");
+    }
+
+    #[test]
+    fn test_folding_keeps_selection_on_nearest_visible_ancestor() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(Config::default());
+        let ws = app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let (a, a1, a1a, a2, b) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let a = list.add_todo("A".to_string());
+            let a1 = list.add_child_todo(a, "A1".to_string()).unwrap();
+            let a1a = list.add_child_todo(a1, "A1a".to_string()).unwrap();
+            let a2 = list.add_child_todo(a, "A2".to_string()).unwrap();
+            let b = list.add_todo("B".to_string());
+            (a, a1, a1a, a2, b)
+        };
+        let visible = |app: &App| app.get_visible_todos().iter().map(|(_, todo, _)| todo.id).collect::<Vec<_>>();
+
+        assert!(app.reveal_todo(&ws, a1a));
+        app.collapse_all();
+        assert_eq!(visible(&app), vec![a, b]);
+        assert_eq!(app.get_selected_todo_id(), Some(a));
+
+        app.expand_all();
+        assert_eq!(visible(&app).len(), 5);
+        assert_eq!(app.get_selected_todo_id(), Some(a));
+
+        // Two levels: A's children show, A1's don't
+        assert!(app.reveal_todo(&ws, a1a));
+        app.fold_to_level(2);
+        let rows = visible(&app);
+        assert!(rows.contains(&a1) && rows.contains(&a2) && !rows.contains(&a1a));
+        assert_eq!(app.get_selected_todo_id(), Some(a1));
+
+        // A selection that stays visible doesn't move
+        assert!(app.reveal_todo(&ws, b));
+        app.collapse_all();
+        assert_eq!(app.get_selected_todo_id(), Some(b));
+
+        // The keys: z, then a count before Z
+        let press = |app: &mut App, c: char| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))).unwrap();
+        };
+        press(&mut app, 'z');
+        assert_eq!(visible(&app).len(), 5);
+        assert!(app.reveal_todo(&ws, a1a));
+        press(&mut app, '1');
+        press(&mut app, 'Z');
+        assert_eq!(visible(&app), vec![a, b]);
+        assert_eq!(app.get_selected_todo_id(), Some(a));
+        assert_eq!(app.pending_key, None);
+
+        // A count before any other key is dropped and the key works as usual
+        press(&mut app, '2');
+        press(&mut app, 'j');
+        assert_eq!(app.get_selected_todo_id(), Some(b));
+        assert_eq!(app.pending_key, None);
+
+        // A digit bound to an action does that instead of starting a count
+        let (keymap, warnings) = crate::keymap::Keymap::with_overrides(&[("move_up".to_string(), vec!["k".to_string(), "3".to_string()])]);
+        assert!(warnings.is_empty());
+        app.keymap = keymap;
+        press(&mut app, '3');
+        assert_eq!(app.get_selected_todo_id(), Some(a));
+        assert_eq!(app.pending_key, None);
+
+        println!("✅ Folding everything keeps the selection on a visible row");
+    }
+
+    #[test]
+    fn test_selection_follows_todo_after_changes() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(Config::default());
+        let ws = app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let (a, b, c, parent, child) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let a = list.add_todo("Alpha".to_string());
+            let b = list.add_todo("Bravo".to_string());
+            let c = list.add_todo("Charlie".to_string());
+            let parent = list.add_todo("Parent".to_string());
+            let child = list.add_child_todo(parent, "Child".to_string()).unwrap();
+            (a, b, c, parent, child)
+        };
+        let press = |app: &mut App, code: KeyCode| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(code, KeyModifiers::NONE))).unwrap();
+        };
+        let index_of = |app: &App, id: u32| app.get_visible_todos().iter().position(|(_, todo, _)| todo.id == id).unwrap();
+
+        // Raising the priority moves the todo to the top, and the selection goes with it
+        assert!(app.reveal_todo(&ws, c));
+        press(&mut app, KeyCode::Char('+'));
+        assert_eq!(index_of(&app, c), 0);
+        assert_eq!(app.get_selected_todo_id(), Some(c));
+
+        // Completing in the Pending view hides the todo; the selection stays put, on the next row
+        app.filters.status = Some(StatusFilter::Pending);
+        assert!(app.reveal_todo(&ws, a));
+        let row = app.selected;
+        press(&mut app, KeyCode::Char(' '));
+        assert!(app.get_current_todo_list().unwrap().get_todo(a).unwrap().is_completed());
+        assert_eq!(app.selected, row);
+        assert_eq!(app.get_selected_todo_id(), Some(b));
+        app.filters.status = None;
+
+        // Collapsing a parent moves the selection from its child up to it
+        assert!(app.reveal_todo(&ws, child));
+        app.get_current_todo_list_mut().unwrap().toggle_expanded(parent);
+        app.mark_dirty();
+        app.resync_selection();
+        assert_eq!(app.get_selected_todo_id(), Some(parent));
+
+        // A todo turning up above it (an autosave merge, say) doesn't move the selection off its todo
+        let row = app.selected;
+        let list = app.get_current_todo_list_mut().unwrap();
+        let urgent = list.add_todo("Urgent".to_string());
+        list.get_todo_mut(urgent).unwrap().priority = 5;
+        app.invalidate_visible_rows();
+        app.resync_selection();
+        assert_eq!(app.get_selected_todo_id(), Some(parent));
+        assert_eq!(app.selected, row + 1);
+
+        // Deleting the selected todo leaves the selection on the row that took its place
+        press(&mut app, KeyCode::Char('G'));
+        press(&mut app, KeyCode::Char('k'));
+        let row = app.selected;
+        let next = app.get_visible_todos()[row + 1].1.id;
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.selected, row);
+        assert_eq!(app.get_selected_todo_id(), Some(next));
+        // ...or on the new last row when it was the last
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.selected, app.get_visible_todos().len() - 1);
+
+        println!("✅ The selection follows its todo when rows move");
+    }
+
+    #[test]
+    fn test_undo_in_global_due_selects_todo_from_another_workspace() {
+        let mut app = App::new(Config::default());
+        let personal = app.workspace_manager.ensure_workspace();
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        app.mode = AppMode::Normal;
+        let yesterday = chrono::Local::now() - chrono::Duration::days(1);
+        let add_overdue = |app: &mut App, ws: &str, text: &str| {
+            let list = app.workspace_manager.workspace_todos.get_mut(ws).unwrap();
+            let id = list.add_todo(text.to_string());
+            list.get_todo_mut(id).unwrap().due_date = Some(yesterday);
+            id
+        };
+        add_overdue(&mut app, &personal, "Water the plants");
+        add_overdue(&mut app, &personal, "Call the bank");
+        let report = add_overdue(&mut app, &work, "File the report");
+        assert_eq!(app.workspace_manager.current_workspace.as_deref(), Some(personal.as_str()));
+
+        app.view_mode = ViewMode::GlobalDue(DueDateFilter::Overdue);
+        app.mark_dirty();
+        assert!(app.reveal_todo(&work, report));
+        app.toggle_todo_complete();
+        assert!(app.workspace_manager.workspace_todos[&work].get_todo(report).unwrap().is_completed());
+        assert_eq!(app.get_visible_todos().len(), 2);
+
+        // Move away, then undo: the Work row comes back and is selected
+        app.selected = 0;
+        app.undo();
+        let (row_ws, todo, _) = app.get_visible_todos()[app.selected];
+        assert_eq!((row_ws, todo.id), (work.as_str(), report));
+
+        // Redo takes it off again; undo once more lands on it from the other end of the list
+        app.redo();
+        app.selected = 1;
+        app.undo();
+        assert_eq!(app.get_selected_todo_id(), Some(report));
+
+        println!("✅ Undo in a cross-workspace view selects the todo it brought back");
+    }
+
+    #[test]
+    fn test_sibling_and_parent_motions() {
+        use crate::app::{next_sibling_index, parent_index, previous_sibling_index};
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        // Root (expanded)
+        //   Folded (collapsed, its child hidden)
+        //   Open
+        //     Leaf one
+        //     Leaf two
+        //   Last
+        // Closed (collapsed)
+        // Loose
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let ids = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            list.sort_mode = SortMode::Created;
+            let root = list.add_todo("Root".to_string());
+            let folded = list.add_child_todo(root, "Folded".to_string()).unwrap();
+            list.add_child_todo(folded, "Hidden".to_string()).unwrap();
+            let open = list.add_child_todo(root, "Open".to_string()).unwrap();
+            let leaf_one = list.add_child_todo(open, "Leaf one".to_string()).unwrap();
+            let leaf_two = list.add_child_todo(open, "Leaf two".to_string()).unwrap();
+            let last = list.add_child_todo(root, "Last".to_string()).unwrap();
+            let closed = list.add_todo("Closed".to_string());
+            list.add_child_todo(closed, "Hidden too".to_string()).unwrap();
+            let loose = list.add_todo("Loose".to_string());
+            // Same creation second, so order by id
+            for (n, id) in [root, folded, open, leaf_one, leaf_two, last, closed, loose].into_iter().enumerate() {
+                list.get_todo_mut(id).unwrap().created_at += chrono::Duration::seconds(n as i64);
+            }
+            list.toggle_expanded(folded);
+            list.toggle_expanded(closed);
+            [root, folded, open, leaf_one, leaf_two, last, closed, loose]
+        };
+        let rows: Vec<u32> = app.get_visible_todos().iter().map(|(_, todo, _)| todo.id).collect();
+        assert_eq!(rows, ids.to_vec());
+        let depths = [0, 1, 1, 2, 2, 1, 0, 0];
+
+        // Siblings skip over open subtrees and stop at the edge of the parent
+        assert_eq!(next_sibling_index(&depths, 0), Some(6));
+        assert_eq!(next_sibling_index(&depths, 1), Some(2));
+        assert_eq!(next_sibling_index(&depths, 2), Some(5));
+        assert_eq!(next_sibling_index(&depths, 4), None);
+        assert_eq!(next_sibling_index(&depths, 5), None);
+        assert_eq!(next_sibling_index(&depths, 7), None);
+        assert_eq!(previous_sibling_index(&depths, 5), Some(2));
+        assert_eq!(previous_sibling_index(&depths, 3), None);
+        assert_eq!(previous_sibling_index(&depths, 1), None);
+        assert_eq!(previous_sibling_index(&depths, 6), Some(0));
+        assert_eq!(parent_index(&depths, 4), Some(2));
+        assert_eq!(parent_index(&depths, 5), Some(0));
+        assert_eq!(parent_index(&depths, 0), None);
+        assert_eq!(next_sibling_index(&depths, 8), None);
+        assert_eq!(next_sibling_index(&[], 0), None);
+
+        // The keys
+        let press = |app: &mut App, c: char| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))).unwrap();
+        };
+        app.selected = 4;
+        press(&mut app, 'p');
+        assert_eq!(app.get_selected_todo_id(), Some(ids[2]));
+        press(&mut app, ')');
+        assert_eq!(app.get_selected_todo_id(), Some(ids[5]));
+        press(&mut app, ')');
+        assert_eq!(app.get_selected_todo_id(), Some(ids[5]));
+        press(&mut app, '(');
+        press(&mut app, '(');
+        assert_eq!(app.get_selected_todo_id(), Some(ids[1]));
+        press(&mut app, 'p');
+        press(&mut app, ')');
+        press(&mut app, ')');
+        assert_eq!(app.get_selected_todo_id(), Some(ids[7]));
+
+        println!("✅ Sibling and parent motions follow the visible tree");
+    }
+
+    #[test]
+    fn test_due_date_prompt_rewrites_raw_description() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(Config::default());
+        let ws = app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let id = app.get_current_todo_list_mut().unwrap().add_todo("Call dentist #health due:2030-01-05 @phone".to_string());
+        let press = |app: &mut App, code: KeyCode| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(code, KeyModifiers::NONE))).unwrap();
+        };
+        let todo = |app: &App| app.get_current_todo_list().unwrap().get_todo(id).unwrap().clone();
+        let date = |todo: &Todo| todo.due_date.map(|due| due.format("%Y-%m-%d").to_string());
+
+        // Opens filled in with the current date
+        press(&mut app, KeyCode::Char(','));
+        assert_eq!(app.mode, AppMode::EditDue);
+        assert_eq!(app.input_buffer.content, "2030-01-05");
+
+        // The old token goes and the new date is written at the end
+        app.input_buffer.set("2030-02-10".to_string());
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(todo(&app).raw_description, "Call dentist #health @phone due:2030-02-10");
+        assert_eq!(date(&todo(&app)).as_deref(), Some("2030-02-10"));
+
+        // Something that isn't a date is an error and the prompt stays open with the text
+        press(&mut app, KeyCode::Char(','));
+        app.input_buffer.set("someday".to_string());
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, AppMode::EditDue);
+        assert_eq!(app.input_buffer.content, "someday");
+        assert!(app.message().unwrap().contains("someday"));
+        assert_eq!(date(&todo(&app)).as_deref(), Some("2030-02-10"));
+
+        // "none" removes the token, so editing the text later doesn't bring the date back
+        app.input_buffer.set("none".to_string());
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(todo(&app).raw_description, "Call dentist #health @phone");
+        assert_eq!(todo(&app).due_date, None);
+        let mut edited = todo(&app);
+        edited.update_description(edited.raw_description.clone());
+        assert_eq!(edited.due_date, None);
+
+        // Esc leaves it alone
+        press(&mut app, KeyCode::Char(','));
+        assert_eq!(app.input_buffer.content, "");
+        app.input_buffer.set("2030-03-01".to_string());
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(todo(&app).due_date, None);
+
+        // Each change undoes on its own, back to the original text
+        app.undo();
+        assert_eq!(todo(&app).raw_description, "Call dentist #health @phone due:2030-02-10");
+        assert_eq!(date(&todo(&app)).as_deref(), Some("2030-02-10"));
+        app.undo();
+        assert_eq!(todo(&app).raw_description, "Call dentist #health due:2030-01-05 @phone");
+        assert_eq!(date(&todo(&app)).as_deref(), Some("2030-01-05"));
+        app.redo();
+        assert_eq!(todo(&app).raw_description, "Call dentist #health @phone due:2030-02-10");
+
+        // A relative token opens as written, and Enter on it unchanged does nothing
+        let weekly = app.get_current_todo_list_mut().unwrap().add_todo("Review #work due:friday".to_string());
+        app.mark_dirty();
+        assert!(app.reveal_todo(&ws, weekly));
+        let friday = app.get_current_todo_list().unwrap().get_todo(weekly).unwrap().due_date;
+        press(&mut app, KeyCode::Char(','));
+        assert_eq!(app.input_buffer.content, "friday");
+        app.dirty = false;
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(!app.dirty);
+        let review = app.get_current_todo_list().unwrap().get_todo(weekly).unwrap().clone();
+        assert_eq!(review.raw_description, "Review #work due:friday");
+        assert_eq!(review.due_date, friday);
+        app.undo();
+        assert_eq!(date(&todo(&app)).as_deref(), Some("2030-01-05"));
+
+        // A due time without a token opens with the time, which reads back the same
+        let timed = app.get_current_todo_list_mut().unwrap().add_todo("Standup".to_string());
+        let at_ten = chrono::NaiveDate::from_ymd_opt(2030, 4, 2).unwrap().and_hms_opt(10, 30, 0).unwrap()
+            .and_local_timezone(chrono::Local).single();
+        app.get_current_todo_list_mut().unwrap().get_todo_mut(timed).unwrap().due_date = at_ten;
+        app.mark_dirty();
+        assert!(app.reveal_todo(&ws, timed));
+        press(&mut app, KeyCode::Char(','));
+        assert_eq!(app.input_buffer.content, "2030-04-02 10:30");
+        assert_eq!(Todo::parse_due_date("2030-04-02 10:30"), at_ten);
+
+        println!("✅ The due date prompt rewrites the due: token and can be undone");
+    }
+
+    #[test]
+    fn test_sort_ties_break_on_due_date_then_age() {
+        let mut list = TodoList::new();
+        let old_idea = list.add_todo("Old idea".to_string());
+        let later = list.add_todo("Due next week".to_string());
+        let soon = list.add_todo("Due tomorrow".to_string());
+        let urgent = list.add_todo("Urgent, undated".to_string());
+        let new_idea = list.add_todo("New idea".to_string());
+        let parent = list.add_todo("Parent".to_string());
+        let undated_child = list.add_child_todo(parent, "Undated child".to_string()).unwrap();
+        let dated_child = list.add_child_todo(parent, "Dated child".to_string()).unwrap();
+        let now = chrono::Local::now();
+        for (n, id) in [old_idea, later, soon, urgent, new_idea, parent, undated_child, dated_child].into_iter().enumerate() {
+            let todo = list.get_todo_mut(id).unwrap();
+            todo.created_at = now - chrono::Duration::days(30 - n as i64);
+        }
+        list.get_todo_mut(later).unwrap().due_date = Some(now + chrono::Duration::days(7));
+        list.get_todo_mut(soon).unwrap().due_date = Some(now + chrono::Duration::days(1));
+        list.get_todo_mut(dated_child).unwrap().due_date = Some(now + chrono::Duration::days(3));
+        list.get_todo_mut(urgent).unwrap().priority = 3;
+        let order = |list: &TodoList| list.get_flattened_todos().iter().map(|(todo, _)| todo.id).collect::<Vec<_>>();
+
+        // Same priority: dated before undated, sooner first; undated by age. Children too.
+        assert_eq!(order(&list), vec![urgent, soon, later, old_idea, new_idea, parent, dated_child, undated_child]);
+
+        // By due date, the undated fall back on priority, then age
+        list.sort_mode = SortMode::DueDate;
+        assert_eq!(order(&list), vec![soon, later, urgent, old_idea, new_idea, parent, dated_child, undated_child]);
+
+        // By name, equal names go by priority
+        list.sort_mode = SortMode::Alphabetical;
+        list.get_todo_mut(new_idea).unwrap().description = "Old idea".to_string();
+        list.get_todo_mut(new_idea).unwrap().priority = 1;
+        let ideas: Vec<u32> = order(&list).into_iter().filter(|id| [old_idea, new_idea].contains(id)).collect();
+        assert_eq!(ideas, vec![new_idea, old_idea]);
+
+        // Created ignores everything else, and ids settle identical timestamps
+        list.sort_mode = SortMode::Created;
+        list.get_todo_mut(later).unwrap().created_at = list.get_todo(old_idea).unwrap().created_at;
+        assert_eq!(&order(&list)[..3], &[old_idea, later, soon]);
+
+        println!("✅ Sort ties break on due date, then age");
+    }
+
+    #[test]
+    fn test_in_progress_status_filter() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(Config::default());
+        let ws = app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let (idle, timed, done) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            let idle = list.add_todo("Not started".to_string());
+            let timed = list.add_todo("Timed".to_string());
+            let done = list.add_todo("Done".to_string());
+            list.get_todo_mut(done).unwrap().toggle_complete();
+            (idle, timed, done)
+        };
+        let press = |app: &mut App, c: char| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))).unwrap();
+        };
+        let visible = |app: &App| app.get_visible_todos().iter().map(|(_, todo, _)| todo.id).collect::<Vec<_>>();
+
+        // Starting the timer marks the todo in progress
+        assert!(app.reveal_todo(&ws, timed));
+        press(&mut app, 't');
+        assert_eq!(app.get_current_todo_list().unwrap().get_todo(timed).unwrap().status, TodoStatus::InProgress);
+        assert_eq!(app.get_current_todo_list().unwrap().in_progress_count(), 1);
+        let flattened: Vec<u32> = app.get_current_todo_list().unwrap().get_flattened_in_progress_todos().iter().map(|(todo, _)| todo.id).collect();
+        assert_eq!(flattened, vec![timed]);
+
+        // All, Pending (started or not), In Progress, Completed, All
+        press(&mut app, 'v');
+        assert_eq!(app.filters.status, Some(StatusFilter::Pending));
+        let pending = visible(&app);
+        assert!(pending.contains(&idle) && pending.contains(&timed) && !pending.contains(&done));
+        press(&mut app, 'v');
+        assert_eq!(app.filters.status, Some(StatusFilter::InProgress));
+        assert_eq!(visible(&app), vec![timed]);
+        assert_eq!(app.get_view_name(), "In Progress");
+        press(&mut app, 'v');
+        assert_eq!(visible(&app), vec![done]);
+        press(&mut app, 'v');
+        assert_eq!(app.filters.status, None);
+
+        // Nothing started: the list says how to start something
+        press(&mut app, 'v');
+        press(&mut app, 'v');
+        app.reveal_todo(&ws, timed);
+        press(&mut app, 't');
+        let list = app.get_current_todo_list_mut().unwrap();
+        list.get_todo_mut(timed).unwrap().status = TodoStatus::Pending;
+        app.mark_dirty();
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| crate::ui::draw(f, &mut app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Nothing in progress."));
+
+        println!("✅ The In Progress filter shows started todos");
+    }
+
+    #[test]
+    fn test_visible_rows_cache_refreshes_after_changes() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new(Config::default());
+        app.workspace_manager.ensure_workspace();
+        app.mode = AppMode::Normal;
+        let personal = app.workspace_manager.get_current_workspace_id().unwrap();
+        let (first, second) = {
+            let list = app.get_current_todo_list_mut().unwrap();
+            list.sort_mode = SortMode::Created;
+            let first = list.add_todo("First #home".to_string());
+            let second = list.add_todo("Second".to_string());
+            list.get_todo_mut(first).unwrap().created_at -= chrono::Duration::seconds(2);
+            list.get_todo_mut(second).unwrap().created_at -= chrono::Duration::seconds(1);
+            (first, second)
+        };
+        let press = |app: &mut App, code: KeyCode| {
+            crate::events::handle_event(app, Event::Key(KeyEvent::new(code, KeyModifiers::NONE))).unwrap();
+        };
+        let visible = |app: &App| app.get_visible_todos().iter().map(|(_, todo, _)| todo.id).collect::<Vec<_>>();
+        assert_eq!(visible(&app), vec![first, second]);
+
+        // Rows are reused until something says the todos changed
+        let sneaky = app.workspace_manager.workspace_todos.get_mut(&personal).unwrap().add_todo("Sneaky".to_string());
+        assert_eq!(visible(&app), vec![first, second]);
+        app.invalidate_visible_rows();
+        assert_eq!(visible(&app).len(), 3);
+        app.get_current_todo_list_mut().unwrap().remove_todo(sneaky);
+        assert_eq!(visible(&app), vec![first, second]);
+
+        // Adding through the input
+        press(&mut app, KeyCode::Char('i'));
+        app.input_buffer.set("Third".to_string());
+        press(&mut app, KeyCode::Enter);
+        let third = *visible(&app).iter().find(|id| ![first, second].contains(id)).unwrap();
+        assert_eq!(visible(&app).len(), 3);
+
+        // Completing under a status filter, and the filter itself
+        app.filters.status = Some(StatusFilter::Pending);
+        assert!(app.reveal_todo(&personal, second));
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(visible(&app), vec![first, third]);
+        app.filters.status = None;
+        assert_eq!(visible(&app).len(), 3);
+
+        // Priority, under a priority sort, and the sort mode
+        app.get_current_todo_list_mut().unwrap().sort_mode = SortMode::Priority;
+        assert!(app.reveal_todo(&personal, third));
+        press(&mut app, KeyCode::Char('+'));
+        assert_eq!(visible(&app)[0], third);
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.get_current_todo_list().unwrap().sort_mode, SortMode::DueDate);
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(visible(&app), vec![first, second, third]);
+
+        // Expanding and collapsing
+        let child = app.get_current_todo_list_mut().unwrap().add_child_todo(first, "Child".to_string()).unwrap();
+        app.mark_dirty();
+        assert_eq!(visible(&app), vec![first, child, second, third]);
+        assert!(app.reveal_todo(&personal, first));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(visible(&app), vec![first, second, third]);
+
+        // Grouping, searching and the agenda
+        app.cycle_group_by();
+        assert_eq!(visible(&app), vec![first, second, third]);
+        assert_eq!(app.get_list_rows()[0], ListRow::Header("#home".to_string(), 1));
+        app.cycle_group_by();
+        app.cycle_group_by();
+        assert_eq!(app.group_by, GroupBy::None);
+        app.enter_search_mode();
+        for c in "third".chars() {
+            app.add_char_to_search(c);
+        }
+        assert_eq!(visible(&app), vec![third]);
+        app.cancel_search();
+        assert_eq!(visible(&app).len(), 3);
+        app.toggle_agenda_view();
+        assert_eq!(app.get_list_rows()[0], ListRow::Header("No date".to_string(), 3));
+        app.toggle_agenda_view();
+
+        // Deleting, undoing, and switching workspace
+        assert!(app.reveal_todo(&personal, second));
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(visible(&app), vec![first, third]);
+        app.filters.status = Some(StatusFilter::Pending);
+        assert!(app.reveal_todo(&personal, first));
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(visible(&app), vec![third]);
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(visible(&app), vec![first, third]);
+        app.filters.status = None;
+        let work = app.workspace_manager.create_workspace("Work".to_string(), None);
+        app.workspace_manager.switch_workspace(&work);
+        assert!(visible(&app).is_empty());
+        app.workspace_manager.switch_workspace(&personal);
+        assert_eq!(visible(&app).len(), 2);
+
+        println!("✅ Cached visible rows refresh after every kind of change");
+    }
+
+    #[test]
+    fn test_parsed_description_and_bulk_import() {
+        use crate::import::from_todotxt;
+        use crate::todo::ParsedDescription;
+
+        let parsed = ParsedDescription::parse("Call #Work about the @Office lease due:2030-01-15");
+        assert_eq!(parsed.description, "Call Work about the Office lease");
+        assert!(parsed.tags.contains("work"));
+        assert!(parsed.contexts.contains("office"));
+        assert_eq!(parsed.due_token.as_deref(), Some("2030-01-15"));
+        assert_eq!(parsed.due_date.map(|due| due.format("%Y-%m-%d").to_string()), Some("2030-01-15".to_string()));
+
+        let plain = ParsedDescription::parse("Water the plants");
+        assert_eq!(plain.description, "Water the plants");
+        assert!(plain.tags.is_empty() && plain.contexts.is_empty());
+        assert!(plain.due_token.is_none() && plain.due_date.is_none());
+
+        let (todo_list, skipped) = from_todotxt(&bulk_todotxt(10_000));
+        assert_eq!(skipped, 0);
+        assert_eq!(todo_list.todos.len(), 10_000);
+        assert!(todo_list.todos.values().all(|todo| todo.due_date.is_some() && todo.contexts.len() == 1));
+
+        println!("✅ Descriptions parse with shared regexes and 10k lines import");
+    }
+
+    fn bulk_todotxt(lines: usize) -> String {
+        (0..lines)
+            .map(|i| format!("Todo number {} +project{} @context{} due:2030-01-{:02}\n", i, i % 7, i % 5, i % 28 + 1))
+            .collect()
+    }
+
+    // Timing depends on the machine, so this only runs when asked for:
+    // `cargo test --release -- --ignored bench_bulk_import`
+    #[test]
+    #[ignore]
+    fn bench_bulk_import() {
+        use crate::import::from_todotxt;
+        use std::time::Instant;
+
+        // Guards against compiling the regexes per todo again, which took seconds for this many
+        let text = bulk_todotxt(10_000);
+        let started = Instant::now();
+        let (todo_list, _) = from_todotxt(&text);
+        let elapsed = started.elapsed();
+        assert_eq!(todo_list.todos.len(), 10_000);
+        assert!(elapsed.as_secs() < 2, "importing 10k lines took {:?}", elapsed);
+
+        println!("✅ 10k lines imported in {:?}", elapsed);
+    }
 }
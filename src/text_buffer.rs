@@ -0,0 +1,300 @@
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// Terminal column of a byte offset: accented letters take one cell, CJK and emoji two
+pub fn display_col(text: &str, byte_pos: usize) -> usize {
+    text[..byte_pos].width()
+}
+
+// Line number and display column of a byte offset in multi-line text
+pub fn line_col(text: &str, pos: usize) -> (usize, usize) {
+    let before = &text[..pos];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count(), before[line_start..].width())
+}
+
+// Byte offset of a line and display column, clamped to the text and to the line's length
+pub fn offset_at(text: &str, line: usize, col: usize) -> usize {
+    let Some(line_start) = line_starts(text).nth(line) else { return text.len() };
+    let mut width = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        let w = c.width().unwrap_or(0);
+        if c == '\n' || width + w > col {
+            return line_start + i;
+        }
+        width += w;
+    }
+    text.len()
+}
+
+fn line_starts(text: &str) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1))
+}
+
+// Split multi-line text into screen rows of at most `width` columns, as byte ranges
+// (newlines excluded). Every line, even an empty one, gives at least one row.
+pub fn wrap_rows(text: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    for start in line_starts(text) {
+        let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+        let mut row_start = start;
+        let mut row_width = 0;
+        for (i, c) in text[start..end].char_indices() {
+            let w = c.width().unwrap_or(0);
+            if row_width + w > width {
+                rows.push(row_start..start + i);
+                row_start = start + i;
+                row_width = 0;
+            }
+            row_width += w;
+        }
+        rows.push(row_start..end);
+    }
+    rows
+}
+
+// Screen row and column of a byte offset within rows from `wrap_rows`
+pub fn row_col(text: &str, rows: &[Range<usize>], pos: usize) -> (usize, usize) {
+    let row = rows.iter().rposition(|r| r.start <= pos).unwrap_or(0);
+    let col = rows.get(row).map_or(0, |r| text[r.start..pos.min(r.end).max(r.start)].width());
+    (row, col)
+}
+
+// The part of a single-line input that fits in `width` columns with the cursor in view.
+// Returns the text to draw (with `…` where it is clipped) and the cursor's column within it.
+pub fn scroll_window(text: &str, cursor: usize, width: usize) -> (String, usize) {
+    let total = text.width();
+    if total < width || width < 3 {
+        return (text.to_string(), display_col(text, cursor));
+    }
+
+    // (byte offset, grapheme, columns)
+    let graphemes: Vec<(usize, &str, usize)> = text.grapheme_indices(true)
+        .map(|(offset, g)| (offset, g, g.width()))
+        .collect();
+    let cursor_index = graphemes.iter().position(|(offset, _, _)| *offset >= cursor).unwrap_or(graphemes.len());
+    let cursor_cell = graphemes.get(cursor_index).map_or(1, |(_, _, w)| (*w).max(1));
+    let clipped_right = usize::from(cursor_index + 1 < graphemes.len());
+
+    // Scroll just far enough right that the cursor cell fits
+    let mut start = 0;
+    let mut before_cursor: usize = graphemes[..cursor_index].iter().map(|(_, _, w)| w).sum();
+    while start < cursor_index {
+        let left_marker = usize::from(start > 0);
+        if left_marker + before_cursor + cursor_cell + clipped_right <= width {
+            break;
+        }
+        before_cursor -= graphemes[start].2;
+        start += 1;
+    }
+
+    let left_marker = usize::from(start > 0);
+    let mut visible = String::new();
+    if start > 0 {
+        visible.push('…');
+    }
+    let mut used = left_marker;
+    for (i, (_, g, w)) in graphemes.iter().enumerate().skip(start) {
+        let more_after = i + 1 < graphemes.len();
+        if used + w + usize::from(more_after) > width {
+            visible.push('…');
+            break;
+        }
+        visible.push_str(g);
+        used += w;
+    }
+
+    (visible, left_marker + before_cursor)
+}
+
+// `text` cut down to at most `width` columns, ending in `…` when anything was dropped.
+// Whole graphemes are kept or dropped, so accents and emoji never get split.
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    truncate_with(text, width, "…")
+}
+
+// Same, with the marker for dropped text chosen by the caller (`...` in ASCII mode)
+pub fn truncate_with(text: &str, width: usize, ellipsis: &str) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width <= ellipsis.width() {
+        return ellipsis.chars().take(width).collect();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    for g in text.graphemes(true) {
+        let w = g.width();
+        if used + w + ellipsis.width() > width {
+            break;
+        }
+        truncated.push_str(g);
+        used += w;
+    }
+    truncated.push_str(ellipsis);
+    truncated
+}
+
+// Editable text with a cursor, shared by every text input (add, edit, search, notes, prompts).
+// The cursor is a byte offset into `content` and always sits on a char boundary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextBuffer {
+    pub content: String,
+    pub cursor: usize,
+}
+
+impl TextBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.content
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    // Replace the text, leaving the cursor at the end
+    pub fn set(&mut self, text: String) {
+        self.content = text;
+        self.cursor = self.content.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.content.clear();
+        self.cursor = 0;
+    }
+
+    pub fn before_cursor(&self) -> &str {
+        &self.content[..self.cursor]
+    }
+
+    // Column the terminal cursor belongs in, for single-line inputs
+    pub fn cursor_col(&self) -> usize {
+        display_col(&self.content, self.cursor)
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.content.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        self.content.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    // Backspace
+    pub fn delete_char_before(&mut self) {
+        let start = self.prev_boundary(self.cursor);
+        self.content.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    // Delete key: remove the character under the cursor
+    pub fn delete_char_after(&mut self) {
+        let end = self.next_boundary(self.cursor);
+        self.content.replace_range(self.cursor..end, "");
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.prev_boundary(self.cursor);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = self.next_boundary(self.cursor);
+    }
+
+    // Start of the previous word (Alt+B / Ctrl+Left)
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start_before(self.cursor);
+    }
+
+    // End of the next word (Alt+F / Ctrl+Right)
+    pub fn move_word_right(&mut self) {
+        let rest = &self.content[self.cursor..];
+        let skipped_space = rest.len() - rest.trim_start().len();
+        let word = rest[skipped_space..].find(char::is_whitespace).unwrap_or(rest.len() - skipped_space);
+        self.cursor += skipped_space + word;
+    }
+
+    // Ctrl+W: delete back to the start of the previous word
+    pub fn delete_word_before(&mut self) {
+        let start = self.word_start_before(self.cursor);
+        self.content.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    // Ctrl+U: delete back to the start of the line
+    pub fn kill_to_start(&mut self) {
+        let start = self.line_start();
+        self.content.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    // Ctrl+K: delete to the end of the line
+    pub fn kill_to_end(&mut self) {
+        let end = self.line_end();
+        self.content.replace_range(self.cursor..end, "");
+    }
+
+    // Up/Down keep the display column, clamped to the length of the line moved to
+    pub fn move_up(&mut self) {
+        let (line, col) = line_col(&self.content, self.cursor);
+        if line > 0 {
+            self.cursor = offset_at(&self.content, line - 1, col);
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        let (line, col) = line_col(&self.content, self.cursor);
+        if line < self.content.matches('\n').count() {
+            self.cursor = offset_at(&self.content, line + 1, col);
+        }
+    }
+
+    // Ctrl+Home/Ctrl+End
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.content.len();
+    }
+
+    // Home/End work on the current line, which for single-line inputs is the whole text
+    pub fn move_home(&mut self) {
+        self.cursor = self.line_start();
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.line_end();
+    }
+
+    fn line_start(&self) -> usize {
+        self.content[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self) -> usize {
+        self.content[self.cursor..].find('\n').map_or(self.content.len(), |i| self.cursor + i)
+    }
+
+    fn word_start_before(&self, pos: usize) -> usize {
+        let before = self.content[..pos].trim_end();
+        before.rfind(char::is_whitespace)
+            .map_or(0, |i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+    }
+
+    fn prev_boundary(&self, pos: usize) -> usize {
+        self.content[..pos].chars().next_back().map_or(pos, |c| pos - c.len_utf8())
+    }
+
+    fn next_boundary(&self, pos: usize) -> usize {
+        self.content[pos..].chars().next().map_or(pos, |c| pos + c.len_utf8())
+    }
+}
@@ -1,8 +1,23 @@
-use chrono::{DateTime, Local, NaiveDate, Datelike, Duration};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Datelike, Duration, Timelike};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::sync::LazyLock;
 use regex::Regex;
 
+// Compiled once: descriptions are parsed on every add and edit, and per line when importing
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"#([a-zA-Z0-9_]+)").unwrap());
+static CONTEXT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"@([a-zA-Z0-9_]+)").unwrap());
+static DUE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"due:([\w\-/]+)").unwrap());
+// A `due:` token with the space before it, for taking it out of the text
+static DUE_TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s*due:[\w\-/]+").unwrap());
+static RELATIVE_DATE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:in\s+)?(\d+)\s+(day|days|week|weeks|month|months|year|years)$").unwrap()
+});
+static NEXT_THIS_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(next|this)\s+(monday|tuesday|wednesday|thursday|friday|saturday|sunday|mon|tue|wed|thu|fri|sat|sun)$").unwrap()
+});
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TodoStatus {
     Pending,
@@ -10,8 +25,19 @@ pub enum TodoStatus {
     InProgress,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl TodoStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TodoStatus::Pending => "Pending",
+            TodoStatus::InProgress => "In Progress",
+            TodoStatus::Completed => "Completed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum RecurrencePattern {
+    #[default]
     None,
     Daily,
     Weekly,
@@ -20,50 +46,240 @@ pub enum RecurrencePattern {
     Custom(u32), // Custom interval in days
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimeEntry {
     pub start: DateTime<Local>,
+    #[serde(default)]
     pub end: Option<DateTime<Local>>,
+    #[serde(default)]
     pub description: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl TimeEntry {
+    // Negative for entries that took time off
+    pub fn duration_seconds(&self) -> i64 {
+        self.end.map_or(0, |end| end.signed_duration_since(self.start).num_seconds())
+    }
+    
+    // The day the entry counts toward in reports: the day it started (or, for a correction, ended)
+    pub fn day(&self) -> NaiveDate {
+        self.end.map_or(self.start, |end| end.min(self.start)).date_naive()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TimeTracker {
     pub total_seconds: u64,
     pub entries: Vec<TimeEntry>,
     pub current_session: Option<DateTime<Local>>, // When current session started
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// "45m", "1h30m", "2h" or "-20m" as seconds; units are required and zero isn't a duration
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let text = text.trim().to_lowercase();
+    let (sign, rest) = match text.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, text.strip_prefix('+').unwrap_or(&text)),
+    };
+    
+    let mut total: i64 = 0;
+    let mut number = String::new();
+    for c in rest.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        let value: i64 = std::mem::take(&mut number).parse().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+    }
+    
+    (number.is_empty() && total > 0).then_some(sign * total)
+}
+
+// Tracked time as "1h 5m" or "18m"
+pub fn format_tracked_time(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+// Like `format_tracked_time`, with a minus sign for time taken off
+pub fn format_signed_time(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    format!("{}{}", sign, format_tracked_time(seconds.unsigned_abs()))
+}
+
+// Whether a notes line is a checklist item (`- [ ] ...`, `* [x] ...`, any indent): Some(checked)
+pub fn checklist_state(line: &str) -> Option<bool> {
+    let content = line.trim_start();
+    let item = ["- ", "* ", "+ "].iter().find_map(|bullet| content.strip_prefix(bullet))?;
+    let (mark, rest) = item.strip_prefix('[')?.split_at_checked(2)?;
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    match mark {
+        " ]" => Some(false),
+        "x]" | "X]" => Some(true),
+        _ => None,
+    }
+}
+
+// Flip the checkbox on line `index` of `notes`; None when that line isn't a checklist item
+pub fn toggle_checklist_line(notes: &str, index: usize) -> Option<String> {
+    let line = notes.split('\n').nth(index)?;
+    let checked = checklist_state(line)?;
+    let line_start: usize = notes.split('\n').take(index).map(|line| line.len() + 1).sum();
+    // The mark sits just inside the `[`, after the indent and the two-character bullet
+    let mark = line_start + (line.len() - line.trim_start().len()) + 3;
+    let mut toggled = notes.to_string();
+    toggled.replace_range(mark..mark + 1, if checked { " " } else { "x" });
+    Some(toggled)
+}
+
+// Split pasted text into (depth, text) per non-empty line. Two spaces or a tab of
+// indentation is one level, as is each dash beyond the first in a `--` bullet; a
+// line can sit at most one level below the line before it.
+pub fn parse_outline(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for line in text.lines() {
+        let content = line.trim_start();
+        if content.trim().is_empty() {
+            continue;
+        }
+        let indent = &line[..line.len() - content.len()];
+        let mut depth = indent.matches('\t').count() + indent.matches(' ').count() / 2;
+        
+        let dashes = content.len() - content.trim_start_matches('-').len();
+        let content = if dashes > 0 && content[dashes..].starts_with(' ') {
+            depth += dashes - 1;
+            &content[dashes..]
+        } else if content.starts_with("* ") || content.starts_with("+ ") {
+            &content[1..]
+        } else {
+            content
+        };
+        lines.push((depth, content.trim().to_string()));
+    }
+    
+    let base = lines.iter().map(|(depth, _)| *depth).min().unwrap_or(0);
+    let mut previous: Option<usize> = None;
+    for (depth, _) in lines.iter_mut() {
+        *depth = (*depth - base).min(previous.map_or(0, |p| p + 1));
+        previous = Some(*depth);
+    }
+    lines
+}
+
+// What a todo's text will parse to, shown under the input while typing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsePreview {
+    pub tags: Vec<String>,
+    pub contexts: Vec<String>,
+    pub due_token: Option<String>, // The text after `due:`, if any
+    pub due_date: Option<DateTime<Local>>,
+}
+
+// What a todo's text says: the words without markers, its #tags and @contexts (lowercased)
+// and the `due:` token with the date it reads as
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedDescription {
+    pub description: String,
+    pub tags: HashSet<String>,
+    pub contexts: HashSet<String>,
+    pub due_token: Option<String>,
+    pub due_date: Option<DateTime<Local>>,
+}
+
+impl ParsedDescription {
+    pub fn parse(input: &str) -> Self {
+        let tags = TAG_RE.captures_iter(input).map(|cap| cap[1].to_lowercase()).collect();
+        let contexts = CONTEXT_RE.captures_iter(input).map(|cap| cap[1].to_lowercase()).collect();
+        let due_token = DUE_RE.captures(input).map(|cap| cap[1].to_string());
+        let due_date = due_token.as_deref().and_then(Todo::parse_due_date);
+        
+        // Keep the words of tags and contexts, drop the markers and the due token
+        let description = match due_token {
+            Some(_) => DUE_RE.replace(input, ""),
+            None => input.into(),
+        };
+        let description = TAG_RE.replace_all(&description, "$1");
+        let description = CONTEXT_RE.replace_all(&description, "$1").trim().to_string();
+        
+        Self { description, tags, contexts, due_token, due_date }
+    }
+}
+
+impl ParsePreview {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.contexts.is_empty() && self.due_token.is_none()
+    }
+    
+    // A `due:` was typed but the date parser didn't understand it
+    pub fn has_unrecognized_due(&self) -> bool {
+        self.due_token.is_some() && self.due_date.is_none()
+    }
+}
+
+// Fields other than the id, text, status and creation time may be missing from older
+// or newer files and take their defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Todo {
     pub id: u32,
     pub description: String,
     pub raw_description: String, // Original input with tags
+    #[serde(default)]
     pub tags: HashSet<String>,   // #tags extracted from description
+    #[serde(default)]
     pub contexts: HashSet<String>, // @contexts extracted from description
     pub status: TodoStatus,
     pub created_at: DateTime<Local>,
+    #[serde(default)]
     pub completed_at: Option<DateTime<Local>>,
+    #[serde(default)]
     pub due_date: Option<DateTime<Local>>,
+    #[serde(default)]
     pub priority: u8, // 0-5, higher is more important
+    #[serde(default)]
     pub parent_id: Option<u32>,
+    #[serde(default)]
     pub children: Vec<u32>,
+    #[serde(default = "expanded_by_default")]
     pub expanded: bool, // For UI - whether children are shown
     
     // Advanced features
+    #[serde(default)]
     pub notes: Option<String>, // Detailed notes/description
+    #[serde(default)]
     pub time_tracker: TimeTracker, // Time tracking data
+    #[serde(default)]
     pub recurrence: RecurrencePattern, // Recurring pattern
+    #[serde(default)]
     pub template_id: Option<String>, // If created from template
 }
 
+fn expanded_by_default() -> bool {
+    true
+}
+
 impl Todo {
     pub fn new(id: u32, raw_description: String) -> Self {
-        let (clean_description, tags, contexts, due_date) = Self::parse_description(&raw_description);
+        let ParsedDescription { description, tags, contexts, due_date, .. } = ParsedDescription::parse(&raw_description);
         
         Self {
             id,
-            description: clean_description,
+            description,
             raw_description,
             tags,
             contexts,
@@ -78,64 +294,31 @@ impl Todo {
             
             // Initialize advanced features
             notes: None,
-            time_tracker: TimeTracker {
-                total_seconds: 0,
-                entries: Vec::new(),
-                current_session: None,
-            },
+            time_tracker: TimeTracker::default(),
             recurrence: RecurrencePattern::None,
             template_id: None,
         }
     }
     
-    fn parse_description(input: &str) -> (String, HashSet<String>, HashSet<String>, Option<DateTime<Local>>) {
-        let mut description = input.to_string();
-        let mut tags = HashSet::new();
-        let mut contexts = HashSet::new();
-        let mut due_date = None;
+    pub fn parse_preview(input: &str) -> ParsePreview {
+        let ParsedDescription { tags, contexts, due_token, due_date, .. } = ParsedDescription::parse(input);
+        let mut tags: Vec<String> = tags.into_iter().collect();
+        tags.sort();
+        let mut contexts: Vec<String> = contexts.into_iter().collect();
+        contexts.sort();
         
-        // Extract #tags
-        let tag_re = Regex::new(r"#([a-zA-Z0-9_]+)").unwrap();
-        for cap in tag_re.captures_iter(input) {
-            if let Some(tag) = cap.get(1) {
-                tags.insert(tag.as_str().to_lowercase());
-            }
-        }
-        
-        // Extract @contexts
-        let context_re = Regex::new(r"@([a-zA-Z0-9_]+)").unwrap();
-        for cap in context_re.captures_iter(input) {
-            if let Some(context) = cap.get(1) {
-                contexts.insert(context.as_str().to_lowercase());
-            }
-        }
-        
-        // Extract due dates - simple patterns for now
-        let due_re = Regex::new(r"due:([\w\-/]+)").unwrap();
-        if let Some(cap) = due_re.captures(input) {
-            if let Some(due_str) = cap.get(1) {
-                due_date = Self::parse_due_date(due_str.as_str());
-                description = due_re.replace(&description, "").to_string();
-            }
-        }
-        
-        // Clean up description by removing tag/context markers but keeping the words
-        description = tag_re.replace_all(&description, "$1").to_string();
-        description = context_re.replace_all(&description, "$1").to_string();
-        description = description.trim().to_string();
-        
-        (description, tags, contexts, due_date)
+        ParsePreview { tags, contexts, due_token, due_date }
     }
     
-    fn parse_due_date(date_str: &str) -> Option<DateTime<Local>> {
+    pub(crate) fn parse_due_date(date_str: &str) -> Option<DateTime<Local>> {
         let now = Local::now();
         let date_lower = date_str.to_lowercase();
         
         // Basic relative dates
         match date_lower.as_str() {
-            "today" => return Some(now.date_naive().and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single()?),
-            "tomorrow" | "tmr" => return Some((now.date_naive() + chrono::Duration::days(1)).and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single()?),
-            "yesterday" => return Some((now.date_naive() - chrono::Duration::days(1)).and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single()?),
+            "today" => return now.date_naive().and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single(),
+            "tomorrow" | "tmr" => return (now.date_naive() + chrono::Duration::days(1)).and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single(),
+            "yesterday" => return (now.date_naive() - chrono::Duration::days(1)).and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single(),
             "monday" | "mon" => return Some(Self::next_weekday(now, chrono::Weekday::Mon)),
             "tuesday" | "tue" => return Some(Self::next_weekday(now, chrono::Weekday::Tue)),
             "wednesday" | "wed" => return Some(Self::next_weekday(now, chrono::Weekday::Wed)),
@@ -143,8 +326,8 @@ impl Todo {
             "friday" | "fri" => return Some(Self::next_weekday(now, chrono::Weekday::Fri)),
             "saturday" | "sat" => return Some(Self::next_weekday(now, chrono::Weekday::Sat)),
             "sunday" | "sun" => return Some(Self::next_weekday(now, chrono::Weekday::Sun)),
-            "eod" | "endofday" => return Some(now.date_naive().and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single()?),
-            "noon" => return Some(now.date_naive().and_hms_opt(12, 0, 0)?.and_local_timezone(Local).single()?),
+            "eod" | "endofday" => return now.date_naive().and_hms_opt(23, 59, 59)?.and_local_timezone(Local).single(),
+            "noon" => return now.date_naive().and_hms_opt(12, 0, 0)?.and_local_timezone(Local).single(),
             _ => {}
         }
         
@@ -164,9 +347,7 @@ impl Todo {
     
     fn parse_relative_date(date_str: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
         // Pattern: "in 3 days", "3 days", "in 2 weeks", "2 weeks", "in 1 month", "1 month"
-        let re = Regex::new(r"^(?:in\s+)?(\d+)\s+(day|days|week|weeks|month|months|year|years)$").unwrap();
-        
-        if let Some(caps) = re.captures(date_str) {
+        if let Some(caps) = RELATIVE_DATE_RE.captures(date_str) {
             if let (Some(num_str), Some(unit_str)) = (caps.get(1), caps.get(2)) {
                 if let Ok(num) = num_str.as_str().parse::<i64>() {
                     let duration = match unit_str.as_str() {
@@ -185,9 +366,7 @@ impl Todo {
     }
     
     fn parse_next_this_pattern(date_str: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
-        let re = Regex::new(r"^(next|this)\s+(monday|tuesday|wednesday|thursday|friday|saturday|sunday|mon|tue|wed|thu|fri|sat|sun)$").unwrap();
-        
-        if let Some(caps) = re.captures(date_str) {
+        if let Some(caps) = NEXT_THIS_RE.captures(date_str) {
             if let (Some(modifier), Some(day_str)) = (caps.get(1), caps.get(2)) {
                 let weekday = match day_str.as_str() {
                     "monday" | "mon" => chrono::Weekday::Mon,
@@ -212,6 +391,11 @@ impl Todo {
     }
     
     fn parse_absolute_date(date_str: &str) -> Option<DateTime<Local>> {
+        // A time of day is only written out by the due date editor, for due dates that have one
+        if let Ok(naive) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M") {
+            return naive.and_local_timezone(Local).single();
+        }
+        
         let formats = vec![
             "%Y-%m-%d",      // 2024-12-25
             "%m/%d/%Y",      // 12/25/2024
@@ -269,6 +453,18 @@ impl Todo {
         self.completed_at = None;
     }
 
+    // Completing stamps completed_at (kept if it was already completed); any other status clears it
+    pub fn set_status(&mut self, status: TodoStatus) {
+        match status {
+            TodoStatus::Completed if self.is_completed() => {}
+            TodoStatus::Completed => self.complete(),
+            status => {
+                self.status = status;
+                self.completed_at = None;
+            }
+        }
+    }
+
     pub fn toggle_complete(&mut self) {
         match self.status {
             TodoStatus::Completed => self.uncomplete(),
@@ -303,19 +499,47 @@ impl Todo {
     }
     
     pub fn stop_timer(&mut self) {
+        self.stop_timer_at(Local::now(), None);
+    }
+    
+    // Stop the running session as of `end_time` (never before it started), labelling the entry
+    pub fn stop_timer_at(&mut self, end_time: DateTime<Local>, description: Option<String>) {
         if let Some(start_time) = self.time_tracker.current_session.take() {
-            let end_time = Local::now();
+            let end_time = end_time.max(start_time);
             let duration = end_time.signed_duration_since(start_time);
             
             self.time_tracker.total_seconds += duration.num_seconds() as u64;
             self.time_tracker.entries.push(TimeEntry {
                 start: start_time,
                 end: Some(end_time),
-                description: None,
+                description,
             });
         }
     }
     
+    // Log time after the fact, or take some off with a negative duration. The entry ends now and
+    // spans the logged time (backwards for a correction); the total never drops below zero.
+    pub fn add_manual_time(&mut self, duration_secs: i64, description: Option<String>) {
+        let end = Local::now();
+        self.time_tracker.entries.push(TimeEntry {
+            start: end - Duration::seconds(duration_secs),
+            end: Some(end),
+            description,
+        });
+        self.time_tracker.total_seconds = self.time_tracker.total_seconds.saturating_add_signed(duration_secs);
+    }
+    
+    // Drop a time entry and recount the total from the entries that are left
+    pub fn remove_time_entry(&mut self, index: usize) -> Option<TimeEntry> {
+        if index >= self.time_tracker.entries.len() {
+            return None;
+        }
+        let removed = self.time_tracker.entries.remove(index);
+        let total: i64 = self.time_tracker.entries.iter().map(TimeEntry::duration_seconds).sum();
+        self.time_tracker.total_seconds = total.max(0) as u64;
+        Some(removed)
+    }
+    
     pub fn is_timer_running(&self) -> bool {
         self.time_tracker.current_session.is_some()
     }
@@ -326,6 +550,12 @@ impl Todo {
         })
     }
     
+    // Running session as a clock, e.g. "00:42:13"
+    pub fn get_current_session_formatted(&self) -> Option<String> {
+        let seconds = self.get_current_session_duration()?.num_seconds().max(0);
+        Some(format!("{:02}:{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60, seconds % 60))
+    }
+    
     pub fn get_total_time_formatted(&self) -> String {
         let mut total_seconds = self.time_tracker.total_seconds;
         
@@ -334,14 +564,7 @@ impl Todo {
             total_seconds += duration.num_seconds() as u64;
         }
         
-        let hours = total_seconds / 3600;
-        let minutes = (total_seconds % 3600) / 60;
-        
-        if hours > 0 {
-            format!("{}h {}m", hours, minutes)
-        } else {
-            format!("{}m", minutes)
-        }
+        format_tracked_time(total_seconds)
     }
     
     // Notes methods
@@ -349,15 +572,56 @@ impl Todo {
         self.notes = notes;
     }
     
+    // (checked, total) over the checklist lines in the notes, if there are any
+    pub fn notes_checklist_progress(&self) -> Option<(usize, usize)> {
+        let states: Vec<bool> = self.notes.as_deref()?.lines().filter_map(checklist_state).collect();
+        if states.is_empty() {
+            return None;
+        }
+        Some((states.iter().filter(|&&checked| checked).count(), states.len()))
+    }
+    
     pub fn update_description(&mut self, new_raw_description: String) {
-        let (clean_description, tags, contexts, due_date) = Self::parse_description(&new_raw_description);
+        let ParsedDescription { description, tags, contexts, due_date, .. } = ParsedDescription::parse(&new_raw_description);
         self.raw_description = new_raw_description;
-        self.description = clean_description;
+        self.description = description;
         self.tags = tags;
         self.contexts = contexts;
         self.due_date = due_date; // Always update due_date, even if None (to clear existing dates)
     }
     
+    // Like update_description, but keeps the due date: re-parsing a relative `due:` would move it
+    pub fn replace_raw_description(&mut self, new_raw_description: String) {
+        let due_date = self.due_date;
+        self.update_description(new_raw_description);
+        self.due_date = due_date;
+    }
+    
+    // Set or clear the due date, rewriting the `due:` token so editing the text later
+    // re-parses the same day
+    pub fn set_due_date(&mut self, due_date: Option<DateTime<Local>>) {
+        let mut raw = DUE_TOKEN_RE.replace_all(&self.raw_description, "").trim().to_string();
+        if let Some(due) = due_date {
+            raw = format!("{} due:{}", raw, due.format("%Y-%m-%d"));
+        }
+        self.replace_raw_description(raw);
+        self.due_date = due_date;
+    }
+    
+    // The due date as it's typed back in: the `due:` token as written, or the date (with the
+    // time, unless it's the end of the day) when the text has none
+    pub fn due_input(&self) -> String {
+        let Some(due) = self.due_date else { return String::new() };
+        if let Some(token) = DUE_RE.captures(&self.raw_description) {
+            return token[1].to_string();
+        }
+        if (due.hour(), due.minute(), due.second()) == (23, 59, 59) {
+            due.format("%Y-%m-%d").to_string()
+        } else {
+            due.format("%Y-%m-%d %H:%M").to_string()
+        }
+    }
+    
     pub fn has_notes(&self) -> bool {
         self.notes.is_some() && !self.notes.as_ref().unwrap().trim().is_empty()
     }
@@ -399,10 +663,85 @@ impl Todo {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortMode {
+    #[default]
+    Priority,    // Priority (high to low), then due date
+    DueDate,     // Earliest due first, undated last, then priority
+    Created,     // Oldest first
+    Alphabetical,
+}
+
+// What todos can be ordered by. Each sort mode compares by its own key first and breaks ties
+// with the rest of the chain below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Priority,    // High to low
+    DueDate,     // Earliest first, undated last
+    Created,     // Oldest first
+    Description, // A to Z, ignoring case
+}
+
+impl SortKey {
+    fn compare(self, a: &Todo, b: &Todo) -> std::cmp::Ordering {
+        match self {
+            SortKey::Priority => b.priority.cmp(&a.priority),
+            SortKey::DueDate => match (&a.due_date, &b.due_date) {
+                (Some(a_due), Some(b_due)) => a_due.cmp(b_due),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            },
+            SortKey::Created => a.created_at.cmp(&b.created_at),
+            SortKey::Description => a.description.to_lowercase().cmp(&b.description.to_lowercase()),
+        }
+    }
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Priority => SortMode::DueDate,
+            SortMode::DueDate => SortMode::Created,
+            SortMode::Created => SortMode::Alphabetical,
+            SortMode::Alphabetical => SortMode::Priority,
+        }
+    }
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            SortMode::Priority => "priority",
+            SortMode::DueDate => "due date",
+            SortMode::Created => "created",
+            SortMode::Alphabetical => "name",
+        }
+    }
+    
+    // Keys compared in turn until one tells the todos apart
+    fn keys(self) -> &'static [SortKey] {
+        match self {
+            SortMode::Priority => &[SortKey::Priority, SortKey::DueDate, SortKey::Created],
+            SortMode::DueDate => &[SortKey::DueDate, SortKey::Priority, SortKey::Created],
+            SortMode::Created => &[SortKey::Created],
+            SortMode::Alphabetical => &[SortKey::Description, SortKey::Priority, SortKey::DueDate, SortKey::Created],
+        }
+    }
+    
+    // Ids settle anything left, so the order doesn't depend on the map's
+    pub fn compare(self, a: &Todo, b: &Todo) -> std::cmp::Ordering {
+        self.keys().iter()
+            .map(|key| key.compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.id.cmp(&b.id))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TodoList {
     pub todos: HashMap<u32, Todo>,
     pub next_id: u32,
+    #[serde(default)]
+    pub sort_mode: SortMode,
 }
 
 impl TodoList {
@@ -410,6 +749,7 @@ impl TodoList {
         Self {
             todos: HashMap::new(),
             next_id: 1,
+            sort_mode: SortMode::default(),
         }
     }
 
@@ -421,6 +761,27 @@ impl TodoList {
         id
     }
 
+    // Add one todo per outline line, nesting indented lines under the line above;
+    // top-level lines go under `parent` when given. Returns the new ids in order.
+    pub fn add_outline(&mut self, text: &str, parent: Option<u32>) -> Vec<u32> {
+        let mut added = Vec::new();
+        let mut ancestors: Vec<(usize, u32)> = Vec::new();
+        for (depth, line) in parse_outline(text) {
+            while ancestors.last().is_some_and(|(d, _)| *d >= depth) {
+                ancestors.pop();
+            }
+            let id = match ancestors.last().map(|(_, id)| *id).or(parent) {
+                Some(parent_id) => self.add_child_todo(parent_id, line),
+                None => Some(self.add_todo(line)),
+            };
+            if let Some(id) = id {
+                ancestors.push((depth, id));
+                added.push(id);
+            }
+        }
+        added
+    }
+
     pub fn remove_todo(&mut self, id: u32) -> Option<Todo> {
         // First, get the todo to check if it has a parent
         let todo = self.todos.get(&id);
@@ -447,11 +808,7 @@ impl TodoList {
 
     pub fn get_all_todos(&self) -> Vec<&Todo> {
         let mut todos: Vec<&Todo> = self.todos.values().collect();
-        // Sort by priority (high to low), then by creation date
-        todos.sort_by(|a, b| {
-            b.priority.cmp(&a.priority)
-                .then_with(|| a.created_at.cmp(&b.created_at))
-        });
+        todos.sort_by(|a, b| self.sort_mode.compare(a, b));
         todos
     }
 
@@ -461,6 +818,15 @@ impl TodoList {
             .collect()
     }
 
+    // Pending todos that are overdue or due within `within` of `now`, soonest first
+    pub fn due_soon(&self, within: Duration, now: DateTime<Local>) -> Vec<&Todo> {
+        let mut todos: Vec<&Todo> = self.todos.values()
+            .filter(|todo| !todo.is_completed() && todo.due_date.is_some_and(|due| due <= now + within))
+            .collect();
+        todos.sort_by_key(|todo| (todo.due_date, todo.id));
+        todos
+    }
+
     pub fn get_completed_todos(&self) -> Vec<&Todo> {
         self.get_all_todos().into_iter()
             .filter(|todo| todo.is_completed())
@@ -483,6 +849,10 @@ impl TodoList {
         self.todos.values().filter(|todo| todo.is_completed()).count()
     }
 
+    pub fn in_progress_count(&self) -> usize {
+        self.todos.values().filter(|todo| todo.status == TodoStatus::InProgress).count()
+    }
+
     // Hierarchical methods
     pub fn add_child_todo(&mut self, parent_id: u32, description: String) -> Option<u32> {
         // Check if parent exists
@@ -509,11 +879,7 @@ impl TodoList {
             .filter(|todo| todo.parent_id.is_none())
             .collect();
         
-        // Sort by priority (high to low), then by creation date
-        todos.sort_by(|a, b| {
-            b.priority.cmp(&a.priority)
-                .then_with(|| a.created_at.cmp(&b.created_at))
-        });
+        todos.sort_by(|a, b| self.sort_mode.compare(a, b));
         todos
     }
 
@@ -523,11 +889,7 @@ impl TodoList {
                 .filter_map(|&child_id| self.todos.get(&child_id))
                 .collect();
             
-            // Sort children by priority, then creation date
-            children.sort_by(|a, b| {
-                b.priority.cmp(&a.priority)
-                    .then_with(|| a.created_at.cmp(&b.created_at))
-            });
+            children.sort_by(|a, b| self.sort_mode.compare(a, b));
             children
         } else {
             Vec::new()
@@ -539,6 +901,7 @@ impl TodoList {
         
         fn add_todo_and_children<'a>(
             todos: &'a HashMap<u32, Todo>,
+            sort_mode: SortMode,
             result: &mut Vec<(&'a Todo, u32)>,
             todo: &'a Todo,
             depth: u32,
@@ -551,20 +914,17 @@ impl TodoList {
                     .collect();
                 
                 // Sort children
-                children.sort_by(|a, b| {
-                    b.priority.cmp(&a.priority)
-                        .then_with(|| a.created_at.cmp(&b.created_at))
-                });
+                children.sort_by(|a, b| sort_mode.compare(a, b));
                 
                 for child in children {
-                    add_todo_and_children(todos, result, child, depth + 1);
+                    add_todo_and_children(todos, sort_mode, result, child, depth + 1);
                 }
             }
         }
         
         let root_todos = self.get_root_todos();
         for todo in root_todos {
-            add_todo_and_children(&self.todos, &mut result, todo, 0);
+            add_todo_and_children(&self.todos, self.sort_mode, &mut result, todo, 0);
         }
         
         result
@@ -576,6 +936,12 @@ impl TodoList {
             .collect()
     }
 
+    pub fn get_flattened_in_progress_todos(&self) -> Vec<(&Todo, u32)> {
+        self.get_flattened_todos().into_iter()
+            .filter(|(todo, _)| todo.status == TodoStatus::InProgress)
+            .collect()
+    }
+
     pub fn get_flattened_completed_todos(&self) -> Vec<(&Todo, u32)> {
         self.get_flattened_todos().into_iter()
             .filter(|(todo, _)| todo.is_completed())
@@ -588,6 +954,35 @@ impl TodoList {
         }
     }
 
+    // Expand or collapse every todo with children
+    pub fn set_all_expanded(&mut self, expanded: bool) {
+        for todo in self.todos.values_mut().filter(|todo| !todo.children.is_empty()) {
+            todo.expanded = expanded;
+        }
+    }
+
+    // Show the tree down to `depth`: todos above it expanded, those at or below it collapsed.
+    // Depth 0 leaves only the top level showing.
+    pub fn collapse_to_depth(&mut self, depth: u32) {
+        let depths: Vec<(u32, u32)> = self.todos.keys().map(|&id| (id, self.depth_of(id))).collect();
+        for (id, todo_depth) in depths {
+            if let Some(todo) = self.todos.get_mut(&id).filter(|todo| !todo.children.is_empty()) {
+                todo.expanded = todo_depth < depth;
+            }
+        }
+    }
+
+    // Number of ancestors above a todo
+    pub fn depth_of(&self, id: u32) -> u32 {
+        let mut depth = 0;
+        let mut parent = self.todos.get(&id).and_then(|todo| todo.parent_id);
+        while let Some(parent_id) = parent {
+            depth += 1;
+            parent = self.todos.get(&parent_id).and_then(|todo| todo.parent_id);
+        }
+        depth
+    }
+
     pub fn remove_todo_and_children(&mut self, id: u32) -> Vec<Todo> {
         let mut removed = Vec::new();
         
@@ -623,12 +1018,72 @@ impl TodoList {
         removed
     }
 
+    // Insert todos taken from another list, assigning fresh ids from this list's counter.
+    // Parent/child links inside the batch are remapped; todos whose parent isn't part of
+    // the batch become root todos. Returns the inserted todos with their new ids.
+    pub fn adopt_todos(&mut self, todos: Vec<Todo>) -> Vec<Todo> {
+        let mut id_map: HashMap<u32, u32> = HashMap::new();
+        for todo in &todos {
+            id_map.insert(todo.id, self.next_id);
+            self.next_id += 1;
+        }
+
+        let mut adopted = Vec::new();
+        for mut todo in todos {
+            todo.id = id_map[&todo.id];
+            todo.parent_id = todo.parent_id.and_then(|parent_id| id_map.get(&parent_id).copied());
+            todo.children = todo.children.iter()
+                .filter_map(|child_id| id_map.get(child_id).copied())
+                .collect();
+            self.todos.insert(todo.id, todo.clone());
+            adopted.push(todo);
+        }
+
+        adopted
+    }
+
+    // Re-insert previously removed todos with their original ids, re-linking them
+    // to any parent that is still present in the list
+    pub fn restore_todos(&mut self, todos: &[Todo]) {
+        for todo in todos {
+            self.todos.insert(todo.id, todo.clone());
+            if todo.id >= self.next_id {
+                self.next_id = todo.id + 1;
+            }
+        }
+
+        for todo in todos {
+            if let Some(parent_id) = todo.parent_id {
+                if let Some(parent) = self.todos.get_mut(&parent_id) {
+                    if !parent.children.contains(&todo.id) {
+                        parent.children.push(todo.id);
+                    }
+                } else if let Some(orphan) = self.todos.get_mut(&todo.id) {
+                    orphan.parent_id = None;
+                }
+            }
+        }
+    }
+
     pub fn has_children(&self, id: u32) -> bool {
         self.todos.get(&id)
             .map(|todo| !todo.children.is_empty())
             .unwrap_or(false)
     }
 
+    pub fn has_ancestor_in(&self, id: u32, candidates: &[u32]) -> bool {
+        let mut current = self.todos.get(&id).and_then(|todo| todo.parent_id);
+        
+        while let Some(parent_id) = current {
+            if candidates.contains(&parent_id) {
+                return true;
+            }
+            current = self.todos.get(&parent_id).and_then(|todo| todo.parent_id);
+        }
+        
+        false
+    }
+
     pub fn get_depth(&self, id: u32) -> u32 {
         let mut depth = 0;
         let mut current_id = id;
@@ -646,52 +1101,131 @@ impl TodoList {
     }
     
     // Filtering and search methods
+    // Matches in score order (best first, ties by priority), shown flat rather than as a tree
     pub fn search_todos(&self, query: &str) -> Vec<(&Todo, u32)> {
-        let query_lower = query.to_lowercase();
-        self.get_flattened_todos().into_iter()
-            .filter(|(todo, _)| {
-                todo.description.to_lowercase().contains(&query_lower) ||
-                todo.tags.iter().any(|tag| tag.contains(&query_lower)) ||
-                todo.contexts.iter().any(|ctx| ctx.contains(&query_lower))
-            })
+        self.search_scored(query).into_iter()
+            .map(|(todo, _)| (todo, 0))
             .collect()
     }
     
-    pub fn filter_by_tag(&self, tag: &str) -> Vec<(&Todo, u32)> {
-        let tag_lower = tag.to_lowercase();
-        self.get_flattened_todos().into_iter()
-            .filter(|(todo, _)| todo.tags.contains(&tag_lower))
-            .collect()
+    pub fn search_scored(&self, query: &str) -> Vec<(&Todo, i64)> {
+        let mut results: Vec<(&Todo, i64)> = self.todos.values()
+            .filter_map(|todo| crate::search::score_todo(todo, query).map(|score| (todo, score)))
+            .collect();
+        results.sort_by(|(a, a_score), (b, b_score)| {
+            b_score.cmp(a_score).then_with(|| SortMode::Priority.compare(a, b))
+        });
+        results
     }
     
-    pub fn filter_by_context(&self, context: &str) -> Vec<(&Todo, u32)> {
-        let context_lower = context.to_lowercase();
-        self.get_flattened_todos().into_iter()
-            .filter(|(todo, _)| todo.contexts.contains(&context_lower))
+    // Todos passing every active filter; a text query switches to flat, score-ordered results
+    pub fn filter_todos(&self, filters: &FilterSet) -> Vec<(&Todo, u32)> {
+        let now = Local::now();
+        let todos = match &filters.query {
+            Some(query) => self.search_todos(query),
+            None => self.get_flattened_todos(),
+        };
+        
+        todos.into_iter()
+            .filter(|(todo, _)| filters.matches(todo, now))
             .collect()
     }
     
-    pub fn filter_by_due_date(&self, filter_type: DueDateFilter) -> Vec<(&Todo, u32)> {
-        let now = Local::now();
-        let today = now.date_naive();
+    // Pending todos under their agenda bucket, earliest due first; empty buckets are left out
+    pub fn agenda_buckets(&self) -> Vec<(AgendaBucket, Vec<&Todo>)> {
+        self.agenda_buckets_at(Local::now())
+    }
+    
+    pub fn agenda_buckets_at(&self, now: DateTime<Local>) -> Vec<(AgendaBucket, Vec<&Todo>)> {
+        let mut todos: Vec<&Todo> = self.todos.values().filter(|todo| !todo.is_completed()).collect();
+        todos.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| SortMode::Priority.compare(a, b)));
         
-        self.get_flattened_todos().into_iter()
-            .filter(|(todo, _)| {
-                match (&todo.due_date, filter_type) {
-                    (Some(due), DueDateFilter::Overdue) => due < &now && !todo.is_completed(),
-                    (Some(due), DueDateFilter::Today) => due.date_naive() == today,
-                    (Some(due), DueDateFilter::Tomorrow) => due.date_naive() == today + chrono::Duration::days(1),
-                    (Some(due), DueDateFilter::ThisWeek) => {
-                        let week_from_now = now + chrono::Duration::days(7);
-                        due >= &now && due <= &week_from_now
-                    },
-                    (None, DueDateFilter::NoDueDate) => true,
-                    _ => false,
-                }
+        AgendaBucket::ALL.into_iter()
+            .map(|bucket| {
+                let rows: Vec<&Todo> = todos.iter().copied()
+                    .filter(|todo| AgendaBucket::for_due(todo.due_date, now) == bucket)
+                    .collect();
+                (bucket, rows)
             })
+            .filter(|(_, rows)| !rows.is_empty())
             .collect()
     }
     
+    pub fn filter_excluding_tag(&self, tag: &str) -> Vec<(&Todo, u32)> {
+        self.filter_todos(&FilterSet { tag: Some(tag.to_string()), exclude_tag: true, ..FilterSet::default() })
+    }
+    
+    pub fn filter_excluding_context(&self, context: &str) -> Vec<(&Todo, u32)> {
+        self.filter_todos(&FilterSet { context: Some(context.to_string()), exclude_context: true, ..FilterSet::default() })
+    }
+    
+    // Rename a tag on every todo carrying it; returns how many todos changed
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> usize {
+        self.rewrite_label('#', old, Some(new))
+    }
+    
+    pub fn rename_context(&mut self, old: &str, new: &str) -> usize {
+        self.rewrite_label('@', old, Some(new))
+    }
+    
+    // Strip a tag from every todo carrying it; returns how many todos changed
+    pub fn remove_tag(&mut self, tag: &str) -> usize {
+        self.rewrite_label('#', tag, None)
+    }
+    
+    pub fn remove_context(&mut self, context: &str) -> usize {
+        self.rewrite_label('@', context, None)
+    }
+    
+    // Rewrite `#old`/`@old` as `#new`/`@new` (or drop it) in raw descriptions, leaving
+    // longer labels such as `#oldest` alone
+    fn rewrite_label(&mut self, marker: char, old: &str, new: Option<&str>) -> usize {
+        let old = old.to_lowercase();
+        let label = format!("{}{}", regex::escape(&marker.to_string()), regex::escape(&old));
+        let (pattern, replacement) = match new {
+            Some(new) => (format!(r"(?i){}([^a-zA-Z0-9_]|$)", label), format!("{}{}${{1}}", marker, new)),
+            None => (format!(r"(?i)\s*{}([^a-zA-Z0-9_]|$)", label), "${1}".to_string()),
+        };
+        let label_re = Regex::new(&pattern).unwrap();
+        
+        let mut changed = 0;
+        for todo in self.todos.values_mut() {
+            let labels = if marker == '#' { &todo.tags } else { &todo.contexts };
+            if !labels.contains(&old) {
+                continue;
+            }
+            let raw = label_re.replace_all(&todo.raw_description, replacement.as_str()).trim().to_string();
+            todo.replace_raw_description(raw);
+            changed += 1;
+        }
+        changed
+    }
+    
+    // Append `#tag` to the given todos that don't carry it yet; returns how many todos changed
+    pub fn add_tag(&mut self, ids: &[u32], tag: &str) -> usize {
+        self.add_label('#', ids, tag)
+    }
+    
+    pub fn add_context(&mut self, ids: &[u32], context: &str) -> usize {
+        self.add_label('@', ids, context)
+    }
+    
+    fn add_label(&mut self, marker: char, ids: &[u32], label: &str) -> usize {
+        let lower = label.to_lowercase();
+        let mut changed = 0;
+        for id in ids {
+            let Some(todo) = self.todos.get_mut(id) else { continue };
+            let labels = if marker == '#' { &todo.tags } else { &todo.contexts };
+            if labels.contains(&lower) {
+                continue;
+            }
+            let raw = format!("{} {}{}", todo.raw_description.trim_end(), marker, label);
+            todo.replace_raw_description(raw);
+            changed += 1;
+        }
+        changed
+    }
+    
     pub fn get_all_tags(&self) -> Vec<String> {
         let mut tags: HashSet<String> = HashSet::new();
         for todo in self.todos.values() {
@@ -741,6 +1275,89 @@ impl TodoList {
         sorted_tags
     }
     
+    // Time logged on days in `days`, per #tag and @context (a todo with several counts toward each),
+    // most first. Todos with neither are grouped as "(untagged)".
+    pub fn time_by_tag(&self, days: &RangeInclusive<NaiveDate>) -> Vec<(String, u64)> {
+        let mut totals: HashMap<String, i64> = HashMap::new();
+        for todo in self.todos.values() {
+            let seconds: i64 = todo.time_tracker.entries.iter()
+                .filter(|entry| days.contains(&entry.day()))
+                .map(TimeEntry::duration_seconds)
+                .sum();
+            if seconds == 0 {
+                continue;
+            }
+            
+            let mut labels: Vec<String> = todo.tags.iter().map(|tag| format!("#{}", tag))
+                .chain(todo.contexts.iter().map(|context| format!("@{}", context)))
+                .collect();
+            if labels.is_empty() {
+                labels.push("(untagged)".to_string());
+            }
+            for label in labels {
+                *totals.entry(label).or_insert(0) += seconds;
+            }
+        }
+        
+        let mut sorted: Vec<(String, u64)> = totals.into_iter()
+            .filter(|(_, seconds)| *seconds > 0)
+            .map(|(label, seconds)| (label, seconds as u64))
+            .collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        sorted
+    }
+    
+    // Todos due on each day in `days`, in order and earliest first. Pending todos already past
+    // their due time are left out; the calendar lists them with `overdue_todos` instead.
+    pub fn due_by_day(&self, days: &RangeInclusive<NaiveDate>, now: DateTime<Local>) -> Vec<(NaiveDate, Vec<&Todo>)> {
+        let mut todos: Vec<&Todo> = self.todos.values()
+            .filter(|todo| todo.due_date.is_some_and(|due| days.contains(&due.date_naive())))
+            .filter(|todo| !DueDateFilter::Overdue.matches(todo, now))
+            .collect();
+        todos.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| SortMode::Priority.compare(a, b)));
+        
+        days.start().iter_days()
+            .take_while(|day| day <= days.end())
+            .map(|day| {
+                let due: Vec<&Todo> = todos.iter().copied()
+                    .filter(|todo| todo.due_date.is_some_and(|due| due.date_naive() == day))
+                    .collect();
+                (day, due)
+            })
+            .collect()
+    }
+    
+    // Every todo with `status`, including subtasks, in the list's sort order
+    pub fn todos_with_status(&self, status: &TodoStatus) -> Vec<&Todo> {
+        let mut todos: Vec<&Todo> = self.todos.values().filter(|todo| &todo.status == status).collect();
+        todos.sort_by(|a, b| self.sort_mode.compare(a, b));
+        todos
+    }
+    
+    // Pending todos past their due time, most overdue first
+    pub fn overdue_todos(&self, now: DateTime<Local>) -> Vec<&Todo> {
+        let mut todos: Vec<&Todo> = self.todos.values()
+            .filter(|todo| DueDateFilter::Overdue.matches(todo, now))
+            .collect();
+        todos.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| SortMode::Priority.compare(a, b)));
+        todos
+    }
+    
+    // Time logged on each day in `days`, in order, including days with nothing
+    pub fn time_by_day(&self, days: &RangeInclusive<NaiveDate>) -> Vec<(NaiveDate, u64)> {
+        let mut totals: HashMap<NaiveDate, i64> = HashMap::new();
+        for entry in self.todos.values().flat_map(|todo| &todo.time_tracker.entries) {
+            if days.contains(&entry.day()) {
+                *totals.entry(entry.day()).or_insert(0) += entry.duration_seconds();
+            }
+        }
+        
+        days.start().iter_days()
+            .take_while(|day| day <= days.end())
+            .map(|day| (day, totals.get(&day).copied().unwrap_or(0).max(0) as u64))
+            .collect()
+    }
+    
     pub fn get_context_counts(&self) -> Vec<(String, usize)> {
         let mut context_counts: HashMap<String, usize> = HashMap::new();
         
@@ -802,10 +1419,121 @@ impl TodoList {
             .collect()
     }
     
+    // Stop every running timer, returning each todo's description and the seconds it just added
+    pub fn stop_all_timers(&mut self) -> Vec<(String, u64)> {
+        let mut stopped = Vec::new();
+        for todo in self.todos.values_mut().filter(|todo| todo.is_timer_running()) {
+            let before = todo.time_tracker.total_seconds;
+            todo.stop_timer();
+            stopped.push((todo.description.clone(), todo.time_tracker.total_seconds - before));
+        }
+        stopped
+    }
+    
+    // Three-way merge with a copy changed elsewhere, `base` being the version both started from.
+    // Todos are the same todo when id and created_at match, so an id taken by a different todo
+    // on each side means both added one and theirs is given a fresh id. A todo changed on both
+    // sides keeps whichever was completed more recently, and a change here outlives a removal there.
+    pub fn merge(&mut self, base: &TodoList, theirs: TodoList) -> MergeSummary {
+        let same = |a: Option<&Todo>, b: &Todo| a.is_some_and(|a| a.created_at == b.created_at);
+        let mut summary = MergeSummary::default();
+
+        let removed: Vec<u32> = self.todos.values()
+            .filter(|todo| !same(theirs.todos.get(&todo.id), todo) && base.todos.get(&todo.id) == Some(*todo))
+            .map(|todo| todo.id)
+            .collect();
+        for id in removed {
+            self.remove_todo(id);
+            summary.removed += 1;
+        }
+
+        // Ids past either counter are free on both sides
+        self.next_id = self.next_id.max(theirs.next_id);
+        let mut their_todos: Vec<Todo> = theirs.todos.into_values().collect();
+        their_todos.sort_by_key(|todo| todo.id);
+        let mut ids = HashMap::new(); // Their id -> id here
+        let mut incoming = Vec::new();
+        for todo in their_todos {
+            if let Some(ours) = self.todos.get(&todo.id).filter(|ours| same(Some(ours), &todo)) {
+                ids.insert(todo.id, todo.id);
+                let changed_only_there = base.todos.get(&todo.id) == Some(ours);
+                if *ours != todo && (changed_only_there || todo.completed_at > ours.completed_at) {
+                    summary.updated += 1;
+                    incoming.push(todo);
+                }
+            } else if !same(base.todos.get(&todo.id), &todo) {
+                // Added there; todos in the base but gone from here were removed here and stay removed
+                let id = if self.todos.contains_key(&todo.id) {
+                    self.next_id += 1;
+                    self.next_id - 1
+                } else {
+                    todo.id
+                };
+                ids.insert(todo.id, id);
+                summary.added += 1;
+                incoming.push(todo);
+            }
+        }
+
+        for mut todo in incoming {
+            todo.id = ids[&todo.id];
+            todo.parent_id = todo.parent_id.and_then(|parent| ids.get(&parent).copied());
+            todo.children = todo.children.iter().filter_map(|child| ids.get(child).copied()).collect();
+            self.todos.insert(todo.id, todo);
+        }
+        self.relink();
+        summary
+    }
+
+    // Make parent_id and children agree again after todos arrived from a merge
+    fn relink(&mut self) {
+        let mut ids: Vec<u32> = self.todos.keys().copied().collect();
+        ids.sort_unstable();
+        let parents: HashMap<u32, Option<u32>> = self.todos.values()
+            .map(|todo| (todo.id, todo.parent_id.filter(|parent| self.todos.contains_key(parent))))
+            .collect();
+        for id in &ids {
+            let todo = self.todos.get_mut(id).unwrap();
+            todo.parent_id = parents[id];
+            let mut seen = HashSet::new();
+            todo.children.retain(|child| parents.get(child) == Some(&Some(*id)) && seen.insert(*child));
+        }
+        for id in ids {
+            if let Some(parent) = parents[&id] {
+                let children = &mut self.todos.get_mut(&parent).unwrap().children;
+                if !children.contains(&id) {
+                    children.push(id);
+                }
+            }
+        }
+    }
+    
     // Template-related methods will be added when we create the template system
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+// What merging in a copy changed elsewhere did here, counting todos and workspaces
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+impl MergeSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added + self.updated + self.removed == 0
+    }
+}
+
+impl std::ops::AddAssign for MergeSummary {
+    fn add_assign(&mut self, other: Self) {
+        self.added += other.added;
+        self.updated += other.updated;
+        self.removed += other.removed;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum DueDateFilter {
     Overdue,
     Today,
@@ -814,13 +1542,258 @@ pub enum DueDateFilter {
     NoDueDate,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl DueDateFilter {
+    pub fn matches(self, todo: &Todo, now: DateTime<Local>) -> bool {
+        let today = now.date_naive();
+        match (&todo.due_date, self) {
+            (Some(due), DueDateFilter::Overdue) => due < &now && !todo.is_completed(),
+            (Some(due), DueDateFilter::Today) => due.date_naive() == today,
+            (Some(due), DueDateFilter::Tomorrow) => due.date_naive() == today + chrono::Duration::days(1),
+            (Some(due), DueDateFilter::ThisWeek) => {
+                let week_from_now = now + chrono::Duration::days(7);
+                due >= &now && due <= &week_from_now
+            },
+            (None, DueDateFilter::NoDueDate) => true,
+            _ => false,
+        }
+    }
+    
+    // Next filter in the `!` rotation; None once the rotation is exhausted
+    pub fn next(self) -> Option<Self> {
+        match self {
+            DueDateFilter::Overdue => Some(DueDateFilter::Today),
+            DueDateFilter::Today => Some(DueDateFilter::Tomorrow),
+            DueDateFilter::Tomorrow => Some(DueDateFilter::ThisWeek),
+            DueDateFilter::ThisWeek => Some(DueDateFilter::NoDueDate),
+            DueDateFilter::NoDueDate => None,
+        }
+    }
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            DueDateFilter::Overdue => "Overdue",
+            DueDateFilter::Today => "Due Today",
+            DueDateFilter::Tomorrow => "Due Tomorrow",
+            DueDateFilter::ThisWeek => "This Week",
+            DueDateFilter::NoDueDate => "No Due Date",
+        }
+    }
+}
+
+// Agenda sections, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AgendaBucket {
+    Overdue,
+    Today,
+    Tomorrow,
+    ThisWeek, // The rest of the next 7 days
+    Later,
+    NoDate,
+}
+
+impl AgendaBucket {
+    pub const ALL: [AgendaBucket; 6] = [
+        AgendaBucket::Overdue,
+        AgendaBucket::Today,
+        AgendaBucket::Tomorrow,
+        AgendaBucket::ThisWeek,
+        AgendaBucket::Later,
+        AgendaBucket::NoDate,
+    ];
+    
+    // Overdue means past its due time; the other buckets go by calendar day, so "This Week"
+    // covers the days after tomorrow up to 6 days from today
+    pub fn for_due(due: Option<DateTime<Local>>, now: DateTime<Local>) -> Self {
+        let Some(due) = due else { return AgendaBucket::NoDate };
+        if due < now {
+            return AgendaBucket::Overdue;
+        }
+        match (due.date_naive() - now.date_naive()).num_days() {
+            0 => AgendaBucket::Today,
+            1 => AgendaBucket::Tomorrow,
+            2..=6 => AgendaBucket::ThisWeek,
+            _ => AgendaBucket::Later,
+        }
+    }
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            AgendaBucket::Overdue => "Overdue",
+            AgendaBucket::Today => "Today",
+            AgendaBucket::Tomorrow => "Tomorrow",
+            AgendaBucket::ThisWeek => "This Week",
+            AgendaBucket::Later => "Later",
+            AgendaBucket::NoDate => "No date",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StatusFilter {
+    Pending, // Everything not completed, started or not
+    InProgress,
+    Completed,
+}
+
+impl StatusFilter {
+    pub fn matches(self, todo: &Todo) -> bool {
+        match self {
+            StatusFilter::Pending => !todo.is_completed(),
+            StatusFilter::InProgress => todo.status == TodoStatus::InProgress,
+            StatusFilter::Completed => todo.is_completed(),
+        }
+    }
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusFilter::Pending => "Pending",
+            StatusFilter::InProgress => "In Progress",
+            StatusFilter::Completed => "Completed",
+        }
+    }
+}
+
+// Independent filter components; a todo is shown only if it passes every one that is set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterSet {
+    pub status: Option<StatusFilter>,
+    pub tag: Option<String>,
+    pub exclude_tag: bool, // Show todos without the tag instead of with it
+    pub context: Option<String>,
+    pub exclude_context: bool,
+    pub due: Option<DueDateFilter>,
+    pub query: Option<String>,
+}
+
+impl FilterSet {
+    pub fn is_empty(&self) -> bool {
+        *self == FilterSet::default()
+    }
+    
+    pub fn matches(&self, todo: &Todo, now: DateTime<Local>) -> bool {
+        self.status.is_none_or(|status| status.matches(todo))
+            && self.tag.as_ref().is_none_or(|tag| todo.tags.contains(&tag.to_lowercase()) != self.exclude_tag)
+            && self.context.as_ref().is_none_or(|context| todo.contexts.contains(&context.to_lowercase()) != self.exclude_context)
+            && self.due.is_none_or(|due| due.matches(todo, now))
+            && self.query.as_ref().is_none_or(|query| crate::search::score_todo(todo, query).is_some())
+    }
+    
+    // Active components joined for display, e.g. "Pending · #urgent · This Week"
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(status) = self.status {
+            parts.push(status.name().to_string());
+        }
+        if let Some(tag) = &self.tag {
+            parts.push(format!("{}#{}", if self.exclude_tag { "not " } else { "" }, tag));
+        }
+        if let Some(context) = &self.context {
+            parts.push(format!("{}@{}", if self.exclude_context { "not " } else { "" }, context));
+        }
+        if let Some(due) = self.due {
+            parts.push(due.name().to_string());
+        }
+        if let Some(query) = &self.query {
+            parts.push(format!("\"{}\"", query));
+        }
+        
+        if parts.is_empty() {
+            "All".to_string()
+        } else {
+            parts.join(" · ")
+        }
+    }
+}
+
+// How the list is drawn; saved filters remember it
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum ViewMode {
+    #[default]
+    List,                     // The current workspace, narrowed by the active filters
+    GlobalDue(DueDateFilter), // Due todos from every workspace
+    Agenda,                   // The current workspace's pending todos under due date headers
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SearchScope {
+    #[default]
+    Workspace, // Only the current workspace
+    All,       // Every workspace
+}
+
+// Optional sectioning of the todo list
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum GroupBy {
+    #[default]
+    None,
+    Tag,
+    Context,
+}
+
+impl GroupBy {
+    pub fn next(self) -> Self {
+        match self {
+            GroupBy::None => GroupBy::Tag,
+            GroupBy::Tag => GroupBy::Context,
+            GroupBy::Context => GroupBy::None,
+        }
+    }
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            GroupBy::None => "none",
+            GroupBy::Tag => "tag",
+            GroupBy::Context => "context",
+        }
+    }
+    
+    // Section headers a todo is listed under; empty when it has no tag/context
+    pub(crate) fn labels(self, todo: &Todo) -> Vec<String> {
+        match self {
+            GroupBy::None => Vec::new(),
+            GroupBy::Tag => todo.tags.iter().map(|tag| format!("#{}", tag)).collect(),
+            GroupBy::Context => todo.contexts.iter().map(|context| format!("@{}", context)).collect(),
+        }
+    }
+    
+    pub(crate) fn empty_label(self) -> &'static str {
+        match self {
+            GroupBy::Context => "(no context)",
+            _ => "(untagged)",
+        }
+    }
+}
+
+// A named filter combination, sort order and view, recalled from the saved filters popup.
+// Filters saved before the view was remembered open in the plain list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub name: String,
+    pub filters: FilterSet,
+    #[serde(default)]
+    pub sort_mode: SortMode,
+    #[serde(default)]
+    pub view_mode: ViewMode,
+    #[serde(default)]
+    pub group_by: GroupBy,
+    #[serde(default)]
+    pub search_scope: SearchScope,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Workspace {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub description: Option<String>,
     pub created_at: DateTime<Local>,
+    #[serde(default)]
     pub color: Option<u8>, // Index into a predefined color palette
+    #[serde(default)]
+    pub order: u32, // Position in the workspace list (Alt+N switches to the Nth)
+    #[serde(default)]
+    pub demo: bool, // Made by the welcome screen's Quick Demo, which can remove it again
 }
 
 impl Workspace {
@@ -831,6 +1804,8 @@ impl Workspace {
             description: None,
             created_at: Local::now(),
             color: None,
+            order: 0,
+            demo: false,
         }
     }
     
@@ -848,9 +1823,15 @@ impl Workspace {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceManager {
     pub workspaces: HashMap<String, Workspace>,
+    #[serde(default)]
     pub workspace_todos: HashMap<String, TodoList>,
+    #[serde(default)]
     pub current_workspace: Option<String>,
+    #[serde(default)]
+    pub previous_workspace: Option<String>,
     pub next_workspace_id: u32,
+    #[serde(default)]
+    pub saved_filters: Vec<SavedFilter>,
 }
 
 impl WorkspaceManager {
@@ -859,19 +1840,35 @@ impl WorkspaceManager {
             workspaces: HashMap::new(),
             workspace_todos: HashMap::new(),
             current_workspace: None,
+            previous_workspace: None,
             next_workspace_id: 1,
+            saved_filters: Vec::new(),
         }
     }
     
+    // Sessions still open in saved data were interrupted (a crash, or a kill before the timers were
+    // stopped). The time the app was closed can't be counted, so they are dropped with nothing added.
+    pub fn recover_interrupted_timers(&mut self) -> usize {
+        let mut recovered = 0;
+        for todo in self.workspace_todos.values_mut().flat_map(|todo_list| todo_list.todos.values_mut()) {
+            if todo.time_tracker.current_session.take().is_some() {
+                recovered += 1;
+            }
+        }
+        recovered
+    }
+    
     pub fn create_workspace(&mut self, name: String, description: Option<String>) -> String {
         let id = format!("ws_{}", self.next_workspace_id);
         self.next_workspace_id += 1;
         
-        let workspace = if let Some(desc) = description {
+        let mut workspace = if let Some(desc) = description {
             Workspace::new(id.clone(), name).with_description(desc)
         } else {
             Workspace::new(id.clone(), name)
         };
+        // New workspaces go to the end of the list
+        workspace.order = self.workspaces.values().map(|ws| ws.order + 1).max().unwrap_or(0);
         
         self.workspaces.insert(id.clone(), workspace);
         self.workspace_todos.insert(id.clone(), TodoList::new());
@@ -886,7 +1883,7 @@ impl WorkspaceManager {
     
     pub fn switch_workspace(&mut self, workspace_id: &str) -> bool {
         if self.workspaces.contains_key(workspace_id) {
-            self.current_workspace = Some(workspace_id.to_string());
+            self.set_current_workspace(workspace_id.to_string());
             true
         } else {
             false
@@ -895,15 +1892,39 @@ impl WorkspaceManager {
     
     pub fn switch_workspace_by_name(&mut self, workspace_name: &str) -> bool {
         // Find workspace ID by name
-        if let Some((workspace_id, _)) = self.workspaces.iter().find(|(_, ws)| ws.name == workspace_name) {
-            let workspace_id = workspace_id.clone();
-            self.current_workspace = Some(workspace_id);
+        if let Some(workspace_id) = self.find_workspace_id_by_name(workspace_name) {
+            self.set_current_workspace(workspace_id);
             true
         } else {
             false
         }
     }
     
+    // Flip back to the previously active workspace, like `cd -`
+    pub fn switch_to_previous_workspace(&mut self) -> bool {
+        match self.previous_workspace.clone() {
+            Some(workspace_id) if self.workspaces.contains_key(&workspace_id) => {
+                self.set_current_workspace(workspace_id);
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    fn set_current_workspace(&mut self, workspace_id: String) {
+        if self.current_workspace.as_ref() != Some(&workspace_id) {
+            self.previous_workspace = self.current_workspace.take();
+            self.current_workspace = Some(workspace_id);
+        }
+    }
+    
+    // Names can repeat, so the first match in list order wins
+    pub fn find_workspace_id_by_name(&self, workspace_name: &str) -> Option<String> {
+        self.get_all_workspaces().into_iter()
+            .find(|ws| ws.name == workspace_name)
+            .map(|ws| ws.id.clone())
+    }
+    
     pub fn get_current_workspace(&self) -> Option<&Workspace> {
         if let Some(current_id) = &self.current_workspace {
             self.workspaces.get(current_id)
@@ -934,10 +1955,52 @@ impl WorkspaceManager {
     
     pub fn get_all_workspaces(&self) -> Vec<&Workspace> {
         let mut workspaces: Vec<&Workspace> = self.workspaces.values().collect();
-        workspaces.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        workspaces.sort_by_key(|a| (a.order, a.created_at));
         workspaces
     }
     
+    // 1-based position of a workspace in the ordered list
+    pub fn workspace_number(&self, workspace_id: &str) -> Option<usize> {
+        self.get_all_workspaces().iter()
+            .position(|ws| ws.id == workspace_id)
+            .map(|index| index + 1)
+    }
+    
+    pub fn workspace_id_at(&self, number: usize) -> Option<String> {
+        let index = number.checked_sub(1)?;
+        self.get_all_workspaces().get(index).map(|ws| ws.id.clone())
+    }
+    
+    // Swap a workspace with its neighbour in the ordered list
+    pub fn move_workspace(&mut self, workspace_id: &str, up: bool) -> bool {
+        let mut ids: Vec<String> = self.get_all_workspaces().iter()
+            .map(|ws| ws.id.clone())
+            .collect();
+        
+        let index = match ids.iter().position(|id| id == workspace_id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let target = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1).filter(|&i| i < ids.len())
+        };
+        let target = match target {
+            Some(target) => target,
+            None => return false,
+        };
+        
+        ids.swap(index, target);
+        // Renumber everything so legacy workspaces (all order 0) become stable too
+        for (order, id) in ids.iter().enumerate() {
+            if let Some(workspace) = self.workspaces.get_mut(id) {
+                workspace.order = order as u32;
+            }
+        }
+        true
+    }
+    
     pub fn get_workspace_counts(&self) -> Vec<(String, String, usize)> {
         self.workspaces.values()
             .map(|workspace| {
@@ -958,6 +2021,10 @@ impl WorkspaceManager {
         if self.workspaces.remove(workspace_id).is_some() {
             self.workspace_todos.remove(workspace_id);
             
+            if self.previous_workspace.as_deref() == Some(workspace_id) {
+                self.previous_workspace = None;
+            }
+            
             // If we deleted the current workspace, switch to another one
             if self.current_workspace.as_ref() == Some(&workspace_id.to_string()) {
                 self.current_workspace = self.workspaces.keys().next().cloned();
@@ -978,24 +2045,114 @@ impl WorkspaceManager {
         }
     }
     
-    // Search across all workspaces
-    pub fn search_all_workspaces(&self, query: &str) -> Vec<(String, Vec<(&Todo, u32)>)> {
+    // Move a todo and all of its descendants into another workspace
+    pub fn move_todo(&mut self, from_ws: &str, to_ws: &str, todo_id: u32) -> bool {
+        self.move_todo_subtree(from_ws, to_ws, todo_id).is_some()
+    }
+
+    // Returns (original todos, moved todos) so the move can be undone
+    pub fn move_todo_subtree(&mut self, from_ws: &str, to_ws: &str, todo_id: u32) -> Option<(Vec<Todo>, Vec<Todo>)> {
+        self.move_todos(from_ws, to_ws, &[todo_id])
+    }
+
+    // Move several todos (each with its descendants) in one go. Todos whose ancestor is
+    // also being moved are skipped since they travel along with that ancestor.
+    pub fn move_todos(&mut self, from_ws: &str, to_ws: &str, todo_ids: &[u32]) -> Option<(Vec<Todo>, Vec<Todo>)> {
+        if from_ws == to_ws || !self.workspace_todos.contains_key(to_ws) {
+            return None;
+        }
+
+        let source = self.workspace_todos.get_mut(from_ws)?;
+        let roots: Vec<u32> = todo_ids.iter()
+            .copied()
+            .filter(|&id| source.get_todo(id).is_some())
+            .filter(|&id| !source.has_ancestor_in(id, todo_ids))
+            .collect();
+        if roots.is_empty() {
+            return None;
+        }
+
+        let mut original = Vec::new();
+        for id in roots {
+            original.extend(source.remove_todo_and_children(id));
+        }
+
+        let target = self.workspace_todos.get_mut(to_ws)?;
+        let moved = target.adopt_todos(original.clone());
+
+        Some((original, moved))
+    }
+
+    // Todos matching a due date filter across every workspace, in workspace order
+    // and then by due date
+    pub fn collect_due(&self, filter: DueDateFilter) -> Vec<(String, &Todo)> {
+        let now = Local::now();
         let mut results = Vec::new();
         
-        for (workspace_id, todo_list) in &self.workspace_todos {
-            let workspace_results = todo_list.search_todos(query);
-            if !workspace_results.is_empty() {
-                results.push((workspace_id.clone(), workspace_results));
+        for workspace in self.get_all_workspaces() {
+            if let Some(todo_list) = self.workspace_todos.get(&workspace.id) {
+                let mut todos: Vec<&Todo> = todo_list.todos.values()
+                    .filter(|todo| filter.matches(todo, now))
+                    .collect();
+                todos.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| SortMode::Priority.compare(a, b)));
+                results.extend(todos.into_iter().map(|todo| (workspace.id.clone(), todo)));
             }
         }
         
         results
     }
     
+    // Matches from every workspace merged into one score-ordered list
+    pub fn search_all_workspaces(&self, query: &str) -> Vec<(String, &Todo)> {
+        let mut results: Vec<(String, &Todo, i64)> = Vec::new();
+        
+        for workspace in self.get_all_workspaces() {
+            if let Some(todo_list) = self.workspace_todos.get(&workspace.id) {
+                results.extend(todo_list.search_scored(query).into_iter()
+                    .map(|(todo, score)| (workspace.id.clone(), todo, score)));
+            }
+        }
+        
+        // Stable sort keeps workspace order for equal scores
+        results.sort_by(|(_, a, a_score), (_, b, b_score)| {
+            b_score.cmp(a_score).then_with(|| b.priority.cmp(&a.priority))
+        });
+        results.into_iter().map(|(ws_id, todo, _)| (ws_id, todo)).collect()
+    }
+    
+    // Save a filter, replacing any existing one with the same name
+    pub fn save_filter(&mut self, saved_filter: SavedFilter) {
+        match self.saved_filters.iter_mut().find(|existing| existing.name == saved_filter.name) {
+            Some(existing) => *existing = saved_filter,
+            None => self.saved_filters.push(saved_filter),
+        }
+    }
+    
+    pub fn delete_saved_filter(&mut self, index: usize) -> Option<SavedFilter> {
+        (index < self.saved_filters.len()).then(|| self.saved_filters.remove(index))
+    }
+    
     pub fn is_empty(&self) -> bool {
         self.workspaces.is_empty()
     }
     
+    pub fn demo_workspace_id(&self) -> Option<String> {
+        self.workspaces.values().find(|ws| ws.demo).map(|ws| ws.id.clone())
+    }
+    
+    // The Quick Demo's workspace, made (under an unused name) the first time it's asked for
+    pub fn ensure_demo_workspace(&mut self) -> String {
+        if let Some(id) = self.demo_workspace_id() {
+            return id;
+        }
+        let name = self.unused_workspace_name("Demo");
+        let id = self.create_workspace(name, Some("Sample todos from the welcome screen".to_string()));
+        if let Some(workspace) = self.workspaces.get_mut(&id) {
+            workspace.demo = true;
+        }
+        id
+    }
+    
     pub fn ensure_workspace(&mut self) -> String {
         if self.workspaces.is_empty() {
             self.create_workspace("Personal".to_string(), Some("Default workspace".to_string()))
@@ -1007,6 +2164,92 @@ impl WorkspaceManager {
             self.current_workspace.clone().unwrap()
         }
     }
+    
+    // A workspace from someone's export: it gets a fresh id at the end of the list and its
+    // todos fresh ids from 1, keeping their order and subtasks. Returns the new id.
+    pub fn import_workspace(&mut self, mut workspace: Workspace, todos: TodoList) -> String {
+        let id = format!("ws_{}", self.next_workspace_id);
+        self.next_workspace_id += 1;
+        workspace.id = id.clone();
+        workspace.order = self.workspaces.values().map(|ws| ws.order + 1).max().unwrap_or(0);
+
+        let mut imported: Vec<Todo> = todos.todos.into_values().collect();
+        imported.sort_by_key(|todo| todo.id);
+        let mut todo_list = TodoList::new();
+        todo_list.sort_mode = todos.sort_mode;
+        todo_list.adopt_todos(imported);
+
+        self.workspaces.insert(id.clone(), workspace);
+        self.workspace_todos.insert(id.clone(), todo_list);
+        if self.current_workspace.is_none() {
+            self.current_workspace = Some(id.clone());
+        }
+        id
+    }
+
+    // `name`, or `name (2)`, `name (3)`... if that's taken
+    pub fn unused_workspace_name(&self, name: &str) -> String {
+        (1..)
+            .map(|n| if n == 1 { name.to_string() } else { format!("{} ({})", name, n) })
+            .find(|candidate| self.find_workspace_id_by_name(candidate).is_none())
+            .unwrap()
+    }
+    
+    // Fold in a copy saved elsewhere (another machine syncing the same file), `base` being the
+    // version both started from. Workspaces are matched like todos, by id and created_at, and
+    // their lists merged todo by todo. The open workspace is never removed from under the user;
+    // settings such as saved filters stay as they are here.
+    pub fn merge(&mut self, base: &WorkspaceManager, theirs: WorkspaceManager) -> MergeSummary {
+        let same = |a: Option<&Workspace>, b: &Workspace| a.is_some_and(|a| a.created_at == b.created_at);
+        let empty = TodoList::new();
+        let mut summary = MergeSummary::default();
+
+        let removed: Vec<String> = self.workspaces.values()
+            .filter(|ws| {
+                !same(theirs.workspaces.get(&ws.id), ws)
+                    && base.workspaces.get(&ws.id) == Some(*ws)
+                    && base.workspace_todos.get(&ws.id) == self.workspace_todos.get(&ws.id)
+                    && self.current_workspace.as_ref() != Some(&ws.id)
+            })
+            .map(|ws| ws.id.clone())
+            .collect();
+        for id in removed {
+            self.workspaces.remove(&id);
+            self.workspace_todos.remove(&id);
+            if self.previous_workspace.as_ref() == Some(&id) {
+                self.previous_workspace = None;
+            }
+            summary.removed += 1;
+        }
+
+        self.next_workspace_id = self.next_workspace_id.max(theirs.next_workspace_id);
+        let mut their_todos = theirs.workspace_todos;
+        let mut their_workspaces: Vec<Workspace> = theirs.workspaces.into_values().collect();
+        their_workspaces.sort_by(|a, b| a.id.cmp(&b.id));
+        for mut workspace in their_workspaces {
+            let todos = their_todos.remove(&workspace.id).unwrap_or_default();
+            if let Some(ours) = self.workspaces.get_mut(&workspace.id).filter(|ours| same(Some(ours), &workspace)) {
+                let id = workspace.id.clone();
+                // Name, description and color: theirs only if they're unchanged here
+                if *ours != workspace && base.workspaces.get(&id) == Some(ours) {
+                    *ours = workspace;
+                    summary.updated += 1;
+                }
+                let base_todos = base.workspace_todos.get(&id).unwrap_or(&empty);
+                summary += self.workspace_todos.entry(id).or_default().merge(base_todos, todos);
+            } else if !same(base.workspaces.get(&workspace.id), &workspace) {
+                // Added there; one in the base but gone from here was deleted here
+                if self.workspaces.contains_key(&workspace.id) {
+                    workspace.id = format!("ws_{}", self.next_workspace_id);
+                    self.next_workspace_id += 1;
+                }
+                self.workspace_todos.insert(workspace.id.clone(), todos);
+                self.workspaces.insert(workspace.id.clone(), workspace);
+                summary.added += 1;
+            }
+        }
+        summary
+    }
 }
 
 impl Default for TodoList {
@@ -1,5 +1,14 @@
-use crate::app::{App, AppMode, ViewMode};
-use crate::todo::TodoStatus;
+use crate::app::{scroll_offset, App, AppMode, ListRow, BOARD_COLUMNS};
+use crate::colors::Theme;
+use crate::completion::CompletionKind;
+use crate::export::ExportFormat;
+use crate::hints;
+use crate::keymap::Action;
+use crate::markdown::render_markdown_lines;
+use crate::messages::MessageLevel;
+use crate::pomodoro::Phase;
+use crate::text_buffer::{line_col, row_col, scroll_window, truncate_to_width, truncate_with, wrap_rows};
+use crate::todo::{format_signed_time, format_tracked_time, AgendaBucket, FilterSet, GroupBy, RecurrencePattern, SearchScope, SortMode, StatusFilter, Todo, TodoStatus, ViewMode};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -9,6 +18,8 @@ use ratatui::{
     },
     Frame,
 };
+use std::collections::HashMap;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub fn draw(f: &mut Frame, app: &mut App) {
     let _colors = &app.colors;
@@ -18,12 +29,39 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         return;
     }
     
+    if app.show_time_report {
+        draw_time_report(f, app);
+        return;
+    }
+    
     // Check for welcome screen mode
     if matches!(app.mode, AppMode::Welcome) {
         draw_welcome_screen(f, app);
         return;
     }
     
+    // Prompts opened from the welcome or workspace selection screen float over it
+    if matches!(app.mode, AppMode::ImportWorkspacePath | AppMode::ImportWorkspaceName)
+        || (app.mode == AppMode::ExportPath && app.return_mode.is_some())
+    {
+        if app.return_mode == Some(AppMode::Welcome) {
+            draw_welcome_screen(f, app);
+        } else {
+            draw_workspace_selection_ui(f, app);
+        }
+        let area = f.area();
+        let width = area.width.saturating_sub(8).min(80);
+        let prompt_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + area.height.saturating_sub(3) / 2,
+            width,
+            height: area.height.min(3),
+        };
+        f.render_widget(Clear, prompt_area);
+        draw_input(f, prompt_area, app);
+        return;
+    }
+    
     // Check for workspace selection mode - show only workspace selection UI
     if matches!(app.mode, AppMode::WorkspaceSelection) {
         draw_workspace_selection_ui(f, app);
@@ -31,12 +69,24 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     }
     
     // Check for other popup modes
-    if matches!(app.mode, AppMode::TagSelection | AppMode::ContextSelection | AppMode::TemplateSelection | AppMode::RecurrenceSelection) {
+    if matches!(app.mode, AppMode::TagSelection | AppMode::ContextSelection | AppMode::TemplateSelection | AppMode::RecurrenceSelection | AppMode::MoveToWorkspace | AppMode::SavedFilterSelection | AppMode::TimeTracking | AppMode::Export) {
         draw_main_ui(f, app);
         draw_selection_popup(f, app);
         return;
     }
     
+    if matches!(app.mode, AppMode::SaveFilter) {
+        draw_main_ui(f, app);
+        draw_save_filter_popup(f, app);
+        return;
+    }
+    
+    if matches!(app.mode, AppMode::Confirm) {
+        draw_main_ui(f, app);
+        draw_confirm_popup(f, app);
+        return;
+    }
+    
     // Check for notes editing or viewing mode
     if matches!(app.mode, AppMode::EditNotes | AppMode::ViewNotes) {
         draw_main_ui(f, app);
@@ -58,51 +108,219 @@ pub fn draw(f: &mut Frame, app: &mut App) {
 }
 
 fn draw_main_ui(f: &mut Frame, app: &mut App) {
+    let banner = reminder_banner(app, chrono::Local::now());
+    
     // Create main layout
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Header
             Constraint::Min(0),    // Main content
+            Constraint::Length(if banner.is_some() { 1 } else { 0 }), // Due-soon banner
             Constraint::Length(3), // Status bar
-            Constraint::Length(if matches!(app.mode, AppMode::Insert | AppMode::InsertChild | AppMode::EditTodo | AppMode::Search | AppMode::EditNotes) { 3 } else { 0 }), // Input area
+            Constraint::Length(if matches!(app.mode, AppMode::Insert | AppMode::InsertChild | AppMode::EditTodo | AppMode::Search | AppMode::EditNotes | AppMode::RenameTag | AppMode::RenameContext | AppMode::BulkTag | AppMode::BulkContext | AppMode::BulkDue | AppMode::EditDue | AppMode::LogTime | AppMode::EditTimeEntry | AppMode::ExportPath | AppMode::ImportPath) { 3 } else { 0 }), // Input area
+            Constraint::Length(if matches!(app.mode, AppMode::Insert | AppMode::InsertChild | AppMode::EditTodo) { 1 } else { 0 }), // Parse preview
+            Constraint::Length(1), // Key hints
         ])
         .split(f.area());
 
     // Draw header
     draw_header(f, chunks[0], app);
     
-    // Draw todos
-    draw_todos(f, chunks[1], app);
+    // Draw todos, with the detail pane beside them when it's on and there's room
+    if app.mode == AppMode::Calendar {
+        draw_calendar(f, chunks[1], app);
+    } else if app.mode == AppMode::Board {
+        draw_board(f, chunks[1], app);
+    } else if app.display.show_detail_pane && chunks[1].width >= DETAIL_PANE_MIN_WIDTH {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[1]);
+        draw_todos(f, columns[0], app);
+        draw_detail_pane(f, columns[1], app);
+    } else {
+        draw_todos(f, chunks[1], app);
+    }
+    
+    if let Some(banner) = banner {
+        f.render_widget(banner, chunks[2]);
+    }
     
     // Draw status bar
-    draw_status_bar(f, chunks[2], app);
+    draw_status_bar(f, chunks[3], app);
     
     // Draw input area if in insert, search, edit, or notes mode
-    if matches!(app.mode, AppMode::Insert | AppMode::InsertChild | AppMode::EditTodo | AppMode::Search | AppMode::EditNotes) {
-        draw_input(f, chunks[3], app);
+    if matches!(app.mode, AppMode::Insert | AppMode::InsertChild | AppMode::EditTodo | AppMode::Search | AppMode::EditNotes | AppMode::RenameTag | AppMode::RenameContext | AppMode::BulkTag | AppMode::BulkContext | AppMode::BulkDue | AppMode::EditDue | AppMode::LogTime | AppMode::EditTimeEntry | AppMode::ExportPath | AppMode::ImportPath) {
+        draw_input(f, chunks[4], app);
     }
+    
+    // Show what the todo being typed will parse to
+    if matches!(app.mode, AppMode::Insert | AppMode::InsertChild | AppMode::EditTodo) {
+        draw_parse_preview(f, chunks[5], app);
+    }
+    
+    draw_hint_bar(f, chunks[6], app);
 }
 
-fn draw_header(f: &mut Frame, area: Rect, app: &App) {
+// The keys that matter most in the current mode, as many as fit
+fn draw_hint_bar(f: &mut Frame, area: Rect, app: &App) {
     let colors = &app.colors;
+    let mut spans = vec![Span::raw(" ")];
+    for (i, (key, description)) in hints::visible_hints(&app.mode, &app.keymap, area.width.saturating_sub(2) as usize).into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(hints::SEPARATOR, Style::default().fg(colors.dark3)));
+        }
+        spans.push(Span::styled(key, Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD)));
+        spans.push(Span::styled(format!(" {}", description), Style::default().fg(colors.comment)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+// Todos shown by name in the due-soon banner; the rest are counted
+const BANNER_TODOS: usize = 3;
+
+// One line over the status bar naming todos that are overdue (red) or due within the hour
+// (yellow), or None when there are none or the banner was dismissed
+fn reminder_banner(app: &App, now: chrono::DateTime<chrono::Local>) -> Option<Paragraph<'static>> {
+    let colors = &app.colors;
+    let todos = app.reminder_banner_todos(now);
+    if todos.is_empty() {
+        return None;
+    }
+    let overdue = todos.iter().any(|todo| todo.due_date.is_some_and(|due| due < now));
+    let color = if overdue { colors.red } else { colors.yellow };
+    
+    let mut text = String::from(" ");
+    for (i, todo) in todos.iter().take(BANNER_TODOS).enumerate() {
+        let Some(due) = todo.due_date else { continue };
+        if i > 0 {
+            text.push_str(" · ");
+        }
+        let when = if due.date_naive() == now.date_naive() {
+            due.format("%H:%M").to_string()
+        } else {
+            due.format(&app.config.date_format).to_string()
+        };
+        let label = if due < now { "Overdue" } else { "Due" };
+        text.push_str(&format!("{} {}: {}", label, when, todo.description));
+    }
+    if todos.len() > BANNER_TODOS {
+        text.push_str(&format!(" · +{} more", todos.len() - BANNER_TODOS));
+    }
+    text.push_str(&format!("  ({} hides)", app.keymap.label(Action::DismissReminders)));
     
-    let view_name = match &app.view_mode {
-        ViewMode::All => "All Todos".to_string(),
-        ViewMode::Pending => "Pending Todos".to_string(),
-        ViewMode::Completed => "Completed Todos".to_string(),
-        ViewMode::Search(query) => format!("Search: {}", query),
-        ViewMode::FilterByTag(tag) => format!("Tag: #{}", tag),
-        ViewMode::FilterByContext(context) => format!("Context: @{}", context),
-        ViewMode::FilterByDueDate(filter) => match filter {
-            crate::todo::DueDateFilter::Overdue => "Overdue".to_string(),
-            crate::todo::DueDateFilter::Today => "Due Today".to_string(),
-            crate::todo::DueDateFilter::Tomorrow => "Due Tomorrow".to_string(),
-            crate::todo::DueDateFilter::ThisWeek => "Due This Week".to_string(),
-            crate::todo::DueDateFilter::NoDueDate => "No Due Date".to_string(),
-        },
+    Some(Paragraph::new(text).style(colors.badge(color).add_modifier(Modifier::BOLD | colors.alert())))
+}
+
+// Narrower terminals keep the full width for the list even with the detail pane on
+const DETAIL_PANE_MIN_WIDTH: u16 = 100;
+const DETAIL_PANE_NOTES_LINES: usize = 12;
+
+// Descriptions keep at least this many columns even when a row's tags alone would fill it
+const MIN_DESCRIPTION_WIDTH: usize = 12;
+
+// Everything about the selected todo that the list row leaves out
+fn draw_detail_pane(f: &mut Frame, area: Rect, app: &App) {
+    let colors = &app.colors;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.dark3))
+        .title(" Details ")
+        .title_style(Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD));
+    
+    let Some(todo) = app.get_selected_todo() else {
+        let empty = Paragraph::new("No todo selected")
+            .style(Style::default().fg(colors.comment))
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    };
+    
+    let label = Style::default().fg(colors.comment);
+    let field = |name: &str, value: String, color: Color| {
+        Line::from(vec![
+            Span::styled(format!("{:<11}", name), label),
+            Span::styled(value, Style::default().fg(color)),
+        ])
     };
     
+    let mut lines = vec![
+        Line::from(Span::styled(todo.description.clone(), Style::default().fg(colors.fg).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+    ];
+    
+    let (status, status_color) = match todo.status {
+        TodoStatus::Pending => ("Pending", colors.yellow),
+        TodoStatus::InProgress => ("In progress", colors.blue),
+        TodoStatus::Completed => ("Completed", colors.green),
+    };
+    lines.push(field("Status", status.to_string(), status_color));
+    lines.push(field("Created", todo.created_at.format("%Y-%m-%d %H:%M").to_string(), colors.fg));
+    if let Some(completed_at) = todo.completed_at {
+        lines.push(field("Completed", completed_at.format("%Y-%m-%d %H:%M").to_string(), colors.green));
+    }
+    if let Some(due) = todo.due_date {
+        let color = if todo.is_overdue() { colors.red } else { colors.blue };
+        lines.push(field("Due", due.format("%a %Y-%m-%d %H:%M").to_string(), color));
+    }
+    if todo.priority > 0 {
+        lines.push(field("Priority", format!("{} {}", "!".repeat(todo.priority as usize), todo.priority), colors.orange));
+    }
+    if !todo.tags.is_empty() {
+        let mut tags: Vec<String> = todo.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        tags.sort();
+        lines.push(field("Tags", tags.join(" "), colors.cyan));
+    }
+    if !todo.contexts.is_empty() {
+        let mut contexts: Vec<String> = todo.contexts.iter().map(|context| format!("@{}", context)).collect();
+        contexts.sort();
+        lines.push(field("Contexts", contexts.join(" "), colors.orange));
+    }
+    let recurrence = match todo.recurrence {
+        RecurrencePattern::None => None,
+        RecurrencePattern::Daily => Some("Daily".to_string()),
+        RecurrencePattern::Weekly => Some("Weekly".to_string()),
+        RecurrencePattern::Monthly => Some("Monthly".to_string()),
+        RecurrencePattern::Yearly => Some("Yearly".to_string()),
+        RecurrencePattern::Custom(days) => Some(format!("Every {} days", days)),
+    };
+    if let Some(recurrence) = recurrence {
+        lines.push(field("Repeats", recurrence, colors.yellow));
+    }
+    if todo.time_tracker.total_seconds > 0 || todo.is_timer_running() {
+        let running = if todo.is_timer_running() { " (running)" } else { "" };
+        lines.push(field("Tracked", format!("{}{}", todo.get_total_time_formatted(), running), colors.green));
+    }
+    if !todo.children.is_empty() {
+        lines.push(field("Subtasks", todo.children.len().to_string(), colors.fg));
+    }
+    
+    // The start of the notes, as the viewer would show them
+    if let Some(notes) = todo.notes.as_deref().filter(|notes| !notes.trim().is_empty()) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Notes", label.add_modifier(Modifier::BOLD))));
+        let notes_lines = render_markdown_lines(notes, colors);
+        let hidden = notes_lines.len().saturating_sub(DETAIL_PANE_NOTES_LINES);
+        lines.extend(notes_lines.into_iter().take(DETAIL_PANE_NOTES_LINES));
+        if hidden > 0 {
+            lines.push(Line::from(Span::styled(format!("… {} more lines (V to view)", hidden), label)));
+        }
+    }
+    
+    let details = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(block);
+    f.render_widget(details, area);
+}
+
+fn draw_header(f: &mut Frame, area: Rect, app: &App) {
+    let colors = &app.colors;
+    
+    let view_name = app.get_view_name();
+    
     let mode_indicator = match app.mode {
         AppMode::Welcome => ("WELCOME", colors.cyan),
         AppMode::Normal => ("NORMAL", colors.blue),
@@ -119,52 +337,98 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
         AppMode::TimeTracking => ("TIMER", colors.green),
         AppMode::WorkspaceSelection => ("WORKSPACE", colors.magenta),
         AppMode::CreateWorkspace => ("NEW WORKSPACE", colors.green),
+        AppMode::MoveToWorkspace => ("MOVE", colors.magenta),
         AppMode::Visual => ("VISUAL", colors.purple),
         AppMode::BulkOperation => ("BULK OP", colors.red),
+        AppMode::SavedFilterSelection => ("FILTERS", colors.cyan),
+        AppMode::SaveFilter => ("SAVE FILTER", colors.green),
+        AppMode::RenameTag => ("RENAME TAG", colors.cyan),
+        AppMode::LogTime => ("LOG TIME", colors.green),
+        AppMode::EditTimeEntry => ("TIME NOTE", colors.green),
+        AppMode::RenameContext => ("RENAME CONTEXT", colors.orange),
+        AppMode::BulkTag => ("ADD TAG", colors.cyan),
+        AppMode::BulkContext => ("ADD CONTEXT", colors.orange),
+        AppMode::BulkDue | AppMode::EditDue => ("SET DUE", colors.yellow),
+        AppMode::Confirm => ("CONFIRM", colors.red),
+        AppMode::Calendar => ("CALENDAR", colors.cyan),
+        AppMode::Board => ("BOARD", colors.blue),
+        AppMode::Export => ("EXPORT", colors.green),
+        AppMode::ExportPath => ("EXPORT TO", colors.green),
+        AppMode::ImportPath | AppMode::ImportWorkspacePath => ("IMPORT", colors.green),
+        AppMode::ImportWorkspaceName => ("NAME IMPORT", colors.green),
     };
     
-    // Get current workspace name
-    let workspace_name = app.get_current_workspace_name();
+    // The workspace in its own color with its pending count, and how many todos a due date
+    // filter or search leaves
+    let workspace_name = app.get_current_workspace_label();
+    let workspace_color = app.workspace_manager.get_current_workspace_id()
+        .map_or(colors.blue, |id| app.get_workspace_color(&id));
+    let pending = app.get_current_todo_list().map_or(0, |todo_list| todo_list.pending_count());
+    let mut spans = vec![
+        Span::raw(" Paperclip - "),
+        Span::styled(workspace_name, Style::default().fg(workspace_color).add_modifier(Modifier::BOLD)),
+        Span::raw(format!(" ({}) | {} ", pending, view_name)),
+    ];
+    let filters = app.get_active_filters();
+    if filters.due.is_some() || filters.query.is_some() || matches!(app.view_mode, ViewMode::GlobalDue(_)) {
+        let results = app.get_visible_todos().len();
+        spans.push(Span::styled(
+            format!("| {} result{} ", results, if results == 1 { "" } else { "s" }),
+            Style::default().fg(colors.comment),
+        ));
+    }
     
-    let title = Paragraph::new(format!(" Paperclip - {} | {} ", workspace_name, view_name))
+    let title = Paragraph::new(Line::from(spans))
         .style(Style::default().fg(colors.fg).bg(Color::Reset))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(colors.blue))
+                .border_style(Style::default().fg(workspace_color))
         );
     
     f.render_widget(title, area);
     
-    // Mode indicator in top right
-    let mode_area = Rect {
-        x: area.x + area.width.saturating_sub(12),
+    draw_mode_badge(f, area, mode_indicator.0, colors.badge(mode_indicator.1));
+}
+
+// The mode name on the top border of a header, sized to the name and clear of the corner
+fn draw_mode_badge(f: &mut Frame, area: Rect, name: &str, style: Style) {
+    let badge = format!(" {} ", name);
+    let width = (badge.width() as u16).min(area.width.saturating_sub(4));
+    let badge_area = Rect {
+        x: area.x + area.width.saturating_sub(width + 2),
         y: area.y,
-        width: 12,
+        width,
         height: 1,
     };
-    
-    let mode_widget = Paragraph::new(format!(" {} ", mode_indicator.0))
-        .style(Style::default().fg(colors.bg_dark).bg(mode_indicator.1).add_modifier(Modifier::BOLD))
-        .alignment(Alignment::Center);
-    
-    f.render_widget(mode_widget, mode_area);
+    f.render_widget(Paragraph::new(badge).style(style.add_modifier(Modifier::BOLD)), badge_area);
 }
 
 fn draw_todos(f: &mut Frame, area: Rect, app: &mut App) {
     let colors = &app.colors;
+    let glyphs = app.glyphs;
     let todos = app.get_visible_todos();
     
     if todos.is_empty() {
-        let empty_message = match &app.view_mode {
-            ViewMode::All => "No todos yet. Press 'i' to add one!",
-            ViewMode::Pending => "No pending todos!",
-            ViewMode::Completed => "No completed todos yet.",
-            ViewMode::Search(_) => "No todos found for this search.",
-            ViewMode::FilterByTag(_) => "No todos found with this tag.",
-            ViewMode::FilterByContext(_) => "No todos found with this context.",
-            ViewMode::FilterByDueDate(_) => "No todos found for this date filter.",
+        let filters = app.get_active_filters();
+        let only_status = FilterSet { status: None, ..filters.clone() }.is_empty();
+        let empty_message = if let ViewMode::GlobalDue(_) = app.view_mode {
+            "Nothing due in any workspace. Press 'O' for the next view."
+        } else if app.view_mode == ViewMode::Agenda && filters.is_empty() {
+            "Nothing pending in this workspace. Press 'A' for the list."
+        } else if filters.query.is_some() {
+            "No todos found for this search."
+        } else if only_status && filters.status == Some(StatusFilter::Pending) {
+            "No pending todos!"
+        } else if only_status && filters.status == Some(StatusFilter::InProgress) {
+            "Nothing in progress. Starting a timer on a todo puts it here."
+        } else if only_status && filters.status == Some(StatusFilter::Completed) {
+            "No completed todos yet."
+        } else if !filters.is_empty() {
+            "No todos match these filters. Press Esc to clear them."
+        } else {
+            "No todos yet. Press 'i' to add one!"
         };
         
         let paragraph = Paragraph::new(empty_message)
@@ -182,43 +446,56 @@ fn draw_todos(f: &mut Frame, area: Rect, app: &mut App) {
         return;
     }
     
+    let filters = app.get_active_filters();
+    let search_query = filters.query.clone();
+    
+    // Text to highlight inside each row; the search query wins over tag and context filters
+    let highlight_query = match (&filters.query, &filters.tag, &filters.context) {
+        (Some(query), _, _) => query.strip_prefix('\'').unwrap_or(query).to_string(),
+        (None, Some(tag), _) if !filters.exclude_tag => format!("#{}", tag),
+        (None, _, Some(context)) if !filters.exclude_context => format!("@{}", context),
+        _ => String::new(),
+    };
+    let highlight_style = Style::default().fg(colors.yellow).add_modifier(Modifier::BOLD);
+    // A fuzzy search highlights the letters it matched; exact searches and filters every occurrence
+    let fuzzy_query = filters.query.as_deref().filter(|query| !crate::search::is_exact_query(query));
+    let highlight = |text: &str, base_style: Style, highlight_style: Style| match fuzzy_query {
+        Some(query) => fuzzy_highlight_spans(text, query, base_style, highlight_style),
+        None => highlight_spans(text, &highlight_query, base_style, highlight_style),
+    };
+    let row_width = area.width.saturating_sub(2) as usize;
+    let mut selected_truncated = false;
+    let bulk_selecting = matches!(app.mode, AppMode::Visual | AppMode::BulkOperation | AppMode::BulkTag | AppMode::BulkContext | AppMode::BulkDue);
+    
     let items: Vec<ListItem> = todos
         .iter()
         .enumerate()
-        .map(|(i, (todo, depth))| {
+        .map(|(i, (ws_id, todo, depth))| {
             let is_selected = i == app.selected;
+            let in_bulk_selection = bulk_selecting && app.selected_todos.contains(&todo.id);
+            let todo_list = app.workspace_manager.workspace_todos.get(*ws_id);
             
             // Create indentation based on depth
             let indent = "  ".repeat(*depth as usize);
             
             // Tree indicators
-            let tree_indicator = if *depth > 0 {
-                if let Some(todo_list) = app.get_current_todo_list() {
-                    if todo_list.has_children(todo.id) {
-                        if todo.expanded { "└▼ " } else { "└▶ " }
-                    } else {
-                        "└─ "
-                    }
-                } else {
-                    "└─ "
-                }
-            } else if let Some(todo_list) = app.get_current_todo_list() {
-                if todo_list.has_children(todo.id) {
-                    if todo.expanded { "▼ " } else { "▶ " }
-                } else {
-                    ""
-                }
-            } else {
-                ""
+            let has_children = todo_list.is_some_and(|todo_list| todo_list.has_children(todo.id));
+            let expander = if todo.expanded { glyphs.expanded } else { glyphs.collapsed };
+            let tree_indicator = match (*depth > 0, has_children) {
+                (true, true) => format!("{}{} ", glyphs.branch, expander),
+                (true, false) => format!("{}{} ", glyphs.branch, glyphs.leaf),
+                (false, true) => format!("{} ", expander),
+                (false, false) => String::new(),
             };
             
             // Status indicator
             let status_char = match todo.status {
-                TodoStatus::Pending => if todo.is_overdue() { "!" } else { "○" },
-                TodoStatus::InProgress => "◐",
-                TodoStatus::Completed => "●",
+                TodoStatus::Pending => if todo.is_overdue() { glyphs.overdue } else { glyphs.pending },
+                TodoStatus::InProgress => glyphs.in_progress,
+                TodoStatus::Completed => glyphs.completed,
             };
             
+            let status_modifier = if todo.is_overdue() { colors.alert() } else { Modifier::empty() };
             let status_color = match todo.status {
                 TodoStatus::Pending => if todo.is_overdue() { colors.red } else { colors.yellow },
                 TodoStatus::InProgress => colors.blue,
@@ -242,10 +519,12 @@ fn draw_todos(f: &mut Frame, area: Rect, app: &mut App) {
             };
             
             // Description style
-            let desc_style = if todo.is_completed() {
+            let desc_style = if todo.is_completed() && is_selected {
+                colors.selected().fg(colors.comment).add_modifier(Modifier::CROSSED_OUT)
+            } else if todo.is_completed() {
                 Style::default().fg(colors.comment).add_modifier(Modifier::CROSSED_OUT)
             } else if is_selected {
-                Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(colors.fg)
             };
@@ -255,107 +534,486 @@ fn draw_todos(f: &mut Frame, area: Rect, app: &mut App) {
             
             // Add tags
             for tag in &todo.tags {
-                tags_contexts.push(Span::styled(format!(" #{}", tag), Style::default().fg(colors.cyan)));
+                tags_contexts.extend(highlight(&format!(" #{}", tag), Style::default().fg(colors.cyan), highlight_style));
             }
             
             // Add contexts
             for context in &todo.contexts {
-                tags_contexts.push(Span::styled(format!(" @{}", context), Style::default().fg(colors.orange)));
+                tags_contexts.extend(highlight(&format!(" @{}", context), Style::default().fg(colors.orange), highlight_style));
             }
             
-            // Add notes indicator
-            if todo.notes.is_some() && !todo.notes.as_ref().unwrap().trim().is_empty() {
+            // Add notes indicator, with the matching line when a search hit came from the notes
+            let notes_snippet = search_query.as_deref().and_then(|query| crate::search::notes_snippet(todo, query));
+            if let Some(snippet) = notes_snippet {
+                tags_contexts.push(Span::styled(format!(" {}", glyphs.notes_match), Style::default().fg(colors.purple)));
+                let snippet: String = if snippet.chars().count() > 40 {
+                    format!("{}{}", snippet.chars().take(39).collect::<String>(), glyphs.ellipsis)
+                } else {
+                    snippet.to_string()
+                };
+                tags_contexts.push(Span::styled(format!(" {}", snippet), Style::default().fg(colors.comment).add_modifier(Modifier::ITALIC)));
+            } else if todo.notes.is_some() && !todo.notes.as_ref().unwrap().trim().is_empty() {
                 tags_contexts.push(Span::styled(" [N]".to_string(), Style::default().fg(colors.purple)));
             }
             
-            // Add due date indicator
+            // Add checklist progress from `- [ ]` lines in the notes
+            if let Some((checked, total)) = todo.notes_checklist_progress() {
+                let color = if checked == total { colors.green } else { colors.yellow };
+                tags_contexts.push(Span::styled(format!(" [{}/{}]", checked, total), Style::default().fg(color)));
+            }
+            
+            // Add due date indicator, relative to now unless the todo is done
             if let Some(due) = todo.due_date {
-                let now = chrono::Local::now();
-                let due_text = if due.date_naive() == now.date_naive() {
-                    " [today]".to_string()
-                } else if due.date_naive() == now.date_naive() + chrono::Duration::days(1) {
-                    " [tomorrow]".to_string()
-                } else {
-                    format!(" [{}]", due.format("%m/%d"))
-                };
-                
-                let due_color = if todo.is_overdue() {
-                    colors.red
-                } else if due.date_naive() == now.date_naive() {
-                    colors.yellow
+                let (due_text, due_color) = if todo.is_completed() {
+                    (due.format(&app.config.date_format).to_string(), colors.comment)
                 } else {
-                    colors.blue
+                    format_due(due, chrono::Local::now(), &app.config.date_format, colors)
                 };
-                
-                tags_contexts.push(Span::styled(due_text, Style::default().fg(due_color)));
+                let due_modifier = if todo.is_overdue() { colors.alert() } else { Modifier::empty() };
+                tags_contexts.push(Span::styled(format!(" [{}]", due_text), Style::default().fg(due_color).add_modifier(due_modifier)));
             }
             
+            // Tracked time: pulsing while the timer runs, dimmed otherwise
+            if todo.is_timer_running() {
+                let pulse = if chrono::Local::now().timestamp() % 2 == 0 { colors.green } else { colors.teal };
+                tags_contexts.push(Span::styled(format!(" {} {}", glyphs.timer, todo.get_total_time_formatted()), Style::default().fg(pulse).add_modifier(Modifier::BOLD)));
+            } else if todo.time_tracker.total_seconds > 0 {
+                tags_contexts.push(Span::styled(format!(" {} {}", glyphs.timer, todo.get_total_time_formatted()), Style::default().fg(colors.comment)));
+            }
+            
+            // Cross-workspace views show which workspace each todo belongs to
+            let workspace_prefix = if app.is_cross_workspace_view() {
+                let name = app.workspace_manager.workspaces.get(*ws_id).map(|ws| ws.name.as_str()).unwrap_or("?");
+                Span::styled(format!("[{}] ", name), Style::default().fg(app.get_workspace_color(ws_id)))
+            } else {
+                Span::raw("")
+            };
+            
+            // Visual mode marks the bulk selection in a gutter, keeping other rows aligned
+            let bulk_gutter = match (bulk_selecting, in_bulk_selection) {
+                (true, true) => Span::styled(glyphs.selection_gutter, Style::default().fg(colors.purple)),
+                (true, false) => Span::raw(" "),
+                _ => Span::raw(""),
+            };
+            
             let mut line_spans = vec![
+                bulk_gutter,
+                workspace_prefix,
                 Span::styled(indent, Style::default().fg(colors.dark3)),
                 Span::styled(tree_indicator, Style::default().fg(colors.cyan)),
-                Span::styled(format!("{} ", status_char), Style::default().fg(status_color).add_modifier(Modifier::BOLD)),
-                Span::styled(&todo.description, desc_style),
-                Span::styled(priority_indicator, Style::default().fg(priority_color).add_modifier(Modifier::BOLD)),
+                Span::styled(status_char, Style::default().fg(status_color).add_modifier(Modifier::BOLD | status_modifier)),
+                Span::raw(" "),
             ];
             
+            // Shorten the description so the status, priority, tags and due date always fit
+            let fixed_width = line_spans.iter().chain(&tags_contexts).map(Span::width).sum::<usize>()
+                + priority_indicator.width();
+            let description = truncate_with(&todo.description, row_width.saturating_sub(fixed_width).max(MIN_DESCRIPTION_WIDTH), glyphs.ellipsis);
+            if is_selected && description != todo.description {
+                selected_truncated = true;
+            }
+            
+            // Keep the row's background and strike-through on highlighted text
+            let desc_highlight_style = desc_style.fg(colors.yellow).add_modifier(Modifier::BOLD);
+            line_spans.extend(highlight(&description, desc_style, desc_highlight_style));
+            line_spans.push(Span::styled(priority_indicator, Style::default().fg(priority_color).add_modifier(Modifier::BOLD)));
             line_spans.extend(tags_contexts);
             let line = Line::from(line_spans);
             
-            ListItem::new(line)
+            if in_bulk_selection {
+                ListItem::new(line).style(Style::default().bg(colors.blue7))
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+    
+    // Non-default sort and grouping are noted in the list title
+    let mut title_suffix = match app.get_current_todo_list().map(|list| list.sort_mode) {
+        Some(sort_mode) if sort_mode != SortMode::Priority => format!(" · by {}", sort_mode.name()),
+        _ => String::new(),
+    };
+    if app.group_by != GroupBy::None && app.view_mode != ViewMode::Agenda {
+        title_suffix.push_str(&format!(" · grouped by {}", app.group_by.name()));
+    }
+    if bulk_selecting {
+        title_suffix.push_str(&format!(" · {} selected", app.get_bulk_selection().len()));
+    }
+    
+    // Interleave group headers; they are drawn but never selected
+    let header_color = |label: &str| {
+        if app.view_mode == ViewMode::Agenda && label == AgendaBucket::Overdue.name() { colors.red } else { colors.magenta }
+    };
+    let list_rows = app.get_list_rows();
+    let mut todo_items: Vec<Option<ListItem>> = items.into_iter().map(Some).collect();
+    let mut selected_row = 0;
+    let items: Vec<ListItem> = list_rows.iter()
+        .enumerate()
+        .filter_map(|(row_index, row)| match row {
+            ListRow::Header(label, count) => Some(ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", label), Style::default().fg(header_color(label)).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("({})", count), Style::default().fg(colors.comment)),
+            ]))),
+            ListRow::Todo(index) => {
+                if *index == app.selected {
+                    selected_row = row_index;
+                }
+                todo_items.get_mut(*index).and_then(Option::take)
+            }
         })
         .collect();
     
+    let mut block = Block::default();
+    
+    // A shortened selected row is shown in full along the bottom border, unless the detail pane has it
+    let detail_pane_shown = app.display.show_detail_pane && f.area().width >= DETAIL_PANE_MIN_WIDTH;
+    if selected_truncated && !detail_pane_shown {
+        if let Some(todo) = app.get_selected_todo() {
+            let full = truncate_with(&todo.description, row_width.saturating_sub(2), glyphs.ellipsis);
+            block = block.title_bottom(Line::from(Span::styled(format!(" {} ", full), Style::default().fg(colors.fg))));
+        }
+    }
+    
     let list = List::new(items)
         .block(
-            Block::default()
+            block
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(Style::default().fg(colors.blue))
-                .title(format!(" {} ({}) ", 
-                    match &app.view_mode {
-                        ViewMode::All => "All".to_string(),
-                        ViewMode::Pending => "Pending".to_string(), 
-                        ViewMode::Completed => "Completed".to_string(),
-                        ViewMode::Search(_) => "Search".to_string(),
-                        ViewMode::FilterByTag(tag) => format!("#{}", tag),
-                        ViewMode::FilterByContext(context) => format!("@{}", context),
-                        ViewMode::FilterByDueDate(filter) => match filter {
-                            crate::todo::DueDateFilter::Overdue => "Overdue".to_string(),
-                            crate::todo::DueDateFilter::Today => "Today".to_string(),
-                            crate::todo::DueDateFilter::Tomorrow => "Tomorrow".to_string(),
-                            crate::todo::DueDateFilter::ThisWeek => "This Week".to_string(),
-                            crate::todo::DueDateFilter::NoDueDate => "No Due".to_string(),
-                        },
+                .title(format!(" {} ({}){} ", 
+                    match app.view_mode {
+                        ViewMode::List => filters.describe(),
+                        ViewMode::GlobalDue(filter) => format!("All {}", filter.name()),
+                        ViewMode::Agenda if filters.is_empty() => "Agenda".to_string(),
+                        ViewMode::Agenda => format!("Agenda · {}", filters.describe()),
                     },
-                    todos.len()
+                    todos.len(),
+                    title_suffix
                 ))
+                .title(Line::from(format!(" {}/{} ", app.selected + 1, todos.len())).right_aligned())
                 .title_style(Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD))
         )
         .style(Style::default().fg(colors.fg));
     
-    let mut list_state = ListState::default();
-    list_state.select(Some(app.selected));
+    // Keep a margin of rows around the selection instead of letting it hug the edge
+    let row_count = list_rows.len();
+    let height = area.height.saturating_sub(2) as usize;
+    app.list_offset = scroll_offset(app.list_offset, selected_row, row_count, height, app.scrolloff);
+    app.list_view_height = height;
+    
+    let mut list_state = ListState::default()
+        .with_offset(app.list_offset)
+        .with_selected(Some(selected_row));
     
     f.render_stateful_widget(list, area, &mut list_state);
 }
 
+// Break a styled line into rows of at most `width` columns, like `wrap_rows` does for plain text
+fn wrap_styled_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut row: Vec<Span> = Vec::new();
+    let mut row_width = 0;
+    
+    for span in line.spans {
+        let mut piece = String::new();
+        for c in span.content.chars() {
+            let w = c.width().unwrap_or(0);
+            if row_width + w > width {
+                if !piece.is_empty() {
+                    row.push(Span::styled(std::mem::take(&mut piece), span.style));
+                }
+                rows.push(Line::from(std::mem::take(&mut row)));
+                row_width = 0;
+            }
+            piece.push(c);
+            row_width += w;
+        }
+        if !piece.is_empty() {
+            row.push(Span::styled(piece, span.style));
+        }
+    }
+    rows.push(Line::from(row));
+    rows
+}
+
+// Word-wrap a styled line into rows of at most `width` columns, dropping the spaces at the
+// start of each row; a word wider than a whole row is split like `wrap_styled_line` does.
+// Wrapping here rather than in the Paragraph means the row count is just the number of rows.
+fn wrap_styled_words(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let chars: Vec<(char, Style)> = line.spans.iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+    let mut rows: Vec<Vec<(char, Style)>> = Vec::new();
+    let mut row: Vec<(char, Style)> = Vec::new();
+    let mut row_width = 0;
+    let mut spaces: Vec<(char, Style)> = Vec::new();
+    
+    for token in chars.chunk_by(|a, b| a.0.is_whitespace() == b.0.is_whitespace()) {
+        if token[0].0.is_whitespace() {
+            if !row.is_empty() {
+                spaces = token.to_vec();
+            }
+            continue;
+        }
+        let token_width: usize = token.iter().map(|(c, _)| c.width().unwrap_or(0)).sum();
+        let spaces_width: usize = spaces.iter().map(|(c, _)| c.width().unwrap_or(0)).sum();
+        if row_width + spaces_width + token_width <= width {
+            row.append(&mut spaces);
+            row_width += spaces_width;
+        } else if !row.is_empty() {
+            rows.push(std::mem::take(&mut row));
+            row_width = 0;
+        }
+        spaces.clear();
+        for &(c, style) in token {
+            let w = c.width().unwrap_or(0);
+            if row_width + w > width && !row.is_empty() {
+                rows.push(std::mem::take(&mut row));
+                row_width = 0;
+            }
+            row.push((c, style));
+            row_width += w;
+        }
+    }
+    rows.push(row);
+    
+    rows.into_iter()
+        .map(|row| {
+            let spans: Vec<Span> = row.chunk_by(|a, b| a.1 == b.1)
+                .map(|run| Span::styled(run.iter().map(|(c, _)| c).collect::<String>(), run[0].1))
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+// Split `text` into spans with every case-insensitive occurrence of `query` styled as a highlight
+pub fn highlight_spans(text: &str, query: &str, base_style: Style, highlight_style: Style) -> Vec<Span<'static>> {
+    if query.is_empty() {
+        return vec![Span::styled(text.to_string(), base_style)];
+    }
+    
+    let query: Vec<char> = query.chars().map(lowercase_char).collect();
+    let mut spans = Vec::new();
+    let mut plain_start = 0;
+    let mut position = 0;
+    
+    while position < text.len() {
+        match match_len_at(&text[position..], &query) {
+            Some(len) => {
+                if plain_start < position {
+                    spans.push(Span::styled(text[plain_start..position].to_string(), base_style));
+                }
+                spans.push(Span::styled(text[position..position + len].to_string(), highlight_style));
+                position += len;
+                plain_start = position;
+            }
+            None => {
+                // Step over one whole character to stay on a UTF-8 boundary
+                position += text[position..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+    
+    if plain_start < text.len() {
+        spans.push(Span::styled(text[plain_start..].to_string(), base_style));
+    }
+    spans
+}
+
+// Split `text` into spans with the characters a fuzzy `query` matched styled as a highlight,
+// the same characters search scored the match on
+pub fn fuzzy_highlight_spans(text: &str, query: &str, base_style: Style, highlight_style: Style) -> Vec<Span<'static>> {
+    let positions = crate::search::fuzzy_positions(text, query).unwrap_or_default();
+    let chars: Vec<(char, bool)> = text.chars()
+        .enumerate()
+        .map(|(i, c)| (c, positions.contains(&i)))
+        .collect();
+    chars.chunk_by(|a, b| a.1 == b.1)
+        .map(|run| {
+            let style = if run[0].1 { highlight_style } else { base_style };
+            Span::styled(run.iter().map(|(c, _)| c).collect::<String>(), style)
+        })
+        .collect()
+}
+
+fn lowercase_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+// Byte length of the match if `text` starts with `query` (already lowercased)
+fn match_len_at(text: &str, query: &[char]) -> Option<usize> {
+    let mut len = 0;
+    let mut chars = text.chars();
+    for &expected in query {
+        let c = chars.next()?;
+        if lowercase_char(c) != expected {
+            return None;
+        }
+        len += c.len_utf8();
+    }
+    Some(len)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatusCounts {
+    pub total: usize,
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+    pub overdue: usize,
+    pub due_today: usize,
+}
+
+// One count in the status bar; in progress, overdue and due today only appear when nonzero
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CountSegment {
+    Total(usize),
+    Pending(usize),
+    InProgress(usize),
+    Completed(usize),
+    Overdue(usize),
+    DueToday(usize),
+}
+
+impl CountSegment {
+    pub fn text(self) -> String {
+        match self {
+            CountSegment::Total(n) => format!("Total: {}", n),
+            CountSegment::Pending(n) => format!("Pending: {}", n),
+            CountSegment::InProgress(n) => format!("In progress: {}", n),
+            CountSegment::Completed(n) => format!("Completed: {}", n),
+            CountSegment::Overdue(n) => format!("Overdue: {}", n),
+            CountSegment::DueToday(n) => format!("Today: {}", n),
+        }
+    }
+}
+
+const COUNT_SEPARATOR: &str = " | ";
+
+// The counts that fit in `width` columns, in display order. When they don't all fit the least
+// important go first: total, then completed, in progress, pending, due today and overdue last.
+pub fn status_segments(counts: StatusCounts, width: usize) -> Vec<CountSegment> {
+    let mut segments = vec![CountSegment::Total(counts.total), CountSegment::Pending(counts.pending)];
+    if counts.in_progress > 0 {
+        segments.push(CountSegment::InProgress(counts.in_progress));
+    }
+    segments.push(CountSegment::Completed(counts.completed));
+    if counts.overdue > 0 {
+        segments.push(CountSegment::Overdue(counts.overdue));
+    }
+    if counts.due_today > 0 {
+        segments.push(CountSegment::DueToday(counts.due_today));
+    }
+    let drop_order = [CountSegment::Total(counts.total), CountSegment::Completed(counts.completed),
+        CountSegment::InProgress(counts.in_progress), CountSegment::Pending(counts.pending),
+        CountSegment::DueToday(counts.due_today), CountSegment::Overdue(counts.overdue)];
+    let used = |segments: &[CountSegment]| {
+        segments.iter().map(|segment| segment.text().width()).sum::<usize>() + COUNT_SEPARATOR.len() * segments.len().saturating_sub(1)
+    };
+    for least in drop_order {
+        if used(&segments) <= width {
+            break;
+        }
+        segments.retain(|segment| *segment != least);
+    }
+    segments
+}
+
+// A pending todo's due date as the list shows it: "3d overdue" (red), "today" or "today 17:00"
+// (yellow; red once the time has passed), "tomorrow", "in 4d" up to six days out, and the date
+// in `date_format` beyond that. Due dates given without a time are stored as 23:59:59.
+pub fn format_due(due: chrono::DateTime<chrono::Local>, now: chrono::DateTime<chrono::Local>, date_format: &str, colors: &Theme) -> (String, Color) {
+    let days = (due.date_naive() - now.date_naive()).num_days();
+    let time = (due.time() != chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap()).then(|| due.format("%H:%M").to_string());
+    let with_time = |day: &str| time.as_ref().map_or(day.to_string(), |time| format!("{} {}", day, time));
+    match days {
+        ..=-1 => (format!("{}d overdue", -days), colors.red),
+        0 => (with_time("today"), if due < now { colors.red } else { colors.yellow }),
+        1 => (with_time("tomorrow"), colors.blue),
+        2..=6 => (format!("in {}d", days), colors.blue),
+        _ => (due.format(date_format).to_string(), colors.blue),
+    }
+}
+
 fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
     let colors = &app.colors;
     
-    let (pending_count, completed_count, total_count) = if let Some(todo_list) = app.get_current_todo_list() {
-        (todo_list.pending_count(), todo_list.completed_count(), todo_list.total_count())
+    // A running timer leads the status bar so it's never forgotten
+    let mut prefix = Vec::new();
+    let timers = app.get_active_timers();
+    if let Some(pomodoro) = &app.pomodoro {
+        let remaining = pomodoro.remaining_formatted(chrono::Local::now());
+        let (label, color) = match pomodoro.phase {
+            Phase::Work => ("🍅 Work", colors.red),
+            Phase::Break => ("Break", colors.teal),
+        };
+        prefix.push(Span::styled(
+            format!("{} {}", label, remaining),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if matches!(app.mode, AppMode::Visual | AppMode::BulkOperation | AppMode::BulkTag | AppMode::BulkContext | AppMode::BulkDue) {
+        prefix.push(Span::styled(
+            format!("{} selected", app.get_bulk_selection().len()),
+            Style::default().fg(colors.purple).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(warning) = &app.data_warning {
+        prefix.push(Span::styled(warning.clone(), Style::default().fg(colors.red).add_modifier(Modifier::BOLD | colors.alert())));
+    }
+    if let Some(todo) = timers.first() {
+        let elapsed = todo.get_current_session_formatted().unwrap_or_default();
+        let more = if timers.len() > 1 { format!(" (+{} more)", timers.len() - 1) } else { String::new() };
+        prefix.push(Span::styled(
+            format!("Timer: {} {}{}", truncate_to_width(&todo.description, 30), elapsed, more),
+            Style::default().fg(colors.green).add_modifier(Modifier::BOLD),
+        ));
+    }
+    let mut rest = Vec::new();
+    if let Some(message) = app.messages.current() {
+        rest.push(Span::styled(message.text.clone(), message_style(colors, message.level)));
     } else {
-        (0, 0, 0)
-    };
+        let counts = app.get_current_todo_list().map_or_else(StatusCounts::default, |todo_list| StatusCounts {
+            total: todo_list.total_count(),
+            pending: todo_list.pending_count(),
+            in_progress: todo_list.in_progress_count(),
+            completed: todo_list.completed_count(),
+            overdue: todo_list.get_overdue_count(),
+            due_today: todo_list.get_due_today_count(),
+        });
+        let prefix_width = Line::from(prefix.clone()).width() + if prefix.is_empty() { 0 } else { COUNT_SEPARATOR.len() };
+        let room = (area.width.saturating_sub(2) as usize).saturating_sub(prefix_width);
+        for (i, segment) in status_segments(counts, room).into_iter().enumerate() {
+            if i > 0 {
+                rest.push(Span::raw(COUNT_SEPARATOR));
+            }
+            let style = match segment {
+                CountSegment::Overdue(_) => Style::default().fg(colors.red).add_modifier(Modifier::BOLD | colors.alert()),
+                CountSegment::DueToday(_) => Style::default().fg(colors.yellow).add_modifier(Modifier::BOLD),
+                CountSegment::InProgress(_) => Style::default().fg(colors.blue),
+                _ => Style::default(),
+            };
+            rest.push(Span::styled(segment.text(), style));
+        }
+    }
     
-    let status_text = if let Some(msg) = &app.message {
-        msg.clone()
-    } else {
-        format!("Total: {} | Pending: {} | Completed: {} | w: Workspaces | Ctrl+H: Home | ?: Help", 
-                total_count, pending_count, completed_count)
-    };
+    // Separators only go between segments, so nothing trails when the counts don't fit
+    let mut status_spans = Vec::new();
+    for span in prefix {
+        if !status_spans.is_empty() {
+            status_spans.push(Span::raw(COUNT_SEPARATOR));
+        }
+        status_spans.push(span);
+    }
+    if !status_spans.is_empty() && !rest.is_empty() {
+        status_spans.push(Span::raw(COUNT_SEPARATOR));
+    }
+    status_spans.extend(rest);
     
-    let paragraph = Paragraph::new(status_text)
+    let paragraph = Paragraph::new(Line::from(status_spans))
         .style(Style::default().fg(colors.fg_dark).bg(Color::Reset))
         .alignment(Alignment::Center)
         .block(
@@ -368,16 +1026,29 @@ fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(paragraph, area);
 }
 
+// Info keeps the status bar's own color
+fn message_style(colors: &Theme, level: MessageLevel) -> Style {
+    match level {
+        MessageLevel::Info => Style::default(),
+        MessageLevel::Success => Style::default().fg(colors.green),
+        MessageLevel::Warning => Style::default().fg(colors.yellow),
+        MessageLevel::Error => Style::default().fg(colors.red).add_modifier(Modifier::BOLD | colors.alert()),
+    }
+}
+
 fn draw_input(f: &mut Frame, area: Rect, app: &App) {
     let colors = &app.colors;
     
+    // Running count while adding several todos with Shift+Enter
+    let added = if app.quick_entry_count > 0 { format!("({} added) ", app.quick_entry_count) } else { String::new() };
+    
     let title = match app.mode {
-        AppMode::Insert => " Add Todo ".to_string(),
+        AppMode::Insert => format!(" Add Todo {}", added),
         AppMode::InsertChild => {
             if let Some(parent_id) = app.inserting_child_for {
-                if let Some(todo_list) = app.get_current_todo_list() {
+                if let Some(todo_list) = app.get_editing_todo_list() {
                     if let Some(parent) = todo_list.get_todo(parent_id) {
-                        format!(" Add Child to: {} ", parent.description)
+                        format!(" Add Child to: {} {}", parent.description, added)
                     } else {
                         " Add Child Todo ".to_string()
                     }
@@ -390,7 +1061,7 @@ fn draw_input(f: &mut Frame, area: Rect, app: &App) {
         }
         AppMode::EditTodo => {
             if let Some(todo_id) = app.editing_todo_id {
-                if let Some(todo_list) = app.get_current_todo_list() {
+                if let Some(todo_list) = app.get_editing_todo_list() {
                     if let Some(todo) = todo_list.get_todo(todo_id) {
                         format!(" Edit Todo: {} ", todo.description)
                     } else {
@@ -403,10 +1074,13 @@ fn draw_input(f: &mut Frame, area: Rect, app: &App) {
                 " Edit Todo ".to_string()
             }
         }
-        AppMode::Search => " Search Todos ".to_string(),
+        AppMode::Search => match app.search_scope {
+            SearchScope::Workspace => " Search [workspace] (Ctrl+W: all) ".to_string(),
+            SearchScope::All => " Search [all] (Ctrl+W: workspace) ".to_string(),
+        },
         AppMode::EditNotes => {
             if let Some(todo_id) = app.editing_notes_for {
-                if let Some(todo_list) = app.get_current_todo_list() {
+                if let Some(todo_list) = app.get_editing_todo_list() {
                     if let Some(todo) = todo_list.get_todo(todo_id) {
                         format!(" Edit Notes for: {} ", todo.description)
                     } else {
@@ -419,6 +1093,36 @@ fn draw_input(f: &mut Frame, area: Rect, app: &App) {
                 " Edit Notes ".to_string()
             }
         }
+        AppMode::RenameTag | AppMode::RenameContext => {
+            let marker = if app.mode == AppMode::RenameTag { "#" } else { "@" };
+            match app.get_renaming_label() {
+                Some(label) => format!(" Rename {}{} to ", marker, label),
+                None => " Rename ".to_string(),
+            }
+        }
+        AppMode::LogTime => {
+            match app.editing_todo_id.and_then(|id| app.get_editing_todo_list()?.get_todo(id)) {
+                Some(todo) => format!(" Log time on: {} (e.g. 45m, 1h30m, -20m) ", todo.description),
+                None => " Log time ".to_string(),
+            }
+        }
+        AppMode::EditTimeEntry => " Time entry note ".to_string(),
+        AppMode::ExportPath => match app.export_format {
+            Some(format) => format!(" Export {} as {} to (~/ for home) ", app.export_workspace_name(), format.name()),
+            None => " Export to ".to_string(),
+        },
+        AppMode::ImportPath => format!(" Import a todo.txt file into {} (~/ for home) ", app.get_current_workspace_name()),
+        AppMode::ImportWorkspacePath => " Import a shared workspace from (~/ for home) ".to_string(),
+        AppMode::ImportWorkspaceName => match &app.pending_workspace_import {
+            Some((workspace, _)) => format!(" '{}' is taken; import it as ", workspace.name),
+            None => " Import as ".to_string(),
+        },
+        AppMode::BulkTag | AppMode::BulkContext => {
+            let label = if app.mode == AppMode::BulkTag { "tag" } else { "context" };
+            format!(" Add {} to {} selected todos (Tab completes) ", label, app.get_bulk_selection().len())
+        }
+        AppMode::BulkDue => format!(" Due date for {} selected todos (friday, in 3 days, none) ", app.get_bulk_selection().len()),
+        AppMode::EditDue => " Due date (friday, in 3 days, 2024-12-25, none) ".to_string(),
         _ => " Input ".to_string(),
     };
     
@@ -428,104 +1132,196 @@ fn draw_input(f: &mut Frame, area: Rect, app: &App) {
         AppMode::EditTodo => colors.yellow,
         AppMode::Search => colors.cyan,
         AppMode::EditNotes => colors.purple,
+        AppMode::RenameTag | AppMode::BulkTag => colors.cyan,
+        AppMode::RenameContext | AppMode::BulkContext => colors.orange,
+        AppMode::LogTime | AppMode::EditTimeEntry | AppMode::ExportPath | AppMode::ImportPath
+        | AppMode::ImportWorkspacePath | AppMode::ImportWorkspaceName => colors.green,
+        AppMode::BulkDue | AppMode::EditDue => colors.yellow,
         _ => colors.blue,
     };
     
-    let input_text = match app.mode {
+    let buffer = match app.mode {
         AppMode::Search => &app.search_buffer,
         AppMode::EditTodo => &app.edit_buffer,
         AppMode::EditNotes => &app.notes_buffer,
         _ => &app.input_buffer,
     };
     
-    let input = Paragraph::new(input_text.as_str())
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(border_color))
+        .title(title)
+        .title_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD));
+    
+    // Tab completion candidates along the bottom border, current one highlighted
+    if let Some(completion) = &app.completion {
+        let marker = match completion.kind {
+            CompletionKind::Tag => "#",
+            CompletionKind::Context => "@",
+            CompletionKind::Due => "due:",
+        };
+        let mut spans = vec![Span::raw(" ")];
+        for (i, candidate) in completion.candidates.iter().enumerate() {
+            let style = if i == completion.index {
+                Style::default().fg(border_color).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(colors.comment)
+            };
+            spans.push(Span::styled(format!("{}{}", marker, candidate), style));
+            spans.push(Span::raw(" "));
+        }
+        block = block.title_bottom(Line::from(spans));
+    }
+    
+    // Long text scrolls sideways to keep the cursor in view
+    let (visible_text, cursor_x) = scroll_window(buffer.as_str(), buffer.cursor, area.width.saturating_sub(2) as usize);
+    
+    let input = Paragraph::new(visible_text)
         .style(Style::default().fg(colors.fg).bg(Color::Reset))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(border_color))
-                .title(title)
-                .title_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD))
-        );
+        .block(block);
     
     f.render_widget(input, area);
     
-    // Set cursor position based on actual cursor positions
-    let cursor_x = match app.mode {
-        AppMode::Search => app.search_cursor_pos,
-        AppMode::EditTodo => app.edit_cursor_pos,
-        AppMode::EditNotes => app.notes_cursor_pos,
-        _ => app.input_cursor_pos,
-    };
-    
     f.set_cursor_position((
         area.x + cursor_x as u16 + 1,
         area.y + 1,
     ));
 }
 
-fn draw_help(f: &mut Frame, app: &App) {
+// One line under the input: recognized tags, contexts and the resolved due date
+fn draw_parse_preview(f: &mut Frame, area: Rect, app: &App) {
     let colors = &app.colors;
     
-    let help_text = vec![
+    let text = if app.mode == AppMode::EditTodo { app.edit_buffer.as_str() } else { app.input_buffer.as_str() };
+    let preview = Todo::parse_preview(text);
+    if preview.is_empty() {
+        return;
+    }
+    
+    let mut spans = vec![Span::raw(" ")];
+    for tag in &preview.tags {
+        spans.push(Span::styled(format!("#{} ", tag), Style::default().fg(colors.cyan)));
+    }
+    for context in &preview.contexts {
+        spans.push(Span::styled(format!("@{} ", context), Style::default().fg(colors.orange)));
+    }
+    match (&preview.due_token, preview.due_date) {
+        (_, Some(due)) => spans.push(Span::styled(format!("due {} {}", due.format("%a"), due.format(&app.config.date_format)), Style::default().fg(colors.yellow))),
+        (Some(token), None) => spans.push(Span::styled(format!("unrecognized date: {}", token), Style::default().fg(colors.red))),
+        (None, None) => {}
+    }
+    
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn help_lines(app: &App) -> Vec<Line<'static>> {
+    let colors = &app.colors;
+    let glyphs = app.glyphs;
+    // Remappable keys show the effective binding
+    let key = |action: Action| app.keymap.label(action);
+    
+    vec![
         Line::from(vec![Span::styled("Paperclip - Help", Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD))]),
         Line::from(""),
         Line::from(vec![Span::styled("Navigation:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  j/↓     - Move down"),
-        Line::from("  k/↑     - Move up"), 
-        Line::from("  g       - Go to top"),
-        Line::from("  G       - Go to bottom"),
+        Line::from(format!("  {:<8}- Move down", key(Action::MoveDown))),
+        Line::from(format!("  {:<8}- Move up", key(Action::MoveUp))), 
+        Line::from(format!("  {:<8}- Go to top", format!("{}g", key(Action::GPrefix)))),
+        Line::from(format!("  {:<8}- Go to bottom", key(Action::GoToBottom))),
+        Line::from(format!("  {} / {}   - Previous / next todo at the same level", key(Action::PreviousSibling), key(Action::NextSibling))),
+        Line::from(format!("  {:<8}- Go to the parent todo", key(Action::GoToParent))),
+        Line::from(format!("  {} / {} - Half a page down / up ({} / {}: a full page)", key(Action::HalfPageDown), key(Action::HalfPageUp), key(Action::PageDown), key(Action::PageUp))),
+        Line::from(format!("  {:<8}- Group by tag / context / off", key(Action::GroupBy))),
+        Line::from(format!("  {:<8}- Toggle the detail pane (100+ columns wide)", key(Action::ToggleDetailPane))),
+        Line::from(format!("  {:<8}- Next color theme", key(Action::CycleTheme))),
         Line::from(""),
         Line::from(vec![Span::styled("Actions:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  i       - Insert new todo"),
-        Line::from("  e       - Edit selected todo"),
-        Line::from("  a       - Add child todo"),
-        Line::from("  Space   - Toggle todo complete"),
-        Line::from("  d       - Delete selected todo"),
-        Line::from("  v       - Cycle view mode (all/pending/completed)"),
+        Line::from(format!("  {:<8}- Insert new todo", key(Action::AddTodo))),
+        Line::from(format!("  {:<8}- Edit selected todo", key(Action::EditTodo))),
+        Line::from(format!("  {:<8}- Change just the due date (none clears)", key(Action::SetDue))),
+        Line::from(format!("  {:<8}- Add child todo", key(Action::AddChild))),
+        Line::from(format!("  {:<8}- Copy todo text / todo with notes and subtasks as Markdown", format!("{} / {}", key(Action::Copy), key(Action::CopyWithDetails)))),
+        Line::from("  Shift+Enter - (while adding) Add and keep typing the next todo"),
+        Line::from("  Ctrl+W/U/K  - (while typing) Delete word / to start / to end"),
+        Line::from("  Alt+B/F     - (while typing) Move by word; Home/End jump to line edges"),
+        Line::from("  Tab     - (while typing) Complete #tag, @context or due: keyword"),
+        Line::from(format!("  {:<8}- Toggle todo complete", key(Action::ToggleComplete))),
+        Line::from(format!("  {:<8}- Delete selected todo", key(Action::DeleteTodo))),
+        Line::from(format!("  {:<8}- Cycle status filter (all/pending/in progress/completed)", key(Action::CycleStatusFilter))),
+        Line::from(format!("  {:<8}- Overdue / due today across all workspaces", key(Action::DueOverview))),
+        Line::from(format!("  {:<8}- Agenda: pending todos under due date headers", key(Action::Agenda))),
+        Line::from(format!("  {:<8}- Week calendar (h/l: day, [/]: week, Enter: show in list)", key(Action::Calendar))),
+        Line::from(format!("  {:<8}- Status board (h/l: column, H/L: move todo, u: undo)", key(Action::Board))),
+        Line::from(format!("  {:<8}- Cycle sort mode (priority/due date/created/name)", key(Action::CycleSort))),
         Line::from(""),
         Line::from(vec![Span::styled("Undo/Redo:", Style::default().fg(colors.green).add_modifier(Modifier::BOLD))]),
-        Line::from("  u       - Undo last operation"),
-        Line::from("  Ctrl+R  - Redo last undone operation"),
+        Line::from(format!("  {:<8}- Undo last operation", key(Action::Undo))),
+        Line::from(format!("  {:<8}- Redo last undone operation", key(Action::Redo))),
         Line::from(""),
         Line::from(vec![Span::styled("Search & Filter:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  /       - Search todos (by text, tags, contexts)"),
-        Line::from("  #       - Select tag filter (popup with counts)"),
-        Line::from("  @       - Select context filter (popup with counts)"),
-        Line::from("  !       - Cycle due date filter"),
-        Line::from("  Esc     - Clear filters"),
+        Line::from(format!("  {:<8}- Fuzzy search todos (by text, tags, contexts, notes)", key(Action::Search))),
+        Line::from("  Ctrl+W  - (while searching) Toggle current workspace / all workspaces"),
+        Line::from("  'text   - (while searching) Exact match instead of fuzzy"),
+        Line::from("  ↑/↓     - (while searching) Recall previous searches"),
+        Line::from(format!("  {:<8}- Next/previous match of the last search (Esc twice to forget it)", format!("{}/{}", key(Action::NextMatch), key(Action::PreviousMatch)))),
+        Line::from(format!("  {:<8}- Select tag filter (pick it again to clear)", key(Action::TagFilter))),
+        Line::from(format!("  {:<8}- Select context filter (pick it again to clear)", key(Action::ContextFilter))),
+        Line::from(format!("  {:<8}- Filter by the selected todo's tag", format!("{}t / {}", key(Action::GPrefix), key(Action::FilterByTag)))),
+        Line::from(format!("  {:<8}- Filter by the selected todo's context", format!("{}c", key(Action::GPrefix)))),
+        Line::from("  !/Space - (in tag/context popup) Exclude instead of filter"),
+        Line::from("  r       - (in tag/context popup) Rename it on every todo"),
+        Line::from("  d       - (in tag/context popup) Remove it from every todo"),
+        Line::from(format!("  {:<8}- Saved filters (Enter apply, n save current, d delete)", key(Action::SavedFilters))),
+        Line::from(format!("  {:<8}- Cycle due date filter (filters stack)", key(Action::DueFilter))),
+        Line::from(format!("  {:<8}- Dismiss an error, else clear all filters", key(Action::ClearFilters))),
         Line::from(""),
         Line::from(vec![Span::styled("Hierarchy:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  Enter   - Expand/collapse todo"),
-        Line::from("  D       - Delete todo and all children"),
+        Line::from(format!("  {:<8}- Expand/collapse todo", key(Action::ToggleExpand))),
+        Line::from(format!("  {:<8}- Collapse everything (2{} shows two levels)", key(Action::CollapseAll), key(Action::CollapseAll))),
+        Line::from(format!("  {:<8}- Expand everything", key(Action::ExpandAll))),
+        Line::from(format!("  {:<8}- Delete todo and all children", key(Action::DeleteWithChildren))),
         Line::from(""),
         Line::from(vec![Span::styled("Priority:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  +/=     - Increase priority (0-5 scale)"),
-        Line::from("  -       - Decrease priority"),
+        Line::from(format!("  {:<8}- Increase priority (0-5 scale)", key(Action::IncreasePriority))),
+        Line::from(format!("  {:<8}- Decrease priority", key(Action::DecreasePriority))),
         Line::from(""),
         Line::from(vec![Span::styled("Bulk Operations:", Style::default().fg(colors.purple).add_modifier(Modifier::BOLD))]),
-        Line::from("  Shift+V - Enter visual mode for bulk operations"),
+        Line::from(format!("  {:<8}- Enter visual mode for bulk operations", key(Action::EnterVisual))),
         Line::from("  In visual mode:"),
-        Line::from("    j/k   - Extend selection"),
-        Line::from("    Space - Toggle individual selection"),
-        Line::from("    c     - Complete selected todos"),
-        Line::from("    d     - Delete selected todos"),
-        Line::from("    1-5   - Set priority for selected todos"),
+        Line::from(format!("    {:<6}- Extend selection", format!("{} {}", key(Action::MoveDown), key(Action::MoveUp)))),
+        Line::from(format!("    {:<6}- Toggle individual selection", key(Action::ToggleSelection))),
+        Line::from(format!("    {:<6}- Select every visible todo", key(Action::SelectAll))),
+        Line::from(format!("    {:<6}- Invert the selection", key(Action::InvertSelection))),
+        Line::from(format!("    {:<6}- Add the visible todos with a tag", key(Action::SelectByTag))),
+        Line::from(format!("    {:<6}- Complete selected todos", key(Action::BulkComplete))),
+        Line::from(format!("    {:<6}- Delete selected todos", key(Action::BulkDelete))),
+        Line::from("    0-5   - Set priority for selected todos"),
+        Line::from(format!("    {:<6}- Add a tag / context to selected todos", format!("{} / {}", key(Action::BulkTag), key(Action::BulkContext)))),
+        Line::from(format!("    {:<6}- Set due date for selected todos (none clears)", key(Action::BulkDue))),
+        Line::from(format!("    {:<6}- Move selected todos to another workspace", key(Action::BulkMove))),
         Line::from(""),
         Line::from(vec![Span::styled("Advanced Features:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  n       - Edit notes for selected todo"),
-        Line::from("  V       - View notes for selected todo (read-only)"),
-        Line::from("  t       - Toggle timer for selected todo"),
-        Line::from("  T       - Apply template to new todo"),
-        Line::from("  r       - Set recurrence for selected todo"),
+        Line::from(format!("  {:<8}- Edit notes for selected todo", key(Action::EditNotes))),
+        Line::from(format!("  {:<8}- Edit notes in $VISUAL / $EDITOR", key(Action::EditNotesExternal))),
+        Line::from(format!("  {:<8}- View notes for selected todo (read-only)", format!("{}v", key(Action::GPrefix)))),
+        Line::from("            j/k, PgUp/PgDn, g/G move through notes, Space ticks - [ ] items"),
+        Line::from(format!("  {:<8}- Toggle timer for selected todo", key(Action::ToggleTimer))),
+        Line::from(format!("  {:<8}- Log time by hand (45m, 1h30m, -20m + optional note)", key(Action::LogTime))),
+        Line::from(format!("  {:<8}- Time entries (d: delete, e: edit note)", key(Action::TimeEntries))),
+        Line::from(format!("  {:<8}- Time report by tag/context and by day", key(Action::TimeReport))),
+        Line::from(format!("  {:<8}- Pomodoro: 25m work on the selected todo, then a 5m break", key(Action::Pomodoro))),
+        Line::from(format!("  {:<8}- Apply template to new todo", key(Action::ApplyTemplate))),
+        Line::from(format!("  {:<8}- Set recurrence for selected todo", key(Action::SetRecurrence))),
         Line::from(""),
         Line::from(vec![Span::styled("Visual Indicators:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  ○       - Pending | ◐ In Progress | ● Completed"),
-        Line::from("  !       - Overdue | ▼▶ Expandable | [!] Priority"),
+        Line::from(format!("  {:<8}- Pending | {} In Progress | {} Completed", glyphs.pending, glyphs.in_progress, glyphs.completed)),
+        Line::from(format!("  {:<8}- Overdue | {}{} Expandable | [!] Priority", glyphs.overdue, glyphs.expanded, glyphs.collapsed)),
         Line::from("  #tag    - Tags (cyan) | @context (orange)"),
-        Line::from("  [N]     - Has notes (purple) | [today] Due dates"),
-        Line::from("  [date]  - Due dates (red=overdue, yellow=today)"),
+        Line::from("  [N]     - Has notes (purple) | [2/5] Notes checklist | [in 3d] Due dates"),
+        Line::from(format!("  {:<8}- Tracked time (bold while the timer runs)", format!("{} 12m", glyphs.timer))),
+        Line::from("  [2d overdue] [today 17:00] [in 4d] - Due dates (red=overdue, yellow=today)"),
+        Line::from(format!("  {:<8}- In the visual mode selection (count shown in the title)", glyphs.selection_gutter)),
         Line::from(""),
         Line::from(vec![Span::styled("Todo Format & Date Parsing:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
         Line::from("  Example: 'Fix bug #urgent @work due:today'"),
@@ -539,41 +1335,299 @@ fn draw_help(f: &mut Frame, app: &App) {
         Line::from("  due:eod (end of day), due:noon"),
         Line::from(""),
         Line::from(vec![Span::styled("Workspaces & Navigation:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  w       - Switch workspace (popup selection)"),
-        Line::from("  Ctrl+H  - Return to welcome screen from any workspace"),
+        Line::from(format!("  {:<8}- Switch workspace (popup selection)", key(Action::SwitchWorkspace))),
+        Line::from(format!("  {:<8}- Move selected todo (and children) to another workspace", key(Action::MoveToWorkspace))),
+        Line::from(format!("  {:<8}- Export: workspace (todo.txt, JSON), todos / time (CSV), due dates (iCal)", key(Action::Export))),
+        Line::from(format!("  {:<8}- Import a todo.txt file into the workspace", key(Action::Import))),
+        Line::from(format!("  {:<8}- Switch to the previous workspace", key(Action::PreviousWorkspace))),
+        Line::from("  Alt+1-9 - Jump to workspace by number"),
+        Line::from(format!("  {:<8}- Return to welcome screen from any workspace", key(Action::Welcome))),
+        Line::from(format!("  {:<8}- Hide the due-soon banner for this session", key(Action::DismissReminders))),
         Line::from("  In workspace selection popup:"),
         Line::from("    n     - Create new workspace"),
         Line::from("    d     - Delete selected workspace"),
+        Line::from("    x     - Export the workspace to share it"),
+        Line::from("    I     - Import a shared workspace"),
         Line::from("    Enter - Select workspace"),
         Line::from(""),
         Line::from(vec![Span::styled("Other:", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD))]),
-        Line::from("  ?       - Toggle this help"),
-        Line::from("  q       - Quit"),
+        Line::from(format!("  {:<8}- Toggle this help", key(Action::Help))),
+        Line::from(format!("  {:<8}- Quit", key(Action::Quit))),
         Line::from(""),
         Line::from(vec![Span::styled("In popups: j/k to navigate, Enter to select, Esc to cancel", Style::default().fg(colors.comment))]),
-    ];
+    ]
+}
+
+// Help lines containing `query` (ignoring case), each under its section heading. A matching
+// heading keeps its whole section.
+pub fn filter_help_lines(lines: Vec<Line<'static>>, query: &str) -> Vec<Line<'static>> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return lines;
+    }
+    let text = |line: &Line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>();
+    let is_heading = |line: &Line| line.spans.len() == 1 && line.spans[0].style.add_modifier.contains(Modifier::BOLD);
+
+    let mut kept = Vec::new();
+    let mut heading: Option<Line<'static>> = None;
+    let mut heading_matches = false;
+    for line in lines {
+        let matches = text(&line).to_lowercase().contains(&query);
+        if is_heading(&line) {
+            heading_matches = matches;
+            if matches {
+                kept.push(line);
+                heading = None;
+            } else {
+                heading = Some(line);
+            }
+        } else if matches || (heading_matches && !text(&line).trim().is_empty()) {
+            kept.extend(heading.take());
+            kept.push(line);
+        }
+    }
+    kept
+}
+
+fn draw_help(f: &mut Frame, app: &mut App) {
+    let colors = app.colors;
+    let lines = filter_help_lines(help_lines(app), &app.help_filter);
+    let no_matches = lines.is_empty();
     
-    let help_widget = Paragraph::new(help_text)
+    // Center the help dialog
+    let area = centered_rect(60, 80, f.area());
+    let height = area.height.saturating_sub(2) as usize;
+    let width = area.width.saturating_sub(2) as usize;
+    let lines: Vec<Line> = lines.into_iter().flat_map(|line| wrap_styled_words(line, width)).collect();
+    let rows = lines.len();
+    let mut help_widget = Paragraph::new(lines)
         .style(Style::default().fg(colors.fg).bg(Color::Reset))
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Left);
+    app.help_scroll = app.help_scroll.min(rows.saturating_sub(height));
+    app.help_view_height = height;
+    if no_matches {
+        help_widget = Paragraph::new(Line::from(Span::styled("No help lines match", Style::default().fg(colors.comment))));
+    }
+    
+    let mut title = String::from(" Help ");
+    if app.help_filtering || !app.help_filter.is_empty() {
+        title.push_str(&format!("/{}{} ", app.help_filter, if app.help_filtering { "_" } else { "" }));
+    }
+    if rows > height {
+        title.push_str(&format!("· {}-{} of {} ", app.help_scroll + 1, (app.help_scroll + height).min(rows), rows));
+    }
+    let hints = if app.help_filtering { " Enter: keep · Esc: clear " } else { " j/k PgUp/PgDn: scroll · /: filter · Esc: close " };
+    
+    let help_widget = help_widget
+        .scroll((app.help_scroll as u16, 0))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(Style::default().fg(colors.cyan))
-                .title(" Help ")
+                .title(title)
                 .title_style(Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD))
+                .title_bottom(Line::from(Span::styled(
+                    app.data_dir.as_ref().map(|dir| format!(" Data: {} ", dir.display())).unwrap_or_default(),
+                    Style::default().fg(colors.comment),
+                )))
+                .title_bottom(Line::from(Span::styled(hints, Style::default().fg(colors.comment))).right_aligned())
         );
     
-    // Center the help dialog
-    let area = centered_rect(60, 80, f.area());
     f.render_widget(Clear, area);
     f.render_widget(help_widget, area);
 }
 
+const TIME_REPORT_DAYS: i64 = 14;
+
+// Tracked time in the current workspace over the last two weeks, by tag/context and by day
+fn draw_time_report(f: &mut Frame, app: &App) {
+    let colors = &app.colors;
+    let today = chrono::Local::now().date_naive();
+    let days = today - chrono::Duration::days(TIME_REPORT_DAYS - 1)..=today;
+    let (by_tag, by_day) = match app.get_current_todo_list() {
+        Some(todo_list) => (todo_list.time_by_tag(&days), todo_list.time_by_day(&days)),
+        None => (Vec::new(), Vec::new()),
+    };
+    
+    let area = f.area();
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.green))
+        .title(format!(" Time Report: last {} days · {} ", TIME_REPORT_DAYS, app.get_current_workspace_name()))
+        .title_style(Style::default().fg(colors.green).add_modifier(Modifier::BOLD))
+        .title_bottom(Line::from(" Press any key to close ").centered());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    
+    let day_rows: Vec<(String, u64)> = by_day.into_iter()
+        .map(|(day, seconds)| (day.format("%a %b %-d").to_string(), seconds))
+        .collect();
+    draw_report_table(f, columns[0], app, " By tag / context ", &by_tag, colors.cyan);
+    draw_report_table(f, columns[1], app, " By day ", &day_rows, colors.blue);
+}
+
+// Label, time and a bar scaled to the largest value
+fn draw_report_table(f: &mut Frame, area: Rect, app: &App, title: &str, rows: &[(String, u64)], color: Color) {
+    let colors = &app.colors;
+    let label_width = rows.iter().map(|(label, _)| label.width()).max().unwrap_or(0).min(24);
+    let bar_width = (area.width as usize).saturating_sub(label_width + 14);
+    let max = rows.iter().map(|(_, seconds)| *seconds).max().unwrap_or(0);
+    
+    let lines: Vec<Line> = if max == 0 {
+        vec![Line::from(Span::styled("No time tracked in this period", Style::default().fg(colors.comment)))]
+    } else {
+        rows.iter().map(|(label, seconds)| {
+            let bar = "█".repeat((bar_width as u64 * seconds / max) as usize);
+            Line::from(vec![
+                Span::styled(format!("{:<width$} ", truncate_to_width(label, label_width), width = label_width), Style::default().fg(colors.fg)),
+                Span::styled(format!("{:>8} ", format_tracked_time(*seconds)), Style::default().fg(colors.comment)),
+                Span::styled(bar, Style::default().fg(color)),
+            ])
+        }).collect()
+    };
+    
+    let table = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(colors.dark3))
+            .title(title)
+            .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+    );
+    f.render_widget(table, area);
+}
+
+// A column of todos for each status in BOARD_COLUMNS
+fn draw_board(f: &mut Frame, area: Rect, app: &App) {
+    let colors = &app.colors;
+    let columns = app.get_board_columns();
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3); 3])
+        .split(area);
+    
+    for (column, ((status, todos), column_area)) in BOARD_COLUMNS.iter().zip(&columns).zip(areas.iter()).enumerate() {
+        let focused = app.board_selection.0 == column;
+        let accent = match status {
+            TodoStatus::Pending => colors.yellow,
+            TodoStatus::InProgress => colors.blue,
+            TodoStatus::Completed => colors.green,
+        };
+        
+        let width = column_area.width.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = todos.iter().map(|todo| {
+            let style = if todo.is_completed() {
+                Style::default().fg(colors.comment)
+            } else if todo.is_overdue() {
+                Style::default().fg(colors.red)
+            } else {
+                Style::default().fg(colors.fg)
+            };
+            ListItem::new(Line::from(Span::styled(truncate_to_width(&todo.description, width), style)))
+        }).collect();
+        
+        let border_style = if focused {
+            Style::default().fg(accent).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors.dark3)
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(border_style)
+                    .title(format!(" {} ({}) ", status.name(), todos.len()))
+                    .title_style(Style::default().fg(accent).add_modifier(Modifier::BOLD))
+            )
+            .highlight_style(colors.selected().add_modifier(Modifier::BOLD));
+        
+        let mut state = ListState::default();
+        if focused && !todos.is_empty() {
+            state.select(Some(app.board_selection.1.min(todos.len() - 1)));
+        }
+        f.render_stateful_widget(list, *column_area, &mut state);
+    }
+}
+
+// The calendar's week: the overdue gutter, then a column per day from Monday
+fn draw_calendar(f: &mut Frame, area: Rect, app: &App) {
+    let colors = &app.colors;
+    let today = chrono::Local::now().date_naive();
+    let week = app.calendar.days();
+    let (overdue, days) = app.get_calendar_columns();
+    
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.blue))
+        .title(format!(" Week of {} – {} ", week.start().format("%b %-d"), week.end().format("%b %-d %Y")))
+        .title_style(Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 8); 8])
+        .split(inner);
+    
+    let columns = std::iter::once((None, overdue))
+        .chain(days.into_iter().map(|(day, todos)| (Some(day), todos)));
+    for (column, ((day, todos), column_area)) in columns.zip(areas.iter()).enumerate() {
+        let focused = app.calendar.focused_day.map_or(0, |day| day + 1) == column;
+        let (title, title_color) = match day {
+            None => (" Overdue ".to_string(), colors.red),
+            Some(day) if day == today => (format!(" {} ", day.format("%a %-d")), colors.cyan),
+            Some(day) => (format!(" {} ", day.format("%a %-d")), colors.fg_dark),
+        };
+        
+        let width = column_area.width.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = todos.iter().map(|todo| {
+            let style = if todo.is_completed() {
+                Style::default().fg(colors.comment).add_modifier(Modifier::CROSSED_OUT)
+            } else if day.is_none() {
+                Style::default().fg(colors.red)
+            } else {
+                Style::default().fg(colors.fg)
+            };
+            ListItem::new(Line::from(Span::styled(truncate_to_width(&todo.description, width), style)))
+        }).collect();
+        
+        let border_style = if focused {
+            Style::default().fg(colors.blue).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors.dark3)
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(border_style)
+                    .title(title)
+                    .title_style(Style::default().fg(title_color).add_modifier(Modifier::BOLD))
+            )
+            .highlight_style(colors.selected().add_modifier(Modifier::BOLD));
+        
+        let mut state = ListState::default();
+        if focused && !todos.is_empty() {
+            state.select(Some(app.calendar.selected_idx.min(todos.len() - 1)));
+        }
+        f.render_stateful_widget(list, *column_area, &mut state);
+    }
+}
+
 fn draw_welcome_screen(f: &mut Frame, app: &App) {
     let colors = &app.colors;
+    let glyphs = app.glyphs;
     
     // Create main layout
     let chunks = Layout::default()
@@ -581,7 +1635,7 @@ fn draw_welcome_screen(f: &mut Frame, app: &App) {
         .constraints([
             Constraint::Length(8),  // Header with logo
             Constraint::Min(10),    // Options list
-            Constraint::Length(3),  // Footer with instructions
+            Constraint::Length(if app.data_warning.is_some() { 4 } else { 3 }),  // Footer with instructions
         ])
         .split(f.area());
     
@@ -589,7 +1643,7 @@ fn draw_welcome_screen(f: &mut Frame, app: &App) {
     let logo_text = vec![
         Line::from(""),
         Line::from(vec![
-            Span::styled("  📎 ", Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  {}", glyphs.logo), Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD)),
             Span::styled("Welcome to ", Style::default().fg(colors.fg)),
             Span::styled("Paperclip", Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD)),
         ]),
@@ -600,11 +1654,11 @@ fn draw_welcome_screen(f: &mut Frame, app: &App) {
         Line::from(""),
         if app.is_first_launch {
             Line::from(vec![
-                Span::styled("     🎉 Thanks for trying Paperclip! Choose an option below:", Style::default().fg(colors.green)),
+                Span::styled(format!("     {}Thanks for trying Paperclip! Choose an option below:", glyphs.first_launch), Style::default().fg(colors.green)),
             ])
         } else {
             Line::from(vec![
-                Span::styled("     👋 Welcome back! What would you like to do?", Style::default().fg(colors.blue)),
+                Span::styled(format!("     {}Welcome back! What would you like to do?", glyphs.welcome_back), Style::default().fg(colors.blue)),
             ])
         },
         Line::from(""),
@@ -630,20 +1684,20 @@ fn draw_welcome_screen(f: &mut Frame, app: &App) {
             let is_selected = i == app.welcome_selected;
             
             let title_style = if is_selected {
-                Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(colors.fg)
             };
             
             let description_style = if is_selected {
-                Style::default().fg(colors.comment).bg(colors.bg_highlight)
+                colors.selected().fg(colors.comment)
             } else {
                 Style::default().fg(colors.comment)
             };
             
             let lines = vec![
                 Line::from(vec![
-                    Span::styled(if is_selected { "► " } else { "  " }, title_style),
+                    Span::styled(if is_selected { format!("{} ", glyphs.pointer) } else { "  ".to_string() }, title_style),
                     Span::styled(*title, title_style),
                 ]),
                 Line::from(vec![
@@ -671,18 +1725,23 @@ fn draw_welcome_screen(f: &mut Frame, app: &App) {
     f.render_widget(options_list, chunks[1]);
     
     // Draw footer with instructions
-    let instructions = vec![
+    let mut instructions = vec![
         Line::from(vec![
             Span::styled("Navigate: ", Style::default().fg(colors.comment)),
-            Span::styled("j/k ↓/↑", Style::default().fg(colors.blue).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("j/k {}", glyphs.up_down), Style::default().fg(colors.blue).add_modifier(Modifier::BOLD)),
             Span::styled("  Select: ", Style::default().fg(colors.comment)),
             Span::styled("Enter", Style::default().fg(colors.green).add_modifier(Modifier::BOLD)),
+            Span::styled("  Import: ", Style::default().fg(colors.comment)),
+            Span::styled("I", Style::default().fg(colors.cyan).add_modifier(Modifier::BOLD)),
             Span::styled("  Help: ", Style::default().fg(colors.comment)),
             Span::styled("?", Style::default().fg(colors.yellow).add_modifier(Modifier::BOLD)),
             Span::styled("  Quit: ", Style::default().fg(colors.comment)),
             Span::styled("q", Style::default().fg(colors.red).add_modifier(Modifier::BOLD)),
         ]),
     ];
+    if let Some(warning) = &app.data_warning {
+        instructions.push(Line::from(Span::styled(warning.as_str(), Style::default().fg(colors.red).add_modifier(Modifier::BOLD | colors.alert()))));
+    }
     
     let footer = Paragraph::new(instructions)
         .style(Style::default().fg(colors.fg))
@@ -702,17 +1761,18 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
     
     let (items, title, border_color) = match app.mode {
         AppMode::TagSelection => {
-            let tag_counts = if let Some(todo_list) = app.get_current_todo_list() {
-                todo_list.get_tag_counts()
-            } else {
-                Vec::new()
-            };
+            let counts: HashMap<String, usize> = app.get_current_todo_list()
+                .map(|todo_list| todo_list.get_tag_counts().into_iter().collect())
+                .unwrap_or_default();
+            let tag_counts: Vec<(&String, usize)> = app.available_tags.iter()
+                .map(|tag| (tag, counts.get(tag).copied().unwrap_or(0)))
+                .collect();
             let items: Vec<ListItem> = tag_counts.iter()
                 .enumerate()
                 .map(|(i, (tag, count))| {
                     let is_selected = i == app.popup_selected;
                     let style = if is_selected {
-                        Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(colors.fg)
                     };
@@ -727,20 +1787,22 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
                     ListItem::new(line)
                 })
                 .collect();
-            (items, " Select Tag ", colors.cyan)
+            let title = if app.popup_exclude { " Exclude Tag " } else { " Select Tag " };
+            (items, title, colors.cyan)
         }
         AppMode::ContextSelection => {
-            let context_counts = if let Some(todo_list) = app.get_current_todo_list() {
-                todo_list.get_context_counts()
-            } else {
-                Vec::new()
-            };
+            let counts: HashMap<String, usize> = app.get_current_todo_list()
+                .map(|todo_list| todo_list.get_context_counts().into_iter().collect())
+                .unwrap_or_default();
+            let context_counts: Vec<(&String, usize)> = app.available_contexts.iter()
+                .map(|context| (context, counts.get(context).copied().unwrap_or(0)))
+                .collect();
             let items: Vec<ListItem> = context_counts.iter()
                 .enumerate()
                 .map(|(i, (context, count))| {
                     let is_selected = i == app.popup_selected;
                     let style = if is_selected {
-                        Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(colors.fg)
                     };
@@ -755,7 +1817,8 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
                     ListItem::new(line)
                 })
                 .collect();
-            (items, " Select Context ", colors.orange)
+            let title = if app.popup_exclude { " Exclude Context " } else { " Select Context " };
+            (items, title, colors.orange)
         }
         AppMode::TemplateSelection => {
             let templates = app.template_manager.get_all_templates();
@@ -764,7 +1827,7 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
                 .map(|(i, template)| {
                     let is_selected = i == app.popup_selected;
                     let style = if is_selected {
-                        Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(colors.fg)
                     };
@@ -785,7 +1848,7 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
                 .map(|(i, pattern)| {
                     let is_selected = i == app.popup_selected;
                     let style = if is_selected {
-                        Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(colors.fg)
                     };
@@ -809,26 +1872,103 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
                 .collect();
             (items, " Select Recurrence ", colors.yellow)
         }
-        AppMode::WorkspaceSelection => {
+        AppMode::WorkspaceSelection | AppMode::MoveToWorkspace => {
             let items: Vec<ListItem> = app.available_workspaces.iter()
+                .filter_map(|workspace_id| app.workspace_manager.workspaces.get(workspace_id))
                 .enumerate()
-                .map(|(i, workspace_name)| {
+                .map(|(i, workspace)| {
                     let is_selected = i == app.popup_selected;
                     let style = if is_selected {
-                        Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(colors.fg)
                     };
                     
                     let line = Line::from(vec![
                         Span::styled("  [W] ", Style::default().fg(colors.magenta)),
-                        Span::styled(workspace_name, style),
+                        Span::styled(workspace.name.as_str(), style),
+                    ]);
+                    
+                    ListItem::new(line)
+                })
+                .collect();
+            let title = if app.mode == AppMode::MoveToWorkspace { " Move To Workspace " } else { " Select Workspace " };
+            (items, title, colors.magenta)
+        }
+        AppMode::SavedFilterSelection => {
+            let items: Vec<ListItem> = app.workspace_manager.saved_filters.iter()
+                .enumerate()
+                .map(|(i, saved)| {
+                    let is_selected = i == app.popup_selected;
+                    let style = if is_selected {
+                        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(colors.fg)
+                    };
+                    
+                    let mut summary = saved.filters.describe();
+                    if saved.sort_mode != SortMode::Priority {
+                        summary.push_str(&format!(" · by {}", saved.sort_mode.name()));
+                    }
+                    
+                    let line = Line::from(vec![
+                        Span::styled("  [F] ", Style::default().fg(colors.cyan)),
+                        Span::styled(format!("{} ", saved.name), style),
+                        Span::styled(format!("({})", summary), Style::default().fg(colors.comment)),
                     ]);
                     
                     ListItem::new(line)
                 })
                 .collect();
-            (items, " Select Workspace ", colors.magenta)
+            (items, " Saved Filters ", colors.cyan)
+        }
+        AppMode::TimeTracking => {
+            let Some(todo) = app.get_time_entries_todo() else { return };
+            let row_style = |i: usize| if i == app.popup_selected {
+                colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(colors.fg)
+            };
+            
+            let mut items = Vec::new();
+            if let Some(start) = todo.time_tracker.current_session {
+                let elapsed = todo.get_current_session_formatted().unwrap_or_default();
+                items.push(ListItem::new(Line::from(vec![
+                    Span::styled("  ⏱ ", Style::default().fg(colors.green)),
+                    Span::styled(format!("{}–now ({}) — running", start.format("%b %-d %H:%M"), elapsed), row_style(0).fg(colors.green)),
+                ])));
+            }
+            for entry in todo.time_tracker.entries.iter().rev() {
+                let end = entry.end.map_or("?".to_string(), |end| end.format("%H:%M").to_string());
+                let mut spans = vec![
+                    Span::styled(format!("  {}–{} ", entry.start.format("%b %-d %H:%M"), end), row_style(items.len())),
+                    Span::styled(format!("({})", format_signed_time(entry.duration_seconds())), Style::default().fg(colors.green)),
+                ];
+                if let Some(note) = &entry.description {
+                    spans.push(Span::styled(format!(" — {}", note), Style::default().fg(colors.comment)));
+                }
+                items.push(ListItem::new(Line::from(spans)));
+            }
+            (items, " Time Entries ", colors.green)
+        }
+        AppMode::Export => {
+            let workspace = app.get_current_workspace_name();
+            let items: Vec<ListItem> = ExportFormat::ALL.iter()
+                .enumerate()
+                .map(|(i, format)| {
+                    let style = if i == app.popup_selected {
+                        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(colors.fg)
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled("  [X] ", Style::default().fg(colors.green)),
+                        Span::styled(format!("{} ", format.name()), style),
+                        Span::styled(format!("({})", format.default_file(&workspace)), Style::default().fg(colors.comment)),
+                    ]))
+                })
+                .collect();
+            (items, " Export ", colors.green)
         }
         _ => return,
     };
@@ -862,7 +2002,12 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
     };
     
     let instructions = match app.mode {
+        AppMode::TagSelection if app.tag_select_from_visual => "Enter: Add tagged todos to selection | Esc: Cancel | j/k: Navigate",
         AppMode::WorkspaceSelection => "Enter: Select | n: New | d: Delete | Esc: Cancel | j/k: Navigate",
+        AppMode::TagSelection | AppMode::ContextSelection => "Enter: Filter | !: Exclude | Space: Toggle exclude | r: Rename | d: Delete | Esc: Cancel",
+        AppMode::SavedFilterSelection => "Enter: Apply | n: Save current | d: Delete | Esc: Cancel",
+        AppMode::TimeTracking => "d: Delete | e: Edit note | Esc: Close | j/k: Navigate",
+        AppMode::Export => "Enter: Choose where to save | Esc: Cancel | j/k: Navigate",
         _ => "Enter: Select | Esc: Cancel | j/k: Navigate",
     };
     
@@ -873,12 +2018,12 @@ fn draw_selection_popup(f: &mut Frame, app: &App) {
     f.render_widget(instructions_widget, instructions_area);
 }
 
-fn draw_notes_editor(f: &mut Frame, app: &App) {
+fn draw_notes_editor(f: &mut Frame, app: &mut App) {
     let colors = &app.colors;
     
     // Get the todo being edited
     let todo = if let Some(todo_id) = app.editing_notes_for {
-        if let Some(todo_list) = app.get_current_todo_list() {
+        if let Some(todo_list) = app.get_editing_todo_list() {
             todo_list.get_todo(todo_id)
         } else {
             return;
@@ -921,24 +2066,6 @@ fn draw_notes_editor(f: &mut Frame, app: &App) {
     
     f.render_widget(todo_info, chunks[0]);
     
-    // Draw notes text area
-    let notes_text = if app.notes_buffer.is_empty() {
-        "Type your notes here...".to_string()
-    } else {
-        app.notes_buffer.clone()
-    };
-    
-    let notes_editor = Paragraph::new(notes_text)
-        .style(Style::default().fg(if app.notes_buffer.is_empty() { colors.comment } else { colors.fg }).bg(Color::Reset))
-        .wrap(Wrap { trim: false })
-        .block(
-            Block::default()
-                .borders(Borders::LEFT | Borders::RIGHT)
-                .border_style(Style::default().fg(colors.purple))
-        );
-    
-    f.render_widget(notes_editor, chunks[1]);
-    
     // Draw instructions
     let instructions = Paragraph::new("F2, Ctrl+Enter, or Ctrl+S: Save | Esc: Cancel | Enter: New line")
         .style(Style::default().fg(colors.comment))
@@ -952,38 +2079,64 @@ fn draw_notes_editor(f: &mut Frame, app: &App) {
     
     f.render_widget(instructions, chunks[2]);
     
-    // Set cursor position in the notes area
-    // Calculate cursor position based on actual cursor position in buffer
+    // Lines are wrapped here rather than by the Paragraph so the cursor can be placed on the same rows
     let text_area = Rect {
         x: chunks[1].x + 1,
         y: chunks[1].y,
-        width: chunks[1].width - 2,
+        width: chunks[1].width.saturating_sub(2),
         height: chunks[1].height,
     };
+    let notes = app.notes_buffer.as_str();
+    let rows = wrap_rows(notes, text_area.width as usize);
+    let height = (text_area.height as usize).max(1);
+    let (cursor_row, cursor_col) = row_col(notes, &rows, app.notes_buffer.cursor);
+    
+    // Scroll just enough to keep the cursor row on screen
+    if cursor_row < app.notes_scroll {
+        app.notes_scroll = cursor_row;
+    } else if cursor_row >= app.notes_scroll + height {
+        app.notes_scroll = cursor_row + 1 - height;
+    }
+    app.notes_view_height = height;
     
-    // Calculate cursor position based on the current cursor position in buffer
-    let text_before_cursor = &app.notes_buffer[..app.notes_cursor_pos];
-    let lines_before_cursor: Vec<&str> = text_before_cursor.split('\n').collect();
-    let cursor_y = text_area.y + (lines_before_cursor.len().saturating_sub(1)) as u16;
-    let cursor_x = if let Some(current_line) = lines_before_cursor.last() {
-        text_area.x + current_line.len() as u16
+    let notes_lines: Vec<Line> = if app.notes_buffer.is_empty() {
+        vec![Line::from(Span::styled("Type your notes here...", Style::default().fg(colors.comment)))]
     } else {
-        text_area.x
+        rows.iter().map(|row| Line::from(&notes[row.clone()])).collect()
     };
     
+    let (cursor_line, _) = line_col(notes, app.notes_buffer.cursor);
+    let mut block = Block::default()
+        .borders(Borders::LEFT | Borders::RIGHT)
+        .border_style(Style::default().fg(colors.purple));
+    if rows.len() > height {
+        block = block.title(Line::from(format!(" line {}/{} ", cursor_line + 1, notes.split('\n').count())).right_aligned());
+    }
+    
+    let notes_editor = Paragraph::new(notes_lines)
+        .style(Style::default().fg(colors.fg).bg(Color::Reset))
+        .scroll((app.notes_scroll as u16, 0))
+        .block(block);
+    
+    f.render_widget(notes_editor, chunks[1]);
+    
+    // Set cursor position in the notes area
+    let cursor_x = text_area.x + cursor_col as u16;
+    let cursor_y = text_area.y + (cursor_row - app.notes_scroll) as u16;
+    
     // Make sure cursor stays within bounds
-    let cursor_x = cursor_x.min(text_area.x + text_area.width - 1);
-    let cursor_y = cursor_y.min(text_area.y + text_area.height - 1);
+    let cursor_x = cursor_x.min(text_area.x + text_area.width.saturating_sub(1));
+    let cursor_y = cursor_y.min(text_area.y + text_area.height.saturating_sub(1));
     
     f.set_cursor_position((cursor_x, cursor_y));
 }
 
-fn draw_notes_viewer(f: &mut Frame, app: &App) {
+fn draw_notes_viewer(f: &mut Frame, app: &mut App) {
     let colors = &app.colors;
     
     // Get the todo being viewed
     let todo = if let Some(todo_id) = app.editing_notes_for {
-        if let Some(todo_list) = app.get_current_todo_list() {
+        if let Some(todo_list) = app.get_editing_todo_list() {
             todo_list.get_todo(todo_id)
         } else {
             return;
@@ -1026,22 +2179,48 @@ fn draw_notes_viewer(f: &mut Frame, app: &App) {
     
     f.render_widget(todo_info, chunks[0]);
     
-    // Draw notes text area (read-only)
-    let notes_text = app.notes_buffer.clone();
+    // Draw notes text area (read-only) as Markdown, with the line j/k moved to highlighted
+    let width = chunks[1].width.saturating_sub(2) as usize;
+    let mut rows: Vec<Line> = Vec::new();
+    let mut selected_rows = 0..0;
+    for (index, line) in render_markdown_lines(app.notes_buffer.as_str(), colors).into_iter().enumerate() {
+        let start = rows.len();
+        rows.extend(wrap_styled_line(line, width));
+        if index == app.notes_selected_line {
+            selected_rows = start..rows.len();
+            for row in &mut rows[start..] {
+                row.style = colors.selected();
+            }
+        }
+    }
+    let height = (chunks[1].height as usize).max(1);
+    
+    // Scroll just enough to keep the highlighted line on screen
+    if selected_rows.start < app.notes_scroll {
+        app.notes_scroll = selected_rows.start;
+    } else if selected_rows.end > app.notes_scroll + height {
+        app.notes_scroll = selected_rows.end.saturating_sub(height).min(selected_rows.start);
+    }
+    app.notes_scroll = app.notes_scroll.min(rows.len().saturating_sub(height));
+    app.notes_view_height = height;
+    
+    let mut block = Block::default()
+        .borders(Borders::LEFT | Borders::RIGHT)
+        .border_style(Style::default().fg(colors.purple));
+    if rows.len() > height {
+        let last = (app.notes_scroll + height).min(rows.len());
+        block = block.title(Line::from(format!(" {}-{}/{} ", app.notes_scroll + 1, last, rows.len())).right_aligned());
+    }
     
-    let notes_viewer = Paragraph::new(notes_text)
+    let notes_viewer = Paragraph::new(rows)
         .style(Style::default().fg(colors.fg).bg(Color::Reset))
-        .wrap(Wrap { trim: false })
-        .block(
-            Block::default()
-                .borders(Borders::LEFT | Borders::RIGHT)
-                .border_style(Style::default().fg(colors.purple))
-        );
+        .scroll((app.notes_scroll as u16, 0))
+        .block(block);
     
     f.render_widget(notes_viewer, chunks[1]);
     
     // Draw instructions
-    let instructions = Paragraph::new("Esc: Close | n: Edit notes")
+    let instructions = Paragraph::new("Esc: Close | n: Edit notes | j/k, PgUp/PgDn: Move | Space: Tick checklist item")
         .style(Style::default().fg(colors.comment))
         .alignment(Alignment::Center)
         .block(
@@ -1054,6 +2233,69 @@ fn draw_notes_viewer(f: &mut Frame, app: &App) {
     f.render_widget(instructions, chunks[2]);
 }
 
+// Yes/no question for a destructive action
+fn draw_confirm_popup(f: &mut Frame, app: &App) {
+    let colors = &app.colors;
+    
+    let popup_area = centered_rect(50, 20, f.area());
+    f.render_widget(Clear, popup_area);
+    
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(app.get_confirmation_prompt().unwrap_or_default(), Style::default().fg(colors.fg).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(Span::styled("y: Yes | n/Esc: No", Style::default().fg(colors.comment))),
+    ];
+    
+    let popup = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(colors.red))
+                .title(" Confirm ")
+                .title_style(Style::default().fg(colors.red).add_modifier(Modifier::BOLD))
+        );
+    
+    f.render_widget(popup, popup_area);
+}
+
+// Name prompt shown over the list when saving the current filters
+fn draw_save_filter_popup(f: &mut Frame, app: &App) {
+    let colors = &app.colors;
+    
+    let popup_area = centered_rect(50, 20, f.area());
+    f.render_widget(Clear, popup_area);
+    
+    let input_text = if app.input_buffer.is_empty() {
+        "Enter a name for this filter...".to_string()
+    } else {
+        app.input_buffer.content.clone()
+    };
+    
+    let lines = vec![
+        Line::from(Span::styled(app.filters.describe(), Style::default().fg(colors.comment))),
+        Line::from(""),
+        Line::from(Span::styled(input_text, Style::default().fg(if app.input_buffer.is_empty() { colors.comment } else { colors.fg }))),
+        Line::from(""),
+        Line::from(Span::styled("Enter: Save | Esc: Cancel", Style::default().fg(colors.comment))),
+    ];
+    
+    let popup = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(colors.green))
+                .title(" Save Filter ")
+                .title_style(Style::default().fg(colors.green).add_modifier(Modifier::BOLD))
+        );
+    
+    f.render_widget(popup, popup_area);
+}
+
 fn draw_create_workspace_ui(f: &mut Frame, app: &App) {
     let colors = &app.colors;
     
@@ -1090,7 +2332,7 @@ fn draw_create_workspace_ui(f: &mut Frame, app: &App) {
     let input_text = if app.input_buffer.is_empty() {
         "Enter workspace name...".to_string()
     } else {
-        app.input_buffer.clone()
+        app.input_buffer.content.clone()
     };
     
     let input = Paragraph::new(input_text)
@@ -1117,7 +2359,7 @@ fn draw_create_workspace_ui(f: &mut Frame, app: &App) {
     f.render_widget(instructions, chunks[2]);
     
     // Set cursor position in the input field
-    let cursor_x = chunks[1].x + 1 + app.input_buffer.len() as u16;
+    let cursor_x = chunks[1].x + 1 + app.input_buffer.cursor_col() as u16;
     let cursor_y = chunks[1].y + 1;
     
     f.set_cursor_position((cursor_x, cursor_y));
@@ -1145,6 +2387,7 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 
 fn draw_workspace_selection_ui(f: &mut Frame, app: &mut App) {
     let colors = &app.colors;
+    let glyphs = app.glyphs;
     
     // Create main layout - just header, main content, and instructions
     let chunks = Layout::default()
@@ -1167,20 +2410,7 @@ fn draw_workspace_selection_ui(f: &mut Frame, app: &mut App) {
         );
     
     f.render_widget(title, chunks[0]);
-    
-    // Mode indicator in top right
-    let mode_area = Rect {
-        x: chunks[0].x + chunks[0].width.saturating_sub(12),
-        y: chunks[0].y,
-        width: 12,
-        height: 1,
-    };
-    
-    let mode_widget = Paragraph::new(" WORKSPACE ")
-        .style(Style::default().fg(colors.bg_dark).bg(colors.magenta).add_modifier(Modifier::BOLD))
-        .alignment(Alignment::Center);
-    
-    f.render_widget(mode_widget, mode_area);
+    draw_mode_badge(f, chunks[0], "WORKSPACE", colors.badge(colors.magenta));
     
     // Draw workspace list - add Home option first
     let mut workspace_items: Vec<ListItem> = vec![];
@@ -1188,31 +2418,34 @@ fn draw_workspace_selection_ui(f: &mut Frame, app: &mut App) {
     // Add Home option
     let home_selected = 0 == app.popup_selected;
     let home_style = if home_selected {
-        Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+        colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
     } else {
         Style::default().fg(colors.fg)
     };
     
     let home_line = Line::from(vec![
-        Span::styled("  🏠 ", Style::default().fg(colors.cyan)),
-        Span::styled("← Return to Welcome Screen", home_style),
+        Span::styled(format!("  {}", glyphs.home), Style::default().fg(colors.cyan)),
+        Span::styled(format!("{} Return to Welcome Screen", glyphs.back), home_style),
     ]);
     workspace_items.push(ListItem::new(home_line));
     
-    // Add workspace options
-    let workspace_list_items: Vec<ListItem> = app.available_workspaces.iter()
+    // Add workspace options, in the order available_workspaces mirrors, by id so two
+    // workspaces with the same name each get their own color
+    let workspace_list_items: Vec<ListItem> = app.workspace_manager.get_all_workspaces().into_iter()
         .enumerate()
-        .map(|(i, workspace_name)| {
+        .map(|(i, workspace)| {
             let is_selected = (i + 1) == app.popup_selected; // +1 to account for Home option
             let style = if is_selected {
-                Style::default().fg(colors.fg).bg(colors.bg_highlight).add_modifier(Modifier::BOLD)
+                colors.selected().fg(colors.fg).add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(colors.fg)
             };
             
+            let workspace_color = app.get_workspace_color(&workspace.id);
             let line = Line::from(vec![
-                Span::styled("  📁 ", Style::default().fg(colors.magenta)),
-                Span::styled(workspace_name, style),
+                Span::styled(format!("  {}", glyphs.workspace), Style::default().fg(workspace_color)),
+                Span::styled(format!("{}:", i + 1), Style::default().fg(colors.comment)),
+                Span::styled(workspace.name.as_str(), style),
             ]);
             
             ListItem::new(line)
@@ -1238,7 +2471,7 @@ fn draw_workspace_selection_ui(f: &mut Frame, app: &mut App) {
     f.render_stateful_widget(workspace_list, chunks[1], &mut list_state);
     
     // Draw instructions
-    let instructions = Paragraph::new("Enter: Select | n: New Workspace | d: Delete Workspace | Ctrl+H: Home | Esc: Exit | j/k: Navigate")
+    let instructions = Paragraph::new("Enter: Select | n: New Workspace | d: Delete Workspace | J/K: Reorder | x: Export | I: Import | Ctrl+H: Home | Esc: Exit | j/k: Navigate")
         .style(Style::default().fg(colors.comment))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true })
@@ -1252,17 +2485,18 @@ fn draw_workspace_selection_ui(f: &mut Frame, app: &mut App) {
     
     f.render_widget(instructions, chunks[2]);
     
-    // Show message if any
-    if let Some(ref msg) = app.message {
+    // A message takes the place of the instructions while it's up
+    if let Some(message) = app.messages.current() {
         let message_area = Rect {
             x: chunks[2].x + 2,
             y: chunks[2].y + 1,
-            width: chunks[2].width - 4,
+            width: chunks[2].width.saturating_sub(4),
             height: 1,
         };
         
-        let message_widget = Paragraph::new(msg.as_str())
-            .style(Style::default().fg(colors.green).add_modifier(Modifier::BOLD))
+        f.render_widget(Clear, message_area);
+        let message_widget = Paragraph::new(message.text.as_str())
+            .style(message_style(colors, message.level).add_modifier(Modifier::BOLD))
             .alignment(Alignment::Center);
         
         f.render_widget(message_widget, message_area);
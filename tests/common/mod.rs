@@ -0,0 +1,89 @@
+// A headless harness for driving the app with key events, as the terminal loop would, and
+// checking what the list shows afterwards. Shared by the test files in this directory.
+
+#![allow(dead_code)] // Each test file uses its own subset
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use paperclip::app::{App, AppMode};
+use paperclip::config::Config;
+use paperclip::events::handle_event;
+use paperclip::keymap::KeyBinding;
+use paperclip::todo::Todo;
+
+// An app with one empty workspace, past the welcome screen
+pub fn new_app() -> App {
+    let mut app = App::new(Config::default());
+    app.workspace_manager.ensure_workspace();
+    app.mode = AppMode::Normal;
+    app
+}
+
+pub fn send(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    handle_event(app, Event::Key(KeyEvent::new(code, modifiers))).unwrap();
+}
+
+// A key written as in keybindings.toml: "i", "V", "ctrl+r", "space", "esc". Capitals arrive with
+// Shift held, the way terminals send them.
+pub fn press(app: &mut App, key: &str) {
+    let binding = KeyBinding::parse(key).unwrap_or_else(|e| panic!("bad key in test: {}", e));
+    let mut modifiers = binding.modifiers;
+    if matches!(binding.code, KeyCode::Char(c) if c.is_ascii_uppercase()) {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    send(app, binding.code, modifiers);
+}
+
+pub fn press_all(app: &mut App, keys: &[&str]) {
+    for key in keys {
+        press(app, key);
+    }
+}
+
+// Types into whatever has the input, a character at a time
+pub fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        let modifiers = if c.is_ascii_uppercase() { KeyModifiers::SHIFT } else { KeyModifiers::NONE };
+        send(app, KeyCode::Char(c), modifiers);
+    }
+}
+
+pub fn press_enter(app: &mut App) {
+    send(app, KeyCode::Enter, KeyModifiers::NONE);
+}
+
+pub fn press_esc(app: &mut App) {
+    send(app, KeyCode::Esc, KeyModifiers::NONE);
+}
+
+// Opens the add prompt, types the todo and saves it
+pub fn add_todo(app: &mut App, text: &str) {
+    press(app, "i");
+    type_text(app, text);
+    press_enter(app);
+    assert_eq!(app.mode, AppMode::Normal, "adding {:?} left the app in {:?}", text, app.mode);
+}
+
+// Descriptions of the todos in the list, top to bottom
+pub fn visible_descriptions(app: &App) -> Vec<String> {
+    app.get_visible_todos().iter().map(|(_, todo, _)| todo.description.clone()).collect()
+}
+
+#[track_caller]
+pub fn assert_visible(app: &App, expected: &[&str]) {
+    assert_eq!(visible_descriptions(app), expected);
+}
+
+pub fn selected_todo(app: &App) -> Option<Todo> {
+    app.get_visible_todos().get(app.selected).map(|(_, todo, _)| (*todo).clone())
+}
+
+// Every todo in the named workspace, whether shown or not, by description
+pub fn workspace_descriptions(app: &App, name: &str) -> Vec<String> {
+    let workspace = app.workspace_manager.workspaces.values().find(|ws| ws.name == name)
+        .unwrap_or_else(|| panic!("no workspace named {}", name));
+    let mut descriptions: Vec<String> = app.workspace_manager.workspace_todos[&workspace.id].todos.values()
+        .map(|todo| todo.description.clone())
+        .collect();
+    descriptions.sort();
+    descriptions
+}
@@ -0,0 +1,54 @@
+// Drives the app through the library the way the terminal loop does, one key event at a time
+
+mod common;
+
+use common::*;
+use paperclip::app::AppMode;
+use paperclip::todo::TodoStatus;
+
+#[test]
+fn adding_completing_and_undoing_through_key_events() {
+    let mut app = new_app();
+
+    press(&mut app, "i");
+    assert_eq!(app.mode, AppMode::Insert);
+    type_text(&mut app, "Buy milk #errands");
+    press_enter(&mut app);
+    assert_eq!(app.mode, AppMode::Normal);
+    assert_visible(&app, &["Buy milk errands"]);
+    assert!(selected_todo(&app).unwrap().tags.contains("errands"));
+
+    press(&mut app, "space");
+    assert_eq!(selected_todo(&app).unwrap().status, TodoStatus::Completed);
+    press(&mut app, "u");
+    assert_eq!(selected_todo(&app).unwrap().status, TodoStatus::Pending);
+}
+
+#[test]
+fn escape_abandons_a_new_todo() {
+    let mut app = new_app();
+
+    press(&mut app, "i");
+    type_text(&mut app, "Never mind");
+    press_esc(&mut app);
+    assert_eq!(app.mode, AppMode::Normal);
+    assert_visible(&app, &[]);
+}
+
+#[test]
+fn shift_v_enters_visual_mode_alongside_the_move_key() {
+    let mut app = new_app();
+    app.workspace_manager.create_workspace("Work".to_string(), None);
+    add_todo(&mut app, "Pack the boxes");
+
+    // `m` opens the move popup and Esc backs out of it
+    press(&mut app, "m");
+    assert_eq!(app.mode, AppMode::MoveToWorkspace);
+    press_esc(&mut app);
+    assert_eq!(app.mode, AppMode::Normal);
+
+    press(&mut app, "V");
+    assert_eq!(app.mode, AppMode::Visual);
+    press_esc(&mut app);
+    assert_eq!(app.mode, AppMode::Normal);
+}
@@ -0,0 +1,151 @@
+// Whole user flows, typed out key by key through the headless harness
+
+mod common;
+
+use common::*;
+use paperclip::app::AppMode;
+use paperclip::todo::{SearchScope, TodoStatus};
+
+#[test]
+fn add_edit_undo_redo() {
+    let mut app = new_app();
+    add_todo(&mut app, "Buy milk #errands due:2030-01-15");
+    add_todo(&mut app, "Call the bank");
+    assert_eq!(workspace_descriptions(&app, "Personal"), vec!["Buy milk errands", "Call the bank"]);
+
+    // Edit the milk todo: the prompt starts out with the text as typed
+    while selected_todo(&app).unwrap().description != "Buy milk errands" {
+        press(&mut app, "j");
+    }
+    press(&mut app, "e");
+    assert_eq!(app.mode, AppMode::EditTodo);
+    press(&mut app, "ctrl+u");
+    type_text(&mut app, "Buy oat milk #groceries");
+    press_enter(&mut app);
+    let edited = selected_todo(&app).unwrap();
+    assert_eq!(edited.description, "Buy oat milk groceries");
+    assert!(edited.tags.contains("groceries") && !edited.tags.contains("errands"));
+    assert!(edited.due_date.is_none());
+
+    press(&mut app, "u");
+    let restored = selected_todo(&app).unwrap();
+    assert_eq!(restored.description, "Buy milk errands");
+    assert!(restored.tags.contains("errands"));
+    assert!(restored.due_date.is_some());
+
+    press(&mut app, "ctrl+r");
+    assert_eq!(selected_todo(&app).unwrap().description, "Buy oat milk groceries");
+
+    // Undoing past the edit takes the todos back off one at a time
+    press_all(&mut app, &["u", "u"]);
+    assert_eq!(workspace_descriptions(&app, "Personal"), vec!["Buy milk errands"]);
+    press(&mut app, "u");
+    assert_visible(&app, &[]);
+}
+
+#[test]
+fn visual_bulk_delete_and_undo() {
+    let mut app = new_app();
+    for text in ["First", "Second", "Third", "Fourth"] {
+        add_todo(&mut app, text);
+    }
+    let before = visible_descriptions(&app);
+    assert_eq!(before.len(), 4);
+
+    // Select the top two with Shift+V, extending down with j
+    press(&mut app, "g");
+    press(&mut app, "g");
+    press(&mut app, "V");
+    assert_eq!(app.mode, AppMode::Visual);
+    press(&mut app, "j");
+    press(&mut app, "d");
+    assert_eq!(app.mode, AppMode::Normal);
+    assert_eq!(visible_descriptions(&app), before[2..].to_vec());
+
+    press(&mut app, "u");
+    let mut restored = visible_descriptions(&app);
+    restored.sort();
+    let mut expected = before.clone();
+    expected.sort();
+    assert_eq!(restored, expected);
+}
+
+#[test]
+fn create_switch_and_delete_workspaces() {
+    let mut app = new_app();
+    add_todo(&mut app, "Water the plants");
+
+    press(&mut app, "w");
+    assert_eq!(app.mode, AppMode::WorkspaceSelection);
+    press(&mut app, "n");
+    assert_eq!(app.mode, AppMode::CreateWorkspace);
+    type_text(&mut app, "Work");
+    press_enter(&mut app);
+    assert_eq!(app.get_current_workspace_name(), "Work");
+    assert_visible(&app, &[]);
+    add_todo(&mut app, "Send the report");
+
+    // Back to Personal with the previous workspace key, and again
+    press(&mut app, "tab");
+    assert_eq!(app.get_current_workspace_name(), "Personal");
+    assert_visible(&app, &["Water the plants"]);
+    press(&mut app, "tab");
+    assert_visible(&app, &["Send the report"]);
+
+    // Delete Work from the workspace list, from Personal
+    press(&mut app, "tab");
+    press(&mut app, "w");
+    let work = app.workspace_manager.find_workspace_id_by_name("Work").unwrap();
+    let work_row = 1 + app.available_workspaces.iter().position(|id| *id == work).unwrap();
+    while app.popup_selected < work_row {
+        press(&mut app, "j");
+    }
+    press(&mut app, "d");
+    assert!(!app.available_workspaces.contains(&work));
+    assert!(app.workspace_manager.workspaces.values().all(|ws| ws.name != "Work"));
+    press_esc(&mut app);
+    assert_eq!(app.get_current_workspace_name(), "Personal");
+    assert_visible(&app, &["Water the plants"]);
+}
+
+#[test]
+fn search_across_workspaces_acts_on_the_right_todo() {
+    let mut app = new_app();
+    add_todo(&mut app, "Renew passport");
+    add_todo(&mut app, "Pay rent");
+    press(&mut app, "w");
+    press(&mut app, "n");
+    type_text(&mut app, "Work");
+    press_enter(&mut app);
+    add_todo(&mut app, "Renew the domain");
+    add_todo(&mut app, "Plan the offsite");
+
+    // This workspace only, then all of them
+    press(&mut app, "/");
+    type_text(&mut app, "renew");
+    assert_visible(&app, &["Renew the domain"]);
+    press(&mut app, "ctrl+w");
+    assert_eq!(app.search_scope, SearchScope::All);
+    let mut found = visible_descriptions(&app);
+    found.sort();
+    assert_eq!(found, vec!["Renew passport", "Renew the domain"]);
+    press_enter(&mut app);
+    assert_eq!(app.mode, AppMode::Normal);
+
+    // Completing a result from the other workspace changes that todo and nothing else
+    while selected_todo(&app).unwrap().description != "Renew passport" {
+        press(&mut app, "j");
+    }
+    press(&mut app, "space");
+    let status = |app: &paperclip::app::App, name: &str, description: &str| {
+        let workspace = app.workspace_manager.workspaces.values().find(|ws| ws.name == name).unwrap();
+        app.workspace_manager.workspace_todos[&workspace.id].todos.values()
+            .find(|todo| todo.description == description).unwrap().status.clone()
+    };
+    assert_eq!(status(&app, "Personal", "Renew passport"), TodoStatus::Completed);
+    assert_eq!(status(&app, "Personal", "Pay rent"), TodoStatus::Pending);
+    assert_eq!(status(&app, "Work", "Renew the domain"), TodoStatus::Pending);
+    assert_eq!(status(&app, "Work", "Plan the offsite"), TodoStatus::Pending);
+    assert_eq!(workspace_descriptions(&app, "Personal"), vec!["Pay rent", "Renew passport"]);
+    assert_eq!(workspace_descriptions(&app, "Work"), vec!["Plan the offsite", "Renew the domain"]);
+}
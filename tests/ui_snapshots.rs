@@ -0,0 +1,161 @@
+// The rendered screen in a few representative states, at two terminal sizes, compared with
+// the text in tests/snapshots. After a deliberate change to the layout, rewrite them with
+//
+//     UPDATE_SNAPSHOTS=1 cargo test --test ui_snapshots
+//
+// and review the diff. Only the characters are compared, not colors.
+
+mod common;
+
+use chrono::{Local, TimeZone};
+use common::*;
+use paperclip::app::{App, AppMode};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
+
+const SIZES: [(u16, u16); 2] = [(80, 24), (120, 40)];
+
+// Two workspaces of todos with a hierarchy, tags, contexts and notes. Everything is stamped
+// with fixed dates, and the one due date is far enough off to be shown as a plain date, so
+// the screen doesn't depend on when the test runs.
+fn fixture() -> App {
+    let mut app = new_app();
+    let at = |day: u32, hour: u32| Local.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+
+    let manager = &mut app.workspace_manager;
+    let personal = manager.get_current_workspace_id().unwrap();
+    let work = manager.create_workspace("Work".to_string(), Some("Office things".to_string()));
+    for (n, id) in [&personal, &work].into_iter().enumerate() {
+        manager.workspaces.get_mut(id).unwrap().created_at = at(1, n as u32);
+    }
+
+    let list = manager.workspace_todos.get_mut(&personal).unwrap();
+    let garden = list.add_todo("Plan the garden #home".to_string());
+    let seeds = list.add_child_todo(garden, "Buy seeds #home @store".to_string()).unwrap();
+    let beds = list.add_child_todo(garden, "Dig the beds".to_string()).unwrap();
+    let taxes = list.add_todo("File taxes #finance due:2099-04-15".to_string());
+    let call = list.add_todo("Call the dentist @phone".to_string());
+    let books = list.add_todo("Return library books #errands @town".to_string());
+    for (n, id) in [garden, seeds, beds, taxes, call, books].into_iter().enumerate() {
+        list.todos.get_mut(&id).unwrap().created_at = at(2, n as u32);
+    }
+    list.todos.get_mut(&taxes).unwrap().set_priority(4);
+    list.todos.get_mut(&garden).unwrap().notes = Some("- [x] Measure the beds\n- [ ] Pick a layout\nTomatoes along the fence".to_string());
+    let done = list.todos.get_mut(&books).unwrap();
+    done.complete();
+    done.completed_at = Some(at(3, 9));
+
+    let list = manager.workspace_todos.get_mut(&work).unwrap();
+    let report = list.add_todo("Send the quarterly report #reports".to_string());
+    list.todos.get_mut(&report).unwrap().created_at = at(2, 9);
+
+    app.available_workspaces = app.workspace_manager.get_all_workspaces().iter().map(|ws| ws.id.clone()).collect();
+    app.invalidate_visible_rows();
+    app
+}
+
+// The screen as text, one line per row with trailing spaces trimmed, and the cursor position
+// when a text input has it
+fn render(app: &mut App, width: u16, height: u16, with_cursor: bool) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| paperclip::ui::draw(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let mut screen = String::new();
+    for y in 0..height {
+        let mut line = String::new();
+        let mut x = 0;
+        while x < width {
+            let symbol = buffer[(x, y)].symbol();
+            line.push_str(symbol);
+            // The cells covered by a wide character hold nothing of their own
+            x += symbol.width().max(1) as u16;
+        }
+        screen.push_str(line.trim_end());
+        screen.push('\n');
+    }
+    if with_cursor {
+        let cursor = terminal.get_cursor_position().unwrap();
+        screen.push_str(&format!("cursor: {}, {}\n", cursor.x, cursor.y));
+    }
+    screen
+}
+
+fn snapshot_path(name: &str, width: u16, height: u16) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(format!("{}-{}x{}.txt", name, width, height))
+}
+
+// Draws `setup`'s state at each size and checks it against the stored snapshot
+fn assert_snapshots(name: &str, with_cursor: bool, setup: impl Fn(&mut App)) {
+    let bless = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    for (width, height) in SIZES {
+        let mut app = fixture();
+        setup(&mut app);
+        let screen = render(&mut app, width, height, with_cursor);
+        let path = snapshot_path(name, width, height);
+        if bless {
+            std::fs::write(&path, &screen).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!("no snapshot at {}; run with UPDATE_SNAPSHOTS=1 to create it", path.display())
+        });
+        assert!(
+            screen == expected,
+            "{} at {}x{} differs from {}; rerun with UPDATE_SNAPSHOTS=1 if the change is intended.\n--- expected\n{}--- rendered\n{}",
+            name, width, height, path.display(), expected, screen
+        );
+    }
+}
+
+#[test]
+fn snapshot_list_with_hierarchy_and_tags() {
+    assert_snapshots("list", false, |_| {});
+}
+
+#[test]
+fn snapshot_welcome_screen() {
+    assert_snapshots("welcome", false, |app| {
+        app.mode = AppMode::Welcome;
+        app.set_first_launch(false);
+    });
+}
+
+#[test]
+fn snapshot_tag_popup() {
+    assert_snapshots("tag-popup", false, |app| press(app, "#"));
+}
+
+#[test]
+fn snapshot_notes_editor() {
+    assert_snapshots("notes-editor", true, |app| {
+        while selected_todo(app).unwrap().description != "Plan the garden home" {
+            press(app, "j");
+        }
+        press(app, "ctrl+n");
+        assert_eq!(app.mode, AppMode::EditNotes);
+    });
+}
+
+#[test]
+fn snapshot_workspace_selection() {
+    assert_snapshots("workspaces", false, |app| press(app, "w"));
+}
+
+#[test]
+fn status_bar_drops_the_separator_when_no_count_fits() {
+    let mut app = fixture();
+    press(&mut app, "t");
+    app.messages.clear();
+    let screen = render(&mut app, 40, 24, false);
+    let status = screen.lines().find(|line| line.contains("Timer:")).expect("timer in the status bar");
+    let inside = status.trim_matches(|c: char| c == '│' || c.is_whitespace());
+    assert!(inside.starts_with("Timer: File taxes finance"), "{}", status);
+    assert!(!inside.ends_with('|'), "dangling separator: {}", status);
+
+    // With room for the counts, one separator sits between the timer and them
+    let screen = render(&mut app, 120, 40, false);
+    let status = screen.lines().find(|line| line.contains("Timer:")).unwrap();
+    assert!(status.contains(" | Total: 6 | "), "{}", status);
+}